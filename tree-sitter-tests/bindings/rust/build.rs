@@ -2,7 +2,7 @@ fn main() {
     let src_dir = std::path::Path::new("src");
 
     let mut c_config = cc::Build::new();
-    c_config.include(&src_dir);
+    c_config.include(src_dir);
     c_config
         .flag_if_supported("-Wno-unused-parameter")
         .flag_if_supported("-Wno-unused-but-set-variable")
@@ -22,6 +22,25 @@ fn main() {
     c_config.compile("parser");
     println!("cargo:rerun-if-changed={}", parser_path.to_str().unwrap());
 
+    // Mimics a parser generated by the build script, whose `node-types.json` ends up in `OUT_DIR`,
+    // for the tests reading it from there.
+    let node_types_path = src_dir.join("node-types.json");
+    let out_path = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("node-types.json");
+    std::fs::copy(&node_types_path, &out_path).unwrap();
+    println!("cargo:rustc-env=CALC_NODE_TYPES={}", out_path.display());
+    println!(
+        "cargo:rerun-if-changed={}",
+        node_types_path.to_str().unwrap()
+    );
+
+    // Generates a visitor trait into `OUT_DIR`, for the tests including it instead of using the
+    // macro.
+    let config = tree_sitter_visitor_codegen::GenerateConfig::new("pub trait CalcVisitor {}")
+        .options("named_only, visit_children");
+    let code = tree_sitter_visitor_codegen::generate("src/node-types.json", &config).unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(std::path::Path::new(&out_dir).join("calc_visitor.rs"), code).unwrap();
+
     // If your language uses an external scanner written in C++,
     // then include this block of code:
 
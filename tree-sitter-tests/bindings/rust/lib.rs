@@ -10,7 +10,7 @@ pub fn language() -> Language {
 }
 
 pub const NODE_TYPES: &str = include_str!("../../src/node-types.json");
-#[visitor_trait("../../src/node-types.json")]
+#[visitor_trait("src/node-types.json")]
 pub trait CalcVisitor {}
 
 #[cfg(test)]
@@ -49,7 +49,7 @@ mod tests {
     fn test_visitor_works() {
         let mut parser = tree_sitter::Parser::new();
         parser
-            .set_language(super::language())
+            .set_language(&super::language())
             .expect("Error loading dummy language");
 
         let src = "1 + 2";
@@ -0,0 +1,14 @@
+; Operands of additions and the operators of products.
+(add_expr
+  lhs: (number) @operand.left
+  rhs: (_) @operand.right)
+
+[
+  (mul_expr "*" @operator)
+  (div_expr "/" @operator)
+]
+
+((number) @number.zero
+  (#eq? @number.zero "0"))
+
+(paren_expr !body) @empty
@@ -0,0 +1,41 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+use accessors::{add_expr_lhs, add_expr_rhs, mul_expr_lhs, mul_expr_rhs, paren_expr_body};
+
+#[visitor_trait("src/node-types.json", accessors)]
+pub trait CalcVisitor {}
+
+/// Evaluates additions and multiplications through the accessors.
+fn eval(node: Node, source: &str) -> f64 {
+    match node.kind() {
+        "add_expr" => eval(add_expr_lhs(&node), source) + eval(add_expr_rhs(&node), source),
+        "mul_expr" => eval(mul_expr_lhs(&node), source) * eval(mul_expr_rhs(&node), source),
+        "paren_expr" => eval(paren_expr_body(&node), source),
+        "number" => source[node.byte_range()].parse().unwrap(),
+        kind => panic!("unexpected node kind: {}", kind),
+    }
+}
+
+#[test]
+fn test_accessors_return_children_in_fields() {
+    let source = "(1 + 2) * 3";
+    let tree = parse(source);
+    let mul_expr = tree.root_node().named_child(0).unwrap();
+
+    assert_eq!(mul_expr_lhs(&mul_expr).kind(), "paren_expr");
+    assert_eq!(mul_expr_rhs(&mul_expr).byte_range(), 10..11);
+    assert_eq!(eval(mul_expr, source), 9.0);
+}
+
+#[test]
+#[should_panic(expected = "expected a `add_expr` node, got `mul_expr`")]
+fn test_accessors_check_the_kind() {
+    let tree = parse("1 * 2");
+    let mul_expr = tree.root_node().named_child(0).unwrap();
+
+    add_expr_lhs(&mul_expr);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", after_visit)]
+pub trait CalcVisitor {}
+
+#[derive(Default)]
+struct RangeCollector {
+    visited: Vec<&'static str>,
+}
+
+impl CalcVisitor for RangeCollector {
+    type ReturnType = std::ops::Range<usize>;
+
+    fn visit_root(&mut self, node: &Node) -> Self::ReturnType {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> Self::ReturnType {
+        node.byte_range()
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> Self::ReturnType {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+        lhs.start..rhs.end
+    }
+
+    fn after_visit(&mut self, node: &Node, result: &Self::ReturnType) {
+        assert!(node.start_byte() <= result.start && result.end <= node.end_byte());
+        self.visited.push(node.kind());
+    }
+}
+
+#[test]
+fn test_after_visit_is_called_for_every_node() {
+    let parsed = parse("1 + 2");
+
+    let mut visitor = RangeCollector::default();
+    assert_eq!(visitor.visit(&parsed.root_node()), 0..5);
+    assert_eq!(
+        visitor.visited,
+        vec!["number", "number", "add_expr", "root"]
+    );
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait(
+    "src/node-types.json",
+    enclosing(arithmetic = ["add_expr", "sub_expr", "mul_expr", "div_expr"])
+)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_ancestors_and_enclosing() {
+    let parsed = parse("1 * (2)");
+    let number = parsed.root_node().descendant_for_byte_range(5, 6).unwrap();
+    assert_eq!(number.kind(), "number");
+
+    let kinds: Vec<_> = ancestors(&number).map(|node| node.kind()).collect();
+    assert_eq!(kinds, vec!["paren_expr", "mul_expr", "root"]);
+
+    let mul_expr = nearest_enclosing_arithmetic(&number).unwrap();
+    assert_eq!(mul_expr.kind(), "mul_expr");
+    assert!(nearest_enclosing_arithmetic(&mul_expr).is_none());
+    assert!(enclosing(&number, &NodeKindSet::new(&["root"])).is_some());
+    assert!(ENCLOSING_ARITHMETIC.contains(&mul_expr));
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", assert_tree)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_assert_tree_matches() {
+    let tree = parse("1 + (2 * 3)");
+    assert_tree!(
+        tree.root_node(),
+        root {
+            add_expr {
+                lhs: number,
+                rhs: paren_expr {
+                    body: mul_expr { lhs: number, rhs: number },
+                },
+            },
+        }
+    );
+}
+
+#[test]
+fn test_expected_tree_formats_like_the_macro() {
+    let tree = parse("1 - 2");
+    let shape = ExpectedTree::from_node(&tree.root_node());
+    assert_eq!(
+        shape.to_string(),
+        "root {\n    sub_expr {\n        lhs: number,\n        rhs: number,\n    },\n}"
+    );
+}
+
+#[test]
+fn test_assert_tree_mismatch_shows_diff() {
+    let tree = parse("1 + 2 * 3");
+    let result = std::panic::catch_unwind(|| {
+        assert_tree!(
+            tree.root_node(),
+            root {
+                add_expr { lhs: number, rhs: number },
+            }
+        );
+    });
+    let payload = result.unwrap_err();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(message.contains("-        rhs: number,"));
+    assert!(message.contains("+        rhs: mul_expr {"));
+    assert!(message.contains("         lhs: number,"));
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::parse;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", async, named_only, unknown = "hook")]
+pub trait CalcVisitor {}
+
+/// Looks up the value of numbers, standing in for a database.
+struct Values;
+
+impl Values {
+    async fn lookup(&self, text: &str) -> f64 {
+        text.parse().unwrap()
+    }
+}
+
+struct Evaluator<'s> {
+    source: &'s str,
+    values: Values,
+}
+
+impl CalcVisitor for Evaluator<'_> {
+    type ReturnType = f64;
+
+    async fn visit_root(&mut self, node: &Node<'_>) -> f64 {
+        self.visit(&node.named_child(0).unwrap()).await
+    }
+
+    async fn visit_number(&mut self, node: &Node<'_>) -> f64 {
+        self.values.lookup(&self.source[node.byte_range()]).await
+    }
+
+    async fn visit_add_expr(&mut self, node: &Node<'_>) -> f64 {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap()).await;
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap()).await;
+        lhs + rhs
+    }
+
+    async fn visit_unknown(&mut self, _node: &Node<'_>) -> f64 {
+        f64::NAN
+    }
+}
+
+/// Runs a future that never waits to completion.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn test_async_visitor() {
+    let source = "1 + 2 + 3";
+    let tree = parse(source);
+
+    let mut visitor = Evaluator {
+        source,
+        values: Values,
+    };
+    assert_eq!(block_on(visitor.visit(&tree.root_node())), 6.0);
+}
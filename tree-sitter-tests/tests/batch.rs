@@ -0,0 +1,66 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", batch)]
+pub trait CalcVisitor {}
+
+#[derive(Default)]
+struct NumberCollector<'s> {
+    src: &'s str,
+    numbers: Vec<String>,
+}
+
+impl<'s> CalcVisitor for NumberCollector<'s> {
+    type ReturnType = usize;
+
+    fn visit_root(&mut self, node: &Node) -> usize {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> usize {
+        self.numbers.push(self.src[node.byte_range()].to_string());
+        1
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> usize {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+
+        lhs + rhs
+    }
+}
+
+#[test]
+fn test_batch_visits_in_file_id_order() {
+    let sources = ["3 + 4 + 5", "1 + 2"];
+    let trees: Vec<_> = sources.iter().map(|src| parse(src)).collect();
+
+    let batch = visit_batch(
+        NumberCollector::default(),
+        vec![(2, &trees[0]), (1, &trees[1])],
+        |visitor, id| visitor.src = sources[2 - id],
+    )
+    .unwrap();
+
+    assert_eq!(
+        batch.results.into_iter().collect::<Vec<_>>(),
+        vec![(1, 2), (2, 3)]
+    );
+    assert_eq!(batch.visitor.numbers, vec!["1", "2", "3", "4", "5"]);
+}
+
+#[test]
+fn test_batch_rejects_duplicate_file_ids() {
+    let tree = parse("1 + 2");
+
+    let batch = visit_batch(
+        NumberCollector::default(),
+        vec![(2, &tree), (1, &tree), (2, &tree)],
+        |visitor, _| visitor.src = "1 + 2",
+    );
+
+    assert_eq!(batch.err(), Some(DuplicateFileId(2)));
+}
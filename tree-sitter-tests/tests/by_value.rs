@@ -0,0 +1,60 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", by_value)]
+pub trait CalcVisitor {}
+
+#[visitor_trait("src/node-types.json", by_value, fallible, unknown = "hook")]
+pub trait FallibleVisitor {}
+
+/// Returns the text of the leftmost number, borrowing from the source.
+struct Leftmost<'tree> {
+    source: &'tree str,
+}
+
+impl<'tree> CalcVisitor<'tree> for Leftmost<'tree> {
+    type ReturnType = (&'tree str, Node<'tree>);
+
+    fn visit_root(&mut self, node: Node<'tree>) -> Self::ReturnType {
+        self.visit(node.named_child(0).unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: Node<'tree>) -> Self::ReturnType {
+        self.visit(node.child_by_field_name("lhs").unwrap())
+    }
+
+    fn visit_number(&mut self, node: Node<'tree>) -> Self::ReturnType {
+        (&self.source[node.byte_range()], node)
+    }
+}
+
+struct Empty;
+
+impl<'tree> FallibleVisitor<'tree> for Empty {
+    type ReturnType = ();
+    type Error = UnimplementedNode;
+}
+
+#[test]
+fn test_return_borrows_from_tree() {
+    let source = String::from("12 + 3 + 4");
+    let tree = parse(&source);
+    let (text, node) = Leftmost { source: &source }.visit(tree.root_node());
+    assert_eq!(text, "12");
+    assert_eq!(node.kind(), "number");
+    assert_eq!(node.parent().unwrap().kind(), "add_expr");
+}
+
+#[test]
+fn test_fallible_by_value() {
+    let tree = parse("1");
+    let err = Empty.visit(tree.root_node()).unwrap_err();
+    assert_eq!(err.kind, "root");
+
+    let tree = parse("+");
+    let err = Empty.visit(tree.root_node()).unwrap_err();
+    assert_eq!(err.kind, "ERROR");
+}
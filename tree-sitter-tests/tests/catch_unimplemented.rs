@@ -0,0 +1,68 @@
+mod common;
+
+use common::{parse, Calculator};
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", catch_unimplemented)]
+pub trait CalcVisitor {}
+
+common::calculator!(CalcVisitor);
+
+#[test]
+fn test_catch_unimplemented_records_unhandled_kinds() {
+    let sources = ["1 + 2", "1 + 2 * 3"];
+    let mut visitor = CatchUnimplemented::new(Calculator { source: "" });
+
+    let results: Vec<_> = sources
+        .iter()
+        .map(|src| {
+            let parsed = parse(src);
+            visitor.inner.source = src;
+            visitor.visit_or(&parsed.root_node(), f64::NAN)
+        })
+        .collect();
+
+    assert_eq!(results[0], 3.0);
+    assert!(results[1].is_nan());
+    assert_eq!(visitor.unhandled.len(), 1);
+    assert_eq!(visitor.unhandled[0].kind, "mul_expr");
+    assert_eq!(visitor.unhandled[0].byte_range, 4..9);
+}
+
+pub mod tokens {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait("src/node-types.json", catch_unimplemented, token_hook)]
+    pub trait TokenVisitor {}
+
+    /// Names the operators, except `*`.
+    pub struct Names;
+
+    impl TokenVisitor for Names {
+        type ReturnType = String;
+
+        fn visit_token(&mut self, node: &Node, kind: &str) -> String {
+            match kind {
+                "+" => "plus".to_string(),
+                _ => <Self as TokenVisitor>::visit_mul_expr(self, node),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_catch_unimplemented_forwards_hooks() {
+    use tokens::{CatchUnimplemented, Names, TokenVisitor};
+
+    let parsed = parse("1 + 2 * 3");
+    let add_expr = parsed.root_node().named_child(0).unwrap();
+    let plus = add_expr.child(1).unwrap();
+    let times = add_expr.child(2).unwrap().child(1).unwrap();
+
+    let mut visitor = CatchUnimplemented::new(Names);
+    assert_eq!(visitor.visit_token(&plus, "+"), "plus");
+    assert_eq!(visitor.visit_token(&times, "*"), "");
+    assert_eq!(visitor.unhandled.len(), 1);
+    assert_eq!(visitor.unhandled[0].kind, "*");
+}
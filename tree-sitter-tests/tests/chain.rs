@@ -0,0 +1,221 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::{visitor_impl, visitor_trait};
+
+#[visitor_trait("src/node-types.json", chain)]
+pub trait CalcVisitor {}
+
+struct Base;
+
+#[visitor_impl]
+impl CalcVisitor for Base {
+    type ReturnType = &'static str;
+
+    fn visit_root(&mut self, _node: &Node) -> &'static str {
+        "base root"
+    }
+
+    fn visit_number(&mut self, _node: &Node) -> &'static str {
+        "base number"
+    }
+}
+
+struct Specialized;
+
+#[visitor_impl]
+impl CalcVisitor for Specialized {
+    type ReturnType = &'static str;
+
+    fn visit_number(&mut self, _node: &Node) -> &'static str {
+        "specialized number"
+    }
+}
+
+#[test]
+fn test_chain_falls_back_for_unhandled_kinds() {
+    let parsed = parse("1");
+    let root = parsed.root_node();
+    let number = root.child(0).unwrap();
+
+    let mut visitor = ChainedVisitor::new(Specialized, Base);
+    assert_eq!(visitor.visit(&root), "base root");
+    assert_eq!(visitor.visit(&number), "specialized number");
+
+    assert!(Specialized::handles_kind("number"));
+    assert!(!Specialized::handles_kind("root"));
+}
+
+pub mod prefixed {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::{visitor_impl, visitor_trait};
+
+    #[visitor_trait("src/node-types.json", chain, prefix = "on_")]
+    pub trait PrefixedVisitor {}
+
+    pub struct Base;
+
+    #[visitor_impl]
+    impl PrefixedVisitor for Base {
+        type ReturnType = &'static str;
+
+        fn on_root(&mut self, _node: &Node) -> &'static str {
+            "base root"
+        }
+
+        fn on_number(&mut self, _node: &Node) -> &'static str {
+            "base number"
+        }
+    }
+
+    pub struct Specialized;
+
+    #[visitor_impl]
+    impl PrefixedVisitor for Specialized {
+        type ReturnType = &'static str;
+
+        fn on_number(&mut self, _node: &Node) -> &'static str {
+            "specialized number"
+        }
+    }
+}
+
+#[test]
+fn test_chain_with_prefix() {
+    use prefixed::{Base, ChainedVisitor, PrefixedVisitor, Specialized};
+
+    let parsed = parse("1");
+    let root = parsed.root_node();
+    let number = root.child(0).unwrap();
+
+    let mut visitor = ChainedVisitor::new(Specialized, Base);
+    assert_eq!(visitor.visit(&root), "base root");
+    assert_eq!(visitor.visit(&number), "specialized number");
+
+    assert!(Specialized::handles_kind("number"));
+    assert!(!Specialized::handles_kind("root"));
+}
+
+pub mod hooks {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::{visitor_impl, visitor_trait};
+
+    #[visitor_trait(
+        "src/node-types.json",
+        chain,
+        token_hook,
+        error_hooks,
+        unknown = "hook"
+    )]
+    pub trait HookVisitor {}
+
+    pub struct Base;
+
+    #[visitor_impl]
+    impl HookVisitor for Base {
+        type ReturnType = &'static str;
+
+        fn visit_token(&mut self, _node: &Node, _kind: &str) -> &'static str {
+            "base token"
+        }
+
+        fn visit_error(&mut self, _node: &Node) -> &'static str {
+            "base error"
+        }
+    }
+
+    pub struct Specialized;
+
+    #[visitor_impl]
+    impl HookVisitor for Specialized {
+        type ReturnType = &'static str;
+
+        fn visit_token(&mut self, _node: &Node, _kind: &str) -> &'static str {
+            "specialized token"
+        }
+    }
+}
+
+#[test]
+fn test_chain_forwards_hooks() {
+    use hooks::{Base, ChainedVisitor, HookVisitor, Specialized};
+
+    let mut visitor = ChainedVisitor::new(Specialized, Base);
+
+    let parsed = parse("1 + 2");
+    let plus = parsed.root_node().child(0).unwrap().child(1).unwrap();
+    assert_eq!(visitor.visit(&plus), "specialized token");
+
+    let parsed = parse("1 + ) 2");
+    let mut cursor = parsed.root_node().walk();
+    while !cursor.node().is_error() {
+        if !cursor.goto_first_child() {
+            while !cursor.goto_next_sibling() {
+                cursor.goto_parent();
+            }
+        }
+    }
+    let error = cursor.node();
+    assert_eq!(visitor.visit(&error), "base error");
+}
+
+pub mod other_hooks {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::{visitor_impl, visitor_trait};
+
+    #[visitor_trait("src/node-types.json", chain, field_hooks, after_visit, revisit)]
+    pub trait OtherHookVisitor {}
+
+    pub struct Base;
+
+    #[visitor_impl]
+    impl OtherHookVisitor for Base {
+        type ReturnType = &'static str;
+
+        fn visit_number(&mut self, _node: &Node) -> &'static str {
+            "base number"
+        }
+
+        fn visit_field_lhs(&mut self, _parent: &Node, _node: &Node) -> &'static str {
+            "base lhs"
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Specialized {
+        pub visited: Vec<&'static str>,
+        pub invalidated: Vec<&'static str>,
+    }
+
+    #[visitor_impl]
+    impl OtherHookVisitor for Specialized {
+        type ReturnType = &'static str;
+
+        fn after_visit(&mut self, _node: &Node, result: &&'static str) {
+            self.visited.push(result);
+        }
+
+        fn invalidate(&mut self, node: &Node) {
+            self.invalidated.push(node.kind());
+        }
+    }
+}
+
+#[test]
+fn test_chain_forwards_other_hooks() {
+    use other_hooks::{Base, ChainedVisitor, OtherHookVisitor, Specialized};
+
+    let parsed = parse("1 + 2");
+    let add_expr = parsed.root_node().child(0).unwrap();
+
+    let mut visitor = ChainedVisitor::new(Specialized::default(), Base);
+    assert_eq!(visitor.visit_fields(&add_expr), ["base lhs", "base number"]);
+
+    let number = add_expr.child(0).unwrap();
+    assert_eq!(visitor.visit(&number), "base number");
+    assert_eq!(visitor.primary.visited, ["base number"]);
+
+    visitor.invalidate(&add_expr);
+    assert_eq!(visitor.primary.invalidated, ["add_expr"]);
+}
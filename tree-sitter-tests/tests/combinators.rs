@@ -0,0 +1,144 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", combinators, walker, defaults)]
+pub trait CalcVisitor {}
+
+/// Collects the numbers of a tree, without recursing.
+struct Numbers<'s> {
+    source: &'s str,
+    numbers: Vec<i64>,
+}
+
+impl CalcVisitor for Numbers<'_> {
+    type ReturnType = ();
+
+    fn visit_number(&mut self, node: &Node) {
+        self.numbers
+            .push(self.source[node.byte_range()].parse().unwrap());
+    }
+}
+
+/// Counts the operators of a tree, without recursing.
+#[derive(Default)]
+struct Operators {
+    count: usize,
+}
+
+impl CalcVisitor for Operators {
+    type ReturnType = ();
+
+    fn visit_add_expr(&mut self, _node: &Node) {
+        self.count += 1;
+    }
+
+    fn visit_mul_expr(&mut self, _node: &Node) {
+        self.count += 1;
+    }
+}
+
+/// Returns the depth of a subtree, recursing into the children.
+struct Depth;
+
+impl CalcVisitor for Depth {
+    type ReturnType = usize;
+
+    fn visit_root(&mut self, node: &Node) -> usize {
+        1 + self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> usize {
+        1 + self
+            .visit(&node.named_child(0).unwrap())
+            .max(self.visit(&node.named_child(1).unwrap()))
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) -> usize {
+        self.visit_add_expr(node)
+    }
+
+    fn visit_number(&mut self, _node: &Node) -> usize {
+        1
+    }
+}
+
+#[test]
+fn test_multi_visitor_single_traversal() {
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+
+    let numbers = Numbers {
+        source,
+        numbers: Vec::new(),
+    };
+    let mut visitor = MultiVisitor::new((numbers, Operators::default()));
+    let mut steps = 0;
+    Walker::new().walk(&mut visitor, tree.root_node(), |_, _| steps += 1);
+    assert_eq!(steps, 8);
+
+    let (numbers, operators) = visitor.into_inner();
+    assert_eq!(numbers.numbers, [1, 2, 3]);
+    assert_eq!(operators.count, 2);
+}
+
+#[test]
+fn test_multi_visitor_returns_tuples() {
+    let tree = parse("1 + 2 * 3");
+    let root = tree.root_node();
+
+    let mut visitor = MultiVisitor::new((Depth, Depth, Operators::default()));
+    assert_eq!(visitor.visit(&root), (4, 4, ()));
+    let add_expr = root.named_child(0).unwrap();
+    assert_eq!(visitor.visit_add_expr(&add_expr), (3, 3, ()));
+    assert_eq!(visitor.visitors.2.count, 1);
+}
+
+#[test]
+fn test_mapped_visitor() {
+    let tree = parse("1 + 2 * 3");
+    let root = tree.root_node();
+
+    let mut visitor = MappedVisitor::new(Depth, |depth| format!("depth {}", depth));
+    assert_eq!(visitor.visit(&root), "depth 4");
+
+    let mut visitor = MultiVisitor::new((MappedVisitor::new(Depth, |depth| depth * 10), Depth));
+    assert_eq!(visitor.visit(&root), (40, 4));
+}
+
+pub mod tokens {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait("src/node-types.json", combinators, token_hook, defaults)]
+    pub trait TokenVisitor {}
+
+    /// Names the operators.
+    pub struct Names;
+
+    impl TokenVisitor for Names {
+        type ReturnType = String;
+
+        fn visit_token(&mut self, _node: &Node, kind: &str) -> String {
+            format!("token {}", kind)
+        }
+    }
+}
+
+#[test]
+fn test_combinators_forward_hooks() {
+    use tokens::{MappedVisitor, MultiVisitor, Names, TokenVisitor};
+
+    let tree = parse("1 + 2");
+    let plus = tree.root_node().named_child(0).unwrap().child(1).unwrap();
+
+    let mut visitor = MultiVisitor::new((Names, Names));
+    assert_eq!(
+        visitor.visit(&plus),
+        ("token +".to_string(), "token +".to_string())
+    );
+    let mut visitor = MappedVisitor::new(Names, |name: String| name.len());
+    assert_eq!(visitor.visit_token(&plus, "+"), 7);
+}
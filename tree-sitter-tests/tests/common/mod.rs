@@ -0,0 +1,54 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code, unused_imports, unused_macros)]
+
+use tree_sitter::Node;
+
+/// Returns a parser for the calc grammar, for tests reparsing edited sources.
+pub fn parser() -> tree_sitter::Parser {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_tests::language()).unwrap();
+    parser
+}
+
+/// Parses a source with the calc grammar.
+pub fn parse(source: &str) -> tree_sitter::Tree {
+    parser().parse(source, None).unwrap()
+}
+
+/// Evaluates the expressions of a source.
+pub struct Calculator<'s> {
+    pub source: &'s str,
+}
+
+impl Calculator<'_> {
+    /// Parses the text of a `number` node.
+    pub fn number(&self, node: &Node) -> f64 {
+        self.source[node.byte_range()].parse().unwrap()
+    }
+}
+
+/// Implements a visitor trait for `Calculator`, evaluating numbers and additions.
+macro_rules! calculator {
+    ($visitor:path) => {
+        impl $visitor for common::Calculator<'_> {
+            type ReturnType = f64;
+
+            fn visit_root(&mut self, node: &tree_sitter::Node) -> f64 {
+                self.visit(&node.child(0).unwrap())
+            }
+
+            fn visit_number(&mut self, node: &tree_sitter::Node) -> f64 {
+                self.number(node)
+            }
+
+            fn visit_add_expr(&mut self, node: &tree_sitter::Node) -> f64 {
+                let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+                let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+
+                lhs + rhs
+            }
+        }
+    };
+}
+
+pub(crate) use calculator;
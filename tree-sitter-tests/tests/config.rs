@@ -0,0 +1,39 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait(config = "tests/calc-visitor.toml", after_visit)]
+trait CalcVisitor {}
+
+struct Evaluator;
+
+impl CalcVisitor for Evaluator {
+    type ReturnType = i64;
+
+    fn visit_root(&mut self, node: &Node, depth: usize) -> i64 {
+        self.visit_named(&node.child(0).unwrap(), depth + 1)
+    }
+
+    fn visit_number(&mut self, node: &Node, depth: usize) -> i64 {
+        node.byte_range().len() as i64 * depth as i64
+    }
+
+    fn visit_sum(&mut self, node: &Node, depth: usize) -> i64 {
+        let lhs = node.child_by_field_name("lhs").unwrap();
+        let rhs = node.child_by_field_name("rhs").unwrap();
+        let mut operand = |node: Node| match node.kind() {
+            "number" => self.visit_number(&node, depth + 1),
+            _ => self.visit(&node, depth + 1),
+        };
+        operand(lhs) + operand(rhs)
+    }
+}
+
+#[test]
+fn test_config() {
+    let tree = parse("1+22");
+
+    assert_eq!(Evaluator.visit(&tree.root_node(), 0), 2 + 2 * 2);
+}
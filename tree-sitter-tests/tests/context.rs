@@ -0,0 +1,116 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait(
+    "src/node-types.json",
+    context,
+    params(source: &str),
+    named_only,
+    unknown = "skip",
+    visit_children,
+    flavors(immutable)
+)]
+pub trait CalcVisitor {}
+
+/// Per-visit context kept apart from the visitor.
+#[derive(Default)]
+struct Scope {
+    depth: usize,
+    numbers: Vec<(usize, String)>,
+}
+
+/// Collects the numbers of a tree with their nesting depth, counting the expressions it visits.
+#[derive(Default)]
+struct NumberCollector {
+    expressions: usize,
+}
+
+impl NumberCollector {
+    fn visit_expr(&mut self, node: &Node, ctx: &mut Scope, source: &str) {
+        self.expressions += 1;
+        ctx.depth += 1;
+        self.visit_named_children(node, ctx, source);
+        ctx.depth -= 1;
+    }
+}
+
+impl CalcVisitor for NumberCollector {
+    type ReturnType = ();
+    type Context = Scope;
+
+    fn visit_root(&mut self, node: &Node, ctx: &mut Scope, source: &str) {
+        self.visit_named_children(node, ctx, source);
+    }
+
+    fn visit_number(&mut self, node: &Node, ctx: &mut Scope, source: &str) {
+        ctx.numbers
+            .push((ctx.depth, source[node.byte_range()].to_string()));
+    }
+
+    fn visit_add_expr(&mut self, node: &Node, ctx: &mut Scope, source: &str) {
+        self.visit_expr(node, ctx, source);
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node, ctx: &mut Scope, source: &str) {
+        self.visit_expr(node, ctx, source);
+    }
+
+    fn visit_paren_expr(&mut self, node: &Node, ctx: &mut Scope, source: &str) {
+        self.visit_expr(node, ctx, source);
+    }
+}
+
+/// Sums the numbers of a tree into the context, without any state of its own.
+struct Summer;
+
+impl CalcRefVisitor for Summer {
+    type ReturnType = ();
+    type Context = f64;
+
+    fn visit_root(&self, node: &Node, ctx: &mut f64, source: &str) {
+        self.visit(&node.named_child(0).unwrap(), ctx, source);
+    }
+
+    fn visit_number(&self, node: &Node, ctx: &mut f64, source: &str) {
+        *ctx += source[node.byte_range()].parse::<f64>().unwrap();
+    }
+
+    fn visit_add_expr(&self, node: &Node, ctx: &mut f64, source: &str) {
+        self.visit(&node.child_by_field_name("lhs").unwrap(), ctx, source);
+        self.visit(&node.child_by_field_name("rhs").unwrap(), ctx, source);
+    }
+}
+
+#[test]
+fn test_context_is_threaded_through_dispatch() {
+    let source = "1 + 2 * (3)";
+    let tree = parse(source);
+
+    let mut visitor = NumberCollector::default();
+    let mut scope = Scope::default();
+    visitor.visit(&tree.root_node(), &mut scope, source);
+
+    assert_eq!(visitor.expressions, 3);
+    assert_eq!(scope.depth, 0);
+    assert_eq!(
+        scope.numbers,
+        vec![
+            (1, "1".to_string()),
+            (2, "2".to_string()),
+            (3, "3".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_context_in_immutable_flavor() {
+    let source = "1 + 2";
+    let tree = parse(source);
+
+    let mut sum = 0.0;
+    Summer.visit(&tree.root_node(), &mut sum, source);
+    assert_eq!(sum, 3.0);
+}
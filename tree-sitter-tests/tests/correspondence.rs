@@ -0,0 +1,44 @@
+mod common;
+
+use common::parser;
+use tree_sitter::{InputEdit, Point};
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", correspondence)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_correspondence_pairs_unchanged_nodes() {
+    let mut parser = parser();
+
+    let mut old_tree = parser.parse("(1 * 2) + 3", None).expect("Could not parse");
+    // Replace "3" with "45".
+    let edit = InputEdit {
+        start_byte: 10,
+        old_end_byte: 11,
+        new_end_byte: 12,
+        start_position: Point::new(0, 10),
+        old_end_position: Point::new(0, 11),
+        new_end_position: Point::new(0, 12),
+    };
+    old_tree.edit(&edit);
+    let new_tree = parser
+        .parse("(1 * 2) + 45", Some(&old_tree))
+        .expect("Could not parse");
+
+    let correspondence = NodeCorrespondence::new(&old_tree, &new_tree, &[edit]);
+
+    let old_paren = old_tree.root_node().child(0).unwrap().child(0).unwrap();
+    let new_paren = correspondence.new_node(&old_paren).unwrap();
+    assert_eq!(new_paren.kind(), "paren_expr");
+    assert_eq!(correspondence.old_node(&new_paren), Some(old_paren));
+
+    // The parenthesized expression with its 6 descendants and the "+" token are unchanged.
+    assert_eq!(correspondence.len(), 8);
+    assert!(correspondence
+        .pairs()
+        .all(|(old, new)| old.kind() == new.kind() && old.byte_range() == new.byte_range()));
+
+    let new_number = new_tree.root_node().child(0).unwrap().child(2).unwrap();
+    assert!(correspondence.old_node(&new_number).is_none());
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", coverage)]
+pub trait CalcVisitor {}
+
+struct Evaluator;
+
+impl CalcVisitor for Evaluator {
+    type ReturnType = i64;
+
+    fn visit_root(&mut self, node: &Node) -> Self::ReturnType {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> Self::ReturnType {
+        node.byte_range().len() as i64
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> Self::ReturnType {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            + self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+}
+
+#[test]
+fn test_coverage_tracker_counts_dispatches() {
+    let mut tracker = CoverageTracker::new(Evaluator);
+    for source in ["1 + 22", "333"] {
+        let parsed = parse(source);
+        tracker.visit(&parsed.root_node());
+    }
+
+    assert_eq!(tracker.hits("visit_root"), 2);
+    assert_eq!(tracker.hits("visit_number"), 3);
+    assert_eq!(tracker.hits("visit_add_expr"), 1);
+
+    let unexercised = tracker.unexercised();
+    assert!(unexercised.contains(&"visit_mul_expr"));
+    assert!(!unexercised.contains(&"visit_add_expr"));
+    assert_eq!(
+        unexercised.len(),
+        CoverageTracker::<Evaluator>::METHODS.len() - 3
+    );
+
+    // Visits outside the tracker are not counted.
+    let parsed = parse("1");
+    tracker.inner.visit(&parsed.root_node());
+    assert_eq!(tracker.hits("visit_root"), 2);
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::{parse, Calculator};
+use tree_sitter::{Node, TreeCursor};
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", cursor)]
+pub trait CalcVisitor {}
+
+#[visitor_trait("src/node-types.json", cursor, by_value, named_only, unknown = "skip")]
+pub trait ByValueVisitor {}
+
+impl CalcVisitor for Calculator<'_> {
+    type ReturnType = f64;
+
+    fn visit_cursor_root(&mut self, cursor: &mut TreeCursor) -> f64 {
+        self.visit_cursor_children(cursor)[0]
+    }
+
+    fn visit_cursor_add_expr(&mut self, cursor: &mut TreeCursor) -> f64 {
+        cursor.goto_first_child();
+        let lhs = self.visit_cursor(cursor);
+        cursor.goto_next_sibling();
+        cursor.goto_next_sibling();
+        let rhs = self.visit_cursor(cursor);
+        cursor.goto_parent();
+        lhs + rhs
+    }
+
+    // Not overridden for the cursor, so `visit_cursor_number()` forwards here.
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.number(node)
+    }
+}
+
+/// Collects the text of the numbers of a tree, borrowing from the source.
+struct Numbers<'tree> {
+    source: &'tree str,
+}
+
+impl<'tree> ByValueVisitor<'tree> for Numbers<'tree> {
+    type ReturnType = Vec<&'tree str>;
+
+    fn visit_cursor_root(&mut self, cursor: &mut TreeCursor<'tree>) -> Self::ReturnType {
+        self.visit_cursor_children(cursor).concat()
+    }
+
+    fn visit_cursor_add_expr(&mut self, cursor: &mut TreeCursor<'tree>) -> Self::ReturnType {
+        self.visit_cursor_children(cursor).concat()
+    }
+
+    fn visit_number(&mut self, node: Node<'tree>) -> Self::ReturnType {
+        vec![&self.source[node.byte_range()]]
+    }
+}
+
+#[test]
+fn test_visit_cursor() {
+    let source = "1 + 2 + 39";
+    let tree = parse(source);
+    let mut cursor = tree.walk();
+    let result = Calculator { source }.visit_cursor(&mut cursor);
+    assert_eq!(result, 42.0);
+    // The cursor is left where it was.
+    assert_eq!(cursor.node(), tree.root_node());
+}
+
+#[test]
+fn test_visit_cursor_children() {
+    let source = "1 + 23";
+    let tree = parse(source);
+    let mut cursor = tree.walk();
+    let numbers = Numbers { source }.visit_cursor(&mut cursor);
+    assert_eq!(numbers, ["1", "23"]);
+    assert_eq!(cursor.node(), tree.root_node());
+}
+
+#[test]
+#[should_panic(expected = "unknown node kind: ERROR")]
+fn test_visit_cursor_unknown() {
+    let tree = parse("+");
+    let mut cursor = tree.walk();
+    Calculator { source: "+" }.visit_cursor(&mut cursor);
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", defaults, visit_children)]
+pub trait CalcVisitor {}
+
+#[visitor_trait("src/node-types.json", defaults, fallible)]
+pub trait FallibleVisitor {}
+
+pub mod named {
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait("src/node-types.json", defaults, named_only)]
+    pub trait NamedVisitor {}
+
+    #[visitor_trait("src/node-types.json", defaults, named_only, fallible)]
+    pub trait FallibleNamedVisitor {}
+}
+
+/// Collects the numbers of the operands of additions, ignoring every other kind.
+struct AddedNumbers<'a> {
+    source: &'a str,
+    numbers: Vec<&'a str>,
+}
+
+impl CalcVisitor for AddedNumbers<'_> {
+    type ReturnType = usize;
+
+    fn visit_root(&mut self, node: &Node) -> usize {
+        self.visit_children(node).into_iter().sum()
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> usize {
+        for child in self.visit_named_children(node) {
+            assert_eq!(child, 0);
+        }
+        for field in ["lhs", "rhs"] {
+            let operand = node.child_by_field_name(field).unwrap();
+            if operand.kind() == "number" {
+                self.numbers.push(&self.source[operand.byte_range()]);
+            }
+        }
+        1
+    }
+}
+
+struct Nothing;
+
+impl FallibleVisitor for Nothing {
+    type ReturnType = Vec<u8>;
+    type Error = UnimplementedNode;
+}
+
+#[test]
+fn test_defaults_return_default() {
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let mut visitor = AddedNumbers {
+        source,
+        numbers: Vec::new(),
+    };
+    assert_eq!(visitor.visit(&tree.root_node()), 1);
+    assert_eq!(visitor.numbers, ["1"]);
+
+    let mul_expr = tree.root_node().child(0).unwrap().child(2).unwrap();
+    assert_eq!(visitor.visit(&mul_expr), 0);
+    assert_eq!(visitor.visit(&mul_expr.child(1).unwrap()), 0);
+}
+
+#[test]
+fn test_defaults_with_fallible() {
+    let tree = parse("1 + 2");
+    assert_eq!(Nothing.visit(&tree.root_node()), Ok(Vec::new()));
+}
+
+/// Implements the visitors of the `named` module, which are ambiguous with the others.
+struct Empty;
+
+impl named::NamedVisitor for Empty {
+    type ReturnType = Vec<u8>;
+}
+
+impl named::FallibleNamedVisitor for Empty {
+    type ReturnType = Vec<u8>;
+    type Error = named::UnimplementedNode;
+}
+
+#[test]
+#[should_panic(expected = "unknown node kind: +")]
+fn test_defaults_unknown_kinds_panic() {
+    use named::NamedVisitor;
+
+    // The anonymous `+` is of an unknown kind with `named_only`.
+    let tree = parse("1 + 2");
+    let plus = tree.root_node().child(0).unwrap().child(1).unwrap();
+    assert_eq!(Empty.visit(&tree.root_node()), Vec::new());
+    Empty.visit(&plus);
+}
+
+#[test]
+fn test_defaults_unknown_kinds_are_errors_with_fallible() {
+    use named::FallibleNamedVisitor;
+
+    let tree = parse("1 + 2");
+    let plus = tree.root_node().child(0).unwrap().child(1).unwrap();
+    assert_eq!(Empty.visit(&tree.root_node()), Ok(Vec::new()));
+    assert_eq!(Empty.visit(&plus).unwrap_err().kind, "+");
+}
@@ -0,0 +1,270 @@
+mod common;
+
+use common::{parse, Calculator};
+use tree_sitter::Node;
+use tree_sitter_visitor::{visitor_trait, VisitorDelegate};
+
+#[visitor_trait("src/node-types.json")]
+pub trait CalcVisitor {}
+
+common::calculator!(CalcVisitor);
+
+#[derive(VisitorDelegate)]
+#[visitor_delegate(CalcVisitor, "src/node-types.json", overrides(visit_root))]
+struct Counting<V> {
+    #[delegate]
+    inner: V,
+    roots: usize,
+}
+
+impl<V: CalcVisitor> Counting<V> {
+    fn visit_root(&mut self, node: &Node) -> V::ReturnType {
+        self.roots += 1;
+        self.inner.visit_root(node)
+    }
+}
+
+#[test]
+fn test_delegate_forwards_to_inner_visitor() {
+    let source = "1 + 2";
+    let parsed = parse(source);
+
+    let mut visitor = Counting {
+        inner: Calculator { source },
+        roots: 0,
+    };
+
+    assert_eq!(visitor.visit(&parsed.root_node()), 3.0);
+    assert_eq!(visitor.roots, 1);
+}
+
+pub mod grouped {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::{visitor_trait, VisitorDelegate};
+
+    #[visitor_trait(
+        "src/node-types.json",
+        named_only,
+        group(binary = ["add_expr", "sub_expr", "mul_expr", "div_expr"])
+    )]
+    pub trait GroupedVisitor {}
+
+    pub struct Counter;
+
+    impl GroupedVisitor for Counter {
+        type ReturnType = usize;
+
+        fn visit_root(&mut self, node: &Node) -> usize {
+            self.visit(&node.named_child(0).unwrap())
+        }
+
+        fn visit_number(&mut self, _node: &Node) -> usize {
+            0
+        }
+
+        fn visit_paren_expr(&mut self, node: &Node) -> usize {
+            self.visit(&node.named_child(0).unwrap())
+        }
+
+        fn visit_binary(&mut self, node: &Node) -> usize {
+            let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+            let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+
+            lhs + rhs + 1
+        }
+    }
+
+    #[derive(VisitorDelegate)]
+    #[visitor_delegate(
+        GroupedVisitor,
+        "src/node-types.json",
+        named_only,
+        group(binary = ["add_expr", "sub_expr", "mul_expr", "div_expr"])
+    )]
+    pub struct Wrapper(pub Counter);
+}
+
+#[test]
+fn test_delegate_forwards_group_methods() {
+    use grouped::{Counter, GroupedVisitor, Wrapper};
+
+    let parsed = parse("1 + 2 * (3 - 4)");
+
+    assert_eq!(Wrapper(Counter).visit(&parsed.root_node()), 3);
+}
+
+pub mod error_hooks {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::{visitor_trait, VisitorDelegate};
+
+    #[visitor_trait("src/node-types.json", error_hooks, defaults, visit_children)]
+    pub trait CheckedVisitor {}
+
+    #[derive(Default)]
+    pub struct SyntaxChecker {
+        pub problems: Vec<String>,
+    }
+
+    impl CheckedVisitor for SyntaxChecker {
+        type ReturnType = ();
+
+        fn visit_root(&mut self, node: &Node) {
+            self.visit_children(node);
+        }
+
+        fn visit_add_expr(&mut self, node: &Node) {
+            self.visit_children(node);
+        }
+
+        fn visit_paren_expr(&mut self, node: &Node) {
+            self.visit_children(node);
+        }
+
+        fn visit_error(&mut self, node: &Node) {
+            self.problems
+                .push(format!("error at {}", node.start_byte()));
+        }
+
+        fn visit_missing(&mut self, node: &Node) {
+            self.problems
+                .push(format!("missing {} at {}", node.kind(), node.start_byte()));
+        }
+    }
+
+    #[derive(VisitorDelegate)]
+    #[visitor_delegate(
+        CheckedVisitor,
+        "src/node-types.json",
+        error_hooks,
+        defaults,
+        visit_children
+    )]
+    pub struct Wrapper(pub SyntaxChecker);
+}
+
+#[test]
+fn test_delegate_forwards_error_hooks() {
+    use error_hooks::{CheckedVisitor, SyntaxChecker, Wrapper};
+
+    let mut wrapper = Wrapper(SyntaxChecker::default());
+    let parsed = parse("1 + (2");
+    wrapper.visit(&parsed.root_node());
+    let parsed = parse("1 + ) 2");
+    wrapper.visit(&parsed.root_node());
+
+    assert_eq!(wrapper.0.problems, ["missing ) at 6", "error at 4"]);
+}
+
+pub mod extras {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::{visitor_trait, VisitorDelegate};
+
+    // The calc grammar with the parentheses declared as extras, standing in for comments.
+    #[visitor_trait("tests/calc-extras-node-types.json", extras = "hook")]
+    pub trait ExtrasVisitor {}
+
+    #[derive(Default)]
+    pub struct Kinds(pub Vec<&'static str>);
+
+    impl ExtrasVisitor for Kinds {
+        type ReturnType = ();
+
+        fn visit_paren_expr(&mut self, node: &Node) {
+            self.0.push(node.kind());
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                self.visit(&child);
+            }
+        }
+
+        fn visit_number(&mut self, node: &Node) {
+            self.0.push(node.kind());
+        }
+
+        fn visit_extra(&mut self, _node: &Node) {
+            self.0.push("extra");
+        }
+    }
+
+    #[derive(VisitorDelegate)]
+    #[visitor_delegate(ExtrasVisitor, "tests/calc-extras-node-types.json", extras = "hook")]
+    pub struct Wrapper(pub Kinds);
+}
+
+#[test]
+fn test_delegate_forwards_extra_hook() {
+    use extras::{ExtrasVisitor, Kinds, Wrapper};
+
+    let parsed = parse("(1)");
+    let paren_expr = parsed.root_node().child(0).unwrap();
+
+    let mut wrapper = Wrapper(Kinds::default());
+    wrapper.visit(&paren_expr.child(0).unwrap());
+    assert_eq!(wrapper.0 .0, ["extra"]);
+}
+
+pub mod other_hooks {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::{visitor_trait, VisitorDelegate};
+
+    #[visitor_trait("src/node-types.json", field_hooks, after_visit, revisit)]
+    pub trait RecordedVisitor {}
+
+    #[derive(Default)]
+    pub struct Recorder {
+        pub lhs: usize,
+        pub visited: Vec<&'static str>,
+        pub invalidated: Vec<&'static str>,
+    }
+
+    impl RecordedVisitor for Recorder {
+        type ReturnType = ();
+
+        fn visit_number(&mut self, _node: &Node) {}
+
+        fn visit_field_lhs(&mut self, _parent: &Node, node: &Node) {
+            self.lhs += 1;
+            self.visit(node)
+        }
+
+        fn after_visit(&mut self, node: &Node, _result: &()) {
+            self.visited.push(node.kind());
+        }
+
+        fn invalidate(&mut self, node: &Node) {
+            self.invalidated.push(node.kind());
+        }
+    }
+
+    #[derive(VisitorDelegate)]
+    #[visitor_delegate(
+        RecordedVisitor,
+        "src/node-types.json",
+        field_hooks,
+        after_visit,
+        revisit
+    )]
+    pub struct Wrapper(pub Recorder);
+}
+
+#[test]
+fn test_delegate_forwards_other_hooks() {
+    use other_hooks::{RecordedVisitor, Recorder, Wrapper};
+
+    let parsed = parse("1 + 2");
+    let add_expr = parsed.root_node().child(0).unwrap();
+    let lhs = add_expr.child_by_field_name("lhs").unwrap();
+
+    let mut wrapper = Wrapper(Recorder::default());
+    wrapper.visit_fields(&add_expr);
+    assert_eq!(wrapper.0.lhs, 1);
+    wrapper.visit_field_lhs(&add_expr, &lhs);
+    assert_eq!(wrapper.0.lhs, 2);
+
+    wrapper.0.visited.clear();
+    wrapper.visit(&lhs);
+    assert_eq!(wrapper.0.visited, ["number"]);
+
+    wrapper.invalidate(&add_expr);
+    assert_eq!(wrapper.0.invalidated, ["add_expr"]);
+}
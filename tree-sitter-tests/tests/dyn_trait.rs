@@ -0,0 +1,81 @@
+mod common;
+
+use common::parse;
+use std::collections::BTreeMap;
+
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", dyn_trait, named_only)]
+pub trait CalcVisitor {}
+
+struct Evaluator<'s> {
+    source: &'s str,
+}
+
+impl CalcVisitor for Evaluator<'_> {
+    type ReturnType = String;
+
+    fn visit_root(&mut self, node: &Node) -> String {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> String {
+        let lhs: f64 = self.visit(&node.named_child(0).unwrap()).parse().unwrap();
+        let rhs: f64 = self.visit(&node.named_child(1).unwrap()).parse().unwrap();
+        (lhs + rhs).to_string()
+    }
+
+    fn visit_number(&mut self, node: &Node) -> String {
+        self.source[node.byte_range()].to_string()
+    }
+}
+
+/// Names the kinds of the nodes it visits.
+struct KindNamer;
+
+impl CalcVisitor for KindNamer {
+    type ReturnType = String;
+
+    fn visit_root(&mut self, node: &Node) -> String {
+        format!("root({})", self.visit(&node.named_child(0).unwrap()))
+    }
+
+    fn visit_add_expr(&mut self, _node: &Node) -> String {
+        "add_expr".to_string()
+    }
+}
+
+/// Visits a tree with any visitor, as a generic driver would.
+fn run<V: CalcVisitor>(visitor: &mut V, node: &Node) -> V::ReturnType {
+    visitor.visit(node)
+}
+
+#[test]
+fn test_dyn_trait_objects() {
+    let source = "1 + 2";
+    let tree = parse(source);
+    let root = tree.root_node();
+
+    let mut registry: BTreeMap<&str, Box<dyn DynCalcVisitor<String>>> = BTreeMap::new();
+    registry.insert("evaluate", Box::new(Evaluator { source }));
+    registry.insert("kinds", Box::new(KindNamer));
+
+    let results: Vec<_> = registry
+        .iter_mut()
+        .map(|(name, visitor)| (*name, visitor.dyn_visit(&root)))
+        .collect();
+    assert_eq!(
+        results,
+        [
+            ("evaluate", "3".to_string()),
+            ("kinds", "root(add_expr)".to_string())
+        ]
+    );
+
+    // The boxed visitors implement the visitor trait.
+    let visitor = registry.get_mut("evaluate").unwrap();
+    assert_eq!(run(visitor, &root), "3");
+    let add_expr = root.named_child(0).unwrap();
+    assert_eq!(CalcVisitor::visit_add_expr(visitor, &add_expr), "3");
+}
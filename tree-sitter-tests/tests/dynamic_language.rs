@@ -0,0 +1,56 @@
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", libloading)]
+pub trait CalcVisitor {}
+
+struct Numbers;
+
+impl CalcVisitor for Numbers {
+    type ReturnType = usize;
+
+    fn visit_number(&mut self, _node: &Node) -> usize {
+        1
+    }
+}
+
+#[test]
+fn test_load_missing_library() {
+    let result = unsafe { DynamicLanguage::load("libtree-sitter-missing.so", "tree_sitter_calc") };
+    match result {
+        Err(err @ DynamicLanguageError::Load(_)) => {
+            assert!(err.to_string().starts_with("could not load the language: "));
+        }
+        Err(other) => panic!("expected a load error, got {}", other),
+        Ok(_) => panic!("expected a load error"),
+    }
+}
+
+#[test]
+fn test_mismatch_error_display() {
+    let mismatch = DynamicLanguageError::Mismatch(KindMismatch {
+        missing: vec!["number"],
+        unexpected: Vec::new(),
+    });
+    assert_eq!(
+        mismatch.to_string(),
+        KindMismatch {
+            missing: vec!["number"],
+            unexpected: Vec::new(),
+        }
+        .to_string()
+    );
+}
+
+#[test]
+fn test_kind_table_of_the_static_language() {
+    // A `DynamicLanguage` validates the language it loaded like this.
+    let language = tree_sitter_tests::language();
+    assert!(KindTable::try_for_language(language.clone()).is_ok());
+
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse("1", None).unwrap();
+    let number = tree.root_node().child(0).unwrap();
+    assert_eq!(Numbers.visit(&number), 1);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", emitter)]
+pub trait CalcVisitor {}
+
+struct TokenEmitter<'t> {
+    src: &'t str,
+}
+
+impl<'t> CalcEmitter for TokenEmitter<'t> {
+    type Item = &'t str;
+
+    fn emit_number(&mut self, node: &Node, sink: &mut dyn FnMut(&'t str)) {
+        sink(&self.src[node.byte_range()]);
+    }
+
+    fn emit_PLUS(&mut self, _node: &Node, sink: &mut dyn FnMut(&'t str)) {
+        sink("plus");
+    }
+}
+
+#[test]
+fn test_emitter_streams_items() {
+    let src = "1 + (2 * 3)";
+    let parsed = parse(src);
+
+    let mut emitter = TokenEmitter { src };
+    assert_eq!(
+        emitter.collect(&parsed.root_node()),
+        vec!["1", "plus", "2", "3"]
+    );
+}
+
+pub mod named {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait("src/node-types.json", emitter, named_only)]
+    pub trait NamedVisitor {}
+
+    pub struct NumberEmitter<'t> {
+        pub src: &'t str,
+    }
+
+    impl<'t> NamedEmitter for NumberEmitter<'t> {
+        type Item = &'t str;
+
+        fn emit_number(&mut self, node: &Node, sink: &mut dyn FnMut(&'t str)) {
+            sink(&self.src[node.byte_range()]);
+        }
+    }
+}
+
+#[test]
+fn test_emitter_recurses_through_filtered_kinds() {
+    use named::{NamedEmitter, NumberEmitter};
+
+    let src = "1 + (2 * 3)";
+    let parsed = parse(src);
+
+    let mut emitter = NumberEmitter { src };
+    assert_eq!(emitter.collect(&parsed.root_node()), vec!["1", "2", "3"]);
+}
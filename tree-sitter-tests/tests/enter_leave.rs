@@ -0,0 +1,90 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", flavors(enter_leave))]
+pub trait CalcVisitor {}
+
+/// Records the events of a walk, and the maximum nesting of parentheses.
+#[derive(Default)]
+struct Events {
+    events: Vec<String>,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl CalcWalker for Events {
+    fn enter_add_expr(&mut self, _node: &Node<'_>) {
+        self.events.push("enter add_expr".to_string());
+    }
+
+    fn leave_add_expr(&mut self, _node: &Node<'_>) {
+        self.events.push("leave add_expr".to_string());
+    }
+
+    fn enter_number(&mut self, node: &Node<'_>) {
+        self.events.push(format!("number at {}", node.start_byte()));
+    }
+
+    fn enter_paren_expr(&mut self, _node: &Node<'_>) {
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+    }
+
+    fn leave_paren_expr(&mut self, _node: &Node<'_>) {
+        self.depth -= 1;
+    }
+}
+
+#[test]
+fn test_enter_and_leave_in_order() {
+    let tree = parse("1 + 2 + 3");
+    let mut walker = Events::default();
+    walker.walk(tree.root_node());
+
+    assert_eq!(
+        walker.events,
+        [
+            "enter add_expr",
+            "enter add_expr",
+            "number at 0",
+            "number at 4",
+            "leave add_expr",
+            "number at 8",
+            "leave add_expr",
+        ]
+    );
+}
+
+#[test]
+fn test_walk_stays_in_the_subtree() {
+    let tree = parse("((1) + ((2)))");
+    let mut walker = Events::default();
+    walker.walk(tree.root_node());
+    assert_eq!((walker.depth, walker.max_depth), (0, 3));
+
+    // Walking the left operand does not continue with its siblings.
+    let add_expr = tree
+        .root_node()
+        .named_child(0)
+        .unwrap()
+        .named_child(0)
+        .unwrap();
+    let mut walker = Events::default();
+    walker.walk(add_expr.child_by_field_name("lhs").unwrap());
+    assert_eq!(walker.events, ["number at 2"]);
+    assert_eq!((walker.depth, walker.max_depth), (0, 1));
+}
+
+#[test]
+fn test_walk_deep_tree_without_recursion() {
+    let depth = 10_000;
+    let source = format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+    let tree = parse(&source);
+    let mut walker = Events::default();
+    walker.walk(tree.root_node());
+    assert_eq!((walker.depth, walker.max_depth), (0, depth));
+    assert_eq!(walker.events, [format!("number at {}", depth)]);
+}
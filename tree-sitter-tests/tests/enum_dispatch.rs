@@ -0,0 +1,109 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait(
+    "src/node-types.json",
+    enum_dispatch(AnyVisitor = [KindPrinter, ChildCounter])
+)]
+pub trait CalcVisitor {}
+
+pub struct KindPrinter;
+
+impl CalcVisitor for KindPrinter {
+    type ReturnType = String;
+
+    fn visit_root(&mut self, node: &Node) -> String {
+        node.kind().to_string()
+    }
+}
+
+pub struct ChildCounter;
+
+impl CalcVisitor for ChildCounter {
+    type ReturnType = String;
+
+    fn visit_root(&mut self, node: &Node) -> String {
+        node.child_count().to_string()
+    }
+}
+
+#[test]
+fn test_enum_dispatch_selects_variant() {
+    let parsed = parse("1 + 2");
+    let root = parsed.root_node();
+
+    let mut visitors: Vec<AnyVisitor> = vec![KindPrinter.into(), ChildCounter.into()];
+    let results: Vec<_> = visitors.iter_mut().map(|v| v.visit(&root)).collect();
+
+    assert_eq!(results, vec!["root", "1"]);
+}
+
+pub mod hooks {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait(
+        "src/node-types.json",
+        token_hook,
+        error_hooks,
+        enum_dispatch(AnyHookVisitor = [Tokens, Errors])
+    )]
+    pub trait HookVisitor {}
+
+    pub struct Tokens;
+
+    impl HookVisitor for Tokens {
+        type ReturnType = String;
+
+        fn visit_token(&mut self, _node: &Node, kind: &str) -> String {
+            format!("token {}", kind)
+        }
+
+        fn visit_error(&mut self, _node: &Node) -> String {
+            "error".to_string()
+        }
+    }
+
+    pub struct Errors;
+
+    impl HookVisitor for Errors {
+        type ReturnType = String;
+
+        fn visit_token(&mut self, _node: &Node, _kind: &str) -> String {
+            "token".to_string()
+        }
+
+        fn visit_error(&mut self, node: &Node) -> String {
+            format!("error at {}", node.start_byte())
+        }
+    }
+}
+
+#[test]
+fn test_enum_dispatch_forwards_hooks() {
+    use hooks::{AnyHookVisitor, Errors, HookVisitor, Tokens};
+
+    let parsed = parse("1 + ) 2");
+    let mut cursor = parsed.root_node().walk();
+    while !cursor.node().is_error() {
+        if !cursor.goto_first_child() {
+            while !cursor.goto_next_sibling() {
+                cursor.goto_parent();
+            }
+        }
+    }
+    let error = cursor.node();
+    let plus = parsed.root_node().child(0).unwrap().child(1).unwrap();
+    assert_eq!(plus.kind(), "+");
+
+    let mut tokens = AnyHookVisitor::from(Tokens);
+    assert_eq!(tokens.visit_token(&plus, "+"), "token +");
+    assert_eq!(tokens.visit_error(&error), "error");
+
+    let mut errors = AnyHookVisitor::from(Errors);
+    assert_eq!(errors.visit_token(&plus, "+"), "token");
+    assert_eq!(errors.visit_error(&error), "error at 4");
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+// The build script copies `node-types.json` to `OUT_DIR` and sets `CALC_NODE_TYPES` to its path.
+#[visitor_trait("${OUT_DIR}/node-types.json")]
+pub trait OutDirVisitor {}
+
+#[visitor_trait(env = "CALC_NODE_TYPES", named_only)]
+pub trait EnvVisitor {}
+
+struct Counter {
+    numbers: usize,
+}
+
+impl OutDirVisitor for Counter {
+    type ReturnType = ();
+
+    fn visit_root(&mut self, node: &Node) {
+        OutDirVisitor::visit(self, &node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, _node: &Node) {
+        self.numbers += 1;
+    }
+}
+
+impl EnvVisitor for Counter {
+    type ReturnType = ();
+
+    fn visit_root(&mut self, node: &Node) {
+        EnvVisitor::visit(self, &node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, _node: &Node) {
+        self.numbers += 1;
+    }
+}
+
+#[test]
+fn test_paths_from_the_environment() {
+    let tree = parse("1");
+
+    let mut counter = Counter { numbers: 0 };
+    OutDirVisitor::visit(&mut counter, &tree.root_node());
+    EnvVisitor::visit(&mut counter, &tree.root_node());
+    assert_eq!(counter.numbers, 2);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", error_hooks, defaults, visit_children)]
+pub trait CalcVisitor {}
+
+/// Reports the syntax problems of a tree, visiting every node.
+#[derive(Default)]
+struct SyntaxChecker {
+    problems: Vec<String>,
+}
+
+impl CalcVisitor for SyntaxChecker {
+    type ReturnType = ();
+
+    fn visit_root(&mut self, node: &Node) {
+        self.visit_children(node);
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) {
+        self.visit_children(node);
+    }
+
+    fn visit_paren_expr(&mut self, node: &Node) {
+        self.visit_children(node);
+    }
+
+    fn visit_error(&mut self, node: &Node) {
+        self.problems
+            .push(format!("error at {}", node.start_byte()));
+    }
+
+    fn visit_missing(&mut self, node: &Node) {
+        self.problems
+            .push(format!("missing {} at {}", node.kind(), node.start_byte()));
+    }
+}
+
+fn problems(source: &str) -> Vec<String> {
+    let tree = parse(source);
+    let mut checker = SyntaxChecker::default();
+    checker.visit(&tree.root_node());
+    checker.problems
+}
+
+#[test]
+fn test_well_formed() {
+    assert!(problems("1 + (2)").is_empty());
+}
+
+#[test]
+fn test_missing_node() {
+    assert_eq!(problems("1 + (2"), ["missing ) at 6"]);
+}
+
+#[test]
+fn test_error_node() {
+    assert_eq!(problems("1 + ) 2"), ["error at 4"]);
+}
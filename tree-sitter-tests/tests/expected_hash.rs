@@ -0,0 +1,25 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", expected_hash = "41929fe9be8a1d4c")]
+trait CalcVisitor {}
+
+struct Kinds;
+
+impl CalcVisitor for Kinds {
+    type ReturnType = &'static str;
+
+    fn visit_number(&mut self, _node: &tree_sitter::Node) -> &'static str {
+        "number"
+    }
+}
+
+#[test]
+fn test_expected_hash() {
+    let tree = parse("42");
+    let number = tree.root_node().child(0).unwrap();
+
+    assert_eq!(Kinds.visit(&number), "number");
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::parse;
+use tree_sitter::{Node, TreeCursor};
+use tree_sitter_visitor::visitor_trait;
+
+// The calc grammar with the parentheses declared as extras, standing in for comments.
+mod skip {
+    use super::*;
+
+    #[visitor_trait(
+        "tests/calc-extras-node-types.json",
+        extras = "skip",
+        visit_children,
+        cursor
+    )]
+    pub trait CalcVisitor {}
+}
+
+mod hook {
+    use super::*;
+
+    #[visitor_trait("tests/calc-extras-node-types.json", extras = "hook")]
+    pub trait CalcVisitor {}
+}
+
+/// Records the kinds of the nodes it visits.
+#[derive(Default)]
+struct Kinds(Vec<&'static str>);
+
+impl skip::CalcVisitor for Kinds {
+    type ReturnType = ();
+
+    fn visit_root(&mut self, node: &Node) {
+        self.0.push(node.kind());
+        self.visit_children(node);
+    }
+
+    fn visit_paren_expr(&mut self, node: &Node) {
+        self.0.push(node.kind());
+        self.visit_children(node);
+    }
+
+    fn visit_cursor_paren_expr(&mut self, cursor: &mut TreeCursor) {
+        self.0.push(cursor.node().kind());
+        self.visit_cursor_children(cursor);
+    }
+
+    fn visit_number(&mut self, node: &Node) {
+        self.0.push(node.kind());
+    }
+
+    fn visit_LPAREN(&mut self, node: &Node) {
+        self.0.push(node.kind());
+    }
+}
+
+impl hook::CalcVisitor for Kinds {
+    type ReturnType = ();
+
+    fn visit_paren_expr(&mut self, node: &Node) {
+        self.0.push(node.kind());
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            hook::CalcVisitor::visit(self, &child);
+        }
+    }
+
+    fn visit_number(&mut self, node: &Node) {
+        self.0.push(node.kind());
+    }
+
+    fn visit_extra(&mut self, node: &Node) {
+        self.0.push("extra");
+        assert!(["(", ")"].contains(&node.kind()));
+    }
+}
+
+#[test]
+fn test_skip_extras_in_children() {
+    let tree = parse("(1)");
+    let mut kinds = Kinds::default();
+    skip::CalcVisitor::visit(&mut kinds, &tree.root_node());
+    assert_eq!(kinds.0, ["root", "paren_expr", "number"]);
+
+    let paren_expr = tree.root_node().child(0).unwrap();
+    let mut kinds = Kinds::default();
+    skip::CalcVisitor::visit_cursor(&mut kinds, &mut paren_expr.walk());
+    assert_eq!(kinds.0, ["paren_expr", "number"]);
+
+    // Extras are still dispatched when visited directly.
+    let mut kinds = Kinds::default();
+    skip::CalcVisitor::visit(&mut kinds, &paren_expr.child(0).unwrap());
+    assert_eq!(kinds.0, ["("]);
+}
+
+#[test]
+fn test_hook_extras() {
+    let tree = parse("(1)");
+    let paren_expr = tree.root_node().child(0).unwrap();
+    let mut kinds = Kinds::default();
+    hook::CalcVisitor::visit(&mut kinds, &paren_expr);
+    assert_eq!(kinds.0, ["paren_expr", "extra", "number", "extra"]);
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", fallible, named_only)]
+pub trait CalcVisitor {}
+
+#[derive(Debug, PartialEq)]
+enum EvalError {
+    Unimplemented(UnimplementedNode),
+    DivisionByZero,
+}
+
+impl From<UnimplementedNode> for EvalError {
+    fn from(node: UnimplementedNode) -> Self {
+        EvalError::Unimplemented(node)
+    }
+}
+
+/// Evaluates additions and divisions only.
+struct Evaluator<'s> {
+    source: &'s str,
+}
+
+impl Evaluator<'_> {
+    fn operands(&mut self, node: &Node) -> Result<(f64, f64), EvalError> {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap())?;
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap())?;
+        Ok((lhs, rhs))
+    }
+}
+
+impl CalcVisitor for Evaluator<'_> {
+    type ReturnType = f64;
+    type Error = EvalError;
+
+    fn visit_root(&mut self, node: &Node) -> Result<f64, EvalError> {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> Result<f64, EvalError> {
+        Ok(self.source[node.byte_range()].parse().unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> Result<f64, EvalError> {
+        let (lhs, rhs) = self.operands(node)?;
+        Ok(lhs + rhs)
+    }
+
+    fn visit_div_expr(&mut self, node: &Node) -> Result<f64, EvalError> {
+        let (lhs, rhs) = self.operands(node)?;
+        if rhs == 0.0 {
+            return Err(EvalError::DivisionByZero);
+        }
+        Ok(lhs / rhs)
+    }
+}
+
+fn eval(source: &str) -> Result<f64, EvalError> {
+    let tree = parse(source);
+    Evaluator { source }.visit(&tree.root_node())
+}
+
+#[test]
+fn test_fallible_visitor() {
+    assert_eq!(eval("1 + 6 / 2"), Ok(4.0));
+    assert_eq!(eval("1 / 0"), Err(EvalError::DivisionByZero));
+}
+
+#[test]
+fn test_unimplemented_kinds_are_errors() {
+    let expected = UnimplementedNode {
+        kind: "mul_expr",
+        named: true,
+        byte_range: 4..9,
+    };
+    assert_eq!(
+        expected.to_string(),
+        "unimplemented node kind `mul_expr` at bytes 4..9"
+    );
+    assert_eq!(eval("1 + 2 * 3"), Err(EvalError::Unimplemented(expected)));
+}
+
+#[test]
+fn test_unknown_kinds_are_errors() {
+    // Anonymous nodes are left out with `named_only`, so they are of unknown kinds.
+    let tree = parse("1 + 2");
+    let plus = tree.root_node().named_child(0).unwrap().child(1).unwrap();
+
+    let result = Evaluator { source: "1 + 2" }.visit(&plus);
+    match result {
+        Err(EvalError::Unimplemented(node)) => {
+            assert_eq!((node.kind, node.named, node.byte_range), ("+", false, 2..3))
+        }
+        other => panic!("expected an unimplemented node, got {:?}", other),
+    }
+}
+
+pub mod children {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait(
+        "src/node-types.json",
+        fallible,
+        named_only,
+        visit_children,
+        try_visit_children
+    )]
+    pub trait ChildrenVisitor {}
+
+    /// Sums the operands of additions.
+    pub struct Summer<'s> {
+        pub source: &'s str,
+    }
+
+    impl ChildrenVisitor for Summer<'_> {
+        type ReturnType = f64;
+        type Error = UnimplementedNode;
+
+        fn visit_root(&mut self, node: &Node) -> Result<f64, UnimplementedNode> {
+            Ok(self.try_visit_children(node)?.into_iter().sum())
+        }
+
+        fn visit_number(&mut self, node: &Node) -> Result<f64, UnimplementedNode> {
+            Ok(self.source[node.byte_range()].parse().unwrap())
+        }
+
+        fn visit_add_expr(&mut self, node: &Node) -> Result<f64, UnimplementedNode> {
+            let operands: Result<Vec<f64>, _> =
+                self.visit_named_children(node).into_iter().collect();
+            Ok(operands?.into_iter().sum())
+        }
+    }
+}
+
+#[test]
+fn test_fallible_children() {
+    use children::{ChildrenVisitor, Summer, UnimplementedNode};
+
+    let source = "1 + 2 + 3";
+    let tree = parse(source);
+    assert_eq!(Summer { source }.visit(&tree.root_node()), Ok(6.0));
+
+    // The anonymous `+` is of an unknown kind with `named_only`.
+    let add_expr = tree.root_node().named_child(0).unwrap();
+    let plus = UnimplementedNode {
+        kind: "+",
+        named: false,
+        byte_range: 6..7,
+    };
+    assert_eq!(
+        Summer { source }.try_visit_children(&add_expr),
+        Err(plus.clone())
+    );
+    assert_eq!(
+        Summer { source }.try_visit_children_all(&add_expr),
+        Err(vec![plus])
+    );
+
+    let source = "1 * 2";
+    let tree = parse(source);
+    let result = Summer { source }.visit(&tree.root_node());
+    assert_eq!(result.map_err(|node| node.kind), Err("mul_expr"));
+}
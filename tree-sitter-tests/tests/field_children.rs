@@ -0,0 +1,29 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", field_children)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_field_children_pairs_children_with_fields() {
+    let parsed = parse("1 + (2)");
+    let add_expr = parsed.root_node().child(0).unwrap();
+
+    let children: Vec<_> = FieldChildren::new(&add_expr)
+        .map(|(field, child)| (field, child.kind()))
+        .collect();
+    assert_eq!(
+        children,
+        vec![
+            (Some(FieldName::Lhs), "number"),
+            (None, "+"),
+            (Some(FieldName::Rhs), "paren_expr"),
+        ]
+    );
+
+    assert_eq!(FieldName::from_name("body"), Some(FieldName::Body));
+    assert_eq!(FieldName::Body.to_string(), "body");
+    assert_eq!(FieldChildren::new(&add_expr.child(0).unwrap()).count(), 0);
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", field_hooks, visit_children)]
+pub trait CalcVisitor {}
+
+/// Collects the text of every left-hand side operand, whatever the kind of the expression.
+struct LeftOperands<'a> {
+    source: &'a str,
+    operands: Vec<(&'static str, String)>,
+}
+
+impl CalcVisitor for LeftOperands<'_> {
+    type ReturnType = ();
+
+    fn visit_field_lhs(&mut self, parent: &Node, node: &Node) {
+        self.operands
+            .push((parent.kind(), self.source[node.byte_range()].to_string()));
+        self.visit(node)
+    }
+
+    fn visit_root(&mut self, node: &Node) {
+        self.visit_children(node);
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) {
+        self.visit_fields(node);
+    }
+
+    fn visit_sub_expr(&mut self, node: &Node) {
+        self.visit_fields(node);
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) {
+        self.visit_fields(node);
+    }
+
+    fn visit_div_expr(&mut self, node: &Node) {
+        self.visit_fields(node);
+    }
+
+    fn visit_paren_expr(&mut self, node: &Node) {
+        self.visit_fields(node);
+    }
+
+    fn visit_number(&mut self, _node: &Node) {}
+}
+
+#[test]
+fn test_field_hooks_dispatch_by_field() {
+    let source = "1 * 2 - (3 + 4)";
+    let tree = parse(source);
+    let mut visitor = LeftOperands {
+        source,
+        operands: Vec::new(),
+    };
+    visitor.visit(&tree.root_node());
+    assert_eq!(
+        visitor.operands,
+        [
+            ("sub_expr", "1 * 2".to_string()),
+            ("mul_expr", "1".to_string()),
+            ("add_expr", "3".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_visit_fields_skips_children_outside_fields() {
+    let source = "1 + 2";
+    let tree = parse(source);
+    let add_expr = tree.root_node().child(0).unwrap();
+    let mut visitor = LeftOperands {
+        source,
+        operands: Vec::new(),
+    };
+    // `lhs` and `rhs`, but not the `+` token.
+    assert_eq!(visitor.visit_fields(&add_expr).len(), 2);
+}
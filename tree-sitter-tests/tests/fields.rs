@@ -0,0 +1,35 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", fields)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_fields_resolve_field_ids() {
+    let language = tree_sitter_tests::language();
+    let parsed = parse("1 + 2");
+    let add_expr = parsed.root_node().child(0).unwrap();
+
+    let fields = Fields::for_language(language.clone());
+    assert_eq!(fields, Fields::new(language.clone()));
+    assert_eq!(fields.lhs, language.field_id_for_name("lhs"));
+
+    let rhs = Fields::child(&add_expr, fields.rhs).unwrap();
+    assert_eq!(rhs.byte_range(), 4..5);
+    assert!(Fields::child(&add_expr, fields.body).is_none());
+}
+
+#[test]
+fn test_field_getters_return_children() {
+    let language = tree_sitter_tests::language();
+    let parsed = parse("(1) * 2");
+    let mul_expr = parsed.root_node().child(0).unwrap();
+
+    let fields = Fields::for_language(language);
+    let lhs = fields.lhs(&mul_expr).unwrap();
+    assert_eq!(lhs.kind(), "paren_expr");
+    assert_eq!(fields.body(&lhs).unwrap().kind(), "number");
+    assert!(fields.body(&mul_expr).is_none());
+}
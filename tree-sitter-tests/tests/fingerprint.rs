@@ -0,0 +1,65 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", fingerprint)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_fingerprints_survive_edits_elsewhere() {
+    let old_source = "1 + (2 * 3)";
+    let new_source = "10 + (2 * 3)";
+    let old_tree = parse(old_source);
+    let new_tree = parse(new_source);
+
+    let fingerprint = |tree: &tree_sitter::Tree, source: &str, start: usize, end: usize| {
+        let node = tree
+            .root_node()
+            .named_descendant_for_byte_range(start, end)
+            .unwrap();
+        NodeFingerprint::new(&node, source.as_bytes())
+    };
+
+    let old_mul = fingerprint(&old_tree, old_source, 5, 10);
+    let new_mul = fingerprint(&new_tree, new_source, 6, 11);
+    assert_eq!(old_mul, new_mul);
+    assert_eq!(old_mul.depth, 3);
+
+    let old_lhs = fingerprint(&old_tree, old_source, 0, 1);
+    let new_lhs = fingerprint(&new_tree, new_source, 0, 2);
+    assert_eq!(old_lhs.kind_path, new_lhs.kind_path);
+    assert_ne!(old_lhs, new_lhs);
+
+    let two = fingerprint(&old_tree, old_source, 5, 6);
+    let three = fingerprint(&old_tree, old_source, 9, 10);
+    assert_eq!((two.ordinal, three.ordinal), (0, 1));
+}
+
+#[test]
+fn test_fingerprint_structure_covers_the_subtree_only() {
+    let fingerprint = |source: &str| {
+        let tree = parse(source);
+        let lhs = tree
+            .root_node()
+            .named_child(0)
+            .unwrap()
+            .child_by_field_name("lhs")
+            .unwrap();
+        NodeFingerprint::new(&lhs, source).structure
+    };
+
+    assert_eq!(fingerprint("(1 + 2) * 3"), fingerprint("(1 + 2) * 4"));
+    assert_ne!(fingerprint("(1 + 2) * 3"), fingerprint("(1 + 5) * 3"));
+    assert_ne!(fingerprint("(1 + 2) * 3"), fingerprint("((1 + 2)) * 3"));
+}
+
+#[test]
+fn test_fingerprint_of_deeply_nested_source() {
+    let depth = 100_000;
+    let source = format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+    let tree = parse(&source);
+
+    let fingerprint = NodeFingerprint::new(&tree.root_node(), source.as_str());
+    assert_eq!(fingerprint.depth, 0);
+}
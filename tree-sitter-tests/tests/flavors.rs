@@ -0,0 +1,117 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", flavors(immutable), params(depth: usize))]
+pub trait CalcVisitor {}
+
+/// Evaluates an expression without mutable state, so it can be shared.
+struct Evaluator<'s> {
+    source: &'s str,
+}
+
+impl CalcRefVisitor for Evaluator<'_> {
+    type ReturnType = f64;
+
+    fn visit_root(&self, node: &Node, depth: usize) -> f64 {
+        self.visit(&node.child(0).unwrap(), depth + 1)
+    }
+
+    fn visit_number(&self, node: &Node, _depth: usize) -> f64 {
+        self.source[node.byte_range()].parse().unwrap()
+    }
+
+    fn visit_add_expr(&self, node: &Node, depth: usize) -> f64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap(), depth + 1)
+            + self.visit(&node.child_by_field_name("rhs").unwrap(), depth + 1)
+    }
+
+    fn visit_mul_expr(&self, node: &Node, depth: usize) -> f64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap(), depth + 1)
+            * self.visit(&node.child_by_field_name("rhs").unwrap(), depth + 1)
+    }
+}
+
+#[test]
+fn test_immutable_flavor() {
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+
+    let evaluator = Evaluator { source };
+    let shared = &evaluator;
+    assert_eq!(shared.visit(&tree.root_node(), 0), 7.0);
+    assert_eq!(evaluator.visit(&tree.root_node(), 0), 7.0);
+}
+
+pub mod hooks {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait("src/node-types.json", token_hook, error_hooks, flavors(immutable))]
+    pub trait HookVisitor {}
+
+    /// Renders the kinds of the visited nodes, marking tokens and syntax errors.
+    pub struct Renderer;
+
+    impl Renderer {
+        fn children(&self, node: &Node) -> String {
+            let mut cursor = node.walk();
+            let children: Vec<_> = node
+                .children(&mut cursor)
+                .map(|child| self.visit(&child))
+                .collect();
+            children.join(" ")
+        }
+    }
+
+    impl HookRefVisitor for Renderer {
+        type ReturnType = String;
+
+        fn visit_root(&self, node: &Node) -> String {
+            self.children(node)
+        }
+
+        fn visit_add_expr(&self, node: &Node) -> String {
+            self.children(node)
+        }
+
+        fn visit_paren_expr(&self, node: &Node) -> String {
+            self.children(node)
+        }
+
+        fn visit_number(&self, _node: &Node) -> String {
+            "number".to_string()
+        }
+
+        fn visit_token(&self, _node: &Node, kind: &str) -> String {
+            format!("token {}", kind)
+        }
+
+        fn visit_error(&self, _node: &Node) -> String {
+            "error".to_string()
+        }
+
+        fn visit_missing(&self, node: &Node) -> String {
+            format!("missing {}", node.kind())
+        }
+    }
+}
+
+#[test]
+fn test_immutable_flavor_hooks() {
+    use hooks::{HookRefVisitor, Renderer};
+
+    let tree = parse("1 + (2");
+    assert_eq!(
+        Renderer.visit(&tree.root_node()),
+        "number token + token ( number missing )"
+    );
+
+    let tree = parse("1 + ) 2");
+    assert_eq!(
+        Renderer.visit(&tree.root_node()),
+        "number token + error number"
+    );
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", fn_visitor)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_fn_visitor_dispatches_to_closures() {
+    let src = "1 + 2";
+    let parsed = parse(src);
+    let add_expr = parsed.root_node().child(0).unwrap();
+
+    let mut numbers = 0;
+    let mut visitor = FnVisitor::new()
+        .on("number", |node| {
+            numbers += 1;
+            src[node.byte_range()].to_string()
+        })
+        .otherwise(|node| node.kind().to_string());
+
+    assert_eq!(visitor.visit(&add_expr), "add_expr");
+    assert_eq!(visitor.visit(&add_expr.child(2).unwrap()), "2");
+    drop(visitor);
+
+    assert_eq!(numbers, 1);
+}
+
+pub mod hooks {
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait("src/node-types.json", fn_visitor, token_hook, error_hooks)]
+    pub trait HookVisitor {}
+
+    pub mod fields {
+        use tree_sitter_visitor::visitor_trait;
+
+        #[visitor_trait("src/node-types.json", fn_visitor, field_hooks)]
+        pub trait FieldVisitor {}
+    }
+}
+
+#[test]
+fn test_fn_visitor_hooks() {
+    use hooks::{FnVisitor, HookVisitor};
+
+    let parsed = parse("1 + (2");
+    let add_expr = parsed.root_node().child(0).unwrap();
+    let plus = add_expr.child(1).unwrap();
+    let paren_expr = add_expr.child(2).unwrap();
+    let missing = paren_expr.child(2).unwrap();
+    assert!(missing.is_missing());
+
+    let mut visitor = FnVisitor::new()
+        .hook("visit_token", |node| format!("token {}", node.kind()))
+        .otherwise(|node| format!("fallback {}", node.kind()));
+
+    assert_eq!(visitor.visit(&plus), "token +");
+    assert_eq!(visitor.visit(&missing), "fallback )");
+    assert_eq!(visitor.visit(&add_expr), "fallback add_expr");
+}
+
+#[test]
+fn test_fn_visitor_field_hooks() {
+    use hooks::fields::{FieldVisitor, FnVisitor};
+
+    let parsed = parse("1 + 2");
+    let add_expr = parsed.root_node().child(0).unwrap();
+
+    // The hook of `rhs` has no handler, so the operand is visited with `visit()`.
+    let mut visitor = FnVisitor::new()
+        .hook("visit_field_lhs", |node| format!("lhs {}", node.kind()))
+        .otherwise(|node| node.kind().to_string());
+    assert_eq!(visitor.visit_fields(&add_expr), ["lhs number", "number"]);
+}
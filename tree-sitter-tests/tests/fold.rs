@@ -0,0 +1,64 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", flavors(fold))]
+pub trait CalcVisitor {}
+
+/// Evaluates an expression bottom-up.
+struct Evaluator<'s> {
+    source: &'s str,
+}
+
+impl CalcFolder for Evaluator<'_> {
+    type ReturnType = f64;
+
+    fn fold_root(&mut self, _node: &Node<'_>, children: Vec<f64>) -> f64 {
+        children[0]
+    }
+
+    fn fold_number(&mut self, node: &Node<'_>, _children: Vec<f64>) -> f64 {
+        self.source[node.byte_range()].parse().unwrap()
+    }
+
+    fn fold_add_expr(&mut self, _node: &Node<'_>, children: Vec<f64>) -> f64 {
+        children[0] + children[1]
+    }
+
+    fn fold_sub_expr(&mut self, _node: &Node<'_>, children: Vec<f64>) -> f64 {
+        children[0] - children[1]
+    }
+
+    fn fold_mul_expr(&mut self, _node: &Node<'_>, children: Vec<f64>) -> f64 {
+        children[0] * children[1]
+    }
+
+    fn fold_div_expr(&mut self, _node: &Node<'_>, children: Vec<f64>) -> f64 {
+        children[0] / children[1]
+    }
+
+    fn fold_paren_expr(&mut self, _node: &Node<'_>, children: Vec<f64>) -> f64 {
+        children[0]
+    }
+}
+
+fn eval(source: &str) -> f64 {
+    let tree = parse(source);
+    Evaluator { source }.fold_tree(tree.root_node())
+}
+
+#[test]
+fn test_fold() {
+    assert_eq!(eval("1"), 1.0);
+    assert_eq!(eval("10 - 2 * 3"), 4.0);
+    assert_eq!(eval("(1 + 2) * (8 / (4 - 2))"), 12.0);
+}
+
+#[test]
+fn test_fold_deep_tree_without_recursion() {
+    let depth = 10_000;
+    let source = format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+    assert_eq!(eval(&source), 1.0);
+}
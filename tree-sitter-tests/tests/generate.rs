@@ -0,0 +1,110 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor_codegen::{generate, GenerateConfig};
+
+pub mod generated {
+    include!(concat!(env!("OUT_DIR"), "/calc_visitor.rs"));
+}
+
+use generated::CalcVisitor;
+
+struct Evaluator<'s> {
+    source: &'s str,
+}
+
+impl CalcVisitor for Evaluator<'_> {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit_named_children(node).into_iter().sum()
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        self.visit_named_children(node).into_iter().sum()
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) -> f64 {
+        self.visit_named_children(node).into_iter().product()
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.source[node.byte_range()].parse().unwrap()
+    }
+}
+
+#[test]
+fn test_generated_trait_from_build_script() {
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+
+    let mut evaluator = Evaluator { source };
+    assert_eq!(evaluator.visit(&tree.root_node()), 7.0);
+}
+
+#[test]
+fn test_generate_formats_source() {
+    let config = GenerateConfig::new("pub trait CalcVisitor {}").options("named_only");
+    let code = generate("src/node-types.json", &config).unwrap();
+    assert!(code.starts_with("pub trait CalcVisitor {\n"));
+    assert!(code.contains(
+        "    fn visit_add_expr(&mut self, node: &::tree_sitter::Node<'_>) -> Self::ReturnType {\n"
+    ));
+}
+
+#[test]
+fn test_generate_reports_errors() {
+    let config = GenerateConfig::new("pub trait CalcVisitor {}");
+    let err = generate("src/missing-node-types.json", &config).unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("could not read `src/missing-node-types.json`"));
+
+    let config = config.options("no_such_option");
+    let err = generate("src/node-types.json", &config).unwrap_err();
+    assert_eq!(err.to_string(), "unknown option `no_such_option`");
+}
+
+#[test]
+fn test_generate_tracks_node_types() {
+    let config = GenerateConfig::new("pub trait CalcVisitor {}");
+    let code = generate("src/node-types.json", &config).unwrap();
+    let path = std::fs::canonicalize("src/node-types.json").unwrap();
+    assert!(code.contains(&format!("const _: &[u8] = include_bytes!({:?});", path)));
+}
+
+#[test]
+fn test_generate_reports_conflicting_options() {
+    let config = GenerateConfig::new("pub trait CalcVisitor {}").options("fallible, chain");
+    let err = generate("src/node-types.json", &config).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`fallible` cannot be combined with `chain`"
+    );
+
+    // `defaults` bounds `ReturnType` by `Default`, which is not an explicit `return_bounds`.
+    let config = config.options("return_type = \"()\", defaults");
+    generate("src/node-types.json", &config).unwrap();
+}
+
+#[test]
+fn test_generate_rejects_parallel_fn_visitor() {
+    let config = GenerateConfig::new("pub trait CalcVisitor {}").options("parallel, fn_visitor");
+    let err = generate("src/node-types.json", &config).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`parallel` cannot be combined with `fn_visitor`"
+    );
+}
+
+#[test]
+fn test_generate_rejects_grammar_name_kind_id_dispatch() {
+    let config = GenerateConfig::new("pub trait CalcVisitor {}")
+        .options("grammar_name_dispatch, kind_id_dispatch");
+    let err = generate("src/node-types.json", &config).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`grammar_name_dispatch` cannot be combined with `kind_id_dispatch`"
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::parse;
+use std::collections::HashMap;
+
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait(
+    "src/node-types.json",
+    chain,
+    fn_visitor,
+    combinators,
+    walker,
+    owned_parse,
+    try_visit_children
+)]
+pub trait CalcVisitor<'ctx, T>
+where
+    T: Clone + std::fmt::Debug + 'ctx,
+{
+    /// Describes a value of a variable.
+    fn describe(&self, value: &'ctx T) -> String {
+        format!("{:?}", value.clone())
+    }
+}
+
+struct Lookup<'ctx> {
+    values: &'ctx HashMap<&'static str, i64>,
+}
+
+impl<'ctx> CalcVisitor<'ctx, i64> for Lookup<'ctx> {
+    type ReturnType = Option<String>;
+
+    fn visit_number(&mut self, node: &Node) -> Self::ReturnType {
+        let key = if node.start_byte() == 0 {
+            "first"
+        } else {
+            "other"
+        };
+        self.values.get(key).map(|value| self.describe(value))
+    }
+}
+
+#[test]
+fn test_generics_are_preserved() {
+    let values = HashMap::from([("first", 42)]);
+    let parsed = parse("1");
+
+    let mut lookup = Lookup { values: &values };
+    let number = parsed.root_node().child(0).unwrap();
+    assert_eq!(lookup.visit(&number), Some("42".to_string()));
+
+    let mut results = Vec::new();
+    Walker::new()
+        .leaves_only()
+        .walk(&mut lookup, parsed.root_node(), |_, result| {
+            results.push(result)
+        });
+    assert_eq!(results, vec![Some("42".to_string())]);
+
+    let mut chained = ChainedVisitor::new(Lookup { values: &values }, Lookup { values: &values });
+    assert_eq!(chained.visit(&number), Some("42".to_string()));
+
+    let mut fn_visitor = FnVisitor::new().otherwise(|node| node.kind().len());
+    assert_eq!(CalcVisitor::<'_, i64>::visit(&mut fn_visitor, &number), 6);
+}
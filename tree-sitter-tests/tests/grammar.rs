@@ -0,0 +1,90 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+// `number` is declared as an external token of this copy of the grammar.
+#[visitor_trait(
+    "src/node-types.json",
+    grammar = "tests/calc-grammar-externals.json",
+    named_only
+)]
+pub trait ExternalVisitor {}
+
+#[visitor_trait("src/node-types.json", grammar = "src/grammar.json")]
+pub trait DocumentedVisitor {}
+
+struct Tokens<'a> {
+    source: &'a str,
+    tokens: Vec<String>,
+}
+
+impl ExternalVisitor for Tokens<'_> {
+    type ReturnType = ();
+
+    fn visit_root(&mut self, node: &Node) {
+        self.visit(&node.named_child(0).unwrap());
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) {
+        self.visit(&node.child_by_field_name("lhs").unwrap());
+        self.visit(&node.child_by_field_name("rhs").unwrap());
+    }
+
+    fn visit_external(&mut self, node: &Node) {
+        let text = node.utf8_text(self.source.as_bytes()).unwrap();
+        self.tokens.push(format!("{} {}", node.kind(), text));
+    }
+}
+
+struct Evaluator<'a> {
+    source: &'a str,
+}
+
+impl DocumentedVisitor for Evaluator<'_> {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        node.utf8_text(self.source.as_bytes())
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+}
+
+#[test]
+fn test_external_tokens_forward_to_hook() {
+    let source = "1 + 23";
+    let tree = parse(source);
+    let mut visitor = Tokens {
+        source,
+        tokens: Vec::new(),
+    };
+    ExternalVisitor::visit(&mut visitor, &tree.root_node());
+    assert_eq!(visitor.tokens, ["number 1", "number 23"]);
+}
+
+#[test]
+#[should_panic(expected = "external token `number`")]
+fn test_external_hook_panics_by_default() {
+    struct Empty;
+    impl ExternalVisitor for Empty {
+        type ReturnType = ();
+    }
+    let tree = parse("1");
+    let number = tree.root_node().named_child(0).unwrap();
+    ExternalVisitor::visit(&mut Empty, &number);
+}
+
+#[test]
+fn test_grammar_without_externals() {
+    let source = "42";
+    let tree = parse(source);
+    let result = DocumentedVisitor::visit(&mut Evaluator { source }, &tree.root_node());
+    assert_eq!(result, 42.0);
+}
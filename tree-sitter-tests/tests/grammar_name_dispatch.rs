@@ -0,0 +1,50 @@
+mod common;
+
+use common::{parse, Calculator};
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", grammar_name_dispatch, named_dispatch)]
+pub trait CalcVisitor {}
+
+impl CalcVisitor for Calculator<'_> {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.number(node)
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) -> f64 {
+        self.visit_named(&node.child_by_field_name("lhs").unwrap())
+            * self.visit_named(&node.child_by_field_name("rhs").unwrap())
+    }
+
+    fn visit_STAR(&mut self, _node: &Node) -> f64 {
+        0.0
+    }
+}
+
+#[test]
+fn test_grammar_name_dispatch() {
+    let source = "6 * 7";
+    let tree = parse(source);
+
+    let mut calculator = Calculator { source };
+    assert_eq!(calculator.visit(&tree.root_node()), 42.0);
+
+    let star = tree.root_node().child(0).unwrap().child(1).unwrap();
+    assert_eq!(star.grammar_name(), "*");
+    assert_eq!(calculator.visit_token(&star), 0.0);
+}
+
+#[test]
+#[should_panic(expected = "unknown node kind: ERROR")]
+fn test_grammar_name_dispatch_unknown_kind() {
+    let tree = parse("+");
+
+    Calculator { source: "+" }.visit(&tree.root_node());
+}
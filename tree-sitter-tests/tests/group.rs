@@ -0,0 +1,80 @@
+mod common;
+
+use common::{parse, Calculator};
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait(
+    "src/node-types.json",
+    named_only,
+    group(
+        binary = ["add_expr", "sub_expr", "mul_expr", "div_expr"],
+        atom = ["number"]
+    )
+)]
+pub trait CalcVisitor {}
+
+pub mod prefixed {
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait(
+        "src/node-types.json",
+        prefix = "on_",
+        return_type = "String",
+        group(binary = ["add_expr", "sub_expr"])
+    )]
+    pub trait CalcVisitor {
+        /// Visits additions and subtractions, returning their kind by default.
+        fn on_binary(&mut self, node: &tree_sitter::Node) -> String {
+            node.kind().to_string()
+        }
+    }
+}
+
+impl CalcVisitor for Calculator<'_> {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_paren_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child_by_field_name("body").unwrap())
+    }
+
+    fn visit_binary(&mut self, node: &Node) -> f64 {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+        match node.kind() {
+            "add_expr" => lhs + rhs,
+            "sub_expr" => lhs - rhs,
+            "mul_expr" => lhs * rhs,
+            _ => lhs / rhs,
+        }
+    }
+
+    fn visit_atom(&mut self, node: &Node) -> f64 {
+        self.number(node)
+    }
+}
+
+#[test]
+fn test_grouped_kinds_are_dispatched() {
+    let source = "(1 + 2) * 8 / 4 - 1";
+    let tree = parse(source);
+    let result = Calculator { source }.visit(&tree.root_node());
+    assert_eq!(result, 5.0);
+}
+
+struct Kinds;
+
+impl prefixed::CalcVisitor for Kinds {}
+
+#[test]
+fn test_group_methods_follow_prefix_and_keep_defaults() {
+    use prefixed::CalcVisitor;
+
+    let tree = parse("1 - 2");
+    let sub_expr = tree.root_node().named_child(0).unwrap();
+    assert_eq!(Kinds.visit(&sub_expr), "sub_expr");
+}
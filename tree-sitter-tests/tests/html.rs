@@ -0,0 +1,93 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Query;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", html)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_render_by_kind() {
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let renderer = HtmlRenderer::new()
+        .class("number", "num")
+        .class("mul_expr", "product")
+        .class("+", "op");
+
+    assert_eq!(
+        renderer.render(&tree, source),
+        "<span class=\"num\">1</span> <span class=\"op\">+</span> <span class=\"product\">\
+         <span class=\"num\">2</span> * <span class=\"num\">3</span></span>"
+    );
+}
+
+#[test]
+fn test_render_escapes_and_highlights_errors() {
+    let source = "1 < 2";
+    let tree = parse(source);
+
+    assert!(tree.root_node().has_error());
+    let html = HtmlRenderer::new().render(&tree, source);
+    assert!(html.contains("<span class=\"error\">"));
+    assert!(html.contains("&lt;"));
+    assert!(!html.contains(" < "));
+}
+
+#[test]
+fn test_render_captures() {
+    let source = "(1 + 2)";
+    let tree = parse(source);
+    let query = Query::new(
+        &tree_sitter_tests::language(),
+        r#"(number) @constant.numeric ["(" ")"] @punctuation"#,
+    )
+    .unwrap();
+
+    let spans = HtmlRenderer::capture_spans(&query, &tree, source.as_bytes());
+    assert_eq!(
+        HtmlRenderer::render_spans(source, [spans]),
+        "<span class=\"punctuation\">(</span><span class=\"constant numeric\">1</span> + \
+         <span class=\"constant numeric\">2</span><span class=\"punctuation\">)</span>"
+    );
+}
+
+#[test]
+fn test_capture_spans_keep_the_first_capture_of_a_range() {
+    let source = "1 + 2";
+    let tree = parse(source);
+    let query = Query::new(
+        &tree_sitter_tests::language(),
+        r#"(number) @constant.numeric (number) @number"#,
+    )
+    .unwrap();
+
+    let spans = HtmlRenderer::capture_spans(&query, &tree, source.as_bytes());
+    assert_eq!(
+        spans,
+        [
+            (0..1, "constant numeric".to_string()),
+            (4..5, "constant numeric".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_render_overlapping_layers() {
+    let source = "abcdef";
+    let outer = vec![(0..4, "a".to_string())];
+    let inner = vec![(2..6, "b".to_string()), (0..4, "c".to_string())];
+
+    assert_eq!(
+        HtmlRenderer::render_spans(source, [outer, inner]),
+        "<span class=\"a\"><span class=\"c\">ab<span class=\"b\">cd</span></span></span>\
+         <span class=\"b\">ef</span>"
+    );
+}
+
+#[test]
+#[should_panic(expected = "unknown node kind `numbr`")]
+fn test_unknown_kind() {
+    HtmlRenderer::new().class("numbr", "num");
+}
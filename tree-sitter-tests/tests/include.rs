@@ -0,0 +1,61 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait(
+    "src/node-types.json",
+    include = ["root", "number"],
+    include_pattern = "_expr$",
+    exclude = ["paren_expr"],
+    exclude_pattern = "^(div|mul)_",
+    unknown = "hook"
+)]
+pub trait CalcVisitor {}
+
+/// Sums the numbers of a tree, ignoring the nodes of kinds that are left out of the trait.
+struct Sum<'s> {
+    source: &'s str,
+    skipped: Vec<&'static str>,
+}
+
+impl CalcVisitor for Sum<'_> {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.source[node.byte_range()].parse().unwrap()
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            + self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+
+    fn visit_sub_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            - self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+
+    fn visit_unknown(&mut self, node: &Node) -> f64 {
+        self.skipped.push(node.kind());
+        0.0
+    }
+}
+
+#[test]
+fn test_include_and_exclude() {
+    let source = "10 + 2 * 3 - (4)";
+    let tree = parse(source);
+
+    let mut visitor = Sum {
+        source,
+        skipped: Vec::new(),
+    };
+    assert_eq!(visitor.visit(&tree.root_node()), 10.0);
+    assert_eq!(visitor.skipped, ["mul_expr", "paren_expr"]);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::parse;
+use std::collections::HashMap;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", interner)]
+pub trait CalcVisitor {}
+
+/// Counts the occurrences of every number literal.
+struct Literals<'s> {
+    source: &'s [u8],
+    interner: SymbolTableInterner,
+    counts: HashMap<Symbol, usize>,
+}
+
+impl CalcVisitor for Literals<'_> {
+    type ReturnType = ();
+
+    fn visit_root(&mut self, node: &Node) {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) {
+        let symbol = self.interner.intern_number(node, self.source);
+        *self.counts.entry(symbol).or_default() += 1;
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) {
+        self.visit(&node.child_by_field_name("lhs").unwrap());
+        self.visit(&node.child_by_field_name("rhs").unwrap());
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) {
+        self.visit(&node.child_by_field_name("lhs").unwrap());
+        self.visit(&node.child_by_field_name("rhs").unwrap());
+    }
+}
+
+#[test]
+fn test_interner() {
+    let source = "12 + 7 * 12 + 012";
+    let tree = parse(source);
+
+    let mut visitor = Literals {
+        source: source.as_bytes(),
+        interner: SymbolTableInterner::new(),
+        counts: HashMap::new(),
+    };
+    visitor.visit(&tree.root_node());
+
+    let interner = &visitor.interner;
+    assert_eq!(interner.len(), 3);
+    let twelve = interner.get("12").unwrap();
+    assert_eq!(interner.resolve(twelve), "12");
+    assert_eq!(visitor.counts[&twelve], 2);
+    assert_eq!(visitor.counts[&interner.get("012").unwrap()], 1);
+    assert_eq!(interner.get("13"), None);
+}
+
+#[test]
+fn test_intern_returns_equal_symbols_for_equal_strings() {
+    let mut interner = SymbolTableInterner::new();
+    assert!(interner.is_empty());
+
+    let a = interner.intern("a");
+    let b = interner.intern("b");
+    assert_ne!(a, b);
+    assert_eq!(interner.intern("a"), a);
+    assert_eq!((a.index(), b.index()), (0, 1));
+}
@@ -0,0 +1,29 @@
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", introspection)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_introspection_describes_node_types() {
+    assert_eq!(grammar::NODE_TYPES.len(), 13);
+
+    let add_expr = grammar::node_type("add_expr", true).unwrap();
+    assert!(!add_expr.is_supertype());
+    assert!(!add_expr.root);
+    assert!(!add_expr.extra);
+    assert_eq!(add_expr.children, None);
+
+    let lhs = add_expr.field("lhs").unwrap();
+    assert!(lhs.required);
+    assert!(!lhs.multiple);
+    assert!(lhs.types.contains(&grammar::KindRef {
+        kind: "number",
+        named: true
+    }));
+
+    let plus = grammar::node_type("+", false).unwrap();
+    assert!(!plus.named);
+    assert!(plus.fields.is_empty());
+
+    assert!(grammar::node_type("_expr", true).is_none());
+}
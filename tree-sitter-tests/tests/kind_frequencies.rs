@@ -0,0 +1,45 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", kind_frequencies)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_kind_frequencies() {
+    let mut frequencies = KindFrequencies::new();
+    frequencies.add("a.calc", &parse("1 + 2 * 3"));
+    frequencies.add("b.calc", &parse("(4 - 5)"));
+
+    assert_eq!(frequencies.count("number"), 5);
+    assert_eq!(frequencies.count("add_expr"), 1);
+    assert_eq!(frequencies.count("div_expr"), 0);
+    assert_eq!(
+        frequencies.files("number").collect::<Vec<_>>(),
+        vec![&"a.calc", &"b.calc"]
+    );
+    assert_eq!(
+        frequencies.files("mul_expr").collect::<Vec<_>>(),
+        vec![&"a.calc"]
+    );
+
+    let top: Vec<_> = frequencies
+        .stats()
+        .iter()
+        .take(2)
+        .map(|stats| (stats.kind, stats.count))
+        .collect();
+    assert_eq!(top, vec![("number", 5), ("root", 2)]);
+
+    assert_eq!(frequencies.unobserved(), vec!["div_expr", "/"]);
+    assert!(frequencies.undeclared().is_empty());
+}
+
+#[test]
+fn test_kind_frequencies_flags_undeclared_kinds() {
+    let mut frequencies = KindFrequencies::new();
+    frequencies.add(0, &parse("1 + + 2"));
+
+    assert_eq!(frequencies.undeclared(), vec!["ERROR"]);
+}
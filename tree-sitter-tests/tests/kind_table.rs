@@ -0,0 +1,65 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", kind_table)]
+pub trait CalcVisitor {}
+
+struct KindCollector {
+    table: std::sync::Arc<KindTable>,
+    kinds: Vec<&'static str>,
+}
+
+impl CalcVisitor for KindCollector {
+    type ReturnType = ();
+
+    fn visit_root(&mut self, node: &Node) {
+        self.kinds.push(node.kind());
+        let table = self.table.clone();
+        self.visit_by_id(&table, &node.child(0).unwrap());
+    }
+
+    fn visit_sub_expr(&mut self, node: &Node) {
+        self.kinds.push(node.kind());
+        let table = self.table.clone();
+        for i in 0..node.child_count() {
+            self.visit_by_id(&table, &node.child(i).unwrap());
+        }
+    }
+
+    fn visit_number(&mut self, node: &Node) {
+        self.kinds.push(node.kind());
+    }
+
+    fn visit_DASH(&mut self, node: &Node) {
+        self.kinds.push(node.kind());
+    }
+}
+
+#[test]
+fn test_kind_table_dispatches_on_kind_id() {
+    let language = tree_sitter_tests::language();
+    let parsed = parse("1 - 2");
+
+    let table = KindTable::for_language(language.clone());
+    assert!(std::sync::Arc::ptr_eq(
+        &table,
+        &KindTable::for_language(language.clone())
+    ));
+    assert!(table.missing_kinds().is_empty());
+    assert!(table.validate().is_ok());
+    assert!(KindTable::try_for_language(language).is_ok());
+
+    let mut visitor = KindCollector {
+        table: table.clone(),
+        kinds: Vec::new(),
+    };
+    visitor.visit_by_id(&table, &parsed.root_node());
+
+    assert_eq!(
+        visitor.kinds,
+        vec!["root", "sub_expr", "number", "-", "number"]
+    );
+}
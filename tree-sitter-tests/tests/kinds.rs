@@ -0,0 +1,63 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", kinds)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_constants() {
+    assert_eq!(kinds::ADD_EXPR, "add_expr");
+    assert_eq!(kinds::NUMBER, "number");
+    assert_eq!(kinds::PLUS, "+");
+    assert_eq!(kinds::LPAREN, "(");
+}
+
+#[test]
+fn test_arrays() {
+    assert_eq!(kinds::ALL_KINDS.len(), 13);
+    assert!(kinds::ALL_KINDS.contains(&"*"));
+    assert_eq!(
+        kinds::NAMED_KINDS,
+        [
+            "add_expr",
+            "div_expr",
+            "mul_expr",
+            "paren_expr",
+            "root",
+            "sub_expr",
+            "number"
+        ]
+    );
+}
+
+#[test]
+fn test_ids() {
+    let language = tree_sitter_tests::language();
+    let tree = parse("1 + 2");
+    let add_expr = tree.root_node().named_child(0).unwrap();
+    let plus = add_expr.child(1).unwrap();
+
+    assert_eq!(
+        kinds::id(&language, kinds::ADD_EXPR, true),
+        Some(add_expr.kind_id())
+    );
+    assert_eq!(
+        kinds::id(&language, kinds::PLUS, false),
+        Some(plus.kind_id())
+    );
+    assert_eq!(kinds::id(&language, "no_such_kind", true), None);
+
+    let ids = kinds::ids(&language);
+    assert_eq!(ids.len(), kinds::ALL_KINDS.len());
+    assert!(ids.iter().all(Option::is_some));
+}
+
+#[test]
+fn test_query_from_constants() {
+    let language = tree_sitter_tests::language();
+    let source = format!("({} lhs: ({}) @lhs)", kinds::ADD_EXPR, kinds::NUMBER);
+    let query = tree_sitter::Query::new(&language, &source).unwrap();
+    assert_eq!(query.capture_names(), ["lhs"]);
+}
@@ -0,0 +1,73 @@
+use tree_sitter_visitor::visitor_trait;
+
+pub mod calc {
+    use super::*;
+
+    #[visitor_trait("src/node-types.json", language_check)]
+    pub trait CalcVisitor {}
+}
+
+pub mod merged {
+    use super::*;
+
+    // `pow_expr` and `^` are not in the calc language.
+    #[visitor_trait(
+        languages(calc = "src/node-types.json", pow = "tests/calc-pow-node-types.json"),
+        language_check
+    )]
+    pub trait MergedVisitor {}
+}
+
+pub mod partial {
+    use super::*;
+
+    // `div_expr` and `/` are left out of the node types.
+    #[visitor_trait("tests/calc-no-div-node-types.json", language_check)]
+    pub trait PartialVisitor {}
+}
+
+pub mod filtered {
+    use super::*;
+
+    #[visitor_trait("src/node-types.json", named_only, exclude = ["div_expr"], language_check)]
+    pub trait FilteredVisitor {}
+}
+
+#[test]
+fn test_compatible_language() {
+    let report = calc::check_language(&tree_sitter_tests::language());
+    assert!(report.is_compatible());
+    assert_eq!(report.to_string(), "node kinds match the language");
+    calc::assert_language_compatible(&tree_sitter_tests::language());
+}
+
+#[test]
+fn test_filtered_kinds_are_compatible() {
+    let report = filtered::check_language(&tree_sitter_tests::language());
+    assert!(report.is_compatible());
+    filtered::assert_language_compatible(&tree_sitter_tests::language());
+}
+
+#[test]
+fn test_missing_kinds() {
+    let report = merged::check_language(&tree_sitter_tests::language());
+    assert_eq!(report.missing, [("pow_expr", true), ("^", false)]);
+    assert!(report.extra.is_empty());
+}
+
+#[test]
+fn test_extra_kinds() {
+    let report = partial::check_language(&tree_sitter_tests::language());
+    assert!(report.missing.is_empty());
+    assert_eq!(report.extra, [("/", false), ("div_expr", true)]);
+    assert_eq!(
+        report.to_string(),
+        "node kinds do not match the language (missing: [], extra: [\"/\", div_expr])"
+    );
+}
+
+#[test]
+#[should_panic(expected = "missing: [pow_expr, \"^\"]")]
+fn test_assert_language_compatible_panics() {
+    merged::assert_language_compatible(&tree_sitter_tests::language());
+}
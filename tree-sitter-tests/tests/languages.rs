@@ -0,0 +1,55 @@
+mod common;
+
+use common::{parse, Calculator};
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+// `pow_expr` and `^` are only in `pow`, `div_expr` and `/` only in `calc`.
+#[visitor_trait(
+    languages(calc = "src/node-types.json", pow = "tests/calc-pow-node-types.json"),
+    kinds
+)]
+pub trait MergedVisitor {}
+
+impl MergedVisitor for Calculator<'_> {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_div_expr(&mut self, node: &Node) -> f64 {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+        lhs / rhs
+    }
+
+    fn visit_pow_expr(&mut self, node: &Node) -> f64 {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+        lhs.powf(rhs)
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.number(node)
+    }
+}
+
+#[test]
+fn test_union_of_kinds() {
+    assert_eq!(kinds::ALL_KINDS.len(), 15);
+    assert_eq!(kinds::POW_EXPR, "pow_expr");
+    assert_eq!(kinds::CARET, "^");
+    assert_eq!(kinds::DIV_EXPR, "div_expr");
+    // Kinds of both languages appear once.
+    let numbers = kinds::ALL_KINDS.iter().filter(|&&kind| kind == "number");
+    assert_eq!(numbers.count(), 1);
+}
+
+#[test]
+fn test_dispatch() {
+    let source = "84 / 2";
+    let tree = parse(source);
+    let result = Calculator { source }.visit(&tree.root_node());
+    assert_eq!(result, 42.0);
+}
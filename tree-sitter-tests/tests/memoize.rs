@@ -0,0 +1,240 @@
+mod common;
+
+use common::{parse, parser};
+use tree_sitter::{InputEdit, Node, Point};
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", memoize)]
+pub trait CalcVisitor {}
+
+/// Evaluates expressions, counting the nodes it visits.
+struct Evaluator {
+    source: String,
+    visits: usize,
+}
+
+impl CalcVisitor for Evaluator {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visits += 1;
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.visits += 1;
+        self.source[node.byte_range()].parse().unwrap()
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        self.visits += 1;
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            + self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) -> f64 {
+        self.visits += 1;
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            * self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+}
+
+fn memoized(source: &str) -> Memoized<Evaluator> {
+    Memoized::new(Evaluator {
+        source: source.to_string(),
+        visits: 0,
+    })
+}
+
+#[test]
+fn test_memoized_visits_once() {
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let root = tree.root_node();
+
+    let mut memoized = memoized(source);
+    assert!(memoized.is_empty());
+    assert_eq!(memoized.visit(&root), 7.0);
+    assert_eq!(memoized.inner.visits, 6);
+    assert_eq!(memoized.cached(&root), Some(&7.0));
+
+    assert_eq!(memoized.visit(&root), 7.0);
+    assert_eq!(memoized.inner.visits, 6);
+
+    // The children visited by the wrapped visitor are not cached.
+    let mul_expr = root.child(0).unwrap().child_by_field_name("rhs").unwrap();
+    assert!(!memoized.contains(&mul_expr));
+    assert_eq!(memoized.visit(&mul_expr), 6.0);
+    assert_eq!(memoized.inner.visits, 9);
+    assert_eq!(memoized.len(), 2);
+
+    memoized.invalidate(&root);
+    assert!(!memoized.contains(&root));
+    assert_eq!(memoized.visit(&root), 7.0);
+    assert_eq!(memoized.inner.visits, 15);
+
+    memoized.clear();
+    assert!(memoized.is_empty());
+}
+
+#[test]
+fn test_memoized_invalidate_range() {
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let add_expr = tree.root_node().child(0).unwrap();
+    let lhs = add_expr.child_by_field_name("lhs").unwrap();
+    let mul_expr = add_expr.child_by_field_name("rhs").unwrap();
+
+    let mut memoized = memoized(source);
+    for node in [add_expr, lhs, mul_expr] {
+        memoized.visit(&node);
+    }
+
+    // The range of "1" is inside the `add_expr`, but not adjacent to the `mul_expr`.
+    memoized.invalidate_range(0..1);
+    assert!(!memoized.contains(&add_expr));
+    assert!(!memoized.contains(&lhs));
+    assert!(memoized.contains(&mul_expr));
+}
+
+#[test]
+fn test_memoized_edit_keeps_reused_nodes() {
+    let source = "1 + 2 * 3";
+    let mut parser = parser();
+    let mut tree = parser.parse(source, None).unwrap();
+
+    let root = tree.root_node();
+    let two = root
+        .child(0)
+        .unwrap()
+        .child_by_field_name("rhs")
+        .unwrap()
+        .child_by_field_name("lhs")
+        .unwrap();
+
+    let mut memoized = memoized(source);
+    memoized.visit(&root);
+    memoized.visit(&two);
+    assert_eq!(memoized.len(), 2);
+
+    // Replace "1" with "10".
+    let edit = InputEdit {
+        start_byte: 0,
+        old_end_byte: 1,
+        new_end_byte: 2,
+        start_position: Point::new(0, 0),
+        old_end_position: Point::new(0, 1),
+        new_end_position: Point::new(0, 2),
+    };
+    tree.edit(&edit);
+    memoized.edit(&edit);
+    let source = "10 + 2 * 3";
+    let new_tree = parser.parse(source, Some(&tree)).unwrap();
+    memoized.inner.source = source.to_string();
+    memoized.inner.visits = 0;
+
+    // The root was dropped, and the numbers of the `mul_expr` were reused by the parser.
+    let root = new_tree.root_node();
+    let mul_expr = root.child(0).unwrap().child_by_field_name("rhs").unwrap();
+    assert!(!memoized.contains(&root));
+    let two = mul_expr.child_by_field_name("lhs").unwrap();
+    assert_eq!(memoized.cached(&two), Some(&2.0));
+    assert_eq!(memoized.visit(&two), 2.0);
+    assert_eq!(memoized.inner.visits, 0);
+    assert_eq!(memoized.visit(&root), 16.0);
+    assert_eq!(memoized.inner.visits, 6);
+}
+
+pub mod revisit {
+    use tree_sitter::Node;
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait("src/node-types.json", memoize, revisit, named_only, unknown = "skip")]
+    pub trait RevisitVisitor {}
+
+    /// Evaluates expressions, recording the kinds of the nodes it visits and invalidates.
+    #[derive(Default)]
+    pub struct Evaluator {
+        pub source: String,
+        pub visited: Vec<&'static str>,
+        pub invalidated: Vec<&'static str>,
+    }
+
+    impl RevisitVisitor for Evaluator {
+        type ReturnType = f64;
+
+        fn visit_root(&mut self, node: &Node) -> f64 {
+            self.visit(&node.named_child(0).unwrap())
+        }
+
+        fn visit_add_expr(&mut self, node: &Node) -> f64 {
+            self.visited.push(node.kind());
+            self.visit(&node.child_by_field_name("lhs").unwrap())
+                + self.visit(&node.child_by_field_name("rhs").unwrap())
+        }
+
+        fn visit_mul_expr(&mut self, node: &Node) -> f64 {
+            self.visited.push(node.kind());
+            self.visit(&node.child_by_field_name("lhs").unwrap())
+                * self.visit(&node.child_by_field_name("rhs").unwrap())
+        }
+
+        fn visit_number(&mut self, node: &Node) -> f64 {
+            self.visited.push(node.kind());
+            self.source[node.byte_range()].parse().unwrap()
+        }
+
+        fn invalidate(&mut self, node: &Node) {
+            self.invalidated.push(node.kind());
+        }
+    }
+}
+
+#[test]
+fn test_memoized_revisit_changed() {
+    use revisit::{Evaluator, Memoized, RevisitVisitor};
+
+    let mut parser = parser();
+    let mut old_tree = parser.parse("1 + 2 * 3", None).unwrap();
+    let mut memoized = Memoized::new(Evaluator {
+        source: "1 + 2 * 3".to_string(),
+        ..Evaluator::default()
+    });
+    assert_eq!(memoized.visit(&old_tree.root_node()), 7.0);
+
+    // Replace "3" with "45".
+    let edit = InputEdit {
+        start_byte: 8,
+        old_end_byte: 9,
+        new_end_byte: 10,
+        start_position: Point::new(0, 8),
+        old_end_position: Point::new(0, 9),
+        new_end_position: Point::new(0, 10),
+    };
+    old_tree.edit(&edit);
+    let source = "1 + 2 * 45";
+    let new_tree = parser.parse(source, Some(&old_tree)).unwrap();
+    memoized.inner.source = source.to_string();
+    memoized.inner.visited.clear();
+
+    // The ancestors of the changed number are invalidated in the cache and the wrapped visitor.
+    let results = memoized.revisit_changed(&old_tree, &new_tree, &[edit]);
+    assert_eq!(results, [45.0]);
+    assert_eq!(memoized.inner.invalidated, ["mul_expr", "add_expr", "root"]);
+    assert!(!memoized.contains(&new_tree.root_node()));
+    assert_eq!(memoized.inner.visited, ["number"]);
+
+    // The result of the changed number was cached by `revisit_changed()`.
+    let number = new_tree
+        .root_node()
+        .descendant_for_byte_range(8, 10)
+        .unwrap();
+    memoized.inner.visited.clear();
+    assert_eq!(memoized.visit(&number), 45.0);
+    assert!(memoized.inner.visited.is_empty());
+    assert_eq!(memoized.visit(&new_tree.root_node()), 91.0);
+    assert_eq!(
+        memoized.inner.visited,
+        ["add_expr", "number", "mul_expr", "number", "number"]
+    );
+}
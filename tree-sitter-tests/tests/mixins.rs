@@ -0,0 +1,63 @@
+mod common;
+
+use common::{parse, Calculator};
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait(
+    "src/node-types.json",
+    mixins(
+        Arithmetic = ["add_expr", "sub_expr", "mul_expr", "div_expr", "paren_expr"],
+        Leaves = ["root", "number"],
+    )
+)]
+pub trait CalcVisitor {}
+
+impl<'t> CalcVisitorMixin for Calculator<'t> {
+    type ReturnType = f64;
+}
+
+impl<'t> Arithmetic for Calculator<'t> {
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            + self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+
+    fn visit_sub_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            - self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            * self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+
+    fn visit_div_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            / self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+
+    fn visit_paren_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child_by_field_name("body").unwrap())
+    }
+}
+
+impl<'t> Leaves for Calculator<'t> {
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.number(node)
+    }
+}
+
+#[test]
+fn test_mixins_are_combined() {
+    let source = "(1 + 2) * 3 - 4 / 2";
+    let parsed = parse(source);
+
+    let mut visitor = Calculator { source };
+    assert_eq!(visitor.visit(&parsed.root_node()), 7.0);
+}
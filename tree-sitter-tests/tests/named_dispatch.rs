@@ -0,0 +1,27 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", named_dispatch, fn_visitor)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_named_and_token_dispatch() {
+    let parsed = parse("1 + 2");
+    let add_expr = parsed.root_node().child(0).unwrap();
+
+    let mut visitor = FnVisitor::new()
+        .on("add_expr", |_| "add")
+        .on("+", |_| "plus");
+    assert_eq!(visitor.visit_named(&add_expr), "add");
+    assert_eq!(visitor.visit_token(&add_expr.child(1).unwrap()), "plus");
+}
+
+#[test]
+#[should_panic(expected = "expected an anonymous node")]
+fn test_token_dispatch_rejects_named_nodes() {
+    let parsed = parse("1");
+    let mut visitor = FnVisitor::new().otherwise(|_| ());
+    visitor.visit_token(&parsed.root_node());
+}
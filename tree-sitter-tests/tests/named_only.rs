@@ -0,0 +1,114 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", named_only, token_hook)]
+pub trait CalcVisitor {}
+
+#[visitor_trait("src/node-types.json", named_only, unknown = "skip")]
+pub trait SkippingVisitor {}
+
+#[visitor_trait("src/node-types.json", token_hook)]
+pub trait GroupedVisitor {}
+
+/// Renders a tree with the operators spelled out.
+struct Printer<'s> {
+    source: &'s str,
+}
+
+impl CalcVisitor for Printer<'_> {
+    type ReturnType = String;
+
+    fn visit_root(&mut self, node: &Node) -> String {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> String {
+        self.source[node.byte_range()].to_string()
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> String {
+        let mut cursor = node.walk();
+        let parts: Vec<_> = node
+            .children(&mut cursor)
+            .map(|child| self.visit(&child))
+            .collect();
+        parts.join(" ")
+    }
+
+    fn visit_token(&mut self, _node: &Node, kind: &str) -> String {
+        match kind {
+            "+" => "plus".to_string(),
+            other => panic!("unexpected token {}", other),
+        }
+    }
+}
+
+/// Spells out the operators of a tree in a single token handler.
+struct Operators;
+
+impl GroupedVisitor for Operators {
+    type ReturnType = String;
+
+    fn visit_root(&mut self, node: &Node) -> String {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, _node: &Node) -> String {
+        "n".to_string()
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> String {
+        let mut cursor = node.walk();
+        let parts: Vec<_> = node
+            .children(&mut cursor)
+            .map(|child| self.visit(&child))
+            .collect();
+        parts.join(" ")
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) -> String {
+        self.visit_add_expr(node)
+    }
+
+    fn visit_token(&mut self, _node: &Node, kind: &str) -> String {
+        match kind {
+            "+" => "plus".to_string(),
+            "*" => "times".to_string(),
+            other => panic!("unexpected token {}", other),
+        }
+    }
+}
+
+struct Numbers;
+
+impl SkippingVisitor for Numbers {
+    type ReturnType = usize;
+
+    fn visit_number(&mut self, _node: &Node) -> usize {
+        1
+    }
+}
+
+#[test]
+fn test_named_only_with_token_hook() {
+    let source = "1 + 2";
+    let tree = parse(source);
+
+    assert_eq!(Printer { source }.visit(&tree.root_node()), "1 plus 2");
+
+    let add_expr = tree.root_node().child(0).unwrap();
+    let counts: Vec<_> = (0..add_expr.child_count())
+        .map(|i| Numbers.visit(&add_expr.child(i).unwrap()))
+        .collect();
+    assert_eq!(counts, [1, 0, 1]);
+}
+
+#[test]
+fn test_token_hook_implies_named_only() {
+    let tree = parse("1 + 2 * 3");
+
+    assert_eq!(Operators.visit(&tree.root_node()), "n plus n times n");
+}
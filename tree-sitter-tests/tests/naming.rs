@@ -0,0 +1,85 @@
+mod common;
+
+use common::{parse, Calculator};
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", prefix = "on_")]
+pub trait PrefixedVisitor {}
+
+#[visitor_trait("tests/camel-node-types.json", case = "snake", prefix = "handle_")]
+pub trait SnakeVisitor {}
+
+#[visitor_trait("tests/camel-node-types.json")]
+pub trait PreservingVisitor {}
+
+impl PrefixedVisitor for Calculator<'_> {
+    type ReturnType = f64;
+
+    fn on_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn on_mul_expr(&mut self, node: &Node) -> f64 {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+        lhs * rhs
+    }
+
+    fn on_number(&mut self, node: &Node) -> f64 {
+        self.number(node)
+    }
+}
+
+struct Names;
+
+impl SnakeVisitor for Names {
+    type ReturnType = &'static str;
+
+    fn handle_function_definition(&mut self, _node: &Node) -> &'static str {
+        "function_definition"
+    }
+
+    fn handle_html_element(&mut self, _node: &Node) -> &'static str {
+        "html_element"
+    }
+
+    fn handle_identifier(&mut self, _node: &Node) -> &'static str {
+        "identifier"
+    }
+
+    fn handle_LPAREN(&mut self, _node: &Node) -> &'static str {
+        "("
+    }
+}
+
+impl PreservingVisitor for Names {
+    type ReturnType = ();
+
+    #[allow(non_snake_case)]
+    fn visit_HTMLElement(&mut self, _node: &Node) {}
+}
+
+#[test]
+fn test_prefix() {
+    let source = "6 * 7";
+    let tree = parse(source);
+    let result = Calculator { source }.visit(&tree.root_node());
+    assert_eq!(result, 42.0);
+}
+
+#[test]
+#[should_panic(expected = "add_expr")]
+fn test_prefix_default_panics() {
+    let source = "1 + 2";
+    let tree = parse(source);
+    Calculator { source }.visit(&tree.root_node());
+}
+
+#[test]
+fn test_snake_case() {
+    assert_eq!(
+        SnakeVisitor::handle_html_element(&mut Names, &parse("1").root_node()),
+        "html_element"
+    );
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::parse;
+
+pub mod plain {
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait("src/node-types.json", node_ext)]
+    pub trait CalcVisitor {}
+}
+
+pub mod typed {
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait("src/node-types.json", node_ext, nodes)]
+    pub trait CalcVisitor {}
+}
+
+pub mod nested {
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait("tests/calc-nested-supertypes.json", node_ext)]
+    pub trait CalcVisitor {}
+}
+
+#[test]
+fn test_node_ext_checks_kinds() {
+    use plain::NodeExt;
+
+    let tree = parse("1 + 2");
+    let add_expr = tree.root_node().named_child(0).unwrap();
+    let plus = add_expr.child(1).unwrap();
+
+    assert!(add_expr.is_add_expr());
+    assert!(!add_expr.is_number());
+    assert_eq!(add_expr.as_add_expr(), Some(add_expr));
+    assert_eq!(add_expr.as_mul_expr(), None);
+    assert!(tree.root_node().is_root());
+    assert!(!plus.is_add_expr());
+}
+
+#[test]
+fn test_node_ext_converts_to_wrappers() {
+    use typed::nodes::AddExpr;
+    use typed::NodeExt;
+
+    let tree = parse("1 + 2");
+    let add_expr = tree.root_node().named_child(0).unwrap();
+
+    let wrapper: AddExpr = add_expr.as_add_expr().unwrap();
+    assert_eq!(wrapper.into_node(), add_expr);
+    assert!(tree.root_node().as_add_expr().is_none());
+}
+
+#[test]
+fn test_node_ext_checks_supertypes() {
+    use nested::NodeExt;
+
+    let tree = parse("(1 + 2) * 3");
+    let mul_expr = tree.root_node().named_child(0).unwrap();
+    let paren_expr = mul_expr.child_by_field_name("lhs").unwrap();
+    let star = mul_expr.child(1).unwrap();
+
+    assert!(mul_expr.is_binary_expr());
+    assert!(mul_expr.is_expr());
+    assert!(paren_expr.is_expr());
+    assert!(!paren_expr.is_binary_expr());
+    assert!(!star.is_expr());
+    assert!(!tree.root_node().is_expr());
+}
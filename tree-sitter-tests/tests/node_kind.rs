@@ -0,0 +1,76 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", node_kind_dispatch)]
+pub trait CalcVisitor {}
+
+struct Evaluator<'s> {
+    source: &'s str,
+}
+
+impl CalcVisitor for Evaluator<'_> {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.source[node.byte_range()].parse().unwrap()
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            + self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+}
+
+/// Returns whether a node is an operator, matching every kind exhaustively.
+fn is_operator(kind: NodeKind) -> bool {
+    match kind {
+        NodeKind::PLUS | NodeKind::DASH | NodeKind::STAR | NodeKind::SLASH => true,
+        NodeKind::Root
+        | NodeKind::Number
+        | NodeKind::AddExpr
+        | NodeKind::SubExpr
+        | NodeKind::MulExpr
+        | NodeKind::DivExpr
+        | NodeKind::ParenExpr
+        | NodeKind::LPAREN
+        | NodeKind::RPAREN
+        | NodeKind::Unknown => false,
+    }
+}
+
+#[test]
+fn test_dispatch_through_node_kind() {
+    let source = "1 + 2 + 3";
+    let tree = parse(source);
+    assert_eq!(Evaluator { source }.visit(&tree.root_node()), 6.0);
+}
+
+#[test]
+fn test_node_kind_conversions() {
+    let tree = parse("1 + 2");
+    let add_expr = tree.root_node().child(0).unwrap();
+    let plus = add_expr.child(1).unwrap();
+
+    assert_eq!(NodeKind::from(&add_expr), NodeKind::AddExpr);
+    assert_eq!(NodeKind::from(&plus), NodeKind::PLUS);
+    assert!(is_operator(NodeKind::from(&plus)));
+    assert!(!is_operator(NodeKind::from(&add_expr)));
+
+    assert_eq!("add_expr".parse(), Ok(NodeKind::AddExpr));
+    assert_eq!("+".parse(), Ok(NodeKind::PLUS));
+    assert_eq!("let".parse(), Ok(NodeKind::Unknown));
+    assert_eq!(NodeKind::AddExpr.as_str(), "add_expr");
+    assert_eq!(NodeKind::PLUS.to_string(), "+");
+    assert!(NodeKind::Number.is_named());
+    assert!(!NodeKind::PLUS.is_named());
+    assert!(NodeKind::ALL
+        .iter()
+        .all(|kind| kind.as_str().parse() == Ok(*kind)));
+}
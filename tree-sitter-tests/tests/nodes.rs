@@ -0,0 +1,53 @@
+mod common;
+
+use common::parse;
+use std::convert::TryFrom;
+
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", nodes)]
+pub trait CalcVisitor {}
+
+struct Evaluator<'s> {
+    source: &'s str,
+}
+
+impl Evaluator<'_> {
+    fn eval(&mut self, node: Node) -> f64 {
+        if let Ok(add_expr) = nodes::AddExpr::try_from(node) {
+            return self.eval(add_expr.lhs().unwrap()) + self.eval(add_expr.rhs().unwrap());
+        }
+        if let Ok(paren_expr) = nodes::ParenExpr::try_from(node) {
+            return self.eval(paren_expr.body().unwrap());
+        }
+        let number = nodes::Number::try_from(node).expect("unsupported node");
+        self.source[number.as_node().byte_range()].parse().unwrap()
+    }
+}
+
+#[test]
+fn test_field_accessors() {
+    let source = "1 + (2 + 3)";
+    let tree = parse(source);
+    let root = nodes::Root::try_from(tree.root_node()).unwrap();
+    let expr = root.as_node().named_child(0).unwrap();
+
+    assert_eq!(Evaluator { source }.eval(expr), 6.0);
+}
+
+#[test]
+fn test_try_from_checks_the_kind() {
+    let tree = parse("1 + 2");
+    let add_expr = tree.root_node().named_child(0).unwrap();
+
+    assert_eq!(nodes::AddExpr::KIND, "add_expr");
+    let wrapper = nodes::AddExpr::try_from(add_expr).unwrap();
+    assert_eq!(Node::from(wrapper), add_expr);
+    assert_eq!(wrapper.into_node(), add_expr);
+    assert_eq!(nodes::SubExpr::try_from(add_expr), Err(add_expr));
+
+    // The anonymous `+` is not a named node.
+    let plus = add_expr.child(1).unwrap();
+    assert!(nodes::Number::try_from(plus).is_err());
+}
@@ -0,0 +1,21 @@
+mod common;
+
+use common::Calculator;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", owned_parse)]
+pub trait CalcVisitor {}
+
+common::calculator!(CalcVisitor);
+
+fn parse(src: &str) -> OwnedParse {
+    OwnedParse::parse(tree_sitter_tests::language(), src.to_string()).unwrap()
+}
+
+#[test]
+fn test_owned_parse_can_be_visited_later() {
+    let parsed = parse("1 + 2");
+
+    assert_eq!(parsed.visit_with(|source| Calculator { source }), 3.0);
+    assert_eq!(parsed.root_node().kind(), "root");
+}
@@ -0,0 +1,264 @@
+mod common;
+
+use common::parse;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", parallel, error_hooks)]
+pub trait CalcVisitor {}
+
+/// Sums the numbers in a tree, counting the visitors split from it.
+struct Summer {
+    source: Arc<str>,
+    splits: Arc<AtomicUsize>,
+}
+
+impl Summer {
+    fn new(source: &str) -> Self {
+        Summer {
+            source: source.into(),
+            splits: Arc::default(),
+        }
+    }
+
+    fn sum_children(&mut self, node: &Node) -> f64 {
+        let mut cursor = node.walk();
+        let children: Vec<_> = node.named_children(&mut cursor).collect();
+        children.iter().map(|child| self.visit(child)).sum()
+    }
+}
+
+impl CalcVisitor for Summer {
+    type ReturnType = f64;
+
+    fn split(&self) -> Self {
+        self.splits.fetch_add(1, Ordering::SeqCst);
+        Summer {
+            source: self.source.clone(),
+            splits: self.splits.clone(),
+        }
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        self.sum_children(node)
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) -> f64 {
+        self.sum_children(node)
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.source[node.byte_range()].parse().unwrap()
+    }
+
+    fn visit_error(&mut self, node: &Node) -> f64 {
+        self.sum_children(node)
+    }
+}
+
+#[test]
+fn test_visit_parallel_combines_top_level_children() {
+    // The root has two children: an `ERROR` node holding "1 2", and the number 3.
+    let source = "1 2 3";
+    let tree = parse(source);
+    assert_eq!(tree.root_node().child_count(), 2);
+
+    let summer = Summer::new(source);
+    assert_eq!(summer.visit_parallel(&tree, |a, b| a + b), Some(6.0));
+    assert_eq!(summer.splits.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_visit_parallel_combines_in_document_order() {
+    let source = "1 2 3";
+    let tree = parse(source);
+
+    // Subtraction is not commutative, so the order of the results matters.
+    let summer = Summer::new(source);
+    assert_eq!(summer.visit_parallel(&tree, |a, b| a - b), Some(0.0));
+}
+
+#[test]
+fn test_visit_parallel_single_child() {
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+
+    let summer = Summer::new(source);
+    assert_eq!(summer.visit_parallel(&tree, |a, b| a + b), Some(6.0));
+    assert_eq!(summer.splits.load(Ordering::SeqCst), 1);
+}
+
+pub mod wrappers {
+    use std::sync::Arc;
+
+    use tree_sitter::Node;
+    use tree_sitter_visitor::{visitor_impl, visitor_trait, VisitorDelegate};
+
+    #[visitor_trait(
+        "src/node-types.json",
+        parallel,
+        chain,
+        catch_unimplemented,
+        coverage,
+        memoize,
+        combinators,
+        enum_dispatch(AnySummer = [Summer, Numbers])
+    )]
+    pub trait WrapperVisitor {}
+
+    /// Sums the numbers in a tree.
+    #[derive(Clone)]
+    pub struct Summer {
+        pub source: Arc<str>,
+    }
+
+    impl Summer {
+        fn sum_children(&mut self, node: &Node) -> f64 {
+            let mut cursor = node.walk();
+            let children: Vec<_> = node.named_children(&mut cursor).collect();
+            children.iter().map(|child| self.visit(child)).sum()
+        }
+    }
+
+    #[visitor_impl]
+    impl WrapperVisitor for Summer {
+        type ReturnType = f64;
+
+        fn split(&self) -> Self {
+            self.clone()
+        }
+
+        fn visit_add_expr(&mut self, node: &Node) -> f64 {
+            self.sum_children(node)
+        }
+
+        fn visit_mul_expr(&mut self, node: &Node) -> f64 {
+            self.sum_children(node)
+        }
+
+        fn visit_number(&mut self, node: &Node) -> f64 {
+            self.source[node.byte_range()].parse().unwrap()
+        }
+    }
+
+    /// Visits only the numbers.
+    pub struct Numbers;
+
+    #[visitor_impl]
+    impl WrapperVisitor for Numbers {
+        type ReturnType = f64;
+
+        fn split(&self) -> Self {
+            Numbers
+        }
+
+        fn visit_number(&mut self, _node: &Node) -> f64 {
+            1.0
+        }
+    }
+
+    /// Delegates to a `Summer`, keeping a label for each split.
+    #[derive(VisitorDelegate)]
+    #[visitor_delegate(WrapperVisitor, "src/node-types.json", parallel)]
+    pub struct Labeled {
+        #[delegate]
+        pub summer: Summer,
+        pub label: String,
+    }
+}
+
+fn summer(source: &str) -> wrappers::Summer {
+    wrappers::Summer {
+        source: source.into(),
+    }
+}
+
+#[test]
+fn test_visit_parallel_with_chain() {
+    use wrappers::{ChainedVisitor, Numbers, WrapperVisitor};
+
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let visitor = ChainedVisitor::new(summer(source), Numbers);
+    assert_eq!(visitor.visit_parallel(&tree, |a, b| a + b), Some(6.0));
+}
+
+#[test]
+fn test_visit_parallel_with_catch_unimplemented() {
+    use wrappers::{CatchUnimplemented, WrapperVisitor};
+
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let visitor = CatchUnimplemented::new(summer(source));
+    assert_eq!(visitor.visit_parallel(&tree, |a, b| a + b), Some(6.0));
+}
+
+#[test]
+fn test_visit_parallel_with_coverage() {
+    use wrappers::{CoverageTracker, WrapperVisitor};
+
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let summer = summer(source);
+    assert_eq!(summer.visit_parallel(&tree, |a, b| a + b), Some(6.0));
+
+    let mut tracker = CoverageTracker::new(summer);
+    assert_eq!(tracker.visit(&tree.root_node().child(0).unwrap()), 6.0);
+    assert_eq!(tracker.hits("visit_number"), 3);
+}
+
+#[test]
+fn test_visit_parallel_with_memoize() {
+    use wrappers::{Memoized, WrapperVisitor};
+
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let visitor = Memoized::new(summer(source));
+    assert_eq!(visitor.visit_parallel(&tree, |a, b| a + b), Some(6.0));
+}
+
+#[test]
+fn test_visit_parallel_with_combinators() {
+    use wrappers::{MappedVisitor, MultiVisitor, WrapperVisitor};
+
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let visitor = MultiVisitor::new((summer(source), summer(source)));
+    assert_eq!(
+        visitor.visit_parallel(&tree, |a, b| (a.0 + b.0, a.1 + b.1)),
+        Some((6.0, 6.0))
+    );
+    let visitor = MappedVisitor::new(summer(source), |sum| sum * 2.0);
+    assert_eq!(visitor.visit_parallel(&tree, |a, b| a + b), Some(12.0));
+}
+
+#[test]
+fn test_visit_parallel_with_enum_dispatch() {
+    use wrappers::{AnySummer, Numbers, WrapperVisitor};
+
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let visitor = AnySummer::Summer(summer(source));
+    assert_eq!(visitor.visit_parallel(&tree, |a, b| a + b), Some(6.0));
+    assert!(matches!(
+        AnySummer::Numbers(Numbers).split(),
+        AnySummer::Numbers(_)
+    ));
+}
+
+#[test]
+fn test_visit_parallel_with_delegate() {
+    use wrappers::{Labeled, WrapperVisitor};
+
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let visitor = Labeled {
+        summer: summer(source),
+        label: "sum".to_string(),
+    };
+    assert_eq!(visitor.visit_parallel(&tree, |a, b| a + b), Some(6.0));
+    assert_eq!(visitor.split().label, "sum");
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait(
+    "src/node-types.json",
+    params(numbers: &mut Vec<String>, source: &str),
+    walker
+)]
+pub trait CalcVisitor {}
+
+struct NumberCollector;
+
+impl CalcVisitor for NumberCollector {
+    type ReturnType = ();
+
+    fn visit_root(&mut self, node: &Node, numbers: &mut Vec<String>, source: &str) {
+        self.visit(&node.child(0).unwrap(), numbers, source)
+    }
+
+    fn visit_number(&mut self, node: &Node, numbers: &mut Vec<String>, source: &str) {
+        numbers.push(source[node.byte_range()].to_string());
+    }
+
+    fn visit_add_expr(&mut self, node: &Node, numbers: &mut Vec<String>, source: &str) {
+        self.visit(&node.child_by_field_name("lhs").unwrap(), numbers, source);
+        self.visit(&node.child_by_field_name("rhs").unwrap(), numbers, source);
+    }
+
+    fn visit_PLUS(&mut self, _node: &Node, _numbers: &mut Vec<String>, _source: &str) {}
+}
+
+#[test]
+fn test_params_are_threaded_through_dispatch() {
+    let source = "12 + 345";
+    let parsed = parse(source);
+
+    let mut numbers = Vec::new();
+    NumberCollector.visit(&parsed.root_node(), &mut numbers, source);
+    assert_eq!(numbers, vec!["12", "345"]);
+
+    let mut numbers = Vec::new();
+    let add_expr = parsed.root_node().child(0).unwrap();
+    let mut visited = 0;
+    Walker::new().leaves_only().walk(
+        &mut NumberCollector,
+        add_expr,
+        &mut numbers,
+        source,
+        |_, ()| visited += 1,
+    );
+    assert_eq!(visited, 3);
+    assert_eq!(numbers, vec!["12", "345"]);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{parse, Calculator};
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", return_type = "f64")]
+pub trait CalcVisitor {}
+
+#[visitor_trait("src/node-types.json", return_type = "()", visit_children)]
+pub trait CalcLinter {}
+
+#[visitor_trait("src/node-types.json", return_type = "()", named_only)]
+pub trait NamedLinter {}
+
+impl CalcVisitor for Calculator<'_> {
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+        lhs + rhs
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.number(node)
+    }
+}
+
+/// Counts the parenthesized expressions, leaving every other method to its default.
+struct ParenCounter {
+    count: usize,
+}
+
+impl CalcLinter for ParenCounter {
+    fn visit_root(&mut self, node: &Node) {
+        self.visit_children(node);
+    }
+
+    fn visit_paren_expr(&mut self, node: &Node) {
+        self.count += 1;
+        self.visit_children(node);
+    }
+}
+
+#[test]
+fn test_preset_return_type() {
+    let source = "1 + 2";
+    let tree = parse(source);
+    let mut calculator = Calculator { source };
+    assert_eq!(calculator.visit(&tree.root_node()), 3.0);
+}
+
+#[test]
+#[should_panic(expected = "mul_expr")]
+fn test_preset_return_type_defaults_panic() {
+    let source = "1 * 2";
+    let tree = parse(source);
+    Calculator { source }.visit(&tree.root_node());
+}
+
+#[test]
+fn test_unit_return_type_defaults_do_nothing() {
+    let tree = parse("((1)) * 2");
+    let mut counter = ParenCounter { count: 0 };
+    counter.visit(&tree.root_node());
+    // `visit_mul_expr()` does nothing by default, so the parenthesized operand is never reached.
+    assert_eq!(counter.count, 0);
+
+    let tree = parse("((1))");
+    counter.visit(&tree.root_node());
+    assert_eq!(counter.count, 2);
+}
+
+struct Silent;
+
+impl NamedLinter for Silent {}
+
+#[test]
+#[should_panic(expected = "unknown node kind: +")]
+fn test_unit_return_type_unknown_kinds_panic() {
+    // The anonymous `+` is of an unknown kind with `named_only`.
+    let tree = parse("1 + 2");
+    let plus = tree.root_node().child(0).unwrap().child(1).unwrap();
+    Silent.visit(&tree.root_node());
+    Silent.visit(&plus);
+}
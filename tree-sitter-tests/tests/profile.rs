@@ -0,0 +1,47 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", profile = "tests/calc-profile.json", coverage)]
+pub trait CalcVisitor {}
+
+struct Evaluator;
+
+impl CalcVisitor for Evaluator {
+    type ReturnType = i64;
+
+    fn visit_root(&mut self, node: &Node) -> Self::ReturnType {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> Self::ReturnType {
+        node.byte_range().len() as i64
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> Self::ReturnType {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            + self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+}
+
+#[test]
+fn test_profile_round_trip() {
+    let mut tracker = CoverageTracker::new(Evaluator);
+    for source in ["1 + 22", "333"] {
+        let parsed = parse(source);
+        tracker.visit(&parsed.root_node());
+    }
+
+    let profile = tracker.profile();
+    assert!(profile.starts_with('{') && profile.ends_with('}'));
+    for entry in [
+        r#""number": 3"#,
+        r#""add_expr": 1"#,
+        r#""root": 2"#,
+        r#""*": 0"#,
+    ] {
+        assert!(profile.contains(entry), "{} not in {}", entry, profile);
+    }
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::parse;
+use tree_sitter::{Node, Query};
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", query_captures)]
+pub trait CalcVisitor {}
+
+/// Counts the nodes it visits, so that tests can check that only captured nodes are visited.
+#[derive(Default)]
+struct Counter {
+    visited: usize,
+}
+
+impl CalcVisitor for Counter {
+    type ReturnType = String;
+
+    fn visit_number(&mut self, node: &Node) -> String {
+        self.visited += 1;
+        format!("number {:?}", node.byte_range())
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) -> String {
+        self.visited += 1;
+        format!("product {:?}", node.byte_range())
+    }
+}
+
+#[test]
+fn test_visit_captures() {
+    let source = "1 + 2 * 3";
+    let parsed = parse(source);
+    let query = Query::new(
+        &tree_sitter_tests::language(),
+        "(mul_expr lhs: (number) @lhs) @product",
+    )
+    .unwrap();
+
+    let mut visitor = Counter::default();
+    let mut captures = Vec::new();
+    visit_captures(
+        &mut visitor,
+        &query,
+        parsed.root_node(),
+        source.as_bytes(),
+        |capture, result| captures.push((capture.name, result)),
+    );
+
+    assert_eq!(
+        captures,
+        vec![
+            ("product", "product 4..9".to_string()),
+            ("lhs", "number 4..5".to_string()),
+        ]
+    );
+    assert_eq!(visitor.visited, 2);
+}
+
+#[test]
+fn test_visit_captures_evaluates_predicates() {
+    let source = "1 + 2 * 1";
+    let parsed = parse(source);
+    let query = Query::new(
+        &tree_sitter_tests::language(),
+        r#"((number) @one (#eq? @one "1"))"#,
+    )
+    .unwrap();
+
+    let mut visitor = Counter::default();
+    let mut captures = Vec::new();
+    visit_captures(
+        &mut visitor,
+        &query,
+        parsed.root_node(),
+        source.as_bytes(),
+        |capture, result| captures.push((capture.pattern_index, result)),
+    );
+
+    assert_eq!(
+        captures,
+        vec![
+            (0, "number 0..1".to_string()),
+            (0, "number 8..9".to_string())
+        ]
+    );
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::query_visitor;
+
+#[query_visitor("queries/operands.scm", "src/node-types.json")]
+pub trait Operands {}
+
+/// Records the text of every capture, leaving out the right-hand sides of additions.
+struct Recorder<'a> {
+    source: &'a str,
+    captures: Vec<(&'static str, &'a str)>,
+}
+
+impl Operands for Recorder<'_> {
+    fn visit_operand_left(&mut self, node: &Node, _pattern_index: usize) {
+        self.captures
+            .push(("operand.left", &self.source[node.byte_range()]));
+    }
+
+    fn visit_operator(&mut self, node: &Node, pattern_index: usize) {
+        assert_eq!(pattern_index, 1);
+        self.captures
+            .push(("operator", &self.source[node.byte_range()]));
+    }
+
+    fn visit_number_zero(&mut self, node: &Node, _pattern_index: usize) {
+        self.captures
+            .push(("number.zero", &self.source[node.byte_range()]));
+    }
+}
+
+#[test]
+fn test_capture_names() {
+    assert_eq!(
+        Recorder::CAPTURE_NAMES,
+        [
+            "operand.left",
+            "operand.right",
+            "operator",
+            "number.zero",
+            "empty"
+        ]
+    );
+    let query = Recorder::query(tree_sitter_tests::language()).unwrap();
+    assert_eq!(query.capture_names(), Recorder::CAPTURE_NAMES);
+}
+
+#[test]
+fn test_run_dispatches_captures() {
+    let source = "1 + 2 * 0 / 3";
+    let tree = parse(source);
+    let query = Recorder::query(tree_sitter_tests::language()).unwrap();
+    let mut recorder = Recorder {
+        source,
+        captures: Vec::new(),
+    };
+    recorder.run(&query, tree.root_node(), source.as_bytes());
+    assert_eq!(
+        recorder.captures,
+        [
+            ("operand.left", "1"),
+            ("operator", "*"),
+            ("number.zero", "0"),
+            ("operator", "/"),
+        ]
+    );
+}
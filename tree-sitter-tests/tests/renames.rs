@@ -0,0 +1,65 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", renames(sum = "add_expr", "integer" = "number"))]
+trait CalcVisitor {}
+
+/// A visitor written against the old kind names.
+struct Legacy;
+
+impl CalcVisitor for Legacy {
+    type ReturnType = i64;
+
+    fn visit_root(&mut self, node: &Node) -> i64 {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_sum(&mut self, node: &Node) -> i64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            + self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+
+    fn visit_integer(&mut self, _node: &Node) -> i64 {
+        1
+    }
+}
+
+/// A visitor migrated to the new kind names.
+struct Migrated;
+
+impl CalcVisitor for Migrated {
+    type ReturnType = i64;
+
+    fn visit_root(&mut self, node: &Node) -> i64 {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> i64 {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            + self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+
+    fn visit_number(&mut self, _node: &Node) -> i64 {
+        1
+    }
+}
+
+#[test]
+fn test_renames() {
+    let tree = parse("1+2+3");
+
+    assert_eq!(Legacy.visit(&tree.root_node()), 3);
+    assert_eq!(Migrated.visit(&tree.root_node()), 3);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_shim_forwards() {
+    let tree = parse("7");
+    let number = tree.root_node().child(0).unwrap();
+
+    assert_eq!(Migrated.visit_integer(&number), 1);
+}
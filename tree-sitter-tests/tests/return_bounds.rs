@@ -0,0 +1,37 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait(
+    "src/node-types.json",
+    return_bounds = "Clone + Send + 'static",
+    fn_visitor,
+    chain
+)]
+pub trait CalcVisitor {}
+
+/// Visits a node twice and on another thread, relying only on the bounds of `ReturnType`.
+fn visit_twice<V: CalcVisitor>(visitor: &mut V, node: &Node) -> (V::ReturnType, V::ReturnType) {
+    let result = visitor.visit(node);
+    let copy = std::thread::spawn({
+        let result = result.clone();
+        move || result
+    })
+    .join()
+    .unwrap();
+    (result, copy)
+}
+
+#[test]
+fn test_return_type_bounds() {
+    let parsed = parse("1");
+    let number = parsed.root_node().child(0).unwrap();
+
+    let mut visitor = FnVisitor::new().otherwise(|node| node.kind().to_string());
+    assert_eq!(
+        visit_twice(&mut visitor, &number),
+        ("number".to_string(), "number".to_string())
+    );
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", named_only, return_type_param = R, defaults)]
+pub trait CalcVisitor {}
+
+/// A calculator implementing the visitor twice, evaluating expressions and collecting numbers.
+struct Calculator<'s> {
+    source: &'s str,
+    numbers: Vec<f64>,
+}
+
+impl CalcVisitor<f64> for Calculator<'_> {
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        let lhs: f64 = self.visit(&node.named_child(0).unwrap());
+        let rhs: f64 = self.visit(&node.named_child(1).unwrap());
+        lhs + rhs
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.source[node.byte_range()].parse().unwrap()
+    }
+}
+
+impl CalcVisitor<()> for Calculator<'_> {
+    fn visit_root(&mut self, node: &Node) {
+        CalcVisitor::<()>::visit(self, &node.named_child(0).unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) {
+        CalcVisitor::<()>::visit(self, &node.named_child(0).unwrap());
+        CalcVisitor::<()>::visit(self, &node.named_child(1).unwrap());
+    }
+
+    fn visit_number(&mut self, node: &Node) {
+        let number = CalcVisitor::<f64>::visit_number(self, node);
+        self.numbers.push(number);
+    }
+}
+
+/// Visits a node with any visitor returning `String`.
+fn describe<V: CalcVisitor<String>>(visitor: &mut V, node: &Node) -> String {
+    visitor.visit(node)
+}
+
+struct Describer;
+
+impl CalcVisitor<String> for Describer {
+    fn visit_number(&mut self, _node: &Node) -> String {
+        "number".to_string()
+    }
+}
+
+#[test]
+fn test_return_type_param() {
+    let source = "1 + 2";
+    let tree = parse(source);
+    let root = tree.root_node();
+
+    let mut calculator = Calculator {
+        source,
+        numbers: Vec::new(),
+    };
+    let result: f64 = calculator.visit(&root);
+    assert_eq!(result, 3.0);
+    CalcVisitor::<()>::visit(&mut calculator, &root);
+    assert_eq!(calculator.numbers, [1.0, 2.0]);
+
+    // Methods that are not implemented return the default value with `defaults`.
+    assert_eq!(describe(&mut Describer, &root), "");
+    let number = root.named_child(0).unwrap().named_child(0).unwrap();
+    assert_eq!(describe(&mut Describer, &number), "number");
+}
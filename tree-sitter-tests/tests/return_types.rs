@@ -0,0 +1,42 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", return_types(root = Vec<i64>, "paren_expr" = String))]
+pub trait CalcVisitor {}
+
+struct Evaluator;
+
+impl CalcVisitor for Evaluator {
+    type ReturnType = i64;
+
+    fn visit_root(&mut self, node: &Node) -> Vec<i64> {
+        let mut cursor = node.walk();
+        let children: Vec<_> = node.named_children(&mut cursor).collect();
+        children.iter().map(|child| self.visit(child)).collect()
+    }
+
+    fn visit_number(&mut self, node: &Node) -> Self::ReturnType {
+        node.byte_range().len() as i64
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> Self::ReturnType {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            + self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+}
+
+#[test]
+fn test_methods_with_dedicated_return_types() {
+    let parsed = parse("12 + 345");
+    assert_eq!(Evaluator.visit_root(&parsed.root_node()), vec![5]);
+}
+
+#[test]
+#[should_panic(expected = "must be visited with `visit_root()`")]
+fn test_dispatch_excludes_methods_with_dedicated_return_types() {
+    let parsed = parse("1");
+    Evaluator.visit(&parsed.root_node());
+}
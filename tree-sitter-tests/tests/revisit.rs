@@ -0,0 +1,81 @@
+mod common;
+
+use common::parser;
+use tree_sitter::{InputEdit, Node, Point};
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", revisit, named_only, unknown = "skip")]
+pub trait CalcVisitor {}
+
+/// Evaluates expressions, recording the kinds of the nodes it visits and invalidates.
+#[derive(Default)]
+struct Calculator {
+    source: String,
+    visited: Vec<&'static str>,
+    invalidated: Vec<&'static str>,
+}
+
+impl Calculator {
+    fn operands(&mut self, node: &Node) -> (f64, f64) {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+        (lhs, rhs)
+    }
+}
+
+impl CalcVisitor for Calculator {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        self.visited.push(node.kind());
+        let (lhs, rhs) = self.operands(node);
+        lhs + rhs
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) -> f64 {
+        self.visited.push(node.kind());
+        let (lhs, rhs) = self.operands(node);
+        lhs * rhs
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.visited.push(node.kind());
+        self.source[node.byte_range()].parse().unwrap()
+    }
+
+    fn invalidate(&mut self, node: &Node) {
+        self.invalidated.push(node.kind());
+    }
+}
+
+#[test]
+fn test_revisit_changed() {
+    let mut parser = parser();
+    let mut old_tree = parser.parse("1 + 2 * 3", None).unwrap();
+
+    // Replace "3" with "45".
+    let edit = InputEdit {
+        start_byte: 8,
+        old_end_byte: 9,
+        new_end_byte: 10,
+        start_position: Point::new(0, 8),
+        old_end_position: Point::new(0, 9),
+        new_end_position: Point::new(0, 10),
+    };
+    old_tree.edit(&edit);
+    let source = "1 + 2 * 45";
+    let new_tree = parser.parse(source, Some(&old_tree)).unwrap();
+
+    let mut calculator = Calculator {
+        source: source.to_string(),
+        ..Calculator::default()
+    };
+    let results = calculator.revisit_changed(&old_tree, &new_tree, &[edit]);
+    assert_eq!(results, [45.0]);
+    assert_eq!(calculator.visited, ["number"]);
+    assert_eq!(calculator.invalidated, ["mul_expr", "add_expr", "root"]);
+}
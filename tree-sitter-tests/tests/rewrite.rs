@@ -0,0 +1,55 @@
+mod common;
+
+use common::parser;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", flavors(rewrite))]
+pub trait CalcVisitor {}
+
+/// Folds additions of two numbers, and turns subtractions into additions over two lines.
+struct Simplifier;
+
+impl CalcRewriter for Simplifier {
+    fn rewrite_add_expr(&mut self, node: &Node<'_>, source: &str) -> Option<String> {
+        let lhs = node.child_by_field_name("lhs")?;
+        let rhs = node.child_by_field_name("rhs")?;
+        let lhs: f64 = source[lhs.byte_range()].parse().ok()?;
+        let rhs: f64 = source[rhs.byte_range()].parse().ok()?;
+        Some((lhs + rhs).to_string())
+    }
+
+    fn rewrite_DASH(&mut self, _node: &Node<'_>, _source: &str) -> Option<String> {
+        Some("+\n".to_string())
+    }
+}
+
+#[test]
+fn test_rewrite() {
+    let source = "(1 + 2) * (3 - 4)";
+    let mut parser = parser();
+    let mut tree = parser.parse(source, None).expect("Could not parse");
+
+    let edits = Simplifier.edits(tree.root_node(), source);
+    assert_eq!(
+        edits
+            .iter()
+            .map(|edit| edit.range.clone())
+            .collect::<Vec<_>>(),
+        [1..6, 13..14]
+    );
+
+    let (rewritten, input_edits) = Simplifier.rewrite(tree.root_node(), source);
+    assert_eq!(rewritten, "(3) * (3 +\n 4)");
+
+    for input_edit in &input_edits {
+        tree.edit(input_edit);
+    }
+    let reparsed = parser.parse(&rewritten, Some(&tree)).unwrap();
+    let fresh = parser.parse(&rewritten, None).unwrap();
+    assert_eq!(reparsed.root_node().to_sexp(), fresh.root_node().to_sexp());
+    assert_eq!(
+        reparsed.root_node().end_position(),
+        fresh.root_node().end_position()
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::parse;
+use serde_json::json;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", serialize)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_serialize_follows_node_types() {
+    let source = "1 + (2)";
+    let tree = parse(source);
+    let value = serde_json::to_value(SerializableNode::from_tree(&tree, source)).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "type": "root",
+            "named": true,
+            "start": 0,
+            "end": 7,
+            "children": [{
+                "type": "add_expr",
+                "named": true,
+                "start": 0,
+                "end": 7,
+                "fields": {
+                    "lhs": {"type": "number", "named": true, "start": 0, "end": 1, "text": "1"},
+                    "rhs": {
+                        "type": "paren_expr",
+                        "named": true,
+                        "start": 4,
+                        "end": 7,
+                        "fields": {
+                            "body": {"type": "number", "named": true, "start": 5, "end": 6, "text": "2"},
+                        },
+                    },
+                },
+            }],
+        })
+    );
+}
+
+#[test]
+fn test_serialize_error_nodes() {
+    let source = "1 2 3";
+    let tree = parse(source);
+    let error = tree.root_node().child(0).unwrap();
+    assert!(error.is_error());
+    let value = serde_json::to_value(SerializableNode::new(error, source)).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "type": "ERROR",
+            "named": true,
+            "start": 0,
+            "end": 3,
+            "children": [
+                {"type": "number", "named": true, "start": 0, "end": 1, "text": "1"},
+                {"type": "number", "named": true, "start": 2, "end": 3, "text": "2"},
+            ],
+        })
+    );
+}
+
+#[test]
+fn test_serialize_anonymous_nodes() {
+    let source = "1 + 2";
+    let tree = parse(source);
+    let plus = tree.root_node().child(0).unwrap().child(1).unwrap();
+    assert_eq!(
+        serde_json::to_string(&SerializableNode::new(plus, source)).unwrap(),
+        r#"{"type":"+","named":false,"start":2,"end":3,"text":"+"}"#
+    );
+}
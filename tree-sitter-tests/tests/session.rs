@@ -0,0 +1,84 @@
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", session)]
+pub trait CalcVisitor {}
+
+#[derive(Default)]
+struct Calculator {
+    src: String,
+}
+
+impl CalcVisitor for Calculator {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.src[node.byte_range()].parse().unwrap()
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+
+        lhs + rhs
+    }
+}
+
+fn session(src: &str) -> Session<Calculator> {
+    let calculator = Calculator {
+        src: src.to_string(),
+    };
+    Session::new(tree_sitter_tests::language(), src.to_string(), calculator).unwrap()
+}
+
+#[test]
+fn test_session_revisits_after_edit() {
+    let mut session = session("1 + 2");
+    assert_eq!(session.visit(), 3.0);
+
+    session.visitor_mut().src = "1 + 40".to_string();
+    assert_eq!(session.edit(4..5, "40").unwrap(), 41.0);
+    assert_eq!(session.source(), "1 + 40");
+    assert_eq!(
+        session.tree().root_node().to_sexp(),
+        "(root (add_expr lhs: (number) rhs: (number)))"
+    );
+}
+
+#[test]
+fn test_session_visits_changed_nodes_only() {
+    let mut session = session("1 + 2 + 3");
+
+    session.visitor_mut().src = "1 + 2 + 30".to_string();
+    assert_eq!(session.edit_changed(8..9, "30").unwrap(), vec![30.0]);
+}
+
+#[test]
+fn test_session_rejects_invalid_ranges() {
+    let mut session = session("1 + 2");
+
+    assert_eq!(
+        session.edit(4..6, "3"),
+        Err(SessionError::InvalidRange(4..6))
+    );
+    let reversed = std::ops::Range { start: 4, end: 3 };
+    assert_eq!(
+        session.edit_changed(reversed.clone(), "3"),
+        Err(SessionError::InvalidRange(reversed))
+    );
+    assert_eq!(session.source(), "1 + 2");
+    assert_eq!(session.visit(), 3.0);
+}
+
+#[test]
+fn test_session_rejects_ranges_inside_characters() {
+    let mut session = session("1 + \u{e9}");
+    assert_eq!(
+        session.edit(5..6, "2"),
+        Err(SessionError::InvalidRange(5..6))
+    );
+}
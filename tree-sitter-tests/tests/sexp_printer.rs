@@ -0,0 +1,64 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", sexp_printer)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_sexp_printer_prints_fields_and_ranges() {
+    let tree = parse("1 + 2 * 3");
+    assert_eq!(
+        SexpPrinter::new().print(&tree.root_node()),
+        "(root [0..9]
+  (add_expr [0..9]
+    lhs: (number [0..1])
+    rhs: (mul_expr [4..9]
+      lhs: (number [4..5])
+      rhs: (number [8..9]))))"
+    );
+}
+
+#[test]
+fn test_sexp_printer_options() {
+    let source = "(12)";
+    let tree = parse(source);
+    let printer = SexpPrinter::new()
+        .source(source)
+        .anonymous(true)
+        .ranges(false);
+    assert_eq!(
+        printer.print(&tree.root_node()),
+        r#"(root
+  (paren_expr
+    "("
+    body: (number "12")
+    ")"))"#
+    );
+
+    // An anonymous node is printed on its own.
+    let paren = tree.root_node().child(0).unwrap().child(0).unwrap();
+    assert_eq!(printer.print(&paren), r#""(""#);
+}
+
+#[test]
+fn test_sexp_printer_missing_nodes() {
+    let tree = parse("1 +");
+    assert_eq!(
+        SexpPrinter::new().ranges(false).print(&tree.root_node()),
+        "(root
+  (add_expr
+    lhs: (number)
+    rhs: (MISSING number)))"
+    );
+}
+
+#[test]
+fn test_sexp_printer_deep_tree() {
+    let source = format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000));
+    let tree = parse(&source);
+    let printed = SexpPrinter::new().print(&tree.root_node());
+    assert_eq!(printed.lines().count(), 10_002);
+    assert!(printed.ends_with(&")".repeat(10_002)));
+}
@@ -0,0 +1,21 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", siblings)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_semantic_siblings() {
+    let parsed = parse("1 + 2");
+    let add_expr = parsed.root_node().child(0).unwrap();
+    let plus = add_expr.child(1).unwrap();
+
+    // The dummy grammar has no extras, so this matches the plain sibling navigation.
+    assert!(EXTRA_KINDS.is_empty());
+    assert!(!is_extra_node(&plus));
+    assert_eq!(next_semantic_sibling(&plus), plus.next_sibling());
+    assert_eq!(prev_semantic_sibling(&plus), plus.prev_sibling());
+    assert!(next_semantic_sibling(&add_expr).is_none());
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use common::parse;
+use std::fmt::Write;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", source_map, flavors(rewrite))]
+pub trait CalcVisitor {}
+
+/// Transpiles arithmetic to function calls.
+struct Transpiler<'s> {
+    source: &'s str,
+    out: SourceMapBuilder<'s>,
+}
+
+impl<'s> Transpiler<'s> {
+    fn new(source: &'s str) -> Self {
+        Self {
+            source,
+            out: SourceMapBuilder::new(source),
+        }
+    }
+}
+
+/// Spells out the number 2.
+struct Speller;
+
+impl CalcRewriter for Speller {
+    fn rewrite_number(&mut self, node: &Node<'_>, source: &str) -> Option<String> {
+        (&source[node.byte_range()] == "2").then(|| "two".to_string())
+    }
+}
+
+fn operands<'tree>(node: &Node<'tree>) -> (Node<'tree>, Node<'tree>) {
+    (
+        node.child_by_field_name("lhs").unwrap(),
+        node.child_by_field_name("rhs").unwrap(),
+    )
+}
+
+impl CalcVisitor for Transpiler<'_> {
+    type ReturnType = ();
+
+    fn visit_root(&mut self, node: &Node) {
+        self.visit(&node.child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) {
+        let text = &self.source[node.byte_range()];
+        self.out.push_node(node, text);
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) {
+        let (lhs, rhs) = operands(node);
+        self.out.push_node(node, "add(");
+        self.visit(&lhs);
+        self.out.push_str(",\n");
+        self.visit(&rhs);
+        self.out.push_str(")");
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) {
+        let (lhs, rhs) = operands(node);
+        self.out.push_node(node, "mul(");
+        self.visit(&lhs);
+        write!(self.out, ", ").unwrap();
+        self.visit(&rhs);
+        write!(self.out, ")").unwrap();
+    }
+
+    fn visit_sub_expr(&mut self, node: &Node) {
+        let (lhs, rhs) = operands(node);
+        self.visit(&rhs);
+        self.out.push_str(" subtracted from ");
+        self.visit(&lhs);
+    }
+}
+
+#[test]
+fn test_source_map() {
+    let source = "1 + 2 * 3";
+    let tree = parse(source);
+    let mut transpiler = Transpiler::new(source);
+    transpiler.visit(&tree.root_node());
+
+    assert_eq!(transpiler.out.output(), "add(1,\nmul(2, 3))");
+    assert_eq!(
+        transpiler.out.mappings()[2],
+        SourceMapping {
+            generated_line: 1,
+            generated_column: 0,
+            original_line: 0,
+            original_column: 4,
+        }
+    );
+    assert_eq!(
+        transpiler.out.encoded_mappings(),
+        "AAAA,IAAA;AAAI,IAAA,GAAI"
+    );
+    assert_eq!(
+        transpiler.out.to_json("out.js", "in.calc"),
+        r#"{"version":3,"file":"out.js","sources":["in.calc"],"sourcesContent":["1 + 2 * 3"],"names":[],"mappings":"AAAA,IAAA;AAAI,IAAA,GAAI"}"#
+    );
+}
+
+#[test]
+fn test_source_map_backwards_mappings() {
+    let source = "10 - 2";
+    let tree = parse(source);
+    let mut transpiler = Transpiler::new(source);
+    transpiler.visit(&tree.root_node());
+
+    assert_eq!(transpiler.out.output(), "2 subtracted from 10");
+    assert_eq!(transpiler.out.encoded_mappings(), "AAAK,kBAAL");
+}
+
+#[test]
+fn test_rewrite_source_map() {
+    let source = "1 +\n2 * 3";
+    let tree = parse(source);
+    let out = Speller.rewrite_mapped(tree.root_node(), source);
+
+    assert_eq!(out.output(), "1 +\ntwo * 3");
+    assert_eq!(
+        out.to_json("out.calc", "in.calc"),
+        r#"{"version":3,"file":"out.calc","sources":["in.calc"],"sourcesContent":["1 +\n2 * 3"],"names":[],"mappings":"AAAA;AACA,GAAC"}"#
+    );
+}
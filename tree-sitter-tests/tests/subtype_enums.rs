@@ -0,0 +1,42 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+use supertypes::Expr;
+
+#[visitor_trait("tests/calc-supertypes.json", subtype_enums)]
+pub trait CalcVisitor {}
+
+/// Evaluates an expression with an exhaustive match over the subtypes of `_expr`.
+fn eval(node: Node, source: &str) -> f64 {
+    let operand = |field| {
+        let child = node.child_by_field_name(field).unwrap();
+        eval(child, source)
+    };
+    match Expr::classify(node).unwrap() {
+        Expr::AddExpr(_) => operand("lhs") + operand("rhs"),
+        Expr::SubExpr(_) => operand("lhs") - operand("rhs"),
+        Expr::MulExpr(_) => operand("lhs") * operand("rhs"),
+        Expr::DivExpr(_) => operand("lhs") / operand("rhs"),
+        Expr::ParenExpr(_) => operand("body"),
+        Expr::Number(number) => source[number.byte_range()].parse().unwrap(),
+    }
+}
+
+#[test]
+fn test_classify() {
+    let source = "(1 + 2) * 3";
+    let tree = parse(source);
+    let root = tree.root_node();
+    let mul_expr = root.named_child(0).unwrap();
+
+    assert_eq!(Expr::classify(mul_expr), Some(Expr::MulExpr(mul_expr)));
+    assert_eq!(Expr::classify(mul_expr).unwrap().node(), mul_expr);
+    assert_eq!(Expr::classify(root), None);
+    // The anonymous `*` token is not a subtype.
+    assert_eq!(Expr::classify(mul_expr.child(1).unwrap()), None);
+
+    assert_eq!(eval(mul_expr, source), 9.0);
+}
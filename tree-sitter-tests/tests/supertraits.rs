@@ -0,0 +1,49 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+pub trait Named {
+    fn name(&self) -> &'static str;
+}
+
+#[visitor_trait("src/node-types.json", supertraits = "Send + Sync + Named", chain)]
+pub trait CalcVisitor {}
+
+struct Counter;
+
+impl Named for Counter {
+    fn name(&self) -> &'static str {
+        "counter"
+    }
+}
+
+impl CalcVisitor for Counter {
+    type ReturnType = usize;
+
+    fn visit_number(&mut self, _node: &Node) -> Self::ReturnType {
+        1
+    }
+}
+
+impl<A, B> Named for ChainedVisitor<A, B> {
+    fn name(&self) -> &'static str {
+        "chained"
+    }
+}
+
+fn run_in_thread<V: CalcVisitor + 'static>(visitor: V) -> &'static str {
+    std::thread::spawn(move || visitor.name()).join().unwrap()
+}
+
+#[test]
+fn test_supertraits_are_required() {
+    let parsed = parse("1");
+    let number = parsed.root_node().child(0).unwrap();
+
+    let mut chained = ChainedVisitor::new(Counter, Counter);
+    assert_eq!(chained.visit(&number), 1);
+    assert_eq!(run_in_thread(chained), "chained");
+    assert_eq!(run_in_thread(Counter), "counter");
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+// The calc grammar with `_expr` declared as a supertype, as if generated with
+// `supertypes: $ => [$._expr]`.
+#[visitor_trait("tests/calc-supertypes.json", supertypes)]
+pub trait CalcVisitor {}
+
+/// Counts operators, handling every expression it doesn't specialize in the supertype method.
+struct Operators {
+    count: usize,
+}
+
+impl CalcVisitor for Operators {
+    type ReturnType = ();
+
+    fn visit_root(&mut self, node: &Node) {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit__expr(&mut self, node: &Node) {
+        self.count += 1;
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.visit(&child);
+        }
+    }
+
+    fn visit_number(&mut self, _node: &Node) {}
+
+    fn visit_paren_expr(&mut self, node: &Node) {
+        self.visit(&node.child_by_field_name("body").unwrap())
+    }
+}
+
+#[test]
+fn test_subtypes_forward_to_supertype() {
+    let tree = parse("1 + (2 * 3) - 4 / 5");
+
+    let mut visitor = Operators { count: 0 };
+    visitor.visit(&tree.root_node());
+    assert_eq!(visitor.count, 4);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::parser;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", text_provider)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_text_provider_reads_node_text() {
+    let mut parser = parser();
+
+    let src = "12 + 345";
+    let parsed = src.parse_with(&mut parser, None).expect("Could not parse");
+    let add_expr = parsed.root_node().child(0).unwrap();
+    let rhs = add_expr.child_by_field_name("rhs").unwrap();
+
+    assert_eq!(src.node_text(&rhs), "345");
+    assert_eq!(src.as_bytes().node_text(&rhs), "345");
+    assert_eq!(src.to_string().text(0..2), "12");
+}
+
+pub mod rope {
+    use tree_sitter_visitor::visitor_trait;
+
+    #[visitor_trait(
+        "src/node-types.json",
+        ropey,
+        fingerprint,
+        html,
+        interner,
+        serialize,
+        sexp_printer,
+        source_map
+    )]
+    pub trait RopeVisitor {}
+}
+
+#[test]
+fn test_rope_text_provider() {
+    use rope::{
+        HtmlRenderer, NodeFingerprint, SerializableNode, SexpPrinter, SourceMapBuilder,
+        SymbolTableInterner, TextProvider,
+    };
+
+    let mut parser = parser();
+
+    // Small chunks split the source across several rope chunks.
+    let src = "12 + 345 + 6789 ".repeat(100);
+    let mut builder = ropey::RopeBuilder::new();
+    for chunk in src.as_bytes().chunks(7) {
+        builder.append(std::str::from_utf8(chunk).unwrap());
+    }
+    let rope = builder.finish();
+    assert_eq!(rope.len_bytes(), TextProvider::len_bytes(&rope));
+    assert_eq!(rope.full_text(), src);
+
+    let parsed = rope.parse_with(&mut parser, None).expect("Could not parse");
+    let from_str = parser.parse(&src, None).expect("Could not parse");
+    assert_eq!(parsed.root_node().to_sexp(), from_str.root_node().to_sexp());
+
+    let add_expr = parsed.root_node().child(0).unwrap();
+    let rhs = add_expr.child_by_field_name("rhs").unwrap();
+    assert_eq!(rope.node_text(&rhs), "6789");
+    assert_eq!(rope.slice(..).node_text(&rhs), "6789");
+    assert_eq!(rope.text(0..8), "12 + 345");
+    assert!(rope.chunk_at(rope.len_bytes()).is_empty());
+
+    // The helpers read the rope like the contiguous source.
+    assert_eq!(
+        NodeFingerprint::new(&rhs, &rope),
+        NodeFingerprint::new(&rhs, src.as_str())
+    );
+    let mut interner = SymbolTableInterner::new();
+    assert_eq!(
+        interner.intern_node(&rhs, &rope),
+        interner.intern_node(&rhs, src.as_bytes())
+    );
+    assert_eq!(
+        SexpPrinter::new().source(&rope).print(&rhs),
+        SexpPrinter::new().source(src.as_str()).print(&rhs)
+    );
+    let mut source_map = SourceMapBuilder::new(&rope);
+    source_map.push_node(&rhs, "x");
+    assert_eq!(source_map.to_json("out.js", "in.calc"), {
+        let mut source_map = SourceMapBuilder::new(src.as_str());
+        source_map.push_node(&rhs, "x");
+        source_map.to_json("out.js", "in.calc")
+    });
+
+    assert_eq!(
+        serde_json::to_value(SerializableNode::new(rhs, &rope)).unwrap(),
+        serde_json::to_value(SerializableNode::new(rhs, src.as_str())).unwrap()
+    );
+
+    // The text predicates of queries read the rope chunk by chunk.
+    let query = tree_sitter::Query::new(
+        &tree_sitter_tests::language(),
+        r#"((number) @big (#eq? @big "6789"))"#,
+    )
+    .unwrap();
+    let spans = HtmlRenderer::capture_spans(&query, &parsed, &rope);
+    assert_eq!(spans.len(), 100);
+    assert_eq!(
+        spans,
+        HtmlRenderer::capture_spans(&query, &parsed, src.as_bytes())
+    );
+    assert_eq!(
+        HtmlRenderer::render_spans(&rope, [spans.clone()]),
+        HtmlRenderer::render_spans(src.as_str(), [spans])
+    );
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", token_visitor)]
+pub trait CalcVisitor {}
+
+struct TokenCounter;
+
+impl CalcTokenVisitor for TokenCounter {
+    type ReturnType = &'static str;
+
+    fn visit_number(&mut self, _node: &Node) -> Self::ReturnType {
+        "number"
+    }
+
+    fn visit_PLUS(&mut self, _node: &Node) -> Self::ReturnType {
+        "operator"
+    }
+
+    fn visit_STAR(&mut self, _node: &Node) -> Self::ReturnType {
+        "operator"
+    }
+
+    fn visit_LPAREN(&mut self, _node: &Node) -> Self::ReturnType {
+        "punctuation"
+    }
+
+    fn visit_RPAREN(&mut self, _node: &Node) -> Self::ReturnType {
+        "punctuation"
+    }
+}
+
+#[test]
+fn test_token_visitor_visits_tokens() {
+    let parsed = parse("(1 + 2) * 3");
+
+    let mut tokens = Vec::new();
+    TokenCounter.visit_tokens(&parsed.root_node(), &mut |node, class| {
+        tokens.push((node.start_byte(), class))
+    });
+    assert_eq!(
+        tokens,
+        vec![
+            (0, "punctuation"),
+            (1, "number"),
+            (3, "operator"),
+            (5, "number"),
+            (6, "punctuation"),
+            (8, "operator"),
+            (10, "number"),
+        ]
+    );
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::parse;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", tracing, named_only)]
+pub trait CalcVisitor {}
+
+/// A subscriber recording the fields of the spans created.
+#[derive(Clone, Default)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<String>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+/// Formats the fields of a span as `name=value` pairs.
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut fields = Fields(span.metadata().name().to_string());
+        span.record(&mut fields);
+        self.spans.lock().unwrap().push(fields.0);
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+struct Evaluator<'s> {
+    source: &'s str,
+}
+
+impl CalcVisitor for Evaluator<'_> {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.named_child(0).unwrap()) + self.visit(&node.named_child(1).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> f64 {
+        self.source[node.byte_range()].parse().unwrap()
+    }
+}
+
+#[test]
+fn test_tracing_spans() {
+    let source = "1 + 2";
+    let tree = parse(source);
+
+    let recorder = Recorder::default();
+    let result = tracing::subscriber::with_default(recorder.clone(), || {
+        let mut visitor = Evaluator { source };
+        let first = visitor.visit(&tree.root_node());
+        // The depth starts from zero again after a visit.
+        let second = visitor.visit(&tree.root_node().named_child(0).unwrap());
+        (first, second)
+    });
+    assert_eq!(result, (3.0, 3.0));
+
+    assert_eq!(
+        *recorder.spans.lock().unwrap(),
+        [
+            "visit kind=\"root\" start_byte=0 end_byte=5 depth=0",
+            "visit kind=\"add_expr\" start_byte=0 end_byte=5 depth=1",
+            "visit kind=\"number\" start_byte=0 end_byte=1 depth=2",
+            "visit kind=\"number\" start_byte=4 end_byte=5 depth=2",
+            "visit kind=\"add_expr\" start_byte=0 end_byte=5 depth=0",
+            "visit kind=\"number\" start_byte=0 end_byte=1 depth=1",
+            "visit kind=\"number\" start_byte=4 end_byte=5 depth=1",
+        ]
+    );
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", try_visit_children)]
+pub trait CalcVisitor {}
+
+/// Collects the digits of a tree, failing on every token that is not a number.
+struct Digits;
+
+impl CalcVisitor for Digits {
+    type ReturnType = Result<Vec<usize>, &'static str>;
+
+    fn visit_number(&mut self, node: &Node) -> Self::ReturnType {
+        Ok(vec![node.start_byte()])
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> Self::ReturnType {
+        Ok(self.try_visit_children(node)?.concat())
+    }
+
+    fn visit_sub_expr(&mut self, node: &Node) -> Self::ReturnType {
+        self.try_visit_children_all(node)
+            .map(|values| values.concat())
+            .map_err(|errors| errors[0])
+    }
+
+    fn visit_PLUS(&mut self, _node: &Node) -> Self::ReturnType {
+        Ok(vec![])
+    }
+
+    fn visit_DASH(&mut self, _node: &Node) -> Self::ReturnType {
+        Err("minus")
+    }
+}
+
+#[test]
+fn test_try_visit_children() {
+    let parsed = parse("1 + 2");
+    let add_expr = parsed.root_node().child(0).unwrap();
+    assert_eq!(
+        Digits.try_visit_children(&add_expr),
+        Ok(vec![vec![0], vec![], vec![4]])
+    );
+
+    let parsed = parse("1 - 2");
+    let sub_expr = parsed.root_node().child(0).unwrap();
+    assert_eq!(Digits.try_visit_children_all(&sub_expr), Err(vec!["minus"]));
+    assert_eq!(Digits.visit(&sub_expr), Err("minus"));
+}
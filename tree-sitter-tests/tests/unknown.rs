@@ -0,0 +1,50 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", unknown = "skip")]
+pub trait SkippingVisitor {}
+
+#[visitor_trait("src/node-types.json", unknown = "hook")]
+pub trait HookVisitor {}
+
+struct Counter;
+
+impl SkippingVisitor for Counter {
+    type ReturnType = usize;
+
+    fn visit_number(&mut self, _node: &Node) -> usize {
+        1
+    }
+}
+
+struct Recovering {
+    errors: Vec<String>,
+}
+
+impl HookVisitor for Recovering {
+    type ReturnType = Option<f64>;
+
+    fn visit_unknown(&mut self, node: &Node) -> Option<f64> {
+        self.errors.push(node.kind().to_string());
+        None
+    }
+}
+
+#[test]
+fn test_unknown_skip() {
+    let tree = parse("+");
+    let error = tree.root_node();
+    assert_eq!(error.kind(), "ERROR");
+    assert_eq!(SkippingVisitor::visit(&mut Counter, &error), 0);
+}
+
+#[test]
+fn test_unknown_hook() {
+    let tree = parse("+");
+    let mut visitor = Recovering { errors: Vec::new() };
+    assert_eq!(visitor.visit(&tree.root_node()), None);
+    assert_eq!(visitor.errors, ["ERROR"]);
+}
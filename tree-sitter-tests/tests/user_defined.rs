@@ -0,0 +1,67 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json")]
+pub trait CountingVisitor {
+    type ReturnType: From<u8> + std::ops::Add<Output = Self::ReturnType>;
+
+    /// Counts every number as one unless overridden.
+    fn visit_number(&mut self, _node: &Node) -> Self::ReturnType {
+        Self::ReturnType::from(1)
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> Self::ReturnType {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+        lhs + rhs
+    }
+}
+
+struct Counter;
+
+impl CountingVisitor for Counter {
+    type ReturnType = u32;
+
+    fn visit_root(&mut self, node: &Node) -> u32 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+}
+
+struct Summer<'a> {
+    source: &'a str,
+}
+
+impl CountingVisitor for Summer<'_> {
+    type ReturnType = u32;
+
+    fn visit_root(&mut self, node: &Node) -> u32 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_number(&mut self, node: &Node) -> u32 {
+        self.source[node.byte_range()].parse().unwrap()
+    }
+}
+
+#[test]
+fn test_user_defined_defaults_are_dispatched() {
+    let tree = parse("1 + 20 + 300");
+    assert_eq!(Counter.visit(&tree.root_node()), 3);
+}
+
+#[test]
+fn test_user_defined_methods_can_be_overridden() {
+    let source = "1 + 20 + 300";
+    let tree = parse(source);
+    assert_eq!(Summer { source }.visit(&tree.root_node()), 321);
+}
+
+#[test]
+#[should_panic(expected = "mul_expr")]
+fn test_generated_defaults_are_kept() {
+    let tree = parse("2 * 3");
+    Counter.visit(&tree.root_node());
+}
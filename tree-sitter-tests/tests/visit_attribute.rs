@@ -0,0 +1,60 @@
+mod common;
+
+use common::{parse, Calculator};
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", cursor)]
+pub trait CalcVisitor {
+    /// Visits any binary expression.
+    #[visit("add_expr", "sub_expr", "mul_expr", "div_expr")]
+    fn visit_binary(&mut self, node: &Node) -> Self::ReturnType;
+
+    #[visit("number")]
+    fn visit_literal(&mut self, node: &Node) -> Self::ReturnType;
+}
+
+impl CalcVisitor for Calculator<'_> {
+    type ReturnType = f64;
+
+    fn visit_root(&mut self, node: &Node) -> f64 {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_paren_expr(&mut self, node: &Node) -> f64 {
+        self.visit(&node.child_by_field_name("body").unwrap())
+    }
+
+    fn visit_binary(&mut self, node: &Node) -> f64 {
+        let lhs = self.visit(&node.child_by_field_name("lhs").unwrap());
+        let rhs = self.visit(&node.child_by_field_name("rhs").unwrap());
+        match node.kind() {
+            "add_expr" => lhs + rhs,
+            "sub_expr" => lhs - rhs,
+            "mul_expr" => lhs * rhs,
+            _ => lhs / rhs,
+        }
+    }
+
+    fn visit_literal(&mut self, node: &Node) -> f64 {
+        self.number(node)
+    }
+}
+
+#[test]
+fn test_mapped_kinds_are_dispatched() {
+    let source = "(1 + 2) * 8 / 4 - 1";
+    let tree = parse(source);
+    let result = Calculator { source }.visit(&tree.root_node());
+    assert_eq!(result, 5.0);
+}
+
+#[test]
+fn test_mapped_kinds_are_dispatched_from_cursor() {
+    let source = "6 * 7";
+    let tree = parse(source);
+    let mut cursor = tree.walk();
+    cursor.goto_first_child();
+    let result = Calculator { source }.visit_cursor(&mut cursor);
+    assert_eq!(result, 42.0);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", visit_children)]
+pub trait CalcVisitor {}
+
+/// Counts the nodes of a tree, and the numbers separately.
+struct Counter;
+
+impl CalcVisitor for Counter {
+    type ReturnType = (usize, usize);
+
+    fn visit_root(&mut self, node: &Node) -> Self::ReturnType {
+        self.visit_children(node)[0]
+    }
+
+    fn visit_number(&mut self, _node: &Node) -> Self::ReturnType {
+        (1, 1)
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> Self::ReturnType {
+        self.visit_children_with(node, (1, 0), |(nodes, numbers), (n, m)| {
+            (nodes + n, numbers + m)
+        })
+    }
+
+    fn visit_mul_expr(&mut self, node: &Node) -> Self::ReturnType {
+        let children = self.visit_named_children(node);
+        assert_eq!(children.len(), 2);
+        children
+            .into_iter()
+            .fold((1, 0), |(nodes, numbers), (n, m)| (nodes + n, numbers + m))
+    }
+
+    fn visit_paren_expr(&mut self, node: &Node) -> Self::ReturnType {
+        self.visit_named_children_with(node, (1, 0), |_, (n, m)| (n + 1, m))
+    }
+
+    fn visit_PLUS(&mut self, _node: &Node) -> Self::ReturnType {
+        (1, 0)
+    }
+}
+
+#[test]
+fn test_visit_children() {
+    let tree = parse("1 + (2 * 3)");
+
+    // The anonymous `(`, `)` and `*` tokens are skipped by the named variants.
+    assert_eq!(Counter.visit(&tree.root_node()), (7, 3));
+}
+
+#[test]
+fn test_visit_field_and_kind_all() {
+    let tree = parse("1 + (2 * 3)");
+    let add_expr = tree.root_node().child(0).unwrap();
+
+    assert_eq!(Counter.visit_field_all(&add_expr, "lhs"), [(1, 1)]);
+    assert_eq!(Counter.visit_field_all(&add_expr, "rhs"), [(4, 2)]);
+    assert_eq!(Counter.visit_field_all(&add_expr, "body"), []);
+    assert_eq!(Counter.visit_kind_all(&add_expr, "+"), [(1, 0)]);
+    assert_eq!(Counter.visit_kind_all(&add_expr, "number"), [(1, 1)]);
+}
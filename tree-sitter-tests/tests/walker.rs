@@ -0,0 +1,111 @@
+mod common;
+
+use common::parse;
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", walker, fn_visitor)]
+pub trait CalcVisitor {}
+
+#[test]
+fn test_walker_visits_in_pre_order() {
+    let parsed = parse("1 + 2 * 3");
+
+    let kinds: Vec<_> = Walker::new()
+        .nodes(parsed.root_node())
+        .map(|step| (step.depth, step.node.kind()))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (0, "root"),
+            (1, "add_expr"),
+            (2, "number"),
+            (2, "+"),
+            (2, "mul_expr"),
+            (3, "number"),
+            (3, "*"),
+            (3, "number"),
+        ]
+    );
+}
+
+#[test]
+fn test_walker_visits_in_breadth_first_order() {
+    let parsed = parse("1 + 2 * 3");
+
+    let mut visitor = FnVisitor::new().otherwise(|node| node.byte_range());
+    let mut steps = Vec::new();
+    Walker::new()
+        .breadth_first()
+        .walk(&mut visitor, parsed.root_node(), |step, range| {
+            steps.push((step.depth, step.node.kind(), range))
+        });
+    assert_eq!(
+        steps,
+        vec![
+            (0, "root", 0..9),
+            (1, "add_expr", 0..9),
+            (2, "number", 0..1),
+            (2, "+", 2..3),
+            (2, "mul_expr", 4..9),
+            (3, "number", 4..5),
+            (3, "*", 6..7),
+            (3, "number", 8..9),
+        ]
+    );
+}
+
+#[test]
+fn test_walker_visits_in_reverse_order() {
+    let parsed = parse("1 + 2 * 3");
+
+    let kinds: Vec<_> = Walker::new()
+        .reverse()
+        .nodes(parsed.root_node())
+        .map(|step| step.node.kind())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec!["root", "add_expr", "mul_expr", "number", "*", "number", "+", "number"]
+    );
+
+    let ranges: Vec<_> = Walker::new()
+        .breadth_first()
+        .reverse()
+        .nodes(parsed.root_node())
+        .filter(|step| step.depth == 3)
+        .map(|step| step.node.byte_range())
+        .collect();
+    assert_eq!(ranges, vec![8..9, 6..7, 4..5]);
+}
+
+#[test]
+fn test_walker_visits_leaves_only() {
+    let parsed = parse("1 + (2)");
+
+    let leaves: Vec<_> = Walker::new()
+        .leaves_only()
+        .skip_extras()
+        .nodes(parsed.root_node())
+        .map(|step| (step.depth, step.node.kind()))
+        .collect();
+    assert_eq!(
+        leaves,
+        vec![(2, "number"), (2, "+"), (3, "("), (3, "number"), (3, ")"),]
+    );
+}
+
+#[test]
+fn test_walker_visits_named_nodes_only() {
+    let parsed = parse("1 + (2)");
+
+    let mut visitor = FnVisitor::new().otherwise(|node| node.kind());
+    let mut kinds = Vec::new();
+    Walker::new()
+        .named_only()
+        .walk(&mut visitor, parsed.root_node(), |_, kind| kinds.push(kind));
+    assert_eq!(
+        kinds,
+        vec!["root", "add_expr", "number", "paren_expr", "number"]
+    );
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::parse;
+use tree_sitter::{Node, Point};
+use tree_sitter_visitor::visitor_trait;
+
+#[visitor_trait("src/node-types.json", notify, named_only, unknown = "skip")]
+pub trait CalcVisitor {}
+
+/// Counts the numbers in a file.
+struct NumberCounter;
+
+impl CalcVisitor for NumberCounter {
+    type ReturnType = usize;
+
+    fn visit_root(&mut self, node: &Node) -> usize {
+        node.named_child(0).map_or(0, |child| self.visit(&child))
+    }
+
+    fn visit_number(&mut self, _node: &Node) -> usize {
+        1
+    }
+
+    fn visit_paren_expr(&mut self, node: &Node) -> usize {
+        self.visit(&node.named_child(0).unwrap())
+    }
+
+    fn visit_add_expr(&mut self, node: &Node) -> usize {
+        self.visit(&node.child_by_field_name("lhs").unwrap())
+            + self.visit(&node.child_by_field_name("rhs").unwrap())
+    }
+}
+
+/// Returns a fresh directory for the files of a test.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "tree-sitter-visitor-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_update_reparses_changed_files() {
+    let dir = test_dir("update");
+    let path = dir.join("sum.calc");
+    let mut watcher = Watcher::new(tree_sitter_tests::language(), NumberCounter).unwrap();
+
+    std::fs::write(&path, "1 + 2").unwrap();
+    assert_eq!(watcher.update(&path).unwrap(), Some(2));
+    assert_eq!(watcher.update(&path).unwrap(), None);
+
+    std::fs::write(&path, "1 + (2 + 3)").unwrap();
+    assert_eq!(watcher.update(&path).unwrap(), Some(3));
+    let (source, tree) = watcher.file(&path).unwrap();
+    assert_eq!(source, "1 + (2 + 3)");
+    assert_eq!(tree.root_node().end_byte(), source.len());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_update_reports_missing_files() {
+    let dir = test_dir("missing");
+    let mut watcher = Watcher::new(tree_sitter_tests::language(), NumberCounter).unwrap();
+
+    let err = watcher.update(&dir.join("missing.calc")).unwrap_err();
+    assert!(matches!(err, WatchError::Io(_)));
+    assert!(watcher.file(&dir.join("missing.calc")).is_none());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_diff_replaces_changed_bytes() {
+    let edit = Watcher::<NumberCounter>::diff("1 +\n2 + 3", "1 +\n20 + 3");
+    assert_eq!(edit.start_byte, 5);
+    assert_eq!(edit.old_end_byte, 5);
+    assert_eq!(edit.new_end_byte, 6);
+    assert_eq!(edit.start_position, Point::new(1, 1));
+    assert_eq!(edit.old_end_position, Point::new(1, 1));
+    assert_eq!(edit.new_end_position, Point::new(1, 2));
+
+    let edit = Watcher::<NumberCounter>::diff("1 + 2", "1 + 2");
+    assert_eq!(
+        (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+        (5, 5, 5)
+    );
+}
+
+#[test]
+fn test_update_matches_fresh_parse() {
+    let dir = test_dir("edits");
+    let path = dir.join("sum.calc");
+    let mut watcher = Watcher::new(tree_sitter_tests::language(), NumberCounter).unwrap();
+
+    let sources = ["1 + 2", "1 +\n(2 + 3)", "(1 + 4) +\n(2 + 3)", "7"];
+    for source in sources {
+        std::fs::write(&path, source).unwrap();
+        watcher.update(&path).unwrap();
+        let (_, tree) = watcher.file(&path).unwrap();
+        assert_eq!(
+            tree.root_node().to_sexp(),
+            parse(source).root_node().to_sexp()
+        );
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,106 @@
+//! Generation of the `accessors` module, with a function returning the children in each field of
+//! each node kind, checking the kind of the node and the presence of required fields.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+use crate::options::Options;
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node], options: &Options) -> TokenStream {
+    let vis = &item.vis;
+
+    let functions = node_types
+        .iter()
+        .filter(|node| node.named)
+        .flat_map(|node| {
+            let kind = &node.r#type;
+            let kind_name = options.kind_name(kind);
+            node.fields.iter().map(move |(field, child_types)| {
+                let name = format_ident!("{}_{}", kind_name, field);
+                let check_kind = quote! {
+                    assert!(
+                        node.is_named() && node.kind() == #kind,
+                        "expected a `{}` node, got `{}` at {:?}",
+                        #kind,
+                        node.kind(),
+                        node.range()
+                    );
+                };
+                if child_types.multiple {
+                    let doc_string = format!(
+                        "Returns the children in the `{}` field of a `{}` node.\n\nPanics if the \
+                         node is of another kind.",
+                        field, kind
+                    );
+                    quote! {
+                        #[doc = #doc_string]
+                        #[track_caller]
+                        pub fn #name<'tree>(
+                            node: &::tree_sitter::Node<'tree>,
+                        ) -> ::std::vec::Vec<::tree_sitter::Node<'tree>> {
+                            #check_kind
+                            let mut cursor = node.walk();
+                            let mut children = ::std::vec::Vec::new();
+                            children.extend(node.children_by_field_name(#field, &mut cursor));
+                            children
+                        }
+                    }
+                } else if child_types.required {
+                    let doc_string = format!(
+                        "Returns the child in the required `{}` field of a `{}` node.\n\nPanics if \
+                         the node is of another kind, or if the field is absent, which only happens \
+                         in trees with errors.",
+                        field, kind
+                    );
+                    quote! {
+                        #[doc = #doc_string]
+                        #[track_caller]
+                        pub fn #name<'tree>(node: &::tree_sitter::Node<'tree>) -> ::tree_sitter::Node<'tree> {
+                            #check_kind
+                            match node.child_by_field_name(#field) {
+                                ::std::option::Option::Some(child) => child,
+                                ::std::option::Option::None => panic!(
+                                    "`{}` node at {:?} has no `{}` field",
+                                    #kind,
+                                    node.range(),
+                                    #field
+                                ),
+                            }
+                        }
+                    }
+                } else {
+                    let doc_string = format!(
+                        "Returns the child in the optional `{}` field of a `{}` node, if it is \
+                         present.\n\nPanics if the node is of another kind.",
+                        field, kind
+                    );
+                    quote! {
+                        #[doc = #doc_string]
+                        #[track_caller]
+                        pub fn #name<'tree>(
+                            node: &::tree_sitter::Node<'tree>,
+                        ) -> ::std::option::Option<::tree_sitter::Node<'tree>> {
+                            #check_kind
+                            node.child_by_field_name(#field)
+                        }
+                    }
+                }
+            })
+        });
+
+    let doc_string = format!(
+        "Functions returning the children in the fields of the node kinds handled by `{}`, e.g. \
+         `add_expr_lhs(&node)`, instead of `child_by_field_name()` calls with field names that are \
+         not checked at compile time.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #vis mod accessors {
+            #(#functions)*
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Generation of helpers walking up the tree from a node, to find its ancestors or the nearest
+//! ancestor of one of a set of kinds.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+use crate::options::Options;
+use crate::upper_snake_case;
+
+pub(crate) fn generate(
+    item: &ItemTrait,
+    node_types: &[Node],
+    options: &Options,
+) -> syn::Result<TokenStream> {
+    let vis = &item.vis;
+
+    let mut enclosing_items = Vec::new();
+    for kind_set in &options.enclosing {
+        for kind in &kind_set.kinds {
+            let value = kind.value();
+            if !node_types.iter().any(|node| node.r#type == value) {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    format!("unknown node kind `{}`", value),
+                ));
+            }
+        }
+
+        let name = kind_set.name.to_string();
+        let const_name = format_ident!("ENCLOSING_{}", upper_snake_case(&name));
+        let fn_name = format_ident!("nearest_enclosing_{}", name);
+        let kinds = &kind_set.kinds;
+        let const_doc = format!("The node kinds considered a `{}` by `{}()`.", name, fn_name);
+        let fn_doc = format!(
+            "Returns the nearest strict ancestor of `node` that is a `{}`, i.e. one of `{}`.",
+            name, const_name
+        );
+
+        enclosing_items.push(quote! {
+            #[doc = #const_doc]
+            #vis const #const_name: NodeKindSet = NodeKindSet::new(&[#(#kinds),*]);
+
+            #[doc = #fn_doc]
+            #vis fn #fn_name<'tree>(
+                node: &::tree_sitter::Node<'tree>,
+            ) -> ::std::option::Option<::tree_sitter::Node<'tree>> {
+                enclosing(node, &#const_name)
+            }
+        });
+    }
+
+    Ok(quote! {
+        /// A set of node kinds.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #vis struct NodeKindSet {
+            kinds: &'static [&'static str],
+        }
+
+        impl NodeKindSet {
+            /// Creates a set of the given node kinds.
+            pub const fn new(kinds: &'static [&'static str]) -> Self {
+                Self { kinds }
+            }
+
+            /// Returns the node kinds in the set.
+            pub fn kinds(&self) -> &'static [&'static str] {
+                self.kinds
+            }
+
+            /// Returns `true` if the kind of the node is in the set.
+            pub fn contains(&self, node: &::tree_sitter::Node) -> bool {
+                self.kinds.contains(&node.kind())
+            }
+        }
+
+        /// An iterator over the strict ancestors of a node, from its parent up to the root.
+        #[derive(Debug, Clone)]
+        #vis struct Ancestors<'tree> {
+            next: ::std::option::Option<::tree_sitter::Node<'tree>>,
+        }
+
+        impl<'tree> ::std::iter::Iterator for Ancestors<'tree> {
+            type Item = ::tree_sitter::Node<'tree>;
+
+            fn next(&mut self) -> ::std::option::Option<Self::Item> {
+                let node = self.next?;
+                self.next = node.parent();
+                ::std::option::Option::Some(node)
+            }
+        }
+
+        /// Returns an iterator over the strict ancestors of `node`, from its parent up to the root.
+        #vis fn ancestors<'tree>(node: &::tree_sitter::Node<'tree>) -> Ancestors<'tree> {
+            Ancestors { next: node.parent() }
+        }
+
+        /// Returns the nearest strict ancestor of `node` whose kind is in `kinds`.
+        #vis fn enclosing<'tree>(
+            node: &::tree_sitter::Node<'tree>,
+            kinds: &NodeKindSet,
+        ) -> ::std::option::Option<::tree_sitter::Node<'tree>> {
+            ancestors(node).find(|ancestor| kinds.contains(ancestor))
+        }
+
+        #(#enclosing_items)*
+    })
+}
@@ -0,0 +1,228 @@
+//! Generation of the `assert_tree!` test assertion, which compares the shape of a tree with an
+//! expected shape written with the node kinds and fields of the grammar.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+use crate::{field_ident, sanitize_identifier};
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+
+    // Only named nodes are part of a shape, and supertypes never appear in a tree.
+    let kind_consts = node_types
+        .iter()
+        .filter(|node| node.named && !node.is_supertype())
+        .map(|node| {
+            let kind = &node.r#type;
+            let name = field_ident(&sanitize_identifier(kind));
+            quote! { pub const #name: &str = #kind; }
+        });
+    let mut field_names: Vec<_> = node_types
+        .iter()
+        .flat_map(|node| node.fields.keys())
+        .collect();
+    field_names.sort();
+    field_names.dedup();
+    let field_consts = field_names.iter().map(|field| {
+        let name = field_ident(field);
+        quote! { pub const #name: &str = #field; }
+    });
+
+    quote! {
+        /// The node kinds and fields that can be used in the shapes given to `assert_tree!`, so
+        /// misspelled names are compile errors.
+        #[allow(non_upper_case_globals)]
+        #vis mod tree_shape {
+            /// The named node kinds of the grammar.
+            pub mod kind {
+                #(#kind_consts)*
+            }
+
+            /// The fields of the grammar.
+            pub mod field {
+                #(#field_consts)*
+            }
+        }
+
+        /// The shape of a tree: the kinds of its named nodes, and the fields they are in.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct ExpectedTree {
+            pub kind: &'static str,
+            pub field: ::std::option::Option<&'static str>,
+            pub children: ::std::vec::Vec<ExpectedTree>,
+        }
+
+        impl ExpectedTree {
+            /// Creates the shape of a node without named children.
+            pub fn new(kind: &'static str) -> Self {
+                Self {
+                    kind,
+                    field: ::std::option::Option::None,
+                    children: ::std::vec::Vec::new(),
+                }
+            }
+
+            /// Places the node in a field of its parent.
+            pub fn in_field(mut self, field: &'static str) -> Self {
+                self.field = ::std::option::Option::Some(field);
+                self
+            }
+
+            /// Appends a named child.
+            pub fn child(mut self, child: ExpectedTree) -> Self {
+                self.children.push(child);
+                self
+            }
+
+            /// Returns the shape of a node, ignoring anonymous nodes.
+            pub fn from_node(node: &::tree_sitter::Node) -> ExpectedTree {
+                let mut tree = Self::new(node.kind());
+                let mut cursor = node.walk();
+                if cursor.goto_first_child() {
+                    loop {
+                        let child = cursor.node();
+                        if child.is_named() {
+                            let mut child_tree = Self::from_node(&child);
+                            child_tree.field = cursor.field_name();
+                            tree.children.push(child_tree);
+                        }
+                        if !cursor.goto_next_sibling() {
+                            break;
+                        }
+                    }
+                }
+                tree
+            }
+
+            /// Panics with a line diff of the two shapes if the node does not have the expected
+            /// shape. Called by `assert_tree!`.
+            #[track_caller]
+            pub fn assert_matches(&self, node: &::tree_sitter::Node) {
+                let actual = Self::from_node(node);
+                if actual.kind == self.kind && actual.children == self.children {
+                    return;
+                }
+                let expected = self.to_string();
+                let actual = actual.to_string();
+                panic!(
+                    "the tree does not have the expected shape (-expected, +actual):\n{}",
+                    Self::diff(&expected, &actual)
+                );
+            }
+
+            /// Returns the lines of both texts, marking those only in `expected` with `-` and those
+            /// only in `actual` with `+`, using their longest common subsequence.
+            fn diff(expected: &str, actual: &str) -> ::std::string::String {
+                let expected: ::std::vec::Vec<_> = expected.lines().collect();
+                let actual: ::std::vec::Vec<_> = actual.lines().collect();
+                let mut common = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+                for i in (0..expected.len()).rev() {
+                    for j in (0..actual.len()).rev() {
+                        common[i][j] = if expected[i] == actual[j] {
+                            common[i + 1][j + 1] + 1
+                        } else {
+                            common[i + 1][j].max(common[i][j + 1])
+                        };
+                    }
+                }
+                let mut out = ::std::string::String::new();
+                let (mut i, mut j) = (0, 0);
+                while i < expected.len() || j < actual.len() {
+                    let line = if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+                        i += 1;
+                        j += 1;
+                        (' ', expected[i - 1])
+                    } else if j == actual.len() || (i < expected.len() && common[i + 1][j] >= common[i][j + 1]) {
+                        i += 1;
+                        ('-', expected[i - 1])
+                    } else {
+                        j += 1;
+                        ('+', actual[j - 1])
+                    };
+                    out.push(line.0);
+                    out.push_str(line.1);
+                    out.push('\n');
+                }
+                out
+            }
+
+            fn fmt_indented(
+                &self,
+                f: &mut ::std::fmt::Formatter<'_>,
+                indent: usize,
+            ) -> ::std::fmt::Result {
+                write!(f, "{:indent$}", "", indent = indent)?;
+                if let ::std::option::Option::Some(field) = self.field {
+                    write!(f, "{}: ", field)?;
+                }
+                if self.children.is_empty() {
+                    return f.write_str(self.kind);
+                }
+                writeln!(f, "{} {{", self.kind)?;
+                for child in &self.children {
+                    child.fmt_indented(f, indent + 4)?;
+                    writeln!(f, ",")?;
+                }
+                write!(f, "{:indent$}}}", "", indent = indent)
+            }
+        }
+
+        /// Formats the shape in the syntax of `assert_tree!`, one node per line.
+        impl ::std::fmt::Display for ExpectedTree {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                self.fmt_indented(f, 0)
+            }
+        }
+
+        /// Asserts that a node has the given shape, e.g.
+        /// `assert_tree!(tree.root_node(), root { add_expr { lhs: number, rhs: number } })`, where
+        /// nodes are written as their kind followed by their named children in braces, each
+        /// optionally prefixed by the field it is in. Anonymous nodes are ignored. On mismatch, it
+        /// panics with a diff of the expected and the actual shape.
+        ///
+        /// `ExpectedTree` and `tree_shape` must be in scope where it is used.
+        #[allow(unused_macros)]
+        macro_rules! assert_tree {
+            (@node $kind:ident { $($children:tt)* }) => {
+                assert_tree!(@children ExpectedTree::new(tree_shape::kind::$kind); $($children)*)
+            };
+            (@node $kind:ident) => {
+                ExpectedTree::new(tree_shape::kind::$kind)
+            };
+            (@children $tree:expr;) => {
+                $tree
+            };
+            (@children $tree:expr; $field:ident : $kind:ident { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+                assert_tree!(
+                    @children $tree.child(
+                        assert_tree!(@node $kind { $($inner)* }).in_field(tree_shape::field::$field)
+                    );
+                    $($($rest)*)?
+                )
+            };
+            (@children $tree:expr; $field:ident : $kind:ident $(, $($rest:tt)*)?) => {
+                assert_tree!(
+                    @children $tree.child(
+                        assert_tree!(@node $kind).in_field(tree_shape::field::$field)
+                    );
+                    $($($rest)*)?
+                )
+            };
+            (@children $tree:expr; $kind:ident { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+                assert_tree!(
+                    @children $tree.child(assert_tree!(@node $kind { $($inner)* }));
+                    $($($rest)*)?
+                )
+            };
+            (@children $tree:expr; $kind:ident $(, $($rest:tt)*)?) => {
+                assert_tree!(@children $tree.child(assert_tree!(@node $kind)); $($($rest)*)?)
+            };
+            ($node:expr, $($shape:tt)+) => {
+                assert_tree!(@node $($shape)+).assert_matches(&$node)
+            };
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Generation of the multi-tree batch visiting driver.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+pub(crate) fn generate(item: &ItemTrait) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = &item.ident;
+
+    quote! {
+        /// The outcome of visiting many trees with a single shared visitor.
+        #vis struct BatchVisit<K, V: #trait_name> {
+            /// The result of visiting the root of each tree, keyed by file id.
+            pub results: ::std::collections::BTreeMap<K, V::ReturnType>,
+            /// The visitor, holding any state aggregated across all the trees.
+            pub visitor: V,
+        }
+
+        /// The error returned by `visit_batch` when several trees are given the same file id.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct DuplicateFileId<K>(pub K);
+
+        impl<K: ::std::fmt::Debug> ::std::fmt::Display for DuplicateFileId<K> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "duplicate file id: {:?}", self.0)
+            }
+        }
+
+        impl<K: ::std::fmt::Debug> ::std::error::Error for DuplicateFileId<K> {}
+
+        /// Visits the roots of many trees with a single shared visitor.
+        ///
+        /// Trees are visited in ascending order of their file ids, regardless of the order in which
+        /// they are supplied, so that the aggregate state is deterministic. `prepare` is called
+        /// before each tree is visited, e.g. to point the visitor at the source of the next file.
+        /// The file ids must be unique; if they are not, no tree is visited and the duplicate id is
+        /// returned as an error.
+        #vis fn visit_batch<'t, K, V, I, F>(
+            mut visitor: V,
+            trees: I,
+            mut prepare: F,
+        ) -> ::std::result::Result<BatchVisit<K, V>, DuplicateFileId<K>>
+        where
+            K: ::std::cmp::Ord,
+            V: #trait_name,
+            I: ::std::iter::IntoIterator<Item = (K, &'t ::tree_sitter::Tree)>,
+            F: ::std::ops::FnMut(&mut V, &K),
+        {
+            let mut trees: ::std::vec::Vec<_> = trees.into_iter().collect();
+            trees.sort_by(|(a, _), (b, _)| a.cmp(b));
+            if let ::std::option::Option::Some(index) =
+                trees.windows(2).position(|pair| pair[0].0 == pair[1].0)
+            {
+                return ::std::result::Result::Err(DuplicateFileId(trees.swap_remove(index).0));
+            }
+
+            let mut results = ::std::collections::BTreeMap::new();
+            for (id, tree) in trees {
+                prepare(&mut visitor, &id);
+                let result = visitor.visit(&tree.root_node());
+                results.insert(id, result);
+            }
+
+            ::std::result::Result::Ok(BatchVisit { results, visitor })
+        }
+    }
+}
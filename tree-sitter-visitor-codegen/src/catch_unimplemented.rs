@@ -0,0 +1,162 @@
+//! Generation of `CatchUnimplemented`, which turns panics from the default method stubs into
+//! structured records instead of aborting the whole analysis.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Ident, ItemTrait};
+
+use crate::node_types::Node;
+use crate::options::{HookSignature, Options};
+use crate::{supertrait_predicate, upper_snake_case};
+
+fn last_unhandled_ident(trait_name: &Ident) -> Ident {
+    format_ident!(
+        "{}_LAST_UNHANDLED",
+        upper_snake_case(&trait_name.to_string())
+    )
+}
+
+/// Statement recording the node reaching a default stub, placed before the stub panics.
+pub(crate) fn record_unhandled(trait_name: &Ident) -> TokenStream {
+    let last_unhandled = last_unhandled_ident(trait_name);
+
+    quote! {
+        #last_unhandled.with(|last| *last.borrow_mut() = ::std::option::Option::Some(Unhandled::new(node)));
+    }
+}
+
+pub(crate) fn generate(
+    item: &ItemTrait,
+    node_types: &[Node],
+    options: &Options,
+    external_hook: bool,
+) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = &item.ident;
+    let last_unhandled = last_unhandled_ident(trait_name);
+    let supertrait_predicate = supertrait_predicate(item);
+
+    let methods = node_types.iter().map(|node| {
+        let method_name = options.method_name(&node.r#type);
+        quote! {
+            fn #method_name(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
+                self.catch(|inner| inner.#method_name(node))
+                    .unwrap_or_default()
+            }
+        }
+    });
+    // `visit_fields()` keeps its default implementation, calling the forwarded field hooks.
+    let hook_methods = options
+        .hooks(node_types, external_hook)
+        .into_iter()
+        .filter(|hook| hook.signature != HookSignature::Children)
+        .map(|hook| {
+            let name = &hook.name;
+            hook.forward(options, &quote! { Self::ReturnType }, |args| {
+                match hook.signature {
+                    HookSignature::AfterVisit | HookSignature::Invalidate => {
+                        quote! { self.inner.#name(#args) }
+                    }
+                    HookSignature::Split => quote! { Self::new(self.inner.#name()) },
+                    _ => quote! { self.catch(|inner| inner.#name(#args)).unwrap_or_default() },
+                }
+            })
+        });
+
+    quote! {
+        /// A node that reached a default method stub, i.e. a node kind without a handler.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct Unhandled {
+            /// The kind of the node.
+            pub kind: &'static str,
+            /// The byte range of the node.
+            pub byte_range: ::std::ops::Range<usize>,
+            /// The start position of the node.
+            pub start_position: ::tree_sitter::Point,
+        }
+
+        impl Unhandled {
+            fn new(node: &::tree_sitter::Node) -> Self {
+                Self {
+                    kind: node.kind(),
+                    byte_range: node.byte_range(),
+                    start_position: node.start_position(),
+                }
+            }
+        }
+
+        ::std::thread_local! {
+            static #last_unhandled: ::std::cell::RefCell<::std::option::Option<Unhandled>> =
+                ::std::cell::RefCell::new(::std::option::Option::None);
+        }
+
+        /// A wrapper running a visitor under `catch_unwind`, recording the nodes that reach default
+        /// method stubs instead of propagating their panics.
+        ///
+        /// Panics raised by anything other than a default stub are propagated. Note that the state
+        /// of the inner visitor may be left inconsistent by an interrupted visit.
+        #vis struct CatchUnimplemented<V> {
+            /// The wrapped visitor.
+            pub inner: V,
+            /// The nodes that reached default stubs, in the order they were encountered.
+            pub unhandled: ::std::vec::Vec<Unhandled>,
+        }
+
+        impl<V: #trait_name> CatchUnimplemented<V> {
+            /// Wraps a visitor.
+            pub fn new(inner: V) -> Self {
+                Self {
+                    inner,
+                    unhandled: ::std::vec::Vec::new(),
+                }
+            }
+
+            /// Visits a node, returning `None` if the visit reached a default stub.
+            pub fn try_visit(&mut self, node: &::tree_sitter::Node) -> ::std::option::Option<V::ReturnType> {
+                self.catch(|inner| inner.visit(node))
+            }
+
+            /// Visits a node, returning `fallback` if the visit reached a default stub.
+            pub fn visit_or(&mut self, node: &::tree_sitter::Node, fallback: V::ReturnType) -> V::ReturnType {
+                self.try_visit(node).unwrap_or(fallback)
+            }
+
+            fn catch(
+                &mut self,
+                f: impl ::std::ops::FnOnce(&mut V) -> V::ReturnType,
+            ) -> ::std::option::Option<V::ReturnType> {
+                #last_unhandled.with(|last| last.borrow_mut().take());
+                let inner = &mut self.inner;
+                match ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(|| f(inner))) {
+                    Ok(result) => ::std::option::Option::Some(result),
+                    Err(payload) => match #last_unhandled.with(|last| last.borrow_mut().take()) {
+                        ::std::option::Option::Some(unhandled) => {
+                            self.unhandled.push(unhandled);
+                            ::std::option::Option::None
+                        }
+                        ::std::option::Option::None => ::std::panic::resume_unwind(payload),
+                    },
+                }
+            }
+        }
+
+        /// Visits nodes through the wrapped visitor, substituting `Default::default()` for the
+        /// results of visits that reached a default stub.
+        impl<V> #trait_name for CatchUnimplemented<V>
+        where
+            V: #trait_name,
+            V::ReturnType: ::std::default::Default,
+            #supertrait_predicate
+        {
+            type ReturnType = V::ReturnType;
+
+            fn visit(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
+                self.try_visit(node).unwrap_or_default()
+            }
+
+            #(#methods)*
+
+            #(#hook_methods)*
+        }
+    }
+}
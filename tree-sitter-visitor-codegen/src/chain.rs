@@ -0,0 +1,158 @@
+//! Generation of `ChainedVisitor`, which dispatches to a primary visitor for the kinds it handles
+//! and to a fallback visitor for everything else.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, ImplItem, ItemImpl, ItemTrait, LitStr, TraitItem};
+
+use crate::options::{HookSignature, Options};
+use crate::{
+    fresh_type_param, method_return_type, supertrait_predicate, trait_params, trait_path,
+    trait_predicates, Node,
+};
+
+/// Trait items declaring the set of methods an implementation overrides.
+pub(crate) fn trait_items(node_types: &[Node], options: &Options) -> Vec<TraitItem> {
+    let kinds = node_types.iter().map(|node| &node.r#type);
+    let method_names = node_types
+        .iter()
+        .map(|node| options.method_name(&node.r#type).to_string());
+
+    vec![
+        parse_quote! {
+            /// Names of the methods overridden by this implementation, filled in by
+            /// `#[visitor_impl]`.
+            const HANDLED_METHODS: &'static [&'static str] = &[];
+        },
+        parse_quote! {
+            /// Returns `true` if this implementation overrides the method visiting nodes of the given kind.
+            fn handles_kind(kind: &str) -> bool
+            where
+                Self: Sized,
+            {
+                let method = match kind {
+                    #(#kinds => #method_names,)*
+                    _ => return false,
+                };
+                Self::HANDLED_METHODS.contains(&method)
+            }
+        },
+    ]
+}
+
+pub(crate) fn generate(
+    item: &ItemTrait,
+    node_types: &[Node],
+    options: &Options,
+    external_hook: bool,
+) -> TokenStream {
+    let vis = &item.vis;
+
+    let supertrait_predicate = supertrait_predicate(item);
+    let trait_params = trait_params(item);
+    let trait_predicates = trait_predicates(item);
+    let trait_ref = trait_path(item, quote! {});
+    let a = fresh_type_param(item, "A");
+    let b = fresh_type_param(item, "B");
+    let r = fresh_type_param(item, "R");
+    let trait_bound = trait_path(item, quote! { ReturnType = #r });
+    let return_type_predicate = options.return_type_predicate(&r);
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let kind_methods = node_types.iter().map(|node| {
+        let method_name = options.method_name(&node.r#type);
+        let return_type = method_return_type(options, &node.r#type, quote! { #r });
+        (method_name, return_type)
+    });
+    let methods = kind_methods.map(|(method_name, return_type)| {
+        let method_str = method_name.to_string();
+        quote! {
+            fn #method_name(&mut self, node: &::tree_sitter::Node #param_decls) -> #return_type {
+                if #a::HANDLED_METHODS.contains(&#method_str) {
+                    self.primary.#method_name(node #param_args)
+                } else {
+                    self.fallback.#method_name(node #param_args)
+                }
+            }
+        }
+    });
+    // The hooks are dispatched like the methods of the node kinds.
+    let hook_methods = options
+        .hooks(node_types, external_hook)
+        .into_iter()
+        .map(|hook| {
+            let method_name = &hook.name;
+            let method_str = method_name.to_string();
+            hook.forward(options, &quote! { #r }, |args| {
+                if hook.signature == HookSignature::Split {
+                    return quote! {
+                        Self::new(self.primary.#method_name(), self.fallback.#method_name())
+                    };
+                }
+                quote! {
+                    if #a::HANDLED_METHODS.contains(&#method_str) {
+                        self.primary.#method_name(#args)
+                    } else {
+                        self.fallback.#method_name(#args)
+                    }
+                }
+            })
+        });
+
+    quote! {
+        /// A visitor dispatching each node kind to `primary` if it handles it, and to `fallback`
+        /// otherwise.
+        ///
+        /// The kinds handled by `primary` are those whose methods are overridden in an `impl` block
+        /// annotated with `#[visitor_impl]`. Note that when a component visitor recursively visits
+        /// children through its own `visit()`, the recursion stays within that component.
+        #vis struct ChainedVisitor<A, B> {
+            pub primary: A,
+            pub fallback: B,
+        }
+
+        impl<A, B> ChainedVisitor<A, B> {
+            /// Chains `primary` on top of `fallback`.
+            pub fn new(primary: A, fallback: B) -> Self {
+                Self { primary, fallback }
+            }
+        }
+
+        impl<#trait_params #a, #b, #r> #trait_ref for ChainedVisitor<#a, #b>
+        where
+            #a: #trait_bound,
+            #b: #trait_bound,
+            #return_type_predicate
+            #trait_predicates
+            #supertrait_predicate
+        {
+            type ReturnType = #r;
+
+            #(#methods)*
+
+            #(#hook_methods)*
+        }
+    }
+}
+
+/// Records the names of the methods defined in a visitor `impl` block in `HANDLED_METHODS`.
+///
+/// All methods are recorded, since the attribute does not know the `prefix` the trait was generated
+/// with; `handles_kind` only ever looks up the names of kind methods.
+pub(crate) fn annotate_impl(mut item: ItemImpl) -> ItemImpl {
+    let method_names: Vec<LitStr> = item
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Method(method) => Some(&method.sig.ident),
+            _ => None,
+        })
+        .map(|ident| LitStr::new(&ident.to_string(), ident.span()))
+        .collect();
+
+    item.items.push(parse_quote! {
+        const HANDLED_METHODS: &'static [&'static str] = &[#(#method_names),*];
+    });
+
+    item
+}
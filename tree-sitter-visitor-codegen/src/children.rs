@@ -0,0 +1,174 @@
+//! Generation of `visit_children()`, `visit_named_children()` and their variants combining the
+//! results with a closure, which visit the children of a node, and of `visit_field_all()` and
+//! `visit_kind_all()`, which visit only the children in a field or of a kind.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, ItemTrait, TraitItem};
+
+use crate::extras;
+use crate::fresh_type_param;
+use crate::node_types::Node;
+use crate::options::Options;
+
+pub(crate) fn trait_items(
+    item: &ItemTrait,
+    node_types: &[Node],
+    options: &Options,
+) -> Vec<TraitItem> {
+    let a = fresh_type_param(item, "A");
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    // With `fallible`, the results of the children are `Result`s.
+    let return_type = options.result_type(options.self_return_type());
+    let skipped = extras::skipped(node_types, options, quote! { child });
+
+    let helpers = |which: &str, named: bool| {
+        let name = format_ident!("visit_{}", which);
+        let name_with = format_ident!("visit_{}_with", which);
+        let doc_children = if named { "named children" } else { "children" };
+        let collect = for_each_child(
+            named,
+            skipped.as_ref(),
+            quote! { results.push(self.visit(&child #param_args)); },
+        );
+        let combine = for_each_child(
+            named,
+            skipped.as_ref(),
+            quote! {
+                let result = self.visit(&child #param_args);
+                acc = combine(acc, result);
+            },
+        );
+        let doc = format!(
+            "Visits all the {} of a node in document order, returning their results.",
+            doc_children
+        );
+        let doc_with = format!(
+            "Visits all the {} of a node in document order, combining their results with \
+             `combine`, starting from `init`.",
+            doc_children
+        );
+        let items: [TraitItem; 2] = [
+            parse_quote! {
+                #[doc=#doc]
+                fn #name(
+                    &mut self,
+                    node: &::tree_sitter::Node
+                    #param_decls
+                ) -> ::std::vec::Vec<#return_type> {
+                    let mut results = ::std::vec::Vec::new();
+                    #collect
+                    results
+                }
+            },
+            parse_quote! {
+                #[doc=#doc_with]
+                fn #name_with<#a>(
+                    &mut self,
+                    node: &::tree_sitter::Node
+                    #param_decls,
+                    init: #a,
+                    mut combine: impl ::std::ops::FnMut(#a, #return_type) -> #a,
+                ) -> #a
+                where
+                    Self: Sized,
+                {
+                    let mut acc = init;
+                    #combine
+                    acc
+                }
+            },
+        ];
+        items
+    };
+
+    let field_children = for_each_child(
+        false,
+        None,
+        quote! {
+            if cursor.field_name() == ::std::option::Option::Some(field) {
+                results.push(self.visit(&child #param_args));
+            }
+        },
+    );
+    let kind_children = for_each_child(
+        false,
+        None,
+        quote! {
+            if child.kind() == kind {
+                results.push(self.visit(&child #param_args));
+            }
+        },
+    );
+    let filtered: [TraitItem; 2] = [
+        parse_quote! {
+            /// Visits the children of a node in the given field in document order, returning their
+            /// results, e.g. `self.visit_field_all(node, "argument")`.
+            fn visit_field_all(
+                &mut self,
+                node: &::tree_sitter::Node
+                #param_decls,
+                field: &str,
+            ) -> ::std::vec::Vec<#return_type> {
+                let mut results = ::std::vec::Vec::new();
+                #field_children
+                results
+            }
+        },
+        parse_quote! {
+            /// Visits the children of a node of the given kind in document order, returning their
+            /// results, e.g. `self.visit_kind_all(node, "number")`.
+            fn visit_kind_all(
+                &mut self,
+                node: &::tree_sitter::Node
+                #param_decls,
+                kind: &str,
+            ) -> ::std::vec::Vec<#return_type> {
+                let mut results = ::std::vec::Vec::new();
+                #kind_children
+                results
+            }
+        },
+    ];
+
+    helpers("children", false)
+        .into_iter()
+        .chain(helpers("named_children", true))
+        .chain(filtered)
+        .collect()
+}
+
+/// Returns a loop over the children of `node` with a `TreeCursor`, running `body` with `child`
+/// bound to each of them, or only to the named ones, leaving out those for which `skipped` holds.
+fn for_each_child(named: bool, skipped: Option<&TokenStream>, body: TokenStream) -> TokenStream {
+    let body = match skipped {
+        Some(skipped) => quote! {
+            if !#skipped {
+                #body
+            }
+        },
+        None => body,
+    };
+    let body = if named {
+        quote! {
+            if child.is_named() {
+                #body
+            }
+        }
+    } else {
+        body
+    };
+    quote! {
+        let mut cursor = node.walk();
+        if cursor.goto_first_child() {
+            loop {
+                let child = cursor.node();
+                #body
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Generation of `MultiVisitor`, which runs a tuple of visitors over the same nodes, and of
+//! `MappedVisitor`, which maps the results of a visitor.
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Index, ItemTrait};
+
+use crate::options::{HookSignature, Options};
+use crate::{
+    fresh_type_param, supertrait_predicate, trait_params, trait_path, trait_predicates, Node,
+};
+
+/// The largest number of visitors combined by a `MultiVisitor`.
+const MAX_ARITY: usize = 8;
+
+pub(crate) fn generate(
+    item: &ItemTrait,
+    node_types: &[Node],
+    options: &Options,
+    external_hook: bool,
+) -> TokenStream {
+    let vis = &item.vis;
+    let supertrait_predicate = supertrait_predicate(item);
+    let trait_params = trait_params(item);
+    let trait_predicates = trait_predicates(item);
+    let trait_ref = trait_path(item, quote! {});
+    let method_names: Vec<_> = node_types
+        .iter()
+        .map(|node| options.method_name(&node.r#type))
+        .collect();
+    // `visit_fields()` keeps its default implementation, calling the forwarded field hooks.
+    let hooks: Vec<_> = options
+        .hooks(node_types, external_hook)
+        .into_iter()
+        .filter(|hook| hook.signature != HookSignature::Children)
+        .collect();
+
+    let multi_impls = (2..=MAX_ARITY).map(|arity| {
+        let vs: Vec<_> = (0..arity)
+            .map(|i| fresh_type_param(item, &((b'A' + i as u8) as char).to_string()))
+            .collect();
+        let indices: Vec<_> = (0..arity).map(Index::from).collect();
+        let return_type = quote! { (#(<#vs as #trait_ref>::ReturnType,)*) };
+        let return_type_predicate = options.return_type_predicate(&return_type);
+        let methods = method_names.iter().map(|method_name| {
+            quote! {
+                fn #method_name(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
+                    (#(self.visitors.#indices.#method_name(node),)*)
+                }
+            }
+        });
+        let hook_methods = hooks.iter().map(|hook| {
+            let name = &hook.name;
+            hook.forward(options, &quote! { Self::ReturnType }, |args| {
+                match hook.signature {
+                    HookSignature::AfterVisit => quote! {
+                        #(self.visitors.#indices.#name(node, &result.#indices);)*
+                    },
+                    HookSignature::Invalidate => quote! {
+                        #(self.visitors.#indices.#name(#args);)*
+                    },
+                    HookSignature::Split => quote! {
+                        Self::new((#(self.visitors.#indices.#name(),)*))
+                    },
+                    _ => quote! { (#(self.visitors.#indices.#name(#args),)*) },
+                }
+            })
+        });
+
+        quote! {
+            impl<#trait_params #(#vs),*> #trait_ref for MultiVisitor<(#(#vs,)*)>
+            where
+                #(#vs: #trait_ref,)*
+                #return_type_predicate
+                #trait_predicates
+                #supertrait_predicate
+            {
+                type ReturnType = #return_type;
+
+                fn visit(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
+                    (#(self.visitors.#indices.visit(node),)*)
+                }
+
+                #(#methods)*
+
+                #(#hook_methods)*
+            }
+        }
+    });
+
+    let v = fresh_type_param(item, "V");
+    let f = fresh_type_param(item, "F");
+    let u = fresh_type_param(item, "U");
+    let return_type_predicate = options.return_type_predicate(&u);
+    // `split()` clones the function for the new visitor.
+    let clone_predicate = if options.parallel {
+        quote! { #f: ::std::clone::Clone, }
+    } else {
+        quote! {}
+    };
+    let mapped_methods = method_names.iter().map(|method_name| {
+        quote! {
+            fn #method_name(&mut self, node: &::tree_sitter::Node) -> #u {
+                (self.map)(self.inner.#method_name(node))
+            }
+        }
+    });
+    // `after_visit()` is given the mapped result, which the wrapped visitor can't take.
+    let mapped_hook_methods = hooks
+        .iter()
+        .filter(|hook| hook.signature != HookSignature::AfterVisit)
+        .map(|hook| {
+            let name = &hook.name;
+            hook.forward(options, &u.to_token_stream(), |args| match hook.signature {
+                HookSignature::Invalidate => quote! { self.inner.#name(#args) },
+                HookSignature::Split => quote! { Self::new(self.inner.#name(), self.map.clone()) },
+                _ => quote! { (self.map)(self.inner.#name(#args)) },
+            })
+        });
+
+    quote! {
+        /// A visitor running a tuple of visitors, e.g. `MultiVisitor::new((lints, metrics))`, over
+        /// the same nodes, returning a tuple of their results.
+        ///
+        /// Each call is forwarded to every visitor in turn. Note that when a component visitor
+        /// recursively visits children through its own `visit()`, the recursion stays within that
+        /// component; to run several analyses in a single traversal, their methods should not recurse
+        /// and the nodes should be driven from the outside, e.g. with a `Walker`.
+        #vis struct MultiVisitor<T> {
+            /// The combined visitors.
+            pub visitors: T,
+        }
+
+        impl<T> MultiVisitor<T> {
+            /// Combines a tuple of between 2 and 8 visitors.
+            pub fn new(visitors: T) -> Self {
+                Self { visitors }
+            }
+
+            /// Returns the combined visitors.
+            pub fn into_inner(self) -> T {
+                self.visitors
+            }
+        }
+
+        #(#multi_impls)*
+
+        /// A visitor mapping the results of the visitor it wraps with a function, e.g. to combine
+        /// visitors with different return types in a `ChainedVisitor`. With `parallel`, the function
+        /// must implement `Clone`, to be cloned by `split()`.
+        #vis struct MappedVisitor<V, F> {
+            /// The wrapped visitor.
+            pub inner: V,
+            map: F,
+        }
+
+        impl<V, F> MappedVisitor<V, F> {
+            /// Wraps `inner`, mapping each of its results with `map`.
+            pub fn new(inner: V, map: F) -> Self {
+                Self { inner, map }
+            }
+
+            /// Returns the wrapped visitor.
+            pub fn into_inner(self) -> V {
+                self.inner
+            }
+        }
+
+        impl<#trait_params #v, #f, #u> #trait_ref for MappedVisitor<#v, #f>
+        where
+            #v: #trait_ref,
+            #f: ::std::ops::FnMut(<#v as #trait_ref>::ReturnType) -> #u,
+            #clone_predicate
+            #return_type_predicate
+            #trait_predicates
+            #supertrait_predicate
+        {
+            type ReturnType = #u;
+
+            fn visit(&mut self, node: &::tree_sitter::Node) -> #u {
+                (self.map)(self.inner.visit(node))
+            }
+
+            #(#mapped_methods)*
+
+            #(#mapped_hook_methods)*
+        }
+    }
+}
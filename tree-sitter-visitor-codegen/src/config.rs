@@ -0,0 +1,366 @@
+//! Loading of options from a TOML file given with `config = "visitor.toml"`.
+//!
+//! The file mirrors the attribute: flags are booleans, `key = "value"` options are strings, and
+//! options taking a list of entries are tables, e.g.
+//!
+//! ```toml
+//! path = "../src/node-types.json"
+//! walker = true
+//! params = ["depth: usize"]
+//!
+//! [return_types]
+//! number = "i64"
+//!
+//! [renames]
+//! sum = "add_expr"
+//!
+//! [mixins]
+//! Arithmetic = ["add_expr", "sub_expr"]
+//! ```
+//!
+//! Paths in the file are relative to the file itself.
+
+use crate::options::{
+    env_path, expand_env, parse_bound_list, parse_pattern, EnumDispatch, ExtraNodes, KindSet,
+    LanguagePath, NamingCase, Options, Param, Rename, ReturnTypeOverride, UnknownKinds,
+};
+use proc_macro2::Span;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use syn::{Ident, LitStr};
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    path: Option<String>,
+    env: Option<String>,
+    after_visit: bool,
+    text_provider: bool,
+    ropey: bool,
+    session: bool,
+    notify: bool,
+    batch: bool,
+    owned_parse: bool,
+    catch_unimplemented: bool,
+    chain: bool,
+    coverage: bool,
+    memoize: bool,
+    field_children: bool,
+    field_hooks: bool,
+    fields: bool,
+    nodes: bool,
+    accessors: bool,
+    node_ext: bool,
+    subtype_enums: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    include_pattern: Option<String>,
+    exclude_pattern: Option<String>,
+    named_only: bool,
+    token_hook: bool,
+    error_hooks: bool,
+    supertypes: bool,
+    named_dispatch: bool,
+    correspondence: bool,
+    revisit: bool,
+    parallel: bool,
+    tracing: bool,
+    fingerprint: bool,
+    fn_visitor: bool,
+    dyn_trait: bool,
+    combinators: bool,
+    emitter: bool,
+    introspection: bool,
+    kind_table: bool,
+    kind_id_dispatch: bool,
+    language_check: bool,
+    node_kind: bool,
+    node_kind_dispatch: bool,
+    grammar_name_dispatch: bool,
+    kinds: bool,
+    kind_frequencies: bool,
+    html: bool,
+    source_map: bool,
+    interner: bool,
+    assert_tree: bool,
+    sexp_printer: bool,
+    serialize: bool,
+    libloading: bool,
+    siblings: bool,
+    token_visitor: bool,
+    visit_children: bool,
+    try_visit_children: bool,
+    cursor: bool,
+    walker: bool,
+    query_captures: bool,
+    ancestors: bool,
+    context: bool,
+    fallible: bool,
+    defaults: bool,
+    #[serde(rename = "async")]
+    asynchronous: bool,
+    by_value: bool,
+    profile: Option<String>,
+    grammar: Option<String>,
+    expected_hash: Option<String>,
+    unknown: Option<String>,
+    extras: Option<String>,
+    prefix: Option<String>,
+    case: Option<String>,
+    supertraits: Option<String>,
+    return_bounds: Option<String>,
+    return_type: Option<String>,
+    return_type_param: Option<String>,
+    params: Vec<String>,
+    flavors: Vec<String>,
+    return_types: BTreeMap<String, String>,
+    renames: BTreeMap<String, String>,
+    languages: BTreeMap<String, String>,
+    mixins: BTreeMap<String, Vec<String>>,
+    enclosing: BTreeMap<String, Vec<String>>,
+    group: BTreeMap<String, Vec<String>>,
+    enum_dispatch: BTreeMap<String, Vec<String>>,
+}
+
+impl Config {
+    /// Loads the configuration from a path resolved with `resolve_path()`.
+    pub fn load(path: &LitStr) -> syn::Result<Self> {
+        let error = |message: String| syn::Error::new(path.span(), message);
+        let contents = std::fs::read_to_string(crate::resolve_path(&path.value()))
+            .map_err(|err| error(format!("could not read `{}`: {}", path.value(), err)))?;
+        toml::from_str(&contents)
+            .map_err(|err| error(format!("could not parse `{}`: {}", path.value(), err)))
+    }
+
+    /// Adds the options of the configuration to those given in the attribute. `path` is the path of
+    /// the configuration, whose span is used for errors in its values.
+    pub fn merge_into(self, options: &mut Options, path: &LitStr) -> syn::Result<()> {
+        let span = path.span();
+        let relative = |file: String| {
+            let dir = Path::new(&path.value())
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            dir.join(file).to_string_lossy().into_owned()
+        };
+        let parse_value = |value: &str| -> syn::Result<proc_macro2::TokenStream> {
+            let tokens: proc_macro2::TokenStream = syn::parse_str(value)?;
+            Ok(respan(tokens, span))
+        };
+        let ident = |name: &str| -> syn::Result<Ident> {
+            syn::parse_str::<Ident>(name).map(|ident| Ident::new(&ident.to_string(), span))
+        };
+        let lit = |value: &str| LitStr::new(value, span);
+        let lits = |values: &[String]| values.iter().map(|value| lit(value)).collect();
+
+        if let Some(file) = self.path {
+            if options.path.is_empty() {
+                options.path = relative(expand_env(&lit(&file))?);
+            }
+        }
+        if let Some(var) = self.env {
+            if options.path.is_empty() {
+                options.path = env_path(&lit(&var))?;
+            }
+        }
+        options.after_visit |= self.after_visit;
+        options.text_provider |= self.text_provider;
+        options.ropey |= self.ropey;
+        options.session |= self.session;
+        options.notify |= self.notify;
+        options.batch |= self.batch;
+        options.owned_parse |= self.owned_parse;
+        options.catch_unimplemented |= self.catch_unimplemented;
+        options.chain |= self.chain;
+        options.coverage |= self.coverage;
+        options.memoize |= self.memoize;
+        options.field_children |= self.field_children;
+        options.field_hooks |= self.field_hooks;
+        options.fields |= self.fields;
+        options.nodes |= self.nodes;
+        options.accessors |= self.accessors;
+        options.node_ext |= self.node_ext;
+        options.subtype_enums |= self.subtype_enums;
+        options.include.extend(lits(&self.include));
+        options.exclude.extend(lits(&self.exclude));
+        if let Some(pattern) = self.include_pattern {
+            if options.include_pattern.is_none() {
+                options.include_pattern = Some(parse_pattern(&lit(&pattern))?);
+            }
+        }
+        if let Some(pattern) = self.exclude_pattern {
+            if options.exclude_pattern.is_none() {
+                options.exclude_pattern = Some(parse_pattern(&lit(&pattern))?);
+            }
+        }
+        options.named_only |= self.named_only;
+        options.token_hook |= self.token_hook;
+        options.error_hooks |= self.error_hooks;
+        options.supertypes |= self.supertypes;
+        options.named_dispatch |= self.named_dispatch;
+        options.correspondence |= self.correspondence;
+        options.revisit |= self.revisit;
+        options.parallel |= self.parallel;
+        options.tracing |= self.tracing;
+        options.fingerprint |= self.fingerprint;
+        options.fn_visitor |= self.fn_visitor;
+        options.dyn_trait |= self.dyn_trait;
+        options.combinators |= self.combinators;
+        options.emitter |= self.emitter;
+        options.introspection |= self.introspection;
+        options.kind_table |= self.kind_table;
+        options.kind_id_dispatch |= self.kind_id_dispatch;
+        options.language_check |= self.language_check;
+        options.node_kind |= self.node_kind;
+        options.node_kind_dispatch |= self.node_kind_dispatch;
+        options.grammar_name_dispatch |= self.grammar_name_dispatch;
+        options.kinds |= self.kinds;
+        options.kind_frequencies |= self.kind_frequencies;
+        options.html |= self.html;
+        options.source_map |= self.source_map;
+        options.interner |= self.interner;
+        options.assert_tree |= self.assert_tree;
+        options.sexp_printer |= self.sexp_printer;
+        options.serialize |= self.serialize;
+        options.libloading |= self.libloading;
+        options.siblings |= self.siblings;
+        options.token_visitor |= self.token_visitor;
+        options.visit_children |= self.visit_children;
+        options.try_visit_children |= self.try_visit_children;
+        options.cursor |= self.cursor;
+        options.walker |= self.walker;
+        options.query_captures |= self.query_captures;
+        options.ancestors |= self.ancestors;
+        options.context |= self.context;
+        options.fallible |= self.fallible;
+        options.defaults |= self.defaults;
+        options.asynchronous |= self.asynchronous;
+        options.by_value |= self.by_value;
+        if let Some(profile) = self.profile {
+            options
+                .profile
+                .get_or_insert_with(|| lit(&relative(profile)));
+        }
+        if let Some(grammar) = self.grammar {
+            options
+                .grammar
+                .get_or_insert_with(|| lit(&relative(grammar)));
+        }
+        if let Some(expected_hash) = self.expected_hash {
+            options
+                .expected_hash
+                .get_or_insert_with(|| lit(&expected_hash));
+        }
+        if let Some(unknown) = self.unknown {
+            options.unknown = UnknownKinds::parse(&lit(&unknown))?;
+        }
+        if let Some(extras) = self.extras {
+            options.extras = ExtraNodes::parse(&lit(&extras))?;
+        }
+        if let Some(prefix) = self.prefix {
+            options.prefix.get_or_insert_with(|| lit(&prefix));
+        }
+        if let Some(case) = self.case {
+            options.case = NamingCase::parse(&lit(&case))?;
+        }
+        if let Some(supertraits) = self.supertraits {
+            options
+                .supertraits
+                .extend(parse_bound_list(&lit(&supertraits))?);
+        }
+        if let Some(return_bounds) = self.return_bounds {
+            options
+                .return_bounds
+                .extend(parse_bound_list(&lit(&return_bounds))?);
+        }
+        if let Some(return_type) = self.return_type {
+            if options.preset_return_type.is_none() {
+                options.preset_return_type = Some(syn::parse2(parse_value(&return_type)?)?);
+            }
+        }
+        if let Some(param) = self.return_type_param {
+            if options.return_type_param.is_none() {
+                options.return_type_param = Some(syn::parse2(parse_value(&param)?)?);
+            }
+        }
+        for param in self.params {
+            options
+                .params
+                .push(syn::parse2::<Param>(parse_value(&param)?)?);
+        }
+        for flavor in self.flavors {
+            options.add_flavor(ident(&flavor)?)?;
+        }
+        for (kind, ty) in self.return_types {
+            options.return_types.push(ReturnTypeOverride {
+                kind: lit(&kind),
+                ty: syn::parse2(parse_value(&ty)?)?,
+            });
+        }
+        if options.languages.is_empty() {
+            for (name, file) in self.languages {
+                options.languages.push(LanguagePath {
+                    name: ident(&name)?,
+                    path: relative(expand_env(&lit(&file))?),
+                });
+            }
+        }
+        for (old, new) in self.renames {
+            options.renames.push(Rename {
+                old: lit(&old),
+                new: lit(&new),
+            });
+        }
+        for (name, kinds) in self.mixins {
+            options.mixins.push(KindSet {
+                name: ident(&name)?,
+                kinds: lits(&kinds),
+            });
+        }
+        for (name, kinds) in self.enclosing {
+            options.enclosing.push(KindSet {
+                name: ident(&name)?,
+                kinds: lits(&kinds),
+            });
+        }
+        for (name, kinds) in self.group {
+            options.groups.push(KindSet {
+                name: ident(&name)?,
+                kinds: lits(&kinds),
+            });
+        }
+        if let Some((name, variants)) = self.enum_dispatch.into_iter().next() {
+            if options.enum_dispatch.is_none() {
+                options.enum_dispatch = Some(EnumDispatch {
+                    name: ident(&name)?,
+                    variants: variants
+                        .iter()
+                        .map(|variant| syn::parse2(parse_value(variant)?))
+                        .collect::<syn::Result<_>>()?,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Sets the span of all the tokens, so that errors in values from the configuration point to the
+/// `config` option.
+fn respan(tokens: proc_macro2::TokenStream, span: Span) -> proc_macro2::TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            if let proc_macro2::TokenTree::Group(group) = &token {
+                let mut respanned =
+                    proc_macro2::Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                token = respanned.into();
+            } else {
+                token.set_span(span);
+            }
+            token
+        })
+        .collect()
+}
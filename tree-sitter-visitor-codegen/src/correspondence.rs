@@ -0,0 +1,125 @@
+//! Generation of `NodeCorrespondence`, which pairs the nodes of a tree before an edit with their
+//! counterparts in the tree reparsed after it.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+pub(crate) fn generate(item: &ItemTrait) -> TokenStream {
+    let vis = &item.vis;
+
+    quote! {
+        /// Pairs the unchanged nodes of a tree before an edit with their counterparts in the tree
+        /// reparsed after it, so that analysis results for them can be reused.
+        ///
+        /// A node of the new tree is unchanged if it does not overlap any of the ranges reported by
+        /// `Tree::changed_ranges` or any of the edited ranges. Its counterpart is the node of the old
+        /// tree with the same byte range, kind and number of children.
+        #[derive(Debug, Clone)]
+        #vis struct NodeCorrespondence<'old, 'new> {
+            old_to_new: ::std::collections::HashMap<usize, ::tree_sitter::Node<'new>>,
+            new_to_old: ::std::collections::HashMap<usize, ::tree_sitter::Node<'old>>,
+        }
+
+        impl<'old, 'new> NodeCorrespondence<'old, 'new> {
+            /// Matches the nodes of `old_tree` with those of `new_tree`.
+            ///
+            /// `old_tree` must have been updated with `Tree::edit` for every edit, as is required to
+            /// reparse it incrementally, so that its byte offsets refer to the new source. `edits` are
+            /// the same edits.
+            pub fn new(
+                old_tree: &'old ::tree_sitter::Tree,
+                new_tree: &'new ::tree_sitter::Tree,
+                edits: &[::tree_sitter::InputEdit],
+            ) -> Self {
+                let mut changed: ::std::vec::Vec<_> = old_tree
+                    .changed_ranges(new_tree)
+                    .map(|range| range.start_byte..range.end_byte)
+                    .collect();
+                changed.extend(edits.iter().map(|edit| edit.start_byte..edit.new_end_byte));
+
+                let mut correspondence = Self {
+                    old_to_new: ::std::collections::HashMap::new(),
+                    new_to_old: ::std::collections::HashMap::new(),
+                };
+
+                let old_root = old_tree.root_node();
+                let mut pending = ::std::vec![new_tree.root_node()];
+                while let ::std::option::Option::Some(new_node) = pending.pop() {
+                    let is_changed = changed.iter().any(|range| {
+                        // Insertions and deletions are treated as touching the byte after them.
+                        new_node.start_byte() < range.end.max(range.start + 1)
+                            && range.start < new_node.end_byte()
+                    });
+                    if is_changed {
+                        let mut cursor = new_node.walk();
+                        pending.extend(new_node.children(&mut cursor));
+                        continue;
+                    }
+
+                    // Nothing below an unchanged node has changed either.
+                    let mut unchanged = ::std::vec![new_node];
+                    while let ::std::option::Option::Some(new_node) = unchanged.pop() {
+                        if let ::std::option::Option::Some(old_node) =
+                            Self::counterpart(&old_root, &new_node)
+                        {
+                            correspondence.old_to_new.insert(old_node.id(), new_node);
+                            correspondence.new_to_old.insert(new_node.id(), old_node);
+                        }
+                        let mut cursor = new_node.walk();
+                        unchanged.extend(new_node.children(&mut cursor));
+                    }
+                }
+
+                correspondence
+            }
+
+            fn counterpart(
+                old_root: &::tree_sitter::Node<'old>,
+                new_node: &::tree_sitter::Node<'new>,
+            ) -> ::std::option::Option<::tree_sitter::Node<'old>> {
+                let range = new_node.byte_range();
+                let mut candidate = old_root.descendant_for_byte_range(range.start, range.end);
+                while let ::std::option::Option::Some(old_node) = candidate {
+                    if old_node.byte_range() != range {
+                        break;
+                    }
+                    if old_node.kind_id() == new_node.kind_id()
+                        && old_node.child_count() == new_node.child_count()
+                    {
+                        return ::std::option::Option::Some(old_node);
+                    }
+                    candidate = old_node.parent();
+                }
+                ::std::option::Option::None
+            }
+
+            /// Returns the counterpart of a node of the old tree in the new tree, if it is unchanged.
+            pub fn new_node(&self, old_node: &::tree_sitter::Node) -> ::std::option::Option<::tree_sitter::Node<'new>> {
+                self.old_to_new.get(&old_node.id()).copied()
+            }
+
+            /// Returns the counterpart of a node of the new tree in the old tree, if it is unchanged.
+            pub fn old_node(&self, new_node: &::tree_sitter::Node) -> ::std::option::Option<::tree_sitter::Node<'old>> {
+                self.new_to_old.get(&new_node.id()).copied()
+            }
+
+            /// Returns the number of matched nodes.
+            pub fn len(&self) -> usize {
+                self.old_to_new.len()
+            }
+
+            /// Returns `true` if no nodes were matched.
+            pub fn is_empty(&self) -> bool {
+                self.old_to_new.is_empty()
+            }
+
+            /// Returns an iterator over the matched `(old, new)` node pairs, in no particular order.
+            pub fn pairs(
+                &self,
+            ) -> impl ::std::iter::Iterator<Item = (::tree_sitter::Node<'old>, ::tree_sitter::Node<'new>)> + '_ {
+                self.new_to_old.values().map(move |old_node| (*old_node, self.old_to_new[&old_node.id()]))
+            }
+        }
+    }
+}
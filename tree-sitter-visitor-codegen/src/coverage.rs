@@ -0,0 +1,159 @@
+//! Generation of `CoverageTracker`, which records the `visit_*` methods dispatched to by `visit()`
+//! while running a visitor, to report the handlers that were never exercised.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Ident, ItemTrait};
+
+use crate::node_types::Node;
+use crate::options::Options;
+use crate::upper_snake_case;
+
+fn coverage_ident(trait_name: &Ident) -> Ident {
+    format_ident!("{}_COVERAGE", upper_snake_case(&trait_name.to_string()))
+}
+
+/// Statement counting a dispatch to the method with the given index, placed in the match arms of
+/// `visit()`.
+pub(crate) fn record_hit(trait_name: &Ident, index: usize) -> TokenStream {
+    let coverage = coverage_ident(trait_name);
+
+    quote! {
+        #coverage.with(|counts| {
+            if let ::std::option::Option::Some(counts) = counts.borrow_mut().as_mut() {
+                counts[#index] += 1;
+            }
+        });
+    }
+}
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node], options: &Options) -> TokenStream {
+    let vis = &item.vis;
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let trait_name = &item.ident;
+    let coverage = coverage_ident(trait_name);
+    let methods = node_types
+        .iter()
+        .map(|node| options.method_name(&node.r#type).to_string());
+    let kinds = node_types.iter().map(|node| &node.r#type);
+    let method_count = node_types.len();
+
+    quote! {
+        ::std::thread_local! {
+            static #coverage: ::std::cell::RefCell<::std::option::Option<::std::vec::Vec<usize>>> =
+                ::std::cell::RefCell::new(::std::option::Option::None);
+        }
+
+        /// A wrapper counting the dispatches of `visit()` to each `visit_*` method, e.g. while
+        /// running a visitor over a corpus, to find the handlers that are never exercised.
+        ///
+        /// Only dispatches through `visit()` are counted, including those made by the visitor itself
+        /// while visiting a node, but not direct calls to the `visit_*` methods.
+        #vis struct CoverageTracker<V> {
+            /// The wrapped visitor.
+            pub inner: V,
+            counts: ::std::vec::Vec<usize>,
+        }
+
+        impl<V: #trait_name> CoverageTracker<V> {
+            /// The names of the `visit_*` methods, in the order of `counts()`.
+            pub const METHODS: &'static [&'static str] = &[#(#methods),*];
+
+            /// The node kinds visited by the methods in `METHODS`.
+            pub const KINDS: &'static [&'static str] = &[#(#kinds),*];
+
+            /// Wraps a visitor.
+            pub fn new(inner: V) -> Self {
+                Self {
+                    inner,
+                    counts: ::std::vec![0; #method_count],
+                }
+            }
+
+            /// Visits a node with the wrapped visitor, counting the dispatched methods.
+            pub fn visit(&mut self, node: &::tree_sitter::Node #param_decls) -> V::ReturnType {
+                let counts = ::std::mem::take(&mut self.counts);
+                let previous = #coverage.with(|cell| cell.replace(::std::option::Option::Some(counts)));
+
+                // Restores the counts even if the visit panics.
+                struct Restore<'a> {
+                    counts: &'a mut ::std::vec::Vec<usize>,
+                    previous: ::std::option::Option<::std::vec::Vec<usize>>,
+                }
+                impl ::std::ops::Drop for Restore<'_> {
+                    fn drop(&mut self) {
+                        let previous = self.previous.take();
+                        if let ::std::option::Option::Some(counts) =
+                            #coverage.with(|cell| cell.replace(previous))
+                        {
+                            *self.counts = counts;
+                        }
+                    }
+                }
+                let _restore = Restore {
+                    counts: &mut self.counts,
+                    previous,
+                };
+
+                self.inner.visit(node #param_args)
+            }
+
+            /// Returns the number of dispatches to each method, in the order of `METHODS`.
+            pub fn counts(&self) -> &[usize] {
+                &self.counts
+            }
+
+            /// Returns the number of dispatches to the method with the given name.
+            pub fn hits(&self, method: &str) -> usize {
+                Self::METHODS
+                    .iter()
+                    .position(|name| *name == method)
+                    .map_or(0, |index| self.counts[index])
+            }
+
+            /// Returns the names of the methods that were never dispatched to.
+            pub fn unexercised(&self) -> ::std::vec::Vec<&'static str> {
+                Self::METHODS
+                    .iter()
+                    .zip(&self.counts)
+                    .filter(|(_, count)| **count == 0)
+                    .map(|(name, _)| *name)
+                    .collect()
+            }
+
+            /// Returns the fraction of the methods that were dispatched to at least once.
+            pub fn ratio(&self) -> f64 {
+                let exercised = self.counts.iter().filter(|count| **count > 0).count();
+                exercised as f64 / Self::METHODS.len().max(1) as f64
+            }
+
+            /// Returns the counts as a JSON object mapping node kinds to the number of dispatches,
+            /// which can be passed to the `profile` option to order the dispatch by frequency.
+            pub fn profile(&self) -> ::std::string::String {
+                let mut counts = ::std::collections::BTreeMap::new();
+                for (kind, count) in Self::KINDS.iter().zip(&self.counts) {
+                    *counts.entry(*kind).or_insert(0) += count;
+                }
+                let entries: ::std::vec::Vec<_> = counts
+                    .iter()
+                    .map(|(kind, count)| {
+                        let escaped = kind.replace('\\', "\\\\").replace('"', "\\\"");
+                        ::std::format!("\"{}\": {}", escaped, count)
+                    })
+                    .collect();
+                ::std::format!("{{{}}}", entries.join(", "))
+            }
+
+            /// Resets all the counts to zero.
+            pub fn reset(&mut self) {
+                self.counts.iter_mut().for_each(|count| *count = 0);
+            }
+
+            /// Returns the wrapped visitor.
+            pub fn into_inner(self) -> V {
+                self.inner
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Generation of `visit_cursor()` and the `visit_cursor_<kind>()` methods, which visit the node a
+//! `TreeCursor` points at, so implementations can walk the children of a node with the cursor
+//! instead of looking them up by index.
+
+use quote::{format_ident, quote};
+use syn::{parse_quote, TraitItem};
+
+use crate::extras;
+use crate::method_return_type;
+use crate::node_types::Node;
+use crate::options::Options;
+
+pub(crate) fn trait_items(node_types: &[Node], options: &Options) -> Vec<TraitItem> {
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let asyncness = options.asyncness();
+    let result_type = options.result_type(options.self_return_type());
+    let tree = if options.by_value {
+        quote! { 'tree }
+    } else {
+        quote! { '_ }
+    };
+    // The node the cursor points at, as expected by the other visit methods.
+    let node = if options.by_value {
+        quote! { cursor.node() }
+    } else {
+        quote! { &cursor.node() }
+    };
+
+    // Supertypes never appear in a tree, so they are never dispatched.
+    let kinds: Vec<_> = node_types
+        .iter()
+        .filter(|node| !node.is_supertype())
+        .collect();
+
+    let kind_fns = kinds.iter().map(|symbol| {
+        let kind = &symbol.r#type;
+        let name = format_ident!("visit_cursor_{}", options.kind_name(kind));
+        let node_method = options.method_name(kind);
+        let return_type = method_return_type(options, kind, options.self_return_type());
+        let doc_name = format!("{:?}", kind).replace('`', "\\`");
+        let doc_string = format!(
+            "Visits the node of type `{}` the cursor points at. Forwards to `{}()` by default.",
+            doc_name, node_method
+        );
+        let call = options.awaited(quote! { self.#node_method(#node #param_args) });
+        let item: TraitItem = parse_quote! {
+            #[doc=#doc_string]
+            #asyncness fn #name(&mut self, cursor: &mut ::tree_sitter::TreeCursor<#tree> #param_decls) -> #return_type {
+                #call
+            }
+        };
+        item
+    });
+
+    let arms = kinds.iter().map(|symbol| {
+        let kind = &symbol.r#type;
+        let name = format_ident!("visit_cursor_{}", options.kind_name(kind));
+        // Renamed kinds are dispatched to the method of the new kind.
+        let pattern = match options.renames.iter().find(|r| r.new.value() == *kind) {
+            Some(rename) => {
+                let old = rename.old.value();
+                quote! { #kind | #old }
+            }
+            None => quote! { #kind },
+        };
+        if options.return_type(kind).is_some() {
+            let message = format!(
+                "nodes of kind `{}` have a dedicated return type and must be visited with `{}()`",
+                kind, name
+            );
+            quote! { #pattern => panic!(#message) }
+        } else {
+            let call = options.dispatched(quote! { self.#name(cursor #param_args) });
+            quote! { #pattern => #call }
+        }
+    });
+    let token_arm = if options.token_hook {
+        let call = options.dispatched(quote! { self.visit_token(node, node.kind() #param_args) });
+        quote! { _ if !node.is_named() => #call, }
+    } else {
+        quote! {}
+    };
+    let unknown_arm = options.unknown_arm("unknown node kind: {}");
+    let extra_arm = extras::dispatch_arm(node_types, options);
+    let error_arms = options.error_arms();
+    let node_ref = options.node_ref();
+    let after_visit = if options.after_visit {
+        quote! { self.after_visit(#node_ref, &result); }
+    } else {
+        quote! {}
+    };
+    let visit_child = options.awaited(quote! { self.visit_cursor(cursor #param_args) });
+    let push_child = match extras::skipped(node_types, options, quote! { cursor.node() }) {
+        Some(skipped) => quote! {
+            if !#skipped {
+                results.push(#visit_child);
+            }
+        },
+        None => quote! { results.push(#visit_child); },
+    };
+
+    let dispatch_fns: [TraitItem; 2] = [
+        parse_quote! {
+            /// Visits the node of any type the cursor points at, like `visit()`. The methods it
+            /// dispatches to must leave the cursor where they found it.
+            #asyncness fn visit_cursor(&mut self, cursor: &mut ::tree_sitter::TreeCursor<#tree> #param_decls) -> #result_type {
+                let node = #node;
+                let result = match node.kind() {
+                    #error_arms
+                    #extra_arm
+                    #(#arms,)*
+                    #token_arm
+                    #unknown_arm
+                };
+                #after_visit
+                result
+            }
+        },
+        parse_quote! {
+            /// Visits the children of the node the cursor points at in document order with
+            /// `visit_cursor()`, returning their results, and moves the cursor back to the node.
+            #asyncness fn visit_cursor_children(
+                &mut self,
+                cursor: &mut ::tree_sitter::TreeCursor<#tree>
+                #param_decls
+            ) -> ::std::vec::Vec<#result_type> {
+                let mut results = ::std::vec::Vec::new();
+                if cursor.goto_first_child() {
+                    loop {
+                        #push_child
+                        if !cursor.goto_next_sibling() {
+                            break;
+                        }
+                    }
+                    cursor.goto_parent();
+                }
+                results
+            }
+        },
+    ];
+
+    dispatch_fns.into_iter().chain(kind_fns).collect()
+}
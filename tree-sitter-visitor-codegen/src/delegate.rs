@@ -0,0 +1,213 @@
+//! Implementation of `#[derive(VisitorDelegate)]`, which implements a visitor trait for a wrapper
+//! struct by forwarding every typed method to one of its fields.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{parse_quote, Data, DeriveInput, Fields, Ident, Member, Path, Token};
+
+use crate::grammar::Grammar;
+use crate::mappings;
+use crate::method_return_type;
+use crate::options::{HookSignature, Options};
+
+/// Arguments of the `#[visitor_delegate(...)]` attribute.
+struct DelegateArgs {
+    trait_path: Path,
+    options: Options,
+    overrides: Vec<Ident>,
+}
+
+impl Parse for DelegateArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let trait_path: Path = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let mut overrides = Vec::new();
+        let options = Options::parse_with(input, |key, input| {
+            if key != "overrides" {
+                return Ok(false);
+            }
+            let content;
+            syn::parenthesized!(content in input);
+            overrides.extend(Punctuated::<Ident, Token![,]>::parse_terminated(&content)?);
+            Ok(true)
+        })?;
+        if options.by_value {
+            return Err(input.error("`by_value` is not supported by `VisitorDelegate`"));
+        }
+        if options.preset_return_type.is_some() {
+            return Err(input.error("`return_type` is not supported by `VisitorDelegate`"));
+        }
+        if options.return_type_param.is_some() {
+            return Err(input.error("`return_type_param` is not supported by `VisitorDelegate`"));
+        }
+
+        Ok(DelegateArgs {
+            trait_path,
+            options,
+            overrides,
+        })
+    }
+}
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let attr = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("visitor_delegate"))
+        .ok_or_else(|| {
+            syn::Error::new_spanned(
+                &input.ident,
+                "expected a `#[visitor_delegate(Trait, \"path/to/node-types.json\")]` attribute",
+            )
+        })?;
+    let args: DelegateArgs = attr.parse_args()?;
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`VisitorDelegate` can only be derived for structs",
+            ))
+        }
+    };
+    let field_members: Vec<(Member, &syn::Field)> = match fields {
+        Fields::Named(named) => named
+            .named
+            .iter()
+            .map(|field| (Member::Named(field.ident.clone().unwrap()), field))
+            .collect(),
+        Fields::Unnamed(unnamed) => unnamed
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (Member::Unnamed(index.into()), field))
+            .collect(),
+        Fields::Unit => Vec::new(),
+    };
+    let (member, field) = match field_members.iter().find(|(_, field)| {
+        field
+            .attrs
+            .iter()
+            .any(|attr| attr.path.is_ident("delegate"))
+    }) {
+        Some(found) => found.clone(),
+        None if field_members.len() == 1 => field_members[0].clone(),
+        None => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "mark the field to delegate to with `#[delegate]`",
+            ))
+        }
+    };
+
+    let trait_path = &args.trait_path;
+    let field_ty = &field.ty;
+    let node_types = args.options.load_node_types(attr.span())?;
+
+    let param_decls = args.options.param_decls();
+    let param_args = args.options.param_args();
+    let external_hook = match &args.options.grammar {
+        Some(path) => Grammar::load(path)?.has_externals(&node_types),
+        None => false,
+    };
+    // The methods of grouped kinds are replaced by the single method of their group.
+    let mut group_mappings = Vec::new();
+    mappings::add_groups(&mut group_mappings, &args.options, &node_types)?;
+    let is_grouped = |kind: &str| group_mappings.iter().any(|(grouped, _)| grouped == kind);
+    let group_methods = args.options.groups.iter().map(|group| {
+        let return_type = args.options.result_type(args.options.self_return_type());
+        (args.options.group_method_name(&group.name), return_type)
+    });
+    let method_names = node_types
+        .iter()
+        .filter(|node| !is_grouped(&node.r#type))
+        .map(|node| {
+            let return_type =
+                method_return_type(&args.options, &node.r#type, args.options.self_return_type());
+            (args.options.method_name(&node.r#type), return_type)
+        })
+        .chain(group_methods);
+    let asyncness = args.options.asyncness();
+    let methods = method_names.map(|(method_name, return_type)| {
+        let call = if args.overrides.contains(&method_name) {
+            quote! { Self::#method_name(self, node #param_args) }
+        } else {
+            quote! { #trait_path::#method_name(&mut self.#member, node #param_args) }
+        };
+        let call = args.options.awaited(call);
+        quote! {
+            #asyncness fn #method_name(&mut self, node: &::tree_sitter::Node<'_> #param_decls) -> #return_type {
+                #call
+            }
+        }
+    });
+
+    // `split()` splits the field delegated to and clones the others.
+    let split_fields = field_members.iter().map(|(field_member, _)| {
+        if *field_member == member {
+            quote! { #field_member: #trait_path::split(&self.#member) }
+        } else {
+            quote! { #field_member: ::std::clone::Clone::clone(&self.#field_member) }
+        }
+    });
+    let split = quote! { Self { #(#split_fields),* } };
+
+    // The hooks are forwarded like the methods of the node kinds.
+    let hook_methods = args
+        .options
+        .hooks(&node_types, external_hook)
+        .into_iter()
+        .map(|hook| {
+            let method_name = &hook.name;
+            let return_type = args.options.self_return_type();
+            hook.forward(&args.options, &return_type, |hook_args| {
+                if args.overrides.contains(method_name) {
+                    quote! { Self::#method_name(self, #hook_args) }
+                } else if hook.signature == HookSignature::Split {
+                    split.clone()
+                } else {
+                    quote! { #trait_path::#method_name(&mut self.#member, #hook_args) }
+                }
+            })
+        });
+
+    let context_impl = if args.options.context {
+        quote! { type Context = <#field_ty as #trait_path>::Context; }
+    } else {
+        quote! {}
+    };
+    let error_impl = if args.options.fallible {
+        quote! { type Error = <#field_ty as #trait_path>::Error; }
+    } else {
+        quote! {}
+    };
+
+    let tracked_files = args.options.tracked_files();
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(#field_ty: #trait_path));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #trait_path for #name #ty_generics #where_clause {
+            type ReturnType = <#field_ty as #trait_path>::ReturnType;
+            #context_impl
+            #error_impl
+
+            #(#methods)*
+
+            #(#hook_methods)*
+        }
+
+        #tracked_files
+    })
+}
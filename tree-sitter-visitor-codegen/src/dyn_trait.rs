@@ -0,0 +1,94 @@
+//! Generation of an object-safe counterpart of the visitor trait, taking the return type as a type
+//! parameter, with implementations converting between the two.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ItemTrait;
+
+use crate::options::Options;
+use crate::{fresh_lifetime, fresh_type_param, supertrait_predicate, Node};
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node], options: &Options) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = &item.ident;
+    let dyn_name = format_ident!("Dyn{}", trait_name);
+    let v = fresh_type_param(item, "V");
+    let r = fresh_type_param(item, "R");
+    let lifetime = fresh_lifetime(item, "v");
+    let return_type_predicate = options.return_type_predicate(&r);
+    let supertrait_predicate = supertrait_predicate(item);
+
+    // The methods are prefixed with `dyn_`, so calls on a visitor, which implements both traits,
+    // are not ambiguous.
+    let method_names: Vec<_> = node_types
+        .iter()
+        .map(|node| {
+            let method_name = options.method_name(&node.r#type);
+            let dyn_method_name = format_ident!("dyn_{}", method_name);
+            (method_name, dyn_method_name)
+        })
+        .collect();
+    let declarations = method_names.iter().map(|(method_name, dyn_method_name)| {
+        let doc_string = format!("See `{}::{}()`.", trait_name, method_name);
+        quote! {
+            #[doc = #doc_string]
+            fn #dyn_method_name(&mut self, node: &::tree_sitter::Node) -> #r;
+        }
+    });
+    let visitor_methods = method_names.iter().map(|(method_name, dyn_method_name)| {
+        quote! {
+            fn #dyn_method_name(&mut self, node: &::tree_sitter::Node) -> #v::ReturnType {
+                #trait_name::#method_name(self, node)
+            }
+        }
+    });
+    let boxed_methods = method_names.iter().map(|(method_name, dyn_method_name)| {
+        quote! {
+            fn #method_name(&mut self, node: &::tree_sitter::Node) -> #r {
+                #dyn_name::#dyn_method_name(&mut **self, node)
+            }
+        }
+    });
+    let doc_string = format!(
+        "An object-safe counterpart of `{trait_name}`, taking the return type as a type parameter \
+         instead of an associated type, so visitors can be used as `Box<dyn {dyn_name}<R>>`, e.g. \
+         to load them from a plugin registry. Its methods are the methods of `{trait_name}` \
+         prefixed with `dyn_`.\n\nEvery `{trait_name}` implements it, and \
+         `Box<dyn {dyn_name}<R>>` implements `{trait_name}`, forwarding `visit()` and the methods \
+         of the node kinds.",
+    );
+
+    let visit_doc_string = format!("See `{}::visit()`.", trait_name);
+
+    quote! {
+        #[doc = #doc_string]
+        #vis trait #dyn_name<#r> {
+            #[doc = #visit_doc_string]
+            fn dyn_visit(&mut self, node: &::tree_sitter::Node) -> #r;
+
+            #(#declarations)*
+        }
+
+        impl<#v: #trait_name> #dyn_name<#v::ReturnType> for #v {
+            fn dyn_visit(&mut self, node: &::tree_sitter::Node) -> #v::ReturnType {
+                #trait_name::visit(self, node)
+            }
+
+            #(#visitor_methods)*
+        }
+
+        impl<#lifetime, #r> #trait_name for ::std::boxed::Box<dyn #dyn_name<#r> + #lifetime>
+        where
+            #return_type_predicate
+            #supertrait_predicate
+        {
+            type ReturnType = #r;
+
+            fn visit(&mut self, node: &::tree_sitter::Node) -> #r {
+                #dyn_name::dyn_visit(&mut **self, node)
+            }
+
+            #(#boxed_methods)*
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Generation of `DynamicLanguage`, which loads a grammar from a shared library at runtime with
+//! `libloading` and validates it against the visitor's node kinds on first use.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+pub(crate) fn generate(item: &ItemTrait) -> TokenStream {
+    let vis = &item.vis;
+
+    quote! {
+        /// A language loaded from a grammar shared library at runtime.
+        ///
+        /// The library is kept loaded for as long as this value lives, so it must outlive every
+        /// parser and tree using the language.
+        #vis struct DynamicLanguage {
+            language: ::tree_sitter::Language,
+            table: ::std::sync::OnceLock<::std::result::Result<::std::sync::Arc<KindTable>, KindMismatch>>,
+            _library: ::libloading::Library,
+        }
+
+        /// An error loading or validating a `DynamicLanguage`.
+        #[derive(Debug)]
+        #vis enum DynamicLanguageError {
+            /// The library or the language symbol could not be loaded.
+            Load(::libloading::Error),
+            /// The node kinds of the language do not match the visitor.
+            Mismatch(KindMismatch),
+        }
+
+        impl ::std::fmt::Display for DynamicLanguageError {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    DynamicLanguageError::Load(err) => write!(f, "could not load the language: {}", err),
+                    DynamicLanguageError::Mismatch(err) => err.fmt(f),
+                }
+            }
+        }
+
+        impl ::std::error::Error for DynamicLanguageError {}
+
+        impl DynamicLanguage {
+            /// Loads the grammar shared library at `path` and the language returned by its
+            /// `symbol` function (e.g. `tree_sitter_cpp`).
+            ///
+            /// # Safety
+            ///
+            /// The library must be a tree-sitter grammar, and `symbol` must be a function with the
+            /// signature `extern "C" fn() -> *const TSLanguage`, as loading runs arbitrary code.
+            pub unsafe fn load(
+                path: impl ::std::convert::AsRef<::std::ffi::OsStr>,
+                symbol: &str,
+            ) -> ::std::result::Result<Self, DynamicLanguageError> {
+                let library = ::libloading::Library::new(path).map_err(DynamicLanguageError::Load)?;
+                let constructor: ::libloading::Symbol<unsafe extern "C" fn() -> ::tree_sitter::Language> =
+                    library
+                        .get(symbol.as_bytes())
+                        .map_err(DynamicLanguageError::Load)?;
+                let language = constructor();
+
+                Ok(Self {
+                    language,
+                    table: ::std::sync::OnceLock::new(),
+                    _library: library,
+                })
+            }
+
+            /// Returns the loaded language.
+            pub fn language(&self) -> ::tree_sitter::Language {
+                self.language.clone()
+            }
+
+            /// Returns the kind table for the language, validating on first use that its node kinds
+            /// match the visitor's.
+            pub fn kind_table(&self) -> ::std::result::Result<&::std::sync::Arc<KindTable>, DynamicLanguageError> {
+                self.table
+                    .get_or_init(|| KindTable::try_for_language(self.language.clone()))
+                    .as_ref()
+                    .map_err(|mismatch| DynamicLanguageError::Mismatch(mismatch.clone()))
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Generation of the emitter trait, whose methods stream any number of items into a sink instead
+//! of returning a single value.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+use crate::options::{Options, UnknownKinds};
+use crate::{derived_trait_name, prefixed_method_name};
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node], options: &Options) -> TokenStream {
+    let vis = &item.vis;
+    let emitter_name = derived_trait_name(&item.ident, "Emitter");
+
+    let (methods, match_arms): (Vec<_>, Vec<_>) = node_types
+        .iter()
+        .map(|node| {
+            let kind = &node.r#type;
+            let method_name = prefixed_method_name("emit", kind);
+            let doc_string = format!("Emits the items for a node of type `{:?}`", kind);
+
+            let method = quote! {
+                #[doc = #doc_string]
+                fn #method_name(
+                    &mut self,
+                    node: &::tree_sitter::Node,
+                    sink: &mut dyn ::std::ops::FnMut(Self::Item),
+                ) {
+                    self.emit_children(node, sink)
+                }
+            };
+            let match_arm = quote! {
+                #kind => self.#method_name(node, sink)
+            };
+
+            (method, match_arm)
+        })
+        .unzip();
+
+    // Kinds left out of the trait are known to the grammar, so they are recursed through like any
+    // other node rather than reported as unknown.
+    let unknown_arm = match options.unknown {
+        UnknownKinds::Panic if !options.filters_kinds() => {
+            quote! { _ => panic!("unknown node kind: {}", node.kind()) }
+        }
+        UnknownKinds::Skip => quote! { _ => {} },
+        _ => quote! { _ => self.emit_children(node, sink) },
+    };
+
+    quote! {
+        /// A visitor streaming zero or more items per node into a caller-provided sink.
+        ///
+        /// By default, every node emits nothing itself and recurses into its children, so only the
+        /// methods for nodes producing items need to be implemented.
+        #vis trait #emitter_name {
+            type Item;
+
+            /// Emits the items for a node of any type.
+            fn emit(&mut self, node: &::tree_sitter::Node, sink: &mut dyn ::std::ops::FnMut(Self::Item)) {
+                match node.kind() {
+                    #(#match_arms,)*
+                    #unknown_arm
+                }
+            }
+
+            /// Emits the items for all the children of a node, in document order.
+            fn emit_children(
+                &mut self,
+                node: &::tree_sitter::Node,
+                sink: &mut dyn ::std::ops::FnMut(Self::Item),
+            ) {
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor) {
+                    self.emit(&child, sink);
+                }
+            }
+
+            /// Emits the items for a node and collects them into a vector.
+            fn collect(&mut self, node: &::tree_sitter::Node) -> ::std::vec::Vec<Self::Item> {
+                let mut items = ::std::vec::Vec::new();
+                self.emit(node, &mut |item| items.push(item));
+                items
+            }
+
+            #(#methods)*
+        }
+    }
+}
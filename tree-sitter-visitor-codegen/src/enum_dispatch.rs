@@ -0,0 +1,114 @@
+//! Generation of an enum wrapping a closed set of visitor types, implementing the visitor trait by
+//! matching on the variant instead of going through a trait object.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ItemTrait, Type};
+
+use crate::node_types::Node;
+use crate::options::{EnumDispatch, HookSignature, Options};
+use crate::{method_return_type, trait_params, trait_path, trait_predicates};
+
+pub(crate) fn generate(
+    item: &ItemTrait,
+    node_types: &[Node],
+    options: &Options,
+    enum_dispatch: &EnumDispatch,
+    external_hook: bool,
+) -> syn::Result<TokenStream> {
+    let vis = &item.vis;
+    let trait_name = &item.ident;
+    let name = &enum_dispatch.name;
+    let types = &enum_dispatch.variants;
+
+    let variants = types
+        .iter()
+        .map(|ty| match ty {
+            Type::Path(path) if path.qself.is_none() => {
+                Ok(path.path.segments.last().unwrap().ident.clone())
+            }
+            _ => Err(syn::Error::new_spanned(
+                ty,
+                "expected a type path, such as `MyVisitor`",
+            )),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let first_type = types
+        .first()
+        .ok_or_else(|| syn::Error::new(name.span(), "expected at least one visitor type"))?;
+
+    let trait_params = trait_params(item);
+    let trait_predicates = trait_predicates(item);
+    let trait_ref = trait_path(item, quote! {});
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let methods = node_types.iter().map(|node| {
+        let method_name = options.method_name(&node.r#type);
+        let return_type = method_return_type(options, &node.r#type, options.self_return_type());
+        quote! {
+            fn #method_name(&mut self, node: &::tree_sitter::Node #param_decls) -> #return_type {
+                match self {
+                    #(#name::#variants(visitor) => visitor.#method_name(node #param_args),)*
+                }
+            }
+        }
+    });
+    // The hooks are forwarded like the methods of the node kinds.
+    let hook_methods = options
+        .hooks(node_types, external_hook)
+        .into_iter()
+        .map(|hook| {
+            let method_name = &hook.name;
+            hook.forward(options, &options.self_return_type(), |args| {
+                if hook.signature == HookSignature::Split {
+                    return quote! {
+                        match self {
+                            #(#name::#variants(visitor) => #name::#variants(visitor.#method_name()),)*
+                        }
+                    };
+                }
+                quote! {
+                    match self {
+                        #(#name::#variants(visitor) => visitor.#method_name(#args),)*
+                    }
+                }
+            })
+        });
+
+    let doc_string = format!(
+        "One of a closed set of `{}` implementations, dispatched statically.",
+        trait_name
+    );
+
+    Ok(quote! {
+        #[doc = #doc_string]
+        #vis enum #name {
+            #(#variants(#types),)*
+        }
+
+        #(
+            impl ::std::convert::From<#types> for #name {
+                fn from(visitor: #types) -> Self {
+                    #name::#variants(visitor)
+                }
+            }
+        )*
+
+        impl<#trait_params> #trait_ref for #name
+        where
+            #trait_predicates
+        {
+            type ReturnType = <#first_type as #trait_ref>::ReturnType;
+
+            fn visit(&mut self, node: &::tree_sitter::Node #param_decls) -> Self::ReturnType {
+                match self {
+                    #(#name::#variants(visitor) => visitor.visit(node #param_args),)*
+                }
+            }
+
+            #(#methods)*
+
+            #(#hook_methods)*
+        }
+    })
+}
@@ -0,0 +1,49 @@
+//! Handling of extras, such as comments, with `extras = "skip" | "hook"`: the check whether a node
+//! is one, and the `visit_extra()` hook.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::node_types::Node;
+use crate::options::{ExtraNodes, Options};
+
+/// Returns an expression checking whether `node` is an extra, either because the parser marked it
+/// as one or because its kind is declared as an extra in `node-types.json`.
+pub(crate) fn is_extra(node_types: &[Node], node: TokenStream) -> TokenStream {
+    let kinds: Vec<_> = node_types
+        .iter()
+        .filter(|node| node.extra)
+        .map(|node| &node.r#type)
+        .collect();
+    if kinds.is_empty() {
+        quote! { #node.is_extra() }
+    } else {
+        quote! { (#node.is_extra() || matches!(#node.kind(), #(#kinds)|*)) }
+    }
+}
+
+/// Returns the check whether `child` is to be left out by the child iteration helpers, if extras
+/// are skipped.
+pub(crate) fn skipped(
+    node_types: &[Node],
+    options: &Options,
+    child: TokenStream,
+) -> Option<TokenStream> {
+    if options.extras == ExtraNodes::Skip {
+        Some(is_extra(node_types, child))
+    } else {
+        None
+    }
+}
+
+/// Returns the match arm dispatching extras to `visit_extra()`, placed before the arms of the
+/// node kinds, if extras are hooked.
+pub(crate) fn dispatch_arm(node_types: &[Node], options: &Options) -> TokenStream {
+    if options.extras != ExtraNodes::Hook {
+        return quote! {};
+    }
+    let is_extra = is_extra(node_types, quote! { node });
+    let param_args = options.param_args();
+    let call = options.dispatched(quote! { self.visit_extra(node #param_args) });
+    quote! { _ if #is_extra => #call, }
+}
@@ -0,0 +1,51 @@
+//! Generation of `UnimplementedNode`, the error returned by the default implementations of the
+//! visit methods with `fallible`, instead of panicking.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+pub(crate) fn generate(item: &ItemTrait) -> TokenStream {
+    let vis = &item.vis;
+    let doc_string = format!(
+        "A node that reached a default implementation of `{}`: one of a kind the visitor does not \
+         handle, or of a kind that is not in `node-types.json`.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct UnimplementedNode {
+            /// The kind of the node.
+            pub kind: &'static str,
+            /// Whether the node is named.
+            pub named: bool,
+            /// The byte range of the node in the source.
+            pub byte_range: ::std::ops::Range<usize>,
+        }
+
+        impl UnimplementedNode {
+            /// Describes the given node.
+            pub fn new(node: &::tree_sitter::Node) -> Self {
+                Self {
+                    kind: node.kind(),
+                    named: node.is_named(),
+                    byte_range: node.byte_range(),
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for UnimplementedNode {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(
+                    f,
+                    "unimplemented node kind `{}` at bytes {}..{}",
+                    self.kind, self.byte_range.start, self.byte_range.end
+                )
+            }
+        }
+
+        impl ::std::error::Error for UnimplementedNode {}
+    }
+}
@@ -0,0 +1,89 @@
+//! Generation of the `FieldName` enum and of `FieldChildren`, an iterator over the children of a
+//! node paired with the names of the fields they are in.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ItemTrait;
+
+use crate::fields::field_names;
+use crate::node_types::Node;
+use crate::upper_camel_case;
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    let names: Vec<_> = field_names(node_types).into_iter().collect();
+    let variants: Vec<_> = names
+        .iter()
+        .map(|name| format_ident!("{}", upper_camel_case(name)))
+        .collect();
+    let docs = names.iter().map(|name| format!("The `{}` field.", name));
+
+    quote! {
+        /// The names of the fields in the grammar.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #vis enum FieldName {
+            #(
+                #[doc = #docs]
+                #variants,
+            )*
+        }
+
+        impl FieldName {
+            /// All the field names, sorted.
+            pub const ALL: &'static [FieldName] = &[#(FieldName::#variants),*];
+
+            /// Returns the name of the field as it appears in the grammar.
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    #(FieldName::#variants => #names,)*
+                }
+            }
+
+            /// Returns the field with the given name, if the grammar has it.
+            pub fn from_name(name: &str) -> ::std::option::Option<Self> {
+                match name {
+                    #(#names => ::std::option::Option::Some(FieldName::#variants),)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for FieldName {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        /// An iterator over the children of a node, yielding each child together with the field it
+        /// is in, if any.
+        #vis struct FieldChildren<'tree> {
+            cursor: ::tree_sitter::TreeCursor<'tree>,
+            done: bool,
+        }
+
+        impl<'tree> FieldChildren<'tree> {
+            /// Iterates over the children of the given node.
+            pub fn new(node: &::tree_sitter::Node<'tree>) -> Self {
+                let mut cursor = node.walk();
+                let done = !cursor.goto_first_child();
+                Self { cursor, done }
+            }
+        }
+
+        impl<'tree> ::std::iter::Iterator for FieldChildren<'tree> {
+            type Item = (::std::option::Option<FieldName>, ::tree_sitter::Node<'tree>);
+
+            fn next(&mut self) -> ::std::option::Option<Self::Item> {
+                if self.done {
+                    return ::std::option::Option::None;
+                }
+                let item = (
+                    self.cursor.field_name().and_then(FieldName::from_name),
+                    self.cursor.node(),
+                );
+                self.done = !self.cursor.goto_next_sibling();
+                ::std::option::Option::Some(item)
+            }
+        }
+    }
+}
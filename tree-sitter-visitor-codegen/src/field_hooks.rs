@@ -0,0 +1,80 @@
+//! Generation of the `visit_field_<name>()` hooks, one for each field name in the grammar, and of
+//! `visit_fields()`, which dispatches the children of a node to them by the field they are in.
+
+use quote::quote;
+use syn::{parse_quote, TraitItem};
+
+use crate::fields::field_names;
+use crate::node_types::Node;
+use crate::options::Options;
+use crate::prefixed_method_name;
+
+/// Returns the names of the hooks, in the order of the field names.
+pub(crate) fn hook_names(node_types: &[Node]) -> Vec<String> {
+    field_names(node_types)
+        .into_iter()
+        .map(|name| prefixed_method_name("visit_field", name).to_string())
+        .collect()
+}
+
+pub(crate) fn trait_items(node_types: &[Node], options: &Options) -> Vec<TraitItem> {
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let asyncness = options.asyncness();
+    let node_param = options.node_param();
+    let node_type = options.node_type();
+    let result_type = options.result_type(options.self_return_type());
+    let child = if options.by_value {
+        quote! { child }
+    } else {
+        quote! { &child }
+    };
+
+    let names = field_names(node_types);
+    let hooks = names.iter().map(|name| {
+        let hook = prefixed_method_name("visit_field", name);
+        let doc_string = format!(
+            "Visits a child in the `{}` field of `parent`. Visits it with `visit()` by default.",
+            name
+        );
+        let call = options.awaited(quote! { self.visit(node #param_args) });
+        let item: TraitItem = parse_quote! {
+            #[doc=#doc_string]
+            #[allow(unused_variables)]
+            #asyncness fn #hook(&mut self, parent: #node_type, #node_param #param_decls) -> #result_type {
+                #call
+            }
+        };
+        item
+    });
+
+    let arms = names.iter().map(|name| {
+        let hook = prefixed_method_name("visit_field", name);
+        let call = options.dispatched(quote! { self.#hook(node, #child #param_args) });
+        quote! { ::std::option::Option::Some(#name) => results.push(#call), }
+    });
+    let visit_fields: TraitItem = parse_quote! {
+        /// Visits the children of a node that are in a field with the `visit_field_<name>()` hook of
+        /// the field, in document order, returning their results. Children that are not in a field
+        /// are skipped.
+        #asyncness fn visit_fields(&mut self, #node_param #param_decls) -> ::std::vec::Vec<#result_type> {
+            let mut results = ::std::vec::Vec::new();
+            let mut cursor = node.walk();
+            if cursor.goto_first_child() {
+                loop {
+                    let child = cursor.node();
+                    match cursor.field_name() {
+                        #(#arms)*
+                        _ => {}
+                    }
+                    if !cursor.goto_next_sibling() {
+                        break;
+                    }
+                }
+            }
+            results
+        }
+    };
+
+    std::iter::once(visit_fields).chain(hooks).collect()
+}
@@ -0,0 +1,120 @@
+//! Generation of the `Fields` struct, holding the field ids of every field name in the grammar
+//! resolved once from a `Language`.
+
+use std::collections::BTreeSet;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::field_ident;
+use crate::node_types::{concrete_types, Node};
+
+/// Returns the names of all the fields in the grammar, sorted.
+pub(crate) fn field_names(node_types: &[Node]) -> BTreeSet<&str> {
+    node_types
+        .iter()
+        .flat_map(|node| node.fields.keys())
+        .map(String::as_str)
+        .collect()
+}
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    let names = field_names(node_types);
+    let idents: Vec<_> = names.iter().map(|name| field_ident(name)).collect();
+    let docs = names
+        .iter()
+        .map(|name| format!("The id of the `{}` field, if the language has it.", name));
+
+    let getters = names.iter().zip(&idents).map(|(name, ident)| {
+        let parent_arms = node_types.iter().filter_map(|node| {
+            let child_types = node.fields.get(*name)?;
+            let parent_kind = &node.r#type;
+            let allowed = concrete_types(&child_types.types, node_types)
+                .into_iter()
+                .map(|node_ref| {
+                    let kind = &node_ref.r#type;
+                    let named = node_ref.named;
+                    quote! { (#kind, #named) }
+                });
+            Some(quote! {
+                #parent_kind => matches!((child.kind(), child.is_named()), #(#allowed)|*)
+            })
+        });
+        let doc_string = format!(
+            "Returns the child of `node` in the `{}` field.\n\nIn debug builds, asserts that the \
+             kind of the child is one declared for the field in `node-types.json`.",
+            name
+        );
+
+        quote! {
+            #[doc = #doc_string]
+            pub fn #ident<'tree>(
+                &self,
+                node: &::tree_sitter::Node<'tree>,
+            ) -> ::std::option::Option<::tree_sitter::Node<'tree>> {
+                let child = node.child_by_field_id(self.#ident?.get())?;
+                debug_assert!(
+                    child.is_error()
+                        || match node.kind() {
+                            #(#parent_arms,)*
+                            _ => true,
+                        },
+                    "unexpected node kind `{}` in field `{}` of `{}`",
+                    child.kind(),
+                    #name,
+                    node.kind()
+                );
+                ::std::option::Option::Some(child)
+            }
+        }
+    });
+
+    quote! {
+        /// The ids of all the fields in the grammar, resolved from a `Language`, so that children can
+        /// be looked up with `child_by_field_id` instead of by name.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #vis struct Fields {
+            #(
+                #[doc = #docs]
+                pub #idents: ::std::option::Option<::std::num::NonZeroU16>,
+            )*
+        }
+
+        impl Fields {
+            /// Resolves the field ids of the given language.
+            pub fn new(language: ::tree_sitter::Language) -> Self {
+                Self {
+                    #(#idents: language.field_id_for_name(#names),)*
+                }
+            }
+
+            /// Returns the field ids of the given language, resolving them on first use and caching
+            /// them for subsequent calls.
+            pub fn for_language(language: ::tree_sitter::Language) -> Self {
+                static FIELDS: ::std::sync::OnceLock<
+                    ::std::sync::Mutex<::std::collections::HashMap<::tree_sitter::Language, Fields>>,
+                > = ::std::sync::OnceLock::new();
+
+                let mut fields = FIELDS
+                    .get_or_init(::std::default::Default::default)
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                *fields
+                    .entry(language.clone())
+                    .or_insert_with(|| Self::new(language))
+            }
+
+            #(#getters)*
+
+            /// Returns the child of `node` in the field with the given id.
+            pub fn child<'tree>(
+                node: &::tree_sitter::Node<'tree>,
+                field_id: ::std::option::Option<::std::num::NonZeroU16>,
+            ) -> ::std::option::Option<::tree_sitter::Node<'tree>> {
+                node.child_by_field_id(field_id?.get())
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Generation of `NodeFingerprint`, which identifies "the same" node across reparses of edited
+//! source without relying on byte offsets.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+pub(crate) fn generate(item: &ItemTrait) -> TokenStream {
+    let vis = &item.vis;
+
+    quote! {
+        /// Identifies a node across reparses of edited source, so that results computed for it can be
+        /// carried forward.
+        ///
+        /// Two nodes have the same fingerprint if they have the same kinds on the path from the root,
+        /// the same position among the siblings of their kind, and subtrees with the same structure
+        /// and text. Byte offsets are not part of the fingerprint, so edits elsewhere in the source do
+        /// not change it. Fingerprints are only comparable within the same build of a program.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #vis struct NodeFingerprint {
+            /// A hash of the kinds of the node and of its ancestors.
+            pub kind_path: u64,
+            /// A hash of the kinds, fields and token text of the subtree rooted at the node.
+            pub structure: u64,
+            /// The depth of the node below the root.
+            pub depth: usize,
+            /// The number of preceding siblings of the same kind.
+            pub ordinal: usize,
+        }
+
+        impl NodeFingerprint {
+            /// Computes the fingerprint of a node, with `source` being the text it was parsed from.
+            pub fn new(node: &::tree_sitter::Node, source: &(impl TextProvider + ?Sized)) -> Self {
+                use ::std::hash::{Hash, Hasher};
+
+                let mut kind_path = ::std::collections::hash_map::DefaultHasher::new();
+                let mut depth = 0;
+                let mut ancestor = ::std::option::Option::Some(*node);
+                while let ::std::option::Option::Some(current) = ancestor {
+                    current.kind_id().hash(&mut kind_path);
+                    ancestor = current.parent();
+                    depth += 1;
+                }
+
+                let mut ordinal = 0;
+                let mut sibling = node.prev_sibling();
+                while let ::std::option::Option::Some(current) = sibling {
+                    if current.kind_id() == node.kind_id() {
+                        ordinal += 1;
+                    }
+                    sibling = current.prev_sibling();
+                }
+
+                let mut structure = ::std::collections::hash_map::DefaultHasher::new();
+                Self::hash_structure(&mut node.walk(), source, &mut structure);
+
+                Self {
+                    kind_path: kind_path.finish(),
+                    structure: structure.finish(),
+                    depth: depth - 1,
+                    ordinal,
+                }
+            }
+
+            /// Hashes the subtree at the cursor in pre-order. The cursor keeps the path to the
+            /// current node, so deeply nested trees are walked without recursion.
+            fn hash_structure(
+                cursor: &mut ::tree_sitter::TreeCursor,
+                source: &(impl TextProvider + ?Sized),
+                hasher: &mut impl ::std::hash::Hasher,
+            ) {
+                use ::std::hash::Hash;
+
+                // The number of nodes entered below the node the cursor started at.
+                let mut depth = 0usize;
+                loop {
+                    let node = cursor.node();
+                    node.kind_id().hash(hasher);
+                    cursor.field_id().hash(hasher);
+                    if cursor.goto_first_child() {
+                        depth += 1;
+                        continue;
+                    }
+                    source.text(node.byte_range()).hash(hasher);
+                    // Delimits the children of a node from those of its siblings.
+                    usize::MAX.hash(hasher);
+
+                    // Moves on to the next sibling of the node or of its closest ancestor having
+                    // one, closing the ancestors left on the way.
+                    loop {
+                        if depth == 0 {
+                            return;
+                        }
+                        if cursor.goto_next_sibling() {
+                            break;
+                        }
+                        cursor.goto_parent();
+                        depth -= 1;
+                        usize::MAX.hash(hasher);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,651 @@
+//! Generation of the flavors requested with `flavors(...)`: further visitor traits generated from
+//! the same grammar and options as the annotated trait, named after it.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ItemTrait;
+
+use crate::extras;
+use crate::node_types::Node;
+use crate::options::{ExtraNodes, Options, UnknownKinds};
+use crate::{derived_trait_name, method_return_type, prefixed_method_name, sanitize_identifier};
+
+/// The names accepted by `flavors(...)`.
+pub(crate) const FLAVORS: &[&str] = &["immutable", "enter_leave", "fold", "rewrite"];
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node], options: &Options) -> TokenStream {
+    let flavors = options
+        .flavors
+        .iter()
+        .map(|flavor| match flavor.to_string().as_str() {
+            "immutable" => immutable(item, node_types, options),
+            "enter_leave" => enter_leave(item, node_types, options),
+            "fold" => fold(item, node_types, options),
+            "rewrite" => rewrite(item, node_types, options),
+            other => unreachable!("unknown flavor `{}`", other),
+        });
+
+    quote! { #(#flavors)* }
+}
+
+/// Generates the `<Name>RefVisitor` trait, whose methods take `&self`, for visitors that only read
+/// their state or keep it in cells, and can therefore be shared.
+fn immutable(item: &ItemTrait, node_types: &[Node], options: &Options) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = derived_trait_name(&item.ident, "RefVisitor");
+    let generics = &item.generics;
+    let where_clause = &generics.where_clause;
+    let supertraits = &item.supertraits;
+    let colon = item.colon_token;
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let return_type_item = options.return_type_item();
+    let context_item = options.context_item();
+    let error_item = options.error_item();
+    let result_type = options.result_type(options.self_return_type());
+    let asyncness = options.asyncness();
+    let unknown_arm = options.unknown_arm("unknown node kind: {}");
+    let unknown_fn = if options.unknown == UnknownKinds::Hook {
+        let stub = options.stub(quote! { panic!("unknown node kind: {}", node.kind()) });
+        quote! {
+            /// Visits a node whose kind is not in `node-types.json`. Panics by default.
+            #[allow(unused_variables)]
+            #asyncness fn visit_unknown(&self, node: &::tree_sitter::Node<'_> #param_decls) -> #result_type {
+                #stub
+            }
+        }
+    } else {
+        quote! {}
+    };
+    // The hooks of the annotated trait, taking `&self` as well.
+    let hook_fn = |name: &str, doc: &str, params: TokenStream, panic: TokenStream| {
+        let name = format_ident!("{}", name);
+        let stub = options.stub(panic);
+        quote! {
+            #[doc = #doc]
+            #[allow(unused_variables)]
+            #asyncness fn #name(&self, node: &::tree_sitter::Node<'_> #params #param_decls) -> #result_type {
+                #stub
+            }
+        }
+    };
+    let mut hook_fns = Vec::new();
+    if options.token_hook {
+        hook_fns.push(hook_fn(
+            "visit_token",
+            "Visits an anonymous node (a token) of any type, of the given kind. Panics by default.",
+            quote! { , kind: &str },
+            quote! { unimplemented!("token `{}`", kind) },
+        ));
+    }
+    if options.error_hooks {
+        hook_fns.push(hook_fn(
+            "visit_error",
+            "Visits an `ERROR` node. Panics by default.",
+            quote! {},
+            quote! { panic!("syntax error at {:?}", node.range()) },
+        ));
+        hook_fns.push(hook_fn(
+            "visit_missing",
+            "Visits a `MISSING` node instead of the method of its kind. Panics by default.",
+            quote! {},
+            quote! { panic!("missing `{}` at {:?}", node.kind(), node.range()) },
+        ));
+    }
+    if options.extras == ExtraNodes::Hook {
+        hook_fns.push(hook_fn(
+            "visit_extra",
+            "Visits an extra instead of the method of its kind. Panics by default.",
+            quote! {},
+            quote! { panic!("extra node: {}", node.kind()) },
+        ));
+    }
+    let error_arms = options.error_arms();
+    let extra_arm = extras::dispatch_arm(node_types, options);
+    let token_arm = if options.token_hook {
+        let call = options.dispatched(quote! { self.visit_token(node, node.kind() #param_args) });
+        quote! { _ if !node.is_named() => #call, }
+    } else {
+        quote! {}
+    };
+
+    let (methods, match_arms): (Vec<_>, Vec<_>) = node_types
+        .iter()
+        .map(|node| {
+            let kind = &node.r#type;
+            let method_name = options.method_name(kind);
+            let sanitized_name = sanitize_identifier(kind);
+            let doc_string = format!("Visits a node of type `{:?}`", kind);
+            let return_type = method_return_type(options, kind, options.self_return_type());
+            let stub = options.stub(quote! { unimplemented!(#sanitized_name) });
+
+            let method = quote! {
+                #[doc = #doc_string]
+                #[allow(unused_variables)]
+                #asyncness fn #method_name(&self, node: &::tree_sitter::Node<'_> #param_decls) -> #return_type {
+                    #stub
+                }
+            };
+            let match_arm = if options.return_type(kind).is_some() {
+                let message = format!(
+                    "nodes of kind `{}` have a dedicated return type and must be visited with `{}()`",
+                    kind, method_name
+                );
+                quote! { #kind => panic!(#message) }
+            } else {
+                let call = options.dispatched(quote! { self.#method_name(node #param_args) });
+                quote! { #kind => #call }
+            };
+
+            (method, match_arm)
+        })
+        .unzip();
+
+    let doc_string = format!(
+        "A flavor of `{}` whose methods take `&self`, for visitors that don't need to mutate \
+         their state while visiting.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #vis trait #trait_name #generics #colon #supertraits #where_clause {
+            #return_type_item
+            #context_item
+            #error_item
+
+            /// Visits a node of any type.
+            #asyncness fn visit(&self, node: &::tree_sitter::Node<'_> #param_decls) -> #result_type {
+                match node.kind() {
+                    #error_arms
+                    #extra_arm
+                    #(#match_arms,)*
+                    #token_arm
+                    #unknown_arm
+                }
+            }
+
+            #unknown_fn
+
+            #(#hook_fns)*
+
+            #(#methods)*
+        }
+    }
+}
+
+/// Generates the `<Name>Walker` trait, with `enter_<kind>()` and `leave_<kind>()` hooks called by an
+/// iterative depth-first `walk()`, for visitors that only need events and no return values.
+fn enter_leave(item: &ItemTrait, node_types: &[Node], options: &Options) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = derived_trait_name(&item.ident, "Walker");
+    let generics = &item.generics;
+    let where_clause = &generics.where_clause;
+    let supertraits = &item.supertraits;
+    let colon = item.colon_token;
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let context_item = options.context_item();
+
+    // Supertypes never appear in a tree, so they have no hooks.
+    let node_types: Vec<_> = node_types
+        .iter()
+        .filter(|node| !node.is_supertype())
+        .collect();
+    let hooks = |prefix: &str, doc: &str| {
+        let (methods, match_arms): (Vec<_>, Vec<_>) = node_types
+            .iter()
+            .map(|node| {
+                let kind = &node.r#type;
+                let method_name = prefixed_method_name(prefix, kind);
+                let doc_string = format!("{} a node of type `{:?}`.", doc, kind);
+                let method = quote! {
+                    #[doc = #doc_string]
+                    #[allow(unused_variables)]
+                    fn #method_name(&mut self, node: &::tree_sitter::Node<'_> #param_decls) {}
+                };
+                let match_arm = quote! { #kind => self.#method_name(node #param_args) };
+                (method, match_arm)
+            })
+            .unzip();
+        (methods, match_arms)
+    };
+    let (enter_methods, enter_arms) = hooks("enter", "Called before the children of");
+    let (leave_methods, leave_arms) = hooks("leave", "Called after the children of");
+
+    let doc_string = format!(
+        "A flavor of `{}` with `enter_<kind>()` and `leave_<kind>()` hooks, called by `walk()` on \
+         the way down and on the way up of a depth-first traversal.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #vis trait #trait_name #generics #colon #supertraits #where_clause {
+            #context_item
+
+            /// Walks the subtree rooted at `root` depth-first with a `TreeCursor`, calling `enter()`
+            /// for each node before its children and `leave()` after them.
+            fn walk(&mut self, root: ::tree_sitter::Node<'_> #param_decls) {
+                let mut cursor = root.walk();
+                loop {
+                    self.enter(&cursor.node() #param_args);
+                    if cursor.goto_first_child() {
+                        continue;
+                    }
+                    loop {
+                        self.leave(&cursor.node() #param_args);
+                        if cursor.goto_next_sibling() {
+                            break;
+                        }
+                        if !cursor.goto_parent() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            /// Called before the children of a node of any type. Nodes of kinds that are not in
+            /// `node-types.json` are ignored.
+            fn enter(&mut self, node: &::tree_sitter::Node<'_> #param_decls) {
+                match node.kind() {
+                    #(#enter_arms,)*
+                    _ => {}
+                }
+            }
+
+            /// Called after the children of a node of any type. Nodes of kinds that are not in
+            /// `node-types.json` are ignored.
+            fn leave(&mut self, node: &::tree_sitter::Node<'_> #param_decls) {
+                match node.kind() {
+                    #(#leave_arms,)*
+                    _ => {}
+                }
+            }
+
+            #(#enter_methods)*
+
+            #(#leave_methods)*
+        }
+    }
+}
+
+/// Generates the `<Name>Folder` trait, whose `fold_<kind>()` methods receive the results of the
+/// named children of the node, computed bottom-up by an iterative `fold_tree()`.
+fn fold(item: &ItemTrait, node_types: &[Node], options: &Options) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = derived_trait_name(&item.ident, "Folder");
+    let generics = &item.generics;
+    let where_clause = &generics.where_clause;
+    let supertraits = &item.supertraits;
+    let colon = item.colon_token;
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let return_type_item = options.return_type_item();
+    let context_item = options.context_item();
+    let (unknown_arm, unknown_fn) = match options.unknown {
+        UnknownKinds::Panic => (
+            quote! { _ => panic!("unknown node kind: {}", node.kind()) },
+            quote! {},
+        ),
+        UnknownKinds::Skip => (
+            quote! { _ => ::std::default::Default::default() },
+            quote! {},
+        ),
+        UnknownKinds::Hook => (
+            quote! { _ => self.fold_unknown(node, children #param_args) },
+            quote! {
+                /// Folds a node whose kind is not in `node-types.json`. Panics by default.
+                #[allow(unused_variables)]
+                fn fold_unknown(
+                    &mut self,
+                    node: &::tree_sitter::Node<'_>,
+                    children: ::std::vec::Vec<Self::ReturnType>
+                    #param_decls
+                ) -> Self::ReturnType {
+                    panic!("unknown node kind: {}", node.kind())
+                }
+            },
+        ),
+    };
+
+    // Anonymous nodes are not folded, and supertypes never appear in a tree.
+    let (methods, match_arms): (Vec<_>, Vec<_>) = node_types
+        .iter()
+        .filter(|node| node.named && !node.is_supertype())
+        .map(|node| {
+            let kind = &node.r#type;
+            let method_name = prefixed_method_name("fold", kind);
+            let sanitized_name = sanitize_identifier(kind);
+            let doc_string = format!(
+                "Folds a node of type `{:?}`, given the results of its named children.",
+                kind
+            );
+            let method = quote! {
+                #[doc = #doc_string]
+                #[allow(unused_variables)]
+                fn #method_name(
+                    &mut self,
+                    node: &::tree_sitter::Node<'_>,
+                    children: ::std::vec::Vec<Self::ReturnType>
+                    #param_decls
+                ) -> Self::ReturnType {
+                    unimplemented!(#sanitized_name)
+                }
+            };
+            let match_arm = quote! { #kind => self.#method_name(node, children #param_args) };
+            (method, match_arm)
+        })
+        .unzip();
+
+    let doc_string = format!(
+        "A flavor of `{}` whose `fold_<kind>()` methods receive the results of the named children \
+         of the node, computed bottom-up by `fold_tree()`.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #vis trait #trait_name #generics #colon #supertraits #where_clause {
+            #return_type_item
+            #context_item
+
+            /// Folds the subtree rooted at the named node `root` bottom-up, traversing it with a
+            /// `TreeCursor` without recursion, and returns the result of folding `root`. Anonymous
+            /// nodes are skipped.
+            fn fold_tree(&mut self, root: ::tree_sitter::Node<'_> #param_decls) -> Self::ReturnType {
+                let mut cursor = root.walk();
+                // The results of the children folded so far, for each node on the path to the
+                // cursor.
+                let mut pending = vec![::std::vec::Vec::new()];
+                loop {
+                    if cursor.goto_first_child() {
+                        pending.push(::std::vec::Vec::new());
+                        continue;
+                    }
+                    loop {
+                        let node = cursor.node();
+                        let children = pending.pop().unwrap_or_default();
+                        match pending.last_mut() {
+                            ::std::option::Option::None => {
+                                return self.fold(&node, children #param_args);
+                            }
+                            ::std::option::Option::Some(siblings) if node.is_named() => {
+                                siblings.push(self.fold(&node, children #param_args));
+                            }
+                            ::std::option::Option::Some(_) => {}
+                        }
+                        if cursor.goto_next_sibling() {
+                            pending.push(::std::vec::Vec::new());
+                            break;
+                        }
+                        cursor.goto_parent();
+                    }
+                }
+            }
+
+            /// Folds a node of any type, given the results of its named children.
+            fn fold(
+                &mut self,
+                node: &::tree_sitter::Node<'_>,
+                children: ::std::vec::Vec<Self::ReturnType>
+                #param_decls
+            ) -> Self::ReturnType {
+                match node.kind() {
+                    #(#match_arms,)*
+                    #unknown_arm
+                }
+            }
+
+            #unknown_fn
+
+            #(#methods)*
+        }
+    }
+}
+
+/// Generates the `<Name>Rewriter` trait, whose `rewrite_<kind>()` methods can return replacement
+/// text for a node, and `SourceEdit`, with which the replacements are applied to the source.
+fn rewrite(item: &ItemTrait, node_types: &[Node], options: &Options) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = derived_trait_name(&item.ident, "Rewriter");
+    let generics = &item.generics;
+    let where_clause = &generics.where_clause;
+    let supertraits = &item.supertraits;
+    let colon = item.colon_token;
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let context_item = options.context_item();
+
+    // Supertypes never appear in a tree, so they cannot be rewritten.
+    let (methods, match_arms): (Vec<_>, Vec<_>) = node_types
+        .iter()
+        .filter(|node| !node.is_supertype())
+        .map(|node| {
+            let kind = &node.r#type;
+            let method_name = prefixed_method_name("rewrite", kind);
+            let doc_string = format!(
+                "Returns the text replacing a node of type `{:?}`, or `None` to keep it and rewrite \
+                 its children instead, the default.",
+                kind
+            );
+            let method = quote! {
+                #[doc = #doc_string]
+                #[allow(unused_variables)]
+                fn #method_name(
+                    &mut self,
+                    node: &::tree_sitter::Node<'_>,
+                    source: &str
+                    #param_decls
+                ) -> ::std::option::Option<::std::string::String> {
+                    ::std::option::Option::None
+                }
+            };
+            let match_arm = quote! { #kind => self.#method_name(node, source #param_args) };
+            (method, match_arm)
+        })
+        .unzip();
+
+    let doc_string = format!(
+        "A flavor of `{}` whose `rewrite_<kind>()` methods can replace the text of nodes, for \
+         codemods.",
+        item.ident
+    );
+
+    // With source maps, the rewritten source can also be recorded in a `SourceMapBuilder`.
+    let (apply_mapped_fn, rewrite_mapped_fn) = if options.source_map {
+        let apply_mapped_fn = quote! {
+            /// Applies non-overlapping edits, sorted by position, to `source` like `apply()`,
+            /// recording the rewritten source in a `SourceMapBuilder`. Each replacement is mapped
+            /// to the start of the node it replaces, and each line of kept text to where it starts
+            /// in the source.
+            pub fn apply_mapped<'source>(
+                source: &'source str,
+                edits: &[SourceEdit],
+            ) -> SourceMapBuilder<'source> {
+                fn push_kept(
+                    builder: &mut SourceMapBuilder<'_>,
+                    source: &str,
+                    range: ::std::ops::Range<usize>,
+                    mut position: ::tree_sitter::Point,
+                ) {
+                    let mut byte = range.start;
+                    for line in source[range].split_inclusive('\n') {
+                        builder.mark_at(byte, position);
+                        builder.push_str(line);
+                        byte += line.len();
+                        position = ::tree_sitter::Point::new(position.row + 1, 0);
+                    }
+                }
+
+                let mut builder = SourceMapBuilder::new(source);
+                let mut byte = 0;
+                let mut position = ::tree_sitter::Point::new(0, 0);
+                for edit in edits {
+                    push_kept(&mut builder, source, byte..edit.range.start, position);
+                    builder.mark_at(edit.range.start, edit.start_position);
+                    builder.push_str(&edit.replacement);
+                    byte = edit.range.end;
+                    position = edit.old_end_position;
+                }
+                push_kept(&mut builder, source, byte..source.len(), position);
+                builder
+            }
+        };
+        let rewrite_mapped_fn = quote! {
+            /// Rewrites the subtree rooted at `root` like `rewrite()`, returning a
+            /// `SourceMapBuilder` holding the rewritten source and its mappings to `source`.
+            fn rewrite_mapped<'source>(
+                &mut self,
+                root: ::tree_sitter::Node<'_>,
+                source: &'source str
+                #param_decls
+            ) -> SourceMapBuilder<'source> {
+                let edits = self.edits(root, source #param_args);
+                SourceEdit::apply_mapped(source, &edits)
+            }
+        };
+        (apply_mapped_fn, rewrite_mapped_fn)
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    quote! {
+        /// The replacement of the text of a node, with the positions needed to tell a tree about
+        /// it.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct SourceEdit {
+            /// The byte range of the replaced node.
+            pub range: ::std::ops::Range<usize>,
+            /// The position of the start of the replaced node.
+            pub start_position: ::tree_sitter::Point,
+            /// The position of the end of the replaced node.
+            pub old_end_position: ::tree_sitter::Point,
+            /// The replacement text.
+            pub replacement: ::std::string::String,
+        }
+
+        impl SourceEdit {
+            /// Creates an edit replacing the text of `node`.
+            pub fn new(node: &::tree_sitter::Node<'_>, replacement: ::std::string::String) -> Self {
+                Self {
+                    range: node.byte_range(),
+                    start_position: node.start_position(),
+                    old_end_position: node.end_position(),
+                    replacement,
+                }
+            }
+
+            /// Returns the edit for `Tree::edit()`, which is valid once the edits after this one
+            /// in the source have been applied, but not those before it.
+            pub fn input_edit(&self) -> ::tree_sitter::InputEdit {
+                let mut new_end_position = self.start_position;
+                match self.replacement.rfind('\n') {
+                    ::std::option::Option::Some(last_newline) => {
+                        new_end_position.row += self.replacement.matches('\n').count();
+                        new_end_position.column = self.replacement.len() - last_newline - 1;
+                    }
+                    ::std::option::Option::None => {
+                        new_end_position.column += self.replacement.len();
+                    }
+                }
+                ::tree_sitter::InputEdit {
+                    start_byte: self.range.start,
+                    old_end_byte: self.range.end,
+                    new_end_byte: self.range.start + self.replacement.len(),
+                    start_position: self.start_position,
+                    old_end_position: self.old_end_position,
+                    new_end_position,
+                }
+            }
+
+            /// Applies non-overlapping edits, sorted by position, to `source`. Returns the
+            /// rewritten source and the edits to give to `Tree::edit()`, in order, to reparse it
+            /// incrementally.
+            pub fn apply(
+                source: &str,
+                edits: &[SourceEdit],
+            ) -> (::std::string::String, ::std::vec::Vec<::tree_sitter::InputEdit>) {
+                let mut rewritten = source.to_string();
+                let mut input_edits = ::std::vec::Vec::with_capacity(edits.len());
+                // Applying the edits from the last one keeps the positions of the others valid.
+                for edit in edits.iter().rev() {
+                    rewritten.replace_range(edit.range.clone(), &edit.replacement);
+                    input_edits.push(edit.input_edit());
+                }
+                (rewritten, input_edits)
+            }
+
+            #apply_mapped_fn
+        }
+
+        #[doc = #doc_string]
+        #vis trait #trait_name #generics #colon #supertraits #where_clause {
+            #context_item
+
+            /// Returns the replacements of the nodes of the subtree rooted at `root`, in document
+            /// order. The children of replaced nodes are not rewritten, so the edits never
+            /// overlap.
+            fn edits(
+                &mut self,
+                root: ::tree_sitter::Node<'_>,
+                source: &str
+                #param_decls
+            ) -> ::std::vec::Vec<SourceEdit> {
+                let mut edits = ::std::vec::Vec::new();
+                let mut cursor = root.walk();
+                loop {
+                    let node = cursor.node();
+                    match self.rewrite_node(&node, source #param_args) {
+                        ::std::option::Option::Some(replacement) => {
+                            edits.push(SourceEdit::new(&node, replacement));
+                        }
+                        ::std::option::Option::None => {
+                            if cursor.goto_first_child() {
+                                continue;
+                            }
+                        }
+                    }
+                    loop {
+                        if cursor.goto_next_sibling() {
+                            break;
+                        }
+                        if !cursor.goto_parent() {
+                            return edits;
+                        }
+                    }
+                }
+            }
+
+            /// Rewrites the subtree rooted at `root`, returning the rewritten source and the edits
+            /// to give to `Tree::edit()`, in order, to reparse it incrementally.
+            fn rewrite(
+                &mut self,
+                root: ::tree_sitter::Node<'_>,
+                source: &str
+                #param_decls
+            ) -> (::std::string::String, ::std::vec::Vec<::tree_sitter::InputEdit>) {
+                let edits = self.edits(root, source #param_args);
+                SourceEdit::apply(source, &edits)
+            }
+
+            #rewrite_mapped_fn
+
+            /// Returns the text replacing a node of any type, if any. Nodes of kinds that are not
+            /// in `node-types.json` are kept.
+            fn rewrite_node(
+                &mut self,
+                node: &::tree_sitter::Node<'_>,
+                source: &str
+                #param_decls
+            ) -> ::std::option::Option<::std::string::String> {
+                match node.kind() {
+                    #(#match_arms,)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            #(#methods)*
+        }
+    }
+}
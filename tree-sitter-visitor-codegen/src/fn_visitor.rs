@@ -0,0 +1,175 @@
+//! Generation of `FnVisitor`, an implementation of the visitor trait assembled from closures.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::options::{HookSignature, Options};
+use crate::{
+    fresh_lifetime, fresh_type_param, supertrait_predicate, trait_params, trait_path,
+    trait_predicates, Node,
+};
+
+pub(crate) fn generate(
+    item: &ItemTrait,
+    node_types: &[Node],
+    options: &Options,
+    external_hook: bool,
+) -> TokenStream {
+    let vis = &item.vis;
+    let f = fresh_lifetime(item, "f");
+    let r = fresh_type_param(item, "R");
+    let return_type_predicate = options.return_type_predicate(&r);
+    let supertrait_predicate = supertrait_predicate(item);
+    let trait_params = trait_params(item);
+    let trait_predicates = trait_predicates(item);
+    let trait_ref = trait_path(item, quote! {});
+
+    let methods = node_types.iter().map(|node| {
+        let kind = &node.r#type;
+        let method_name = options.method_name(kind);
+        quote! {
+            fn #method_name(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
+                self.call(#kind, node)
+            }
+        }
+    });
+    // The hooks visiting nodes have handlers of their own, set with `hook()`. Without one, the
+    // field hooks visit the node as by default. The other hooks keep their default implementations.
+    let hooks: Vec<_> = options
+        .hooks(node_types, external_hook)
+        .into_iter()
+        .filter(|hook| {
+            matches!(
+                hook.signature,
+                HookSignature::Node | HookSignature::Token | HookSignature::Field
+            )
+        })
+        .collect();
+    let hook_methods = hooks.iter().map(|hook| {
+        let name = hook.name.to_string();
+        hook.forward(options, &quote! { Self::ReturnType }, |_| {
+            if hook.signature == HookSignature::Field {
+                quote! {
+                    match self.hooks.get_mut(#name) {
+                        ::std::option::Option::Some(handler) => handler(node),
+                        ::std::option::Option::None => self.visit(node),
+                    }
+                }
+            } else {
+                quote! { self.call_hook(#name, node) }
+            }
+        })
+    });
+    let call_hook = if hooks
+        .iter()
+        .all(|hook| hook.signature == HookSignature::Field)
+    {
+        quote! {}
+    } else {
+        quote! {
+            fn call_hook(&mut self, name: &str, node: &::tree_sitter::Node) -> R {
+                if let ::std::option::Option::Some(handler) = self.hooks.get_mut(name) {
+                    handler(node)
+                } else if let ::std::option::Option::Some(fallback) = &mut self.fallback {
+                    fallback(node)
+                } else {
+                    panic!("no handler for hook: {}", name)
+                }
+            }
+        }
+    };
+
+    quote! {
+        /// A visitor assembled from closures, one per node kind.
+        ///
+        /// Nodes of kinds without a handler, and hooks such as `visit_token()` without a handler,
+        /// are passed to the fallback handler, if any, and panic otherwise. The field hooks without
+        /// a handler visit the node with `visit()`, as by default.
+        #vis struct FnVisitor<'f, R> {
+            handlers: ::std::collections::HashMap<
+                &'static str,
+                ::std::boxed::Box<dyn ::std::ops::FnMut(&::tree_sitter::Node) -> R + 'f>,
+            >,
+            hooks: ::std::collections::HashMap<
+                &'static str,
+                ::std::boxed::Box<dyn ::std::ops::FnMut(&::tree_sitter::Node) -> R + 'f>,
+            >,
+            fallback: ::std::option::Option<
+                ::std::boxed::Box<dyn ::std::ops::FnMut(&::tree_sitter::Node) -> R + 'f>,
+            >,
+        }
+
+        impl<'f, R> FnVisitor<'f, R> {
+            /// Creates a visitor without any handlers.
+            pub fn new() -> Self {
+                Self {
+                    handlers: ::std::collections::HashMap::new(),
+                    hooks: ::std::collections::HashMap::new(),
+                    fallback: ::std::option::Option::None,
+                }
+            }
+
+            /// Sets the handler for nodes of the given kind.
+            pub fn on(
+                mut self,
+                kind: &'static str,
+                handler: impl ::std::ops::FnMut(&::tree_sitter::Node) -> R + 'f,
+            ) -> Self {
+                self.handlers.insert(kind, ::std::boxed::Box::new(handler));
+                self
+            }
+
+            /// Sets the handler of a hook of the trait, given by the name of its method, e.g.
+            /// `"visit_token"`.
+            pub fn hook(
+                mut self,
+                name: &'static str,
+                handler: impl ::std::ops::FnMut(&::tree_sitter::Node) -> R + 'f,
+            ) -> Self {
+                self.hooks.insert(name, ::std::boxed::Box::new(handler));
+                self
+            }
+
+            /// Sets the handler for nodes of kinds without a dedicated handler.
+            pub fn otherwise(
+                mut self,
+                handler: impl ::std::ops::FnMut(&::tree_sitter::Node) -> R + 'f,
+            ) -> Self {
+                self.fallback = ::std::option::Option::Some(::std::boxed::Box::new(handler));
+                self
+            }
+
+            fn call(&mut self, kind: &str, node: &::tree_sitter::Node) -> R {
+                if let ::std::option::Option::Some(handler) = self.handlers.get_mut(kind) {
+                    handler(node)
+                } else if let ::std::option::Option::Some(fallback) = &mut self.fallback {
+                    fallback(node)
+                } else {
+                    panic!("no handler for node kind: {}", kind)
+                }
+            }
+
+            #call_hook
+        }
+
+        impl<'f, R> ::std::default::Default for FnVisitor<'f, R> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<#f, #trait_params #r> #trait_ref for FnVisitor<#f, #r>
+        where
+            #return_type_predicate
+            #trait_predicates
+            #supertrait_predicate
+        {
+            type ReturnType = #r;
+
+            #(#methods)*
+
+            #(#hook_methods)*
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Generation of visitor traits as Rust source, for build scripts.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::LitStr;
+
+/// The trait and the options given to `generate()`.
+#[derive(Debug, Clone)]
+pub struct GenerateConfig {
+    item: String,
+    options: String,
+}
+
+impl GenerateConfig {
+    /// Creates a configuration for a trait declaration, as it would be written under
+    /// `#[visitor_trait]`, e.g. `pub trait CalcVisitor {}`, without options.
+    pub fn new(item: &str) -> Self {
+        GenerateConfig {
+            item: item.to_string(),
+            options: String::new(),
+        }
+    }
+
+    /// Sets the options, as they would be written after the path in `#[visitor_trait]`, e.g.
+    /// `named_only, walker` or `config = "visitor.toml"`.
+    pub fn options(mut self, options: &str) -> Self {
+        self.options = options.to_string();
+        self
+    }
+}
+
+/// Returns the Rust source of the visitor trait for the `node-types.json` at `path`, with the items
+/// of its options, as `#[visitor_trait(path, options...)]` would generate it for the trait of
+/// `config`. `path` can be empty if the options give it with `config` or `env`.
+///
+/// As in the macro, relative paths are resolved against the directory of the `Cargo.toml` of the
+/// crate being built, which in a build script is the crate the build script belongs to.
+pub fn generate(path: &str, config: &GenerateConfig) -> syn::Result<String> {
+    let options: TokenStream = syn::parse_str(&config.options)?;
+    let args = match (path.is_empty(), options.is_empty()) {
+        (true, _) => options,
+        (false, true) => {
+            let path = LitStr::new(path, proc_macro2::Span::call_site());
+            quote! { #path }
+        }
+        (false, false) => {
+            let path = LitStr::new(path, proc_macro2::Span::call_site());
+            quote! { #path, #options }
+        }
+    };
+    let item: TokenStream = syn::parse_str(&config.item)?;
+
+    let tokens = crate::visitor_trait(args, item);
+    let file: syn::File = syn::parse2(tokens)?;
+    // Errors are expanded to `compile_error!` invocations, as in the macro.
+    if let Some(message) = file.items.iter().find_map(compile_error) {
+        return Err(syn::Error::new(proc_macro2::Span::call_site(), message));
+    }
+    Ok(prettyplease::unparse(&file))
+}
+
+/// Returns the message of a `compile_error!` invocation.
+fn compile_error(item: &syn::Item) -> Option<String> {
+    match item {
+        syn::Item::Macro(item)
+            if item
+                .mac
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "compile_error") =>
+        {
+            item.mac
+                .parse_body::<LitStr>()
+                .ok()
+                .map(|message| message.value())
+        }
+        _ => None,
+    }
+}
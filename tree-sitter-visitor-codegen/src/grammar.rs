@@ -0,0 +1,166 @@
+//! The model of `grammar.json`, which describes the rules of a grammar, given with
+//! `grammar = "path/to/grammar.json"` to document precedence and aliases and to find external
+//! tokens, which `node-types.json` doesn't describe.
+//!
+//! Only the parts of the rules needed for that are modeled; unknown rule types and keys are
+//! ignored.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use syn::LitStr;
+
+use crate::load_json;
+use crate::node_types::Node;
+
+/// A grammar.
+#[derive(Deserialize)]
+pub(crate) struct Grammar {
+    /// The rules by name.
+    pub rules: BTreeMap<String, Rule>,
+    /// The tokens produced by the external scanner.
+    #[serde(default)]
+    pub externals: Vec<Rule>,
+}
+
+/// A rule, or a part of one.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum Rule {
+    Symbol {
+        name: String,
+    },
+    String {
+        value: String,
+    },
+    Alias {
+        content: Box<Rule>,
+        named: bool,
+        value: String,
+    },
+    Seq {
+        members: Vec<Rule>,
+    },
+    Choice {
+        members: Vec<Rule>,
+    },
+    Repeat {
+        content: Box<Rule>,
+    },
+    Repeat1 {
+        content: Box<Rule>,
+    },
+    Field {
+        content: Box<Rule>,
+    },
+    Token {
+        content: Box<Rule>,
+    },
+    ImmediateToken {
+        content: Box<Rule>,
+    },
+    Prec {
+        value: serde_json::Value,
+        content: Box<Rule>,
+    },
+    PrecLeft {
+        value: serde_json::Value,
+        content: Box<Rule>,
+    },
+    PrecRight {
+        value: serde_json::Value,
+        content: Box<Rule>,
+    },
+    PrecDynamic {
+        value: serde_json::Value,
+        content: Box<Rule>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl Rule {
+    /// Returns the rules directly contained in this one.
+    fn contents(&self) -> Vec<&Rule> {
+        match self {
+            Rule::Seq { members } | Rule::Choice { members } => members.iter().collect(),
+            Rule::Alias { content, .. }
+            | Rule::Repeat { content }
+            | Rule::Repeat1 { content }
+            | Rule::Field { content }
+            | Rule::Token { content }
+            | Rule::ImmediateToken { content }
+            | Rule::Prec { content, .. }
+            | Rule::PrecLeft { content, .. }
+            | Rule::PrecRight { content, .. }
+            | Rule::PrecDynamic { content, .. } => vec![content],
+            Rule::Symbol { .. } | Rule::String { .. } | Rule::Other => vec![],
+        }
+    }
+
+    /// Returns how the rule is written in the grammar, for a symbol or a string.
+    fn describe(&self) -> Option<String> {
+        match self {
+            Rule::Symbol { name } => Some(format!("`{}`", name)),
+            Rule::String { value } => Some(format!("`{:?}`", value)),
+            _ => None,
+        }
+    }
+}
+
+impl Grammar {
+    /// Loads `grammar.json` from a path resolved with `resolve_path()`.
+    pub fn load(path: &LitStr) -> syn::Result<Self> {
+        load_json(&path.value(), path.span())
+    }
+
+    /// Returns the precedence of the rule of a named node kind, if the rule is wrapped in a
+    /// precedence, described for documentation, e.g. "`1`, left-associative".
+    pub fn precedence(&self, kind: &str) -> Option<String> {
+        let (value, associativity) = match self.rules.get(kind)? {
+            Rule::Prec { value, .. } => (value, ""),
+            Rule::PrecLeft { value, .. } => (value, ", left-associative"),
+            Rule::PrecRight { value, .. } => (value, ", right-associative"),
+            Rule::PrecDynamic { value, .. } => (value, ", dynamic"),
+            _ => return None,
+        };
+        Some(format!("`{}`{}", value, associativity))
+    }
+
+    /// Returns the rules aliased to the node kind, described for documentation.
+    pub fn aliases_of(&self, kind: &str, named: bool) -> Vec<String> {
+        let mut aliases = Vec::new();
+        let mut pending: Vec<&Rule> = self.rules.values().collect();
+        while let Some(rule) = pending.pop() {
+            if let Rule::Alias {
+                content,
+                named: alias_named,
+                value,
+            } = rule
+            {
+                if value == kind && *alias_named == named {
+                    aliases.extend(content.describe());
+                }
+            }
+            pending.extend(rule.contents());
+        }
+        aliases.sort();
+        aliases.dedup();
+        aliases
+    }
+
+    /// Returns whether any of the node types is produced by the external scanner.
+    pub fn has_externals(&self, node_types: &[Node]) -> bool {
+        node_types
+            .iter()
+            .any(|node| self.is_external(&node.r#type, node.named))
+    }
+
+    /// Returns whether a node kind is produced by the external scanner.
+    pub fn is_external(&self, kind: &str, named: bool) -> bool {
+        self.externals.iter().any(|external| match external {
+            Rule::Symbol { name } => named && name == kind,
+            Rule::String { value } => !named && value == kind,
+            _ => false,
+        })
+    }
+}
@@ -0,0 +1,225 @@
+//! Generation of `HtmlRenderer`, which renders highlighted source to HTML.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    let kinds = node_types
+        .iter()
+        .filter(|node| !node.is_supertype())
+        .map(|node| &node.r#type);
+
+    quote! {
+        /// Renders source to HTML, wrapping highlighted ranges in `<span class="...">` elements.
+        ///
+        /// Ranges are highlighted either by node kind, with the classes given to `class()`, or by the
+        /// captures of a highlights query, with `capture_spans()`. Spans of several layers, e.g. of
+        /// the trees of injected languages parsed over ranges of the same source, can be combined
+        /// with `render_spans()`.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct HtmlRenderer {
+            classes: ::std::collections::BTreeMap<&'static str, ::std::string::String>,
+            error_class: ::std::option::Option<::std::string::String>,
+        }
+
+        impl ::std::default::Default for HtmlRenderer {
+            fn default() -> Self {
+                Self {
+                    classes: ::std::collections::BTreeMap::new(),
+                    error_class: ::std::option::Option::Some("error".into()),
+                }
+            }
+        }
+
+        impl HtmlRenderer {
+            /// The node kinds that can be given a class.
+            pub const KINDS: &'static [&'static str] = &[#(#kinds),*];
+
+            /// Creates a renderer highlighting only `ERROR` nodes, with the class `error`.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Highlights nodes of the given kind with a CSS class. Panics if the kind is not declared
+            /// by the grammar.
+            pub fn class(mut self, kind: &str, class: impl ::std::convert::Into<::std::string::String>) -> Self {
+                let kind = Self::KINDS
+                    .iter()
+                    .find(|known| **known == kind)
+                    .unwrap_or_else(|| panic!("unknown node kind `{}`", kind));
+                self.classes.insert(kind, class.into());
+                self
+            }
+
+            /// Sets the class of `ERROR` nodes, or stops highlighting them.
+            pub fn error_class(mut self, class: ::std::option::Option<::std::string::String>) -> Self {
+                self.error_class = class;
+                self
+            }
+
+            /// Returns the highlighted ranges of a tree with their classes, outer nodes first.
+            pub fn spans(
+                &self,
+                tree: &::tree_sitter::Tree,
+            ) -> ::std::vec::Vec<(::std::ops::Range<usize>, ::std::string::String)> {
+                let mut spans = ::std::vec::Vec::new();
+                let mut cursor = tree.walk();
+                loop {
+                    let node = cursor.node();
+                    let class = if node.is_error() {
+                        self.error_class.as_ref()
+                    } else {
+                        self.classes.get(node.kind())
+                    };
+                    if let ::std::option::Option::Some(class) = class {
+                        spans.push((node.byte_range(), class.clone()));
+                    }
+
+                    if cursor.goto_first_child() || cursor.goto_next_sibling() {
+                        continue;
+                    }
+                    loop {
+                        if !cursor.goto_parent() {
+                            return spans;
+                        }
+                        if cursor.goto_next_sibling() {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            /// Returns the ranges captured by a highlights query, with the capture names as classes
+            /// and dots replaced by spaces (`@function.builtin` becomes `function builtin`). A range
+            /// captured more than once keeps only its first capture.
+            pub fn capture_spans(
+                query: &::tree_sitter::Query,
+                tree: &::tree_sitter::Tree,
+                source: &(impl TextProvider + ?Sized),
+            ) -> ::std::vec::Vec<(::std::ops::Range<usize>, ::std::string::String)> {
+                let capture_names = query.capture_names();
+                let mut cursor = ::tree_sitter::QueryCursor::new();
+                let mut spans: ::std::vec::Vec<(::std::ops::Range<usize>, ::std::string::String)> =
+                    ::std::vec::Vec::new();
+                let mut seen: ::std::collections::HashSet<::std::ops::Range<usize>> =
+                    ::std::collections::HashSet::new();
+                // The text of a node, chunk by chunk, for the predicates of the query.
+                let node_chunks = |node: ::tree_sitter::Node| {
+                    let end = node.end_byte();
+                    let mut byte = node.start_byte();
+                    ::std::iter::from_fn(move || {
+                        let chunk = source.chunk_at(byte).get(..end.saturating_sub(byte))?;
+                        if chunk.is_empty() {
+                            return ::std::option::Option::None;
+                        }
+                        byte += chunk.len();
+                        ::std::option::Option::Some(chunk)
+                    })
+                };
+                for (query_match, capture_index) in cursor.captures(query, tree.root_node(), node_chunks) {
+                    let capture = query_match.captures[capture_index];
+                    let range = capture.node.byte_range();
+                    if !seen.insert(range.clone()) {
+                        continue;
+                    }
+                    let class = capture_names[capture.index as usize].replace('.', " ");
+                    spans.push((range, class));
+                }
+                spans
+            }
+
+            /// Renders the source of a tree, highlighting the nodes of the kinds given a class.
+            pub fn render(
+                &self,
+                tree: &::tree_sitter::Tree,
+                source: &(impl TextProvider + ?Sized),
+            ) -> ::std::string::String {
+                Self::render_spans(source, ::std::iter::once(self.spans(tree)))
+            }
+
+            /// Renders source with the spans of several layers, e.g. of a tree and of the trees of
+            /// the languages injected into it. Spans of later layers are nested inside the spans of
+            /// earlier ones with the same range, and spans that overlap without nesting are closed
+            /// and reopened, so that the elements are always well-formed.
+            pub fn render_spans<L>(source: &(impl TextProvider + ?Sized), layers: L) -> ::std::string::String
+            where
+                L: ::std::iter::IntoIterator,
+                L::Item: ::std::iter::IntoIterator<Item = (::std::ops::Range<usize>, ::std::string::String)>,
+            {
+                fn escape(html: &mut ::std::string::String, text: &str) {
+                    for c in text.chars() {
+                        match c {
+                            '&' => html.push_str("&amp;"),
+                            '<' => html.push_str("&lt;"),
+                            '>' => html.push_str("&gt;"),
+                            '"' => html.push_str("&quot;"),
+                            '\'' => html.push_str("&#39;"),
+                            c => html.push(c),
+                        }
+                    }
+                }
+                fn open(html: &mut ::std::string::String, class: &str) {
+                    html.push_str("<span class=\"");
+                    escape(html, class);
+                    html.push_str("\">");
+                }
+
+                let source = source.full_text();
+                let source: &str = &source;
+                let mut spans: ::std::vec::Vec<_> = layers
+                    .into_iter()
+                    .flatten()
+                    .enumerate()
+                    .filter(|(_, (range, _))| range.start < range.end && range.end <= source.len())
+                    .collect();
+                spans.sort_by_key(|(index, (range, _))| {
+                    (range.start, ::std::cmp::Reverse(range.end), *index)
+                });
+
+                let mut html = ::std::string::String::with_capacity(source.len());
+                // The open spans, innermost last, with their ends.
+                let mut stack: ::std::vec::Vec<(usize, ::std::string::String)> = ::std::vec::Vec::new();
+                let mut spans = spans.into_iter().map(|(_, span)| span).peekable();
+                let mut position = 0;
+                loop {
+                    let next_start = spans.peek().map(|(range, _)| range.start);
+                    let next_end = stack.iter().map(|(end, _)| *end).min();
+                    let boundary = match (next_start, next_end) {
+                        (::std::option::Option::Some(start), ::std::option::Option::Some(end)) => start.min(end),
+                        (::std::option::Option::Some(boundary), ::std::option::Option::None)
+                        | (::std::option::Option::None, ::std::option::Option::Some(boundary)) => boundary,
+                        (::std::option::Option::None, ::std::option::Option::None) => break,
+                    };
+                    escape(&mut html, &source[position..boundary]);
+                    position = boundary;
+
+                    // Closes the spans ending here, together with the spans opened after them, which
+                    // are reopened if they continue.
+                    if let ::std::option::Option::Some(first) = stack.iter().position(|(end, _)| *end <= boundary) {
+                        let closed = stack.split_off(first);
+                        for _ in &closed {
+                            html.push_str("</span>");
+                        }
+                        for (end, class) in closed.into_iter().filter(|(end, _)| *end > boundary) {
+                            open(&mut html, &class);
+                            stack.push((end, class));
+                        }
+                    }
+
+                    while let ::std::option::Option::Some((range, class)) =
+                        spans.next_if(|(range, _)| range.start == boundary)
+                    {
+                        open(&mut html, &class);
+                        stack.push((range.end, class));
+                    }
+                }
+                escape(&mut html, &source[position..]);
+                html
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Generation of `SymbolTableInterner`, which interns the text of nodes into `Symbol`s that can be
+//! compared and hashed cheaply.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+use crate::prefixed_method_name;
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+
+    // Named leaves, such as identifiers and literals, are the nodes whose text is worth interning.
+    let kind_methods = node_types
+        .iter()
+        .filter(|node| node.named && node.is_terminal())
+        .map(|node| {
+            let kind = &node.r#type;
+            let method_name = prefixed_method_name("intern", kind);
+            let doc_string = format!(
+                "Interns the text of a node of type `{:?}`. Panics in debug builds for nodes of other types.",
+                kind
+            );
+            quote! {
+                #[doc = #doc_string]
+                pub fn #method_name(
+                    &mut self,
+                    node: &::tree_sitter::Node,
+                    source: &(impl TextProvider + ?Sized),
+                ) -> Symbol {
+                    debug_assert_eq!(node.kind(), #kind, "unexpected node kind");
+                    self.intern_node(node, source)
+                }
+            }
+        });
+
+    quote! {
+        /// An interned string, which is equal to another symbol of the same interner exactly when
+        /// their strings are equal.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #vis struct Symbol(u32);
+
+        impl Symbol {
+            /// Returns the index of the symbol in the order the strings were first interned.
+            pub fn index(self) -> usize {
+                self.0 as usize
+            }
+        }
+
+        /// Interns the text of nodes, e.g. identifiers, into `Symbol`s, so that analyses comparing
+        /// many names compare and hash integers instead of strings.
+        #[derive(Debug, Clone, Default)]
+        #vis struct SymbolTableInterner {
+            symbols: ::std::collections::HashMap<::std::sync::Arc<str>, Symbol>,
+            strings: ::std::vec::Vec<::std::sync::Arc<str>>,
+        }
+
+        impl SymbolTableInterner {
+            /// Creates an empty interner.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Interns a string, returning the same symbol for equal strings.
+            pub fn intern(&mut self, text: &str) -> Symbol {
+                if let ::std::option::Option::Some(symbol) = self.symbols.get(text) {
+                    return *symbol;
+                }
+                let symbol = Symbol(
+                    ::std::convert::TryFrom::try_from(self.strings.len()).expect("too many symbols"),
+                );
+                let text: ::std::sync::Arc<str> = text.into();
+                self.strings.push(text.clone());
+                self.symbols.insert(text, symbol);
+                symbol
+            }
+
+            /// Interns the text of a node, with `source` being the text it was parsed from. Invalid
+            /// UTF-8 in byte sources is replaced with `U+FFFD`.
+            pub fn intern_node(
+                &mut self,
+                node: &::tree_sitter::Node,
+                source: &(impl TextProvider + ?Sized),
+            ) -> Symbol {
+                self.intern(&source.node_text(node))
+            }
+
+            /// Returns the symbol of a string, if it has been interned.
+            pub fn get(&self, text: &str) -> ::std::option::Option<Symbol> {
+                self.symbols.get(text).copied()
+            }
+
+            /// Returns the string of a symbol of this interner.
+            pub fn resolve(&self, symbol: Symbol) -> &str {
+                &self.strings[symbol.index()]
+            }
+
+            /// Returns the number of distinct strings interned.
+            pub fn len(&self) -> usize {
+                self.strings.len()
+            }
+
+            /// Returns `true` if no string has been interned.
+            pub fn is_empty(&self) -> bool {
+                self.strings.is_empty()
+            }
+
+            #(#kind_methods)*
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Generation of the `grammar` module, exposing the node types model as static data.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::node_types::{ChildTypes, Node, NodeRef};
+
+fn kind_ref(node_ref: &NodeRef) -> TokenStream {
+    let kind = &node_ref.r#type;
+    let named = node_ref.named;
+    quote! {
+        KindRef { kind: #kind, named: #named }
+    }
+}
+
+fn child_info(child_types: &ChildTypes) -> TokenStream {
+    let multiple = child_types.multiple;
+    let required = child_types.required;
+    let types = child_types.types.iter().map(kind_ref);
+    quote! {
+        ChildInfo {
+            multiple: #multiple,
+            required: #required,
+            types: &[#(#types),*],
+        }
+    }
+}
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+
+    let infos = node_types.iter().map(|node| {
+        let kind = &node.r#type;
+        let named = node.named;
+        let root = node.root;
+        let extra = node.extra;
+        let subtypes = node.subtypes.iter().map(kind_ref);
+        let fields = node.fields.iter().map(|(name, child_types)| {
+            let info = child_info(child_types);
+            quote! { (#name, #info) }
+        });
+        let children = match &node.children {
+            Some(child_types) => {
+                let info = child_info(child_types);
+                quote! { ::std::option::Option::Some(#info) }
+            }
+            None => quote! { ::std::option::Option::None },
+        };
+
+        quote! {
+            NodeTypeInfo {
+                kind: #kind,
+                named: #named,
+                root: #root,
+                extra: #extra,
+                subtypes: &[#(#subtypes),*],
+                fields: &[#(#fields),*],
+                children: #children,
+            }
+        }
+    });
+
+    quote! {
+        /// The node types of the grammar, as described by `node-types.json`.
+        #vis mod grammar {
+            /// A reference to a node type by its kind and whether it is named.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub struct KindRef {
+                pub kind: &'static str,
+                pub named: bool,
+            }
+
+            /// The kinds of nodes that can appear in a field or as children of a node.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct ChildInfo {
+                /// Whether more than one node can appear.
+                pub multiple: bool,
+                /// Whether at least one node always appears.
+                pub required: bool,
+                /// The node types that can appear.
+                pub types: &'static [KindRef],
+            }
+
+            /// A node type of the grammar.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct NodeTypeInfo {
+                pub kind: &'static str,
+                pub named: bool,
+                /// Whether the node is the root of the grammar. Only known for grammars generated by
+                /// newer versions of tree-sitter, `false` otherwise.
+                pub root: bool,
+                /// Whether the node can appear anywhere in the tree, like comments.
+                pub extra: bool,
+                /// The subtypes of a supertype, empty for other node types.
+                pub subtypes: &'static [KindRef],
+                /// The fields of the node type, by name.
+                pub fields: &'static [(&'static str, ChildInfo)],
+                /// The children of the node type that are not in any field.
+                pub children: ::std::option::Option<ChildInfo>,
+            }
+
+            impl NodeTypeInfo {
+                /// Returns `true` if this node type is a supertype.
+                pub fn is_supertype(&self) -> bool {
+                    !self.subtypes.is_empty()
+                }
+
+                /// Returns the field with the given name.
+                pub fn field(&self, name: &str) -> ::std::option::Option<&'static ChildInfo> {
+                    self.fields
+                        .iter()
+                        .find(|(field_name, _)| *field_name == name)
+                        .map(|(_, info)| info)
+                }
+            }
+
+            /// All the node types of the grammar.
+            pub const NODE_TYPES: &[NodeTypeInfo] = &[#(#infos),*];
+
+            /// Returns the node type with the given kind.
+            pub fn node_type(kind: &str, named: bool) -> ::std::option::Option<&'static NodeTypeInfo> {
+                NODE_TYPES
+                    .iter()
+                    .find(|info| info.kind == kind && info.named == named)
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Generation of `KindFrequencies`, which counts the node kinds appearing in a corpus and compares
+//! them with the kinds declared by the grammar.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    // Supertypes never appear in trees.
+    let mut declared: Vec<&str> = Vec::new();
+    for node in node_types.iter().filter(|node| !node.is_supertype()) {
+        if !declared.contains(&node.r#type.as_str()) {
+            declared.push(&node.r#type);
+        }
+    }
+
+    quote! {
+        /// The occurrences of a node kind in a corpus.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct KindStats<F> {
+            /// The node kind.
+            pub kind: &'static str,
+            /// The number of nodes of the kind.
+            pub count: usize,
+            /// The files containing nodes of the kind.
+            pub files: ::std::collections::BTreeSet<F>,
+        }
+
+        /// Counts how often each node kind appears in a corpus and in which files, e.g. to prioritize
+        /// the handlers to implement and to find the ones that are never needed.
+        ///
+        /// Its `Display` implementation prints a report listing the kinds by descending frequency,
+        /// followed by the kinds declared by the grammar that were never observed.
+        #[derive(Debug, Clone)]
+        #vis struct KindFrequencies<F> {
+            stats: ::std::collections::BTreeMap<&'static str, KindStats<F>>,
+        }
+
+        impl<F> ::std::default::Default for KindFrequencies<F> {
+            fn default() -> Self {
+                Self {
+                    stats: ::std::collections::BTreeMap::new(),
+                }
+            }
+        }
+
+        impl<F: ::std::cmp::Ord + ::std::clone::Clone> KindFrequencies<F> {
+            /// The node kinds declared by the grammar that can appear in a tree, i.e. all but the
+            /// supertypes.
+            pub const DECLARED: &'static [&'static str] = &[#(#declared),*];
+
+            /// Creates an empty analysis.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Counts the nodes of a tree parsed from the given file.
+            pub fn add(&mut self, file: F, tree: &::tree_sitter::Tree) {
+                let mut cursor = tree.walk();
+                loop {
+                    let kind = cursor.node().kind();
+                    let stats = self.stats.entry(kind).or_insert_with(|| KindStats {
+                        kind,
+                        count: 0,
+                        files: ::std::collections::BTreeSet::new(),
+                    });
+                    stats.count += 1;
+                    if !stats.files.contains(&file) {
+                        stats.files.insert(file.clone());
+                    }
+
+                    if cursor.goto_first_child() || cursor.goto_next_sibling() {
+                        continue;
+                    }
+                    loop {
+                        if !cursor.goto_parent() {
+                            return;
+                        }
+                        if cursor.goto_next_sibling() {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            /// Returns the number of nodes of the given kind.
+            pub fn count(&self, kind: &str) -> usize {
+                self.stats.get(kind).map_or(0, |stats| stats.count)
+            }
+
+            /// Returns the files containing nodes of the given kind, in ascending order.
+            pub fn files(&self, kind: &str) -> impl ::std::iter::Iterator<Item = &F> {
+                self.stats.get(kind).into_iter().flat_map(|stats| stats.files.iter())
+            }
+
+            /// Returns the statistics of the observed kinds by descending count.
+            pub fn stats(&self) -> ::std::vec::Vec<&KindStats<F>> {
+                let mut stats: ::std::vec::Vec<_> = self.stats.values().collect();
+                stats.sort_by(|a, b| b.count.cmp(&a.count).then(a.kind.cmp(b.kind)));
+                stats
+            }
+
+            /// Returns the kinds declared by the grammar that were never observed.
+            pub fn unobserved(&self) -> ::std::vec::Vec<&'static str> {
+                Self::DECLARED
+                    .iter()
+                    .filter(|kind| !self.stats.contains_key(*kind))
+                    .copied()
+                    .collect()
+            }
+
+            /// Returns the observed kinds that are not declared by the grammar, such as `ERROR`.
+            pub fn undeclared(&self) -> ::std::vec::Vec<&'static str> {
+                self.stats
+                    .keys()
+                    .filter(|kind| !Self::DECLARED.contains(kind))
+                    .copied()
+                    .collect()
+            }
+
+            /// Returns the counts as a JSON object mapping node kinds to the number of nodes, which
+            /// can be passed to the `profile` option to order the dispatch by frequency.
+            pub fn profile(&self) -> ::std::string::String {
+                let entries: ::std::vec::Vec<_> = self
+                    .stats
+                    .values()
+                    .map(|stats| {
+                        let escaped = stats.kind.replace('\\', "\\\\").replace('"', "\\\"");
+                        ::std::format!("\"{}\": {}", escaped, stats.count)
+                    })
+                    .collect();
+                ::std::format!("{{{}}}", entries.join(", "))
+            }
+        }
+
+        impl<F: ::std::cmp::Ord + ::std::clone::Clone> ::std::fmt::Display for KindFrequencies<F> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                for stats in self.stats() {
+                    writeln!(
+                        f,
+                        "{:>10}  {:?} in {} file(s)",
+                        stats.count,
+                        stats.kind,
+                        stats.files.len()
+                    )?;
+                }
+                let unobserved = self.unobserved();
+                if !unobserved.is_empty() {
+                    writeln!(f, "never observed: {:?}", unobserved)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Generation of `KindTable`, which maps the numeric kind ids of a `Language` to the node kinds of
+//! the visitor trait, and of the `visit_by_id()` dispatch using it.
+//!
+//! The table is resolved at runtime from the `Language` itself, so nothing depends on the kind ids
+//! assigned when the parser was generated and languages loaded at runtime are supported as well.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, ItemTrait, TraitItem};
+
+use crate::node_types::Node;
+use crate::options::Options;
+
+pub(crate) fn trait_items(node_types: &[Node], options: &Options) -> Vec<TraitItem> {
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let match_arms = node_types.iter().enumerate().filter_map(|(index, node)| {
+        // Methods with a dedicated return type cannot be dispatched to.
+        if options.return_type(&node.r#type).is_some() {
+            return None;
+        }
+        let method_name = options.method_name(&node.r#type);
+        let call = options.dispatched(quote! { self.#method_name(node #param_args) });
+        Some(quote! {
+            ::std::option::Option::Some(#index) => #call
+        })
+    });
+
+    let unknown_arm = options.unknown_arm("unknown node kind: {}");
+    let result_type = options.result_type(options.self_return_type());
+    let asyncness = options.asyncness();
+
+    vec![parse_quote! {
+        #[doc=r"Visits a node of any type, dispatching on its numeric kind id through a `KindTable`
+        resolved for the node's language."]
+        #asyncness fn visit_by_id(&mut self, table: &KindTable, node: &::tree_sitter::Node<'_> #param_decls) -> #result_type {
+            match table.index(node.kind_id()) {
+                #(#match_arms,)*
+                #unknown_arm
+            }
+        }
+    }]
+}
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = &item.ident;
+    let kinds = node_types.iter().map(|node| &node.r#type);
+    let named = node_types.iter().map(|node| node.named);
+    let doc_string = format!(
+        "Maps the kind ids of a particular `Language` to the node kinds handled by `{}`.",
+        trait_name
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #[derive(Debug, Clone)]
+        #vis struct KindTable {
+            language: ::tree_sitter::Language,
+            indices: ::std::vec::Vec<::std::option::Option<usize>>,
+        }
+
+        impl KindTable {
+            /// The node kinds handled by the visitor, as `(kind, named)` pairs.
+            pub const KINDS: &'static [(&'static str, bool)] = &[#((#kinds, #named)),*];
+
+            /// Resolves the kind ids of the given language.
+            pub fn new(language: ::tree_sitter::Language) -> Self {
+                let indices = (0..language.node_kind_count())
+                    .map(|id| {
+                        let id = id as u16;
+                        let kind = language.node_kind_for_id(id)?;
+                        let named = language.node_kind_is_named(id);
+                        Self::KINDS.iter().position(|&entry| entry == (kind, named))
+                    })
+                    .collect();
+
+                Self { language, indices }
+            }
+
+            /// Returns the table for the given language, resolving it on first use and caching it
+            /// for subsequent calls.
+            pub fn for_language(language: ::tree_sitter::Language) -> ::std::sync::Arc<Self> {
+                static TABLES: ::std::sync::OnceLock<
+                    ::std::sync::Mutex<
+                        ::std::collections::HashMap<::tree_sitter::Language, ::std::sync::Arc<KindTable>>,
+                    >,
+                > = ::std::sync::OnceLock::new();
+
+                let mut tables = TABLES
+                    .get_or_init(::std::default::Default::default)
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                tables
+                    .entry(language.clone())
+                    .or_insert_with(|| ::std::sync::Arc::new(Self::new(language)))
+                    .clone()
+            }
+
+            /// Returns the index into `KINDS` of the kind of a node, if the visitor handles it, using
+            /// the table of the node's language. The table last used is cached per thread, so this
+            /// is cheap as long as consecutive nodes are of the same language.
+            pub fn index_of(node: &::tree_sitter::Node) -> ::std::option::Option<usize> {
+                ::std::thread_local! {
+                    static CURRENT: ::std::cell::RefCell<::std::option::Option<::std::sync::Arc<KindTable>>> =
+                        ::std::cell::RefCell::new(::std::option::Option::None);
+                }
+
+                let language = node.language();
+                CURRENT.with(|current| {
+                    let mut current = current.borrow_mut();
+                    let table = match &*current {
+                        ::std::option::Option::Some(table) if table.language == *language => table,
+                        _ => current.insert(Self::for_language(language.clone())),
+                    };
+                    table.index(node.kind_id())
+                })
+            }
+
+            /// Returns the language this table was resolved for.
+            pub fn language(&self) -> ::tree_sitter::Language {
+                self.language.clone()
+            }
+
+            /// Returns the index into `KINDS` of the given kind id, if the visitor handles it.
+            pub fn index(&self, kind_id: u16) -> ::std::option::Option<usize> {
+                self.indices.get(kind_id as usize).copied().flatten()
+            }
+
+            /// Returns the node kinds handled by the visitor that do not exist in the language.
+            pub fn missing_kinds(&self) -> ::std::vec::Vec<&'static str> {
+                Self::KINDS
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| !self.indices.contains(&::std::option::Option::Some(*index)))
+                    .map(|(_, (kind, _))| *kind)
+                    .collect()
+            }
+
+            /// Returns the visible node kinds of the language that the visitor does not handle, sorted
+            /// and without duplicates, as several kind ids can share a name.
+            pub fn unexpected_kinds(&self) -> ::std::vec::Vec<&'static str> {
+                let mut kinds: ::std::vec::Vec<_> = self
+                    .indices
+                    .iter()
+                    .enumerate()
+                    .filter(|(id, index)| index.is_none() && self.language.node_kind_is_visible(*id as u16))
+                    .filter_map(|(id, _)| self.language.node_kind_for_id(id as u16))
+                    .collect();
+                kinds.sort_unstable();
+                kinds.dedup();
+                kinds
+            }
+
+            /// Checks that the node kinds of the language and of the visitor match exactly.
+            pub fn validate(&self) -> ::std::result::Result<(), KindMismatch> {
+                let mismatch = KindMismatch {
+                    missing: self.missing_kinds(),
+                    unexpected: self.unexpected_kinds(),
+                };
+                if mismatch.missing.is_empty() && mismatch.unexpected.is_empty() {
+                    Ok(())
+                } else {
+                    Err(mismatch)
+                }
+            }
+
+            /// Like `for_language()`, but fails if the node kinds of the language and of the visitor
+            /// do not match.
+            pub fn try_for_language(
+                language: ::tree_sitter::Language,
+            ) -> ::std::result::Result<::std::sync::Arc<Self>, KindMismatch> {
+                let table = Self::for_language(language);
+                table.validate()?;
+                Ok(table)
+            }
+        }
+
+        /// The differences between the node kinds of a language and of the visitor.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis struct KindMismatch {
+            /// Node kinds handled by the visitor that do not exist in the language.
+            pub missing: ::std::vec::Vec<&'static str>,
+            /// Visible node kinds of the language that the visitor does not handle.
+            pub unexpected: ::std::vec::Vec<&'static str>,
+        }
+
+        impl ::std::fmt::Display for KindMismatch {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(
+                    f,
+                    "node kinds do not match the language (missing: {:?}, unexpected: {:?})",
+                    self.missing, self.unexpected
+                )
+            }
+        }
+
+        impl ::std::error::Error for KindMismatch {}
+    }
+}
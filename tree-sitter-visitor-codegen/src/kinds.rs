@@ -0,0 +1,117 @@
+//! Generation of the `kinds` module, with a constant for the string of each node kind of the
+//! visitor trait, so code building queries or reading configuration doesn't hardcode them.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Ident, ItemTrait};
+
+use crate::node_types::Node;
+use crate::sanitize_identifier;
+
+/// Returns the names of the constants, in the order of `node_types`. Anonymous kinds whose name
+/// clashes with that of another kind, such as a keyword named like a rule, get a `_TOKEN` suffix.
+fn constant_names(node_types: &[Node]) -> Vec<Ident> {
+    let base_name = |node: &Node| {
+        let sanitized = sanitize_identifier(&node.r#type).to_ascii_uppercase();
+        if sanitized.chars().all(|c| c == '_') {
+            // Kinds made of underscores only, like the `_` wildcard of many grammars.
+            vec!["UNDERSCORE"; sanitized.len()].join("_")
+        } else if sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("KIND_{}", sanitized)
+        } else {
+            sanitized
+        }
+    };
+    let mut taken: Vec<String> = node_types
+        .iter()
+        .filter(|node| node.named)
+        .map(base_name)
+        .chain(["ALL_KINDS".to_string(), "NAMED_KINDS".to_string()])
+        .collect();
+    node_types
+        .iter()
+        .map(|node| {
+            let name = base_name(node);
+            if node.named {
+                return format_ident!("{}", name);
+            }
+            let mut unique = name.clone();
+            let mut suffix = 1;
+            while taken.contains(&unique) {
+                unique = if suffix == 1 {
+                    format!("{}_TOKEN", name)
+                } else {
+                    format!("{}_TOKEN{}", name, suffix)
+                };
+                suffix += 1;
+            }
+            taken.push(unique.clone());
+            format_ident!("{}", unique)
+        })
+        .collect()
+}
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    let constants = constant_names(node_types);
+    let kinds: Vec<_> = node_types.iter().map(|node| &node.r#type).collect();
+    let named: Vec<_> = node_types.iter().map(|node| node.named).collect();
+    let named_constants = constants
+        .iter()
+        .zip(node_types)
+        .filter(|(_, node)| node.named)
+        .map(|(constant, _)| constant);
+    let docs = node_types.iter().map(|node| {
+        let name = format!("{:?}", node.r#type).replace('`', "\\`");
+        if node.named {
+            format!("The kind of the named nodes `{}`.", name)
+        } else {
+            format!("The kind of the anonymous nodes `{}`.", name)
+        }
+    });
+
+    let doc_string = format!(
+        "The node kinds handled by `{}`, as returned by `Node::kind()`.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #vis mod kinds {
+            #(
+                #[doc = #docs]
+                pub const #constants: &str = #kinds;
+            )*
+
+            /// All the node kinds, in the order of `node-types.json`. Kinds of both named and
+            /// anonymous nodes appear twice.
+            pub const ALL_KINDS: &[&str] = &[#(#constants),*];
+
+            /// The kinds of the named nodes, in the order of `node-types.json`.
+            pub const NAMED_KINDS: &[&str] = &[#(#named_constants),*];
+
+            /// Returns the id of a node kind in the given language, as returned by
+            /// `Node::kind_id()`, or `None` if the language has no such kind.
+            pub fn id(
+                language: &::tree_sitter::Language,
+                kind: &str,
+                named: bool,
+            ) -> ::std::option::Option<u16> {
+                match language.id_for_node_kind(kind, named) {
+                    0 => ::std::option::Option::None,
+                    id => ::std::option::Option::Some(id),
+                }
+            }
+
+            /// Returns the ids of `ALL_KINDS` in the given language, in the same order.
+            pub fn ids(
+                language: &::tree_sitter::Language,
+            ) -> ::std::vec::Vec<::std::option::Option<u16>> {
+                [#((#constants, #named)),*]
+                    .iter()
+                    .map(|&(kind, named)| id(language, kind, named))
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Generation of `check_language()` and `assert_language_compatible()`, which compare the node
+//! kinds of the visitor trait with those of a `Language`, catching a parser regenerated from a
+//! changed grammar without regenerating `node-types.json`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = &item.ident;
+    let kinds = node_types.iter().map(|node| &node.r#type);
+    let named = node_types.iter().map(|node| node.named);
+    let report_doc = format!(
+        "The differences between the node kinds of `{}` and of a `Language`, as `(kind, named)` pairs.",
+        trait_name
+    );
+    let check_doc = format!(
+        "Compares the node kinds of `{}`, including those left out of it by `named_only`, `include` or `exclude`, with the visible node kinds of the given language.",
+        trait_name
+    );
+
+    quote! {
+        #[doc = #report_doc]
+        #[derive(Debug, Clone, Default, PartialEq, Eq)]
+        #vis struct LanguageReport {
+            /// Node kinds of the visitor that do not exist in the language.
+            pub missing: ::std::vec::Vec<(&'static str, bool)>,
+            /// Visible node kinds of the language that the visitor does not have.
+            pub extra: ::std::vec::Vec<(&'static str, bool)>,
+        }
+
+        impl LanguageReport {
+            /// Returns whether the node kinds of the visitor and of the language are the same.
+            pub fn is_compatible(&self) -> bool {
+                self.missing.is_empty() && self.extra.is_empty()
+            }
+        }
+
+        impl ::std::fmt::Display for LanguageReport {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let list = |kinds: &[(&str, bool)]| {
+                    kinds
+                        .iter()
+                        .map(|(kind, named)| if *named { format!("{}", kind) } else { format!("{:?}", kind) })
+                        .collect::<::std::vec::Vec<_>>()
+                        .join(", ")
+                };
+                if self.is_compatible() {
+                    return write!(f, "node kinds match the language");
+                }
+                write!(
+                    f,
+                    "node kinds do not match the language (missing: [{}], extra: [{}])",
+                    list(&self.missing),
+                    list(&self.extra)
+                )
+            }
+        }
+
+        #[doc = #check_doc]
+        #vis fn check_language(language: &::tree_sitter::Language) -> LanguageReport {
+            const KINDS: &[(&str, bool)] = &[#((#kinds, #named)),*];
+
+            let missing = KINDS
+                .iter()
+                .filter(|(kind, named)| language.id_for_node_kind(kind, *named) == 0)
+                .copied()
+                .collect();
+            let mut extra: ::std::vec::Vec<_> = (0..language.node_kind_count() as u16)
+                .filter(|&id| language.node_kind_is_visible(id))
+                .filter_map(|id| {
+                    let kind = language.node_kind_for_id(id)?;
+                    ::std::option::Option::Some((kind, language.node_kind_is_named(id)))
+                })
+                .filter(|entry| !KINDS.contains(entry))
+                .collect();
+            // Aliases give several ids the same kind.
+            extra.sort_unstable();
+            extra.dedup();
+
+            LanguageReport { missing, extra }
+        }
+
+        /// Panics with the differences if the node kinds of the visitor and of the given language
+        /// are not the same. Meant to be called from a test, next to the parser it checks.
+        #[track_caller]
+        #vis fn assert_language_compatible(language: &::tree_sitter::Language) {
+            let report = check_language(language);
+            if !report.is_compatible() {
+                panic!("{}", report);
+            }
+        }
+    }
+}
@@ -0,0 +1,1335 @@
+//! The code generation behind the `tree-sitter-visitor` macros, as a library.
+//!
+//! The functions named after the macros take and return the tokens of the macro arguments and
+//! items, and are what the macros call. `generate()` writes out the code of `#[visitor_trait]` as
+//! Rust source instead, so build scripts can generate visitor traits into `OUT_DIR`, which keeps
+//! them readable and spares IDEs from expanding a large macro:
+//!
+//! ```ignore
+//! // build.rs
+//! let config = GenerateConfig::new("pub trait CalcVisitor {}").options("named_only, walker");
+//! let code = tree_sitter_visitor_codegen::generate("src/node-types.json", &config).unwrap();
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! std::fs::write(std::path::Path::new(&out_dir).join("calc_visitor.rs"), code).unwrap();
+//! println!("cargo:rerun-if-changed=src/node-types.json");
+//!
+//! // src/lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/calc_visitor.rs"));
+//! ```
+//!
+//! See the documentation of `tree-sitter-visitor` for the options.
+
+#[cfg(feature = "nightly")]
+extern crate proc_macro;
+
+mod accessors;
+mod ancestors;
+mod assert_tree;
+mod batch;
+mod catch_unimplemented;
+mod chain;
+mod children;
+mod combinators;
+mod config;
+mod correspondence;
+mod coverage;
+mod cursor;
+mod delegate;
+mod dyn_trait;
+mod dynamic_language;
+mod emitter;
+mod enum_dispatch;
+mod extras;
+mod fallible;
+mod field_children;
+mod field_hooks;
+mod fields;
+mod fingerprint;
+mod flavors;
+mod fn_visitor;
+mod generate;
+mod grammar;
+mod html;
+mod interner;
+mod introspection;
+mod kind_frequencies;
+mod kind_table;
+mod kinds;
+mod language_check;
+mod mappings;
+mod memoize;
+mod mixins;
+mod node_ext;
+mod node_kind;
+mod node_types;
+mod nodes;
+mod options;
+mod owned_parse;
+mod parallel;
+mod query;
+mod query_captures;
+mod query_visitor;
+mod revisit;
+mod serialize;
+mod session;
+mod sexp_printer;
+mod siblings;
+mod source_map;
+mod subtype_enums;
+mod text_provider;
+mod token_visitor;
+mod tracing;
+mod try_children;
+mod walker;
+mod watcher;
+
+pub use generate::{generate, GenerateConfig};
+
+use grammar::Grammar;
+use node_types::Node;
+use options::{ExtraNodes, Options, UnknownKinds};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use syn::{parse_quote, DeriveInput, GenericParam, Ident, ItemImpl, ItemTrait, LitStr, TraitItem};
+
+fn sanitize_identifier(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            result.push(c);
+        } else {
+            let replacement = match c {
+                '~' => "TILDE",
+                '`' => "BQUOTE",
+                '!' => "BANG",
+                '@' => "AT",
+                '#' => "POUND",
+                '$' => "DOLLAR",
+                '%' => "PERCENT",
+                '^' => "CARET",
+                '&' => "AMP",
+                '*' => "STAR",
+                '(' => "LPAREN",
+                ')' => "RPAREN",
+                '-' => "DASH",
+                '+' => "PLUS",
+                '=' => "EQ",
+                '{' => "LBRACE",
+                '}' => "RBRACE",
+                '[' => "LBRACK",
+                ']' => "RBRACK",
+                '\\' => "BSLASH",
+                '|' => "PIPE",
+                ':' => "COLON",
+                ';' => "SEMI",
+                '"' => "DQUOTE",
+                '\'' => "SQUOTE",
+                '<' => "LT",
+                '>' => "GT",
+                ',' => "COMMA",
+                '.' => "DOT",
+                '?' => "QMARK",
+                '/' => "SLASH",
+                '\n' => "LF",
+                '\r' => "CR",
+                '\t' => "TAB",
+                _ => continue,
+            };
+            if !result.is_empty() && !result.ends_with('_') {
+                result.push('_');
+            }
+            result += replacement;
+        }
+    }
+    result
+}
+
+/// Converts a `CamelCase` identifier to `UPPER_SNAKE_CASE`.
+fn upper_snake_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 && !result.ends_with('_') {
+            result.push('_');
+        }
+        result.push(c.to_ascii_uppercase());
+    }
+    result
+}
+
+/// Converts a `CamelCase` name to `snake_case`, keeping acronyms together, e.g. `HTMLElement` to
+/// `html_element`. Names that are already in snake case are kept as they are.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::with_capacity(name.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 && !result.ends_with('_') {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_ascii_lowercase());
+            if previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next_is_lower)
+            {
+                result.push('_');
+            }
+        }
+        result.push(c.to_ascii_lowercase());
+    }
+    result
+}
+
+/// Converts a snake case name, like a field name, to upper camel case.
+fn upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Returns an identifier for a field name, using a raw identifier if the name is a keyword.
+fn field_ident(name: &str) -> Ident {
+    syn::parse_str(name).unwrap_or_else(|_| Ident::new_raw(name, proc_macro2::Span::call_site()))
+}
+
+/// Returns the name of the trait method visiting nodes of the given type.
+fn method_name(node_type: &str) -> Ident {
+    prefixed_method_name("visit", node_type)
+}
+
+/// Returns the name of a method, associated type or associated constant of a trait.
+fn trait_item_ident(item: &TraitItem) -> Option<&Ident> {
+    match item {
+        TraitItem::Method(method) => Some(&method.sig.ident),
+        TraitItem::Type(ty) => Some(&ty.ident),
+        TraitItem::Const(constant) => Some(&constant.ident),
+        _ => None,
+    }
+}
+
+/// Returns the name of a method handling nodes of the given type, e.g. `emit_add_expr`.
+fn prefixed_method_name(prefix: &str, node_type: &str) -> Ident {
+    format_ident!("{}_{}", prefix, sanitize_identifier(node_type))
+}
+
+/// Returns the return type of the method visiting nodes of the given kind: the type declared for it
+/// with `return_types(...)`, or `default`, wrapped in a `Result` with `fallible`.
+fn method_return_type(
+    options: &Options,
+    kind: &str,
+    default: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    options.result_type(match options.return_type(kind) {
+        Some(ty) => quote! { #ty },
+        None => default,
+    })
+}
+
+/// Returns the generic parameters of the visitor trait, each followed by a comma, to be prepended
+/// to the generic parameters of generated items referring to the trait.
+fn trait_params(item: &ItemTrait) -> proc_macro2::TokenStream {
+    let params = item.generics.params.iter().map(|param| match param {
+        GenericParam::Type(param) => {
+            let mut param = param.clone();
+            param.eq_token = None;
+            param.default = None;
+            quote! { #param }
+        }
+        param => quote! { #param },
+    });
+    quote! { #(#params,)* }
+}
+
+/// Returns the where clause predicates of the visitor trait, each followed by a comma.
+fn trait_predicates(item: &ItemTrait) -> proc_macro2::TokenStream {
+    let predicates = item
+        .generics
+        .where_clause
+        .iter()
+        .flat_map(|where_clause| &where_clause.predicates);
+    quote! { #(#predicates,)* }
+}
+
+/// Returns a reference to the visitor trait with its generic arguments, followed by the given
+/// associated type bindings, e.g. `CalcVisitor<'a, T, ReturnType = R>`.
+fn trait_path(item: &ItemTrait, bindings: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let trait_name = &item.ident;
+    let args = item.generics.params.iter().map(|param| match param {
+        GenericParam::Type(param) => {
+            let ident = &param.ident;
+            quote! { #ident }
+        }
+        GenericParam::Lifetime(param) => {
+            let lifetime = &param.lifetime;
+            quote! { #lifetime }
+        }
+        GenericParam::Const(param) => {
+            let ident = &param.ident;
+            quote! { #ident }
+        }
+    });
+    let args: Vec<_> = args
+        .chain(Some(bindings).filter(|bindings| !bindings.is_empty()))
+        .collect();
+    if args.is_empty() {
+        quote! { #trait_name }
+    } else {
+        quote! { #trait_name<#(#args),*> }
+    }
+}
+
+/// Returns the given name for a generic parameter of a generated item referring to the visitor
+/// trait, with underscores appended if the trait has a generic parameter with the same name.
+fn fresh_param_name(item: &ItemTrait, name: &str) -> String {
+    let taken: Vec<_> = item
+        .generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Type(param) => param.ident.to_string(),
+            GenericParam::Lifetime(param) => param.lifetime.ident.to_string(),
+            GenericParam::Const(param) => param.ident.to_string(),
+        })
+        .collect();
+    let mut name = name.to_string();
+    while taken.contains(&name) {
+        name.push('_');
+    }
+    name
+}
+
+/// Returns a fresh identifier for a type parameter, see `fresh_param_name()`.
+fn fresh_type_param(item: &ItemTrait, name: &str) -> Ident {
+    Ident::new(
+        &fresh_param_name(item, name),
+        proc_macro2::Span::call_site(),
+    )
+}
+
+/// Returns a fresh lifetime, see `fresh_param_name()`.
+fn fresh_lifetime(item: &ItemTrait, name: &str) -> syn::Lifetime {
+    syn::Lifetime::new(
+        &format!("'{}", fresh_param_name(item, name)),
+        proc_macro2::Span::call_site(),
+    )
+}
+
+/// Returns a where clause predicate requiring `Self` to implement the supertraits of the visitor
+/// trait, if it has any, for implementations generated for generic types.
+fn supertrait_predicate(item: &ItemTrait) -> proc_macro2::TokenStream {
+    let supertraits = &item.supertraits;
+    if supertraits.is_empty() {
+        quote! {}
+    } else {
+        quote! { Self: #supertraits, }
+    }
+}
+
+/// Returns the name of a trait derived from the visitor trait, e.g. `CalcEmitter` for `CalcVisitor`.
+fn derived_trait_name(trait_name: &Ident, suffix: &str) -> Ident {
+    let name = trait_name.to_string();
+    let base = name.strip_suffix("Visitor").unwrap_or(&name);
+    format_ident!("{}{}", base, suffix)
+}
+
+/// Resolves a path relative to the file containing the macro invocation, or to the directory
+/// containing the `Cargo.toml` of the crate being compiled outside of a macro.
+#[cfg(feature = "nightly")]
+fn resolve_path(path: &str) -> PathBuf {
+    if !proc_macro::is_available() {
+        let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+        return PathBuf::from(manifest_dir).join(path);
+    }
+    // Spans of code not read from a file, e.g. generated by other macros, have no local file.
+    match proc_macro::Span::call_site().local_file() {
+        Some(call_site_file) => call_site_file.parent().unwrap().join(path),
+        None => {
+            let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+            PathBuf::from(manifest_dir).join(path)
+        }
+    }
+}
+
+/// Resolves a path relative to the directory containing the `Cargo.toml` of the crate being
+/// compiled.
+#[cfg(not(feature = "nightly"))]
+fn resolve_path(path: &str) -> PathBuf {
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+    PathBuf::from(manifest_dir).join(path)
+}
+
+/// Returns an item including a file given to the attribute in the expansion, which makes Cargo
+/// rebuild the crate when the file changes.
+fn tracked_file(path: &str) -> TokenStream {
+    let resolved = resolve_path(path);
+    let resolved = fs::canonicalize(&resolved).unwrap_or(resolved);
+    let resolved = resolved.to_string_lossy().into_owned();
+    quote! {
+        const _: &[u8] = include_bytes!(#resolved);
+    }
+}
+
+/// Computes the 64-bit FNV-1a hash of a file as 16 hex digits, treating CRLF line endings as LF so
+/// the hash does not depend on how the file was checked out.
+fn file_hash(path: &str, span: proc_macro2::Span) -> syn::Result<String> {
+    let contents = read_file(path, span)?;
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (i, &byte) in contents.iter().enumerate() {
+        if byte == b'\r' && contents.get(i + 1) == Some(&b'\n') {
+            continue;
+        }
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+/// Reads a file given to the attribute, failing with an error at `span` that shows the path it was
+/// resolved to.
+fn read_file(path: &str, span: proc_macro2::Span) -> syn::Result<Vec<u8>> {
+    let resolved = resolve_path(path);
+    fs::read(&resolved).map_err(|err| {
+        syn::Error::new(
+            span,
+            format!(
+                "could not read `{}` (resolved to `{}`): {}",
+                path,
+                resolved.display(),
+                err
+            ),
+        )
+    })
+}
+
+/// Reads and parses a JSON file given to the attribute, see `read_file()`.
+fn load_json<T: DeserializeOwned>(path: &str, span: proc_macro2::Span) -> syn::Result<T> {
+    let contents = read_file(path, span)?;
+    serde_json::from_slice(&contents)
+        .map_err(|err| syn::Error::new(span, format!("could not parse `{}`: {}", path, err)))
+}
+
+/// Loads a profile mapping node kinds to the number of times they were visited.
+fn load_profile(path: &LitStr) -> syn::Result<BTreeMap<String, u64>> {
+    load_json(&path.value(), path.span())
+}
+
+/// The implementation of `#[visitor_trait]`: generates the visit methods and the dispatch of a
+/// visitor trait, and the items of the options.
+pub fn visitor_trait(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut options: Options = match syn::parse2(args) {
+        Ok(options) => options,
+        Err(err) => return err.to_compile_error(),
+    };
+    let mut input: ItemTrait = match syn::parse2(input) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    // Errors reading the grammar point at the attribute.
+    let attr_span = proc_macro2::Span::call_site();
+
+    if let Some(expected_hash) = &options.expected_hash {
+        let actual_hash = match file_hash(&options.path, attr_span) {
+            Ok(hash) => hash,
+            Err(err) => return err.to_compile_error(),
+        };
+        if !expected_hash.value().eq_ignore_ascii_case(&actual_hash) {
+            let err = syn::Error::new(
+                expected_hash.span(),
+                format!(
+                    "`{}` has changed: its hash is `{}` but `{}` was expected. Review the changes \
+                     to the grammar and update `expected_hash` to accept them.",
+                    options.path,
+                    actual_hash,
+                    expected_hash.value()
+                ),
+            );
+            return err.to_compile_error();
+        }
+    }
+
+    let parsed = match options.load_node_types(attr_span) {
+        Ok(parsed) => parsed,
+        Err(err) => return err.to_compile_error(),
+    };
+
+    let mut kind_mappings = match mappings::extract(&mut input, &parsed) {
+        Ok(mappings) => mappings,
+        Err(err) => return err.to_compile_error(),
+    };
+    if let Err(err) = mappings::add_groups(&mut kind_mappings, &options, &parsed) {
+        return err.to_compile_error();
+    }
+    options.mappings = kind_mappings;
+    if !options.mappings.is_empty() {
+        // These options implement every method of the trait, once per node kind.
+        let conflicting = [
+            ("chain", options.chain),
+            ("catch_unimplemented", options.catch_unimplemented),
+            ("enum_dispatch", options.enum_dispatch.is_some()),
+            ("fn_visitor", options.fn_visitor),
+            ("dyn_trait", options.dyn_trait),
+            ("combinators", options.combinators),
+            ("mixins", !options.mixins.is_empty()),
+            ("flavors", !options.flavors.is_empty()),
+        ];
+        if let Some((name, _)) = conflicting.iter().find(|(_, enabled)| *enabled) {
+            let err = syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "`#[visit(...)]` and `group` cannot be combined with `{}`",
+                    name
+                ),
+            );
+            return err.to_compile_error();
+        }
+    }
+
+    for return_type in &options.return_types {
+        let kind = return_type.kind.value();
+        if !parsed.iter().any(|node| node.r#type == kind) {
+            let err = syn::Error::new(
+                return_type.kind.span(),
+                format!("unknown node kind `{}`", kind),
+            );
+            return err.to_compile_error();
+        }
+    }
+
+    for (i, rename) in options.renames.iter().enumerate() {
+        let (old, new) = (rename.old.value(), rename.new.value());
+        let message = if parsed.iter().any(|node| node.r#type == old) {
+            format!("node kind `{}` still exists in the grammar", old)
+        } else if !parsed.iter().any(|node| node.r#type == new) {
+            format!("unknown node kind `{}`", new)
+        } else if options.renames[..i]
+            .iter()
+            .any(|other| other.new.value() == new)
+        {
+            format!("node kind `{}` is the target of more than one rename", new)
+        } else {
+            continue;
+        };
+        let err = syn::Error::new(rename.old.span(), message);
+        return err.to_compile_error();
+    }
+
+    let grammar = match options.grammar.as_ref().map(Grammar::load).transpose() {
+        Ok(grammar) => grammar,
+        Err(err) => return err.to_compile_error(),
+    };
+    let external_hook = grammar
+        .as_ref()
+        .is_some_and(|grammar| grammar.has_externals(&parsed));
+
+    if !options.supertraits.is_empty() {
+        if input.colon_token.is_none() {
+            input.colon_token = Some(Default::default());
+        }
+        input
+            .supertraits
+            .extend(options.supertraits.iter().cloned());
+    }
+
+    if !input.generics.params.is_empty() {
+        let unsupported = [
+            ("session", options.session),
+            ("notify", options.notify),
+            ("batch", options.batch),
+            ("catch_unimplemented", options.catch_unimplemented),
+            ("coverage", options.coverage),
+            ("memoize", options.memoize),
+            ("dyn_trait", options.dyn_trait),
+        ];
+        if let Some((name, _)) = unsupported.iter().find(|(_, enabled)| *enabled) {
+            let err = syn::Error::new_spanned(
+                &input.generics,
+                format!("`{}` is not supported for generic visitor traits", name),
+            );
+            return err.to_compile_error();
+        }
+    }
+
+    // With `by_value`, the trait is generic over the lifetime of the tree, so that `ReturnType`
+    // can borrow from it.
+    if options.by_value {
+        let declared = input
+            .generics
+            .lifetimes()
+            .any(|param| param.lifetime.ident == "tree");
+        if declared {
+            let err = syn::Error::new_spanned(
+                &input.generics,
+                "the lifetime `'tree` is already declared by `by_value`",
+            );
+            return err.to_compile_error();
+        }
+        input.generics.params.insert(0, parse_quote! { 'tree });
+    }
+
+    // With `return_type_param`, the trait is generic over the type returned by the visit methods,
+    // bounded by `return_bounds`, instead of declaring `ReturnType`.
+    if let Some(param) = &options.return_type_param {
+        let declared = input
+            .generics
+            .type_params()
+            .any(|type_param| type_param.ident == *param);
+        if declared {
+            let err = syn::Error::new_spanned(
+                param,
+                format!(
+                    "the type parameter `{}` is already declared by the trait",
+                    param
+                ),
+            );
+            return err.to_compile_error();
+        }
+        let bounds = &options.return_bounds;
+        let type_param: GenericParam = if bounds.is_empty() {
+            parse_quote! { #param }
+        } else {
+            parse_quote! { #param: #(#bounds)+* }
+        };
+        input.generics.params.push(type_param);
+    }
+
+    if options.token_hook && options.named_dispatch {
+        let err = syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "`token_hook` cannot be combined with `named_dispatch`",
+        );
+        return err.to_compile_error();
+    }
+
+    // With `prefix` and `case`, distinct kinds may map to the same method, e.g. `FooBar` and
+    // `foo_bar` with `case = "snake"`.
+    // Kinds mapped with `#[visit(...)]` share their method on purpose.
+    let is_mapped = |kind: &str| options.mappings.iter().any(|(mapped, _)| mapped == kind);
+    let mut seen = BTreeMap::new();
+    for node in parsed.iter().filter(|node| !is_mapped(&node.r#type)) {
+        let method = options.method_name(&node.r#type).to_string();
+        if let Some(other) = seen.insert(method.clone(), &node.r#type) {
+            let err = syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "node kinds `{}` and `{}` both map to the method `{}`",
+                    other, node.r#type, method
+                ),
+            );
+            return err.to_compile_error();
+        }
+    }
+    for (kind, method) in &options.mappings {
+        if let Some(other) = seen.get(&method.to_string()) {
+            let err = syn::Error::new(
+                method.span(),
+                format!(
+                    "`{}()` is mapped to node kind `{}` but is also the method of node kind `{}`",
+                    method, kind, other
+                ),
+            );
+            return err.to_compile_error();
+        }
+    }
+
+    let field_hook_names = field_hooks::hook_names(&parsed);
+    let mut field_methods = vec!["visit_fields"];
+    field_methods.extend(field_hook_names.iter().map(String::as_str));
+    // The methods generated by these options must not clash with the methods of node kinds.
+    let generated_methods: [(&str, bool, &[&str]); 9] = [
+        (
+            "named_dispatch",
+            options.named_dispatch,
+            &["visit_named", "visit_token"],
+        ),
+        (
+            "unknown = \"hook\"",
+            options.unknown == UnknownKinds::Hook,
+            &["visit_unknown"],
+        ),
+        ("token_hook", options.token_hook, &["visit_token"]),
+        (
+            "error_hooks",
+            options.error_hooks,
+            &["visit_error", "visit_missing"],
+        ),
+        (
+            "extras = \"hook\"",
+            options.extras == ExtraNodes::Hook,
+            &["visit_extra"],
+        ),
+        ("grammar", external_hook, &["visit_external"]),
+        (
+            "cursor",
+            options.cursor,
+            &["visit_cursor", "visit_cursor_children"],
+        ),
+        ("field_hooks", options.field_hooks, &field_methods),
+        ("parallel", options.parallel, &["visit_parallel"]),
+    ];
+    for (name, _, methods) in generated_methods.iter().filter(|(_, enabled, _)| *enabled) {
+        let clashing = parsed
+            .iter()
+            .find(|node| methods.contains(&options.method_name(&node.r#type).to_string().as_str()));
+        if let Some(node) = clashing {
+            let err = syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!(
+                    "`{}` clashes with the method of node kind `{}`",
+                    name, node.r#type
+                ),
+            );
+            return err.to_compile_error();
+        }
+    }
+
+    let record_unhandled = if options.catch_unimplemented {
+        catch_unimplemented::record_unhandled(&input.ident)
+    } else {
+        quote! {}
+    };
+
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let asyncness = options.asyncness();
+    let node_param = options.node_param();
+    let node_ref = options.node_ref();
+
+    let node_kind_variants = node_kind::variant_names(&parsed);
+    let (trait_fns, match_arms): (Vec<_>, Vec<_>) = parsed
+        .iter()
+        .enumerate()
+        .map(|(index, symbol)| {
+            let raw_name = &symbol.r#type;
+            let sanitized_name = sanitize_identifier(&symbol.r#type);
+            let method_name = options.method_name(&symbol.r#type);
+            let doc_name = format!("{:?}", raw_name).replace('`', "\\`");
+            let mut doc_string = format!("Visits a node of type `{}`", doc_name);
+            let return_type = method_return_type(&options, raw_name, options.self_return_type());
+
+            // With `grammar`, the precedence and aliases of the kind are documented, and external
+            // tokens fall through to `visit_external()`, unless they have a dedicated return type.
+            let mut external = false;
+            if let Some(grammar) = &grammar {
+                if symbol.named {
+                    if let Some(precedence) = grammar.precedence(raw_name) {
+                        doc_string = format!("{}. Precedence: {}", doc_string, precedence);
+                    }
+                }
+                let aliases = grammar.aliases_of(raw_name, symbol.named);
+                if !aliases.is_empty() {
+                    doc_string = format!("{}. Aliased from {}", doc_string, aliases.join(", "));
+                }
+                external = grammar.is_external(raw_name, symbol.named)
+                    && options.return_type(raw_name).is_none();
+            }
+
+            // With `supertypes`, subtypes fall through to the method of their supertype, unless it
+            // has a different return type.
+            let supertype = node_types::supertype_of(symbol, &parsed).filter(|supertype| {
+                options.supertypes
+                    && method_return_type(&options, &supertype.r#type, options.self_return_type())
+                        .to_string()
+                        == return_type.to_string()
+            });
+            let supertype_method =
+                supertype.map(|supertype| options.method_name(&supertype.r#type));
+            let (body, doc_string) = match supertype_method {
+                _ if external => (
+                    options.awaited(quote! { self.visit_external(node #param_args) }),
+                    format!(
+                        "{}. Produced by the external scanner, forwards to `visit_external()` by \
+                         default.",
+                        doc_string
+                    ),
+                ),
+                Some(supertype_method) => (
+                    options.awaited(quote! { self.#supertype_method(node #param_args) }),
+                    format!("{}. Forwards to `{}()` by default.", doc_string, supertype_method),
+                ),
+                None => {
+                    let stub = options.stub(quote! { unimplemented!(#sanitized_name) });
+                    (
+                        quote! {
+                            #record_unhandled
+                            #stub
+                        },
+                        doc_string,
+                    )
+                }
+            };
+
+            let doc_string = format!(
+                "{}{}{}",
+                doc_string,
+                symbol.shape_doc(),
+                symbol.languages_doc()
+            );
+            let trait_fn: TraitItem = parse_quote! {
+                #[doc=#doc_string]
+                #[allow(unused_variables)]
+                #asyncness fn #method_name(&mut self, #node_param #param_decls) -> #return_type {
+                    #body
+                }
+            };
+
+            // Renamed kinds are dispatched through the shim of the old kind, which forwards to the
+            // method of the new one unless overridden, and the old kind is matched as well.
+            let (pattern, call) = match options.renames.iter().find(|r| r.new.value() == *raw_name) {
+                Some(rename) => {
+                    let old = rename.old.value();
+                    let shim_name = options.method_name(&old);
+                    let shim_call =
+                        options.dispatched(quote! { self.#shim_name(node #param_args) });
+                    (
+                        quote! { #raw_name | #old },
+                        quote! {
+                            {
+                                #[allow(deprecated)]
+                                let result = #shim_call;
+                                result
+                            }
+                        },
+                    )
+                }
+                None => (
+                    quote! { #raw_name },
+                    options.dispatched(quote! { self.#method_name(node #param_args) }),
+                ),
+            };
+            // With `kind_id_dispatch`, nodes are matched by their index in the `KindTable`.
+            // With `node_kind_dispatch`, by their `NodeKind`.
+            let pattern = if options.kind_id_dispatch {
+                quote! { ::std::option::Option::Some(#index) }
+            } else if options.node_kind_dispatch {
+                let variant = &node_kind_variants[index];
+                quote! { NodeKind::#variant }
+            } else {
+                pattern
+            };
+            let match_arm = if options.return_type(raw_name).is_some() {
+                let message = format!(
+                    "nodes of kind `{}` have a dedicated return type and must be visited with `{}()`",
+                    raw_name, method_name
+                );
+                quote! {
+                    #pattern => panic!(#message)
+                }
+            } else if options.coverage {
+                let record_hit = coverage::record_hit(&input.ident, index);
+                quote! {
+                    #pattern => {
+                        #record_hit
+                        #call
+                    }
+                }
+            } else {
+                quote! {
+                    #pattern => #call
+                }
+            };
+
+            (trait_fn, match_arm)
+        })
+        .unzip();
+
+    // Orders the match arms by descending frequency, so the most common kinds are compared first.
+    let mut dispatch_arms: Vec<_> = parsed.iter().zip(match_arms).collect();
+    if let Some(profile) = &options.profile {
+        let profile = match load_profile(profile) {
+            Ok(profile) => profile,
+            Err(err) => return err.to_compile_error(),
+        };
+        dispatch_arms.sort_by_key(|(symbol, _)| {
+            std::cmp::Reverse(profile.get(&symbol.r#type).copied().unwrap_or(0))
+        });
+    }
+
+    let result_type = options.result_type(options.self_return_type());
+    let kind_names: Vec<_> = parsed.iter().map(|symbol| &symbol.r#type).collect();
+    let dispatch_fn = |name: Ident,
+                       doc: &str,
+                       guard: proc_macro2::TokenStream,
+                       arms: &[&proc_macro2::TokenStream],
+                       unknown: &str| {
+        let unknown_arm = options.unknown_arm(unknown);
+        let token_arm = if options.token_hook {
+            let call =
+                options.dispatched(quote! { self.visit_token(node, node.kind() #param_args) });
+            quote! { _ if !node.is_named() => #call, }
+        } else {
+            quote! {}
+        };
+        let extra_arm = extras::dispatch_arm(&parsed, &options);
+        let error_arms = options.error_arms();
+        let scrutinee = if options.kind_id_dispatch {
+            quote! { KindTable::index_of(#node_ref) }
+        } else if options.node_kind_dispatch {
+            quote! { NodeKind::from(#node_ref) }
+        } else if options.grammar_name_dispatch {
+            // Aliases of hidden rules have a grammar name that is not a kind, so they fall back to
+            // their kind.
+            quote! {
+                {
+                    let grammar_name = node.grammar_name();
+                    match grammar_name {
+                        #(#kind_names)|* => grammar_name,
+                        _ => node.kind(),
+                    }
+                }
+            }
+        } else {
+            quote! { node.kind() }
+        };
+        let dispatch = quote! {
+            match #scrutinee {
+                #error_arms
+                #extra_arm
+                #(#arms,)*
+                #token_arm
+                #unknown_arm
+            }
+        };
+        let body = if options.after_visit {
+            quote! {
+                let result = #dispatch;
+                self.after_visit(#node_ref, &result);
+                result
+            }
+        } else {
+            dispatch
+        };
+        let body = if options.tracing {
+            tracing::instrument(&input.ident, body)
+        } else {
+            body
+        };
+        let item: TraitItem = parse_quote! {
+            #[doc=#doc]
+            #asyncness fn #name(&mut self, #node_param #param_decls) -> #result_type {
+                #guard
+                #body
+            }
+        };
+        item
+    };
+    let dispatch_visit_fn = dispatch_fn(
+        format_ident!("visit"),
+        "Visits a node of any type.",
+        quote! {},
+        &dispatch_arms.iter().map(|(_, arm)| arm).collect::<Vec<_>>(),
+        "unknown node kind: {}",
+    );
+
+    let mut generated_items: Vec<TraitItem> = Vec::new();
+    // A preset `return_type` or a `return_type_param` replaces the associated type.
+    if options.preset_return_type.is_none() && options.return_type_param.is_none() {
+        let return_type_item = options.return_type_item();
+        generated_items.push(parse_quote! { #return_type_item });
+    }
+    generated_items.push(dispatch_visit_fn);
+    if options.context {
+        let context_item = options.context_item();
+        generated_items.push(parse_quote! { #context_item });
+    }
+    if options.named_dispatch {
+        let (named_arms, token_arms): (Vec<_>, Vec<_>) =
+            dispatch_arms.iter().partition(|(symbol, _)| symbol.named);
+        let named_arms: Vec<_> = named_arms.into_iter().map(|(_, arm)| arm).collect();
+        let token_arms: Vec<_> = token_arms.into_iter().map(|(_, arm)| arm).collect();
+        generated_items.push(dispatch_fn(
+            format_ident!("visit_named"),
+            "Visits a named node of any type. Panics for anonymous nodes.",
+            quote! {
+                assert!(node.is_named(), "expected a named node, got `{}`", node.kind());
+            },
+            &named_arms,
+            "unknown named node kind: {}",
+        ));
+        generated_items.push(dispatch_fn(
+            format_ident!("visit_token"),
+            "Visits an anonymous node (a token) of any type. Panics for named nodes.",
+            quote! {
+                assert!(!node.is_named(), "expected an anonymous node, got `{}`", node.kind());
+            },
+            &token_arms,
+            "unknown anonymous node kind: {}",
+        ));
+    }
+    if options.fallible {
+        let error_item = options.error_item();
+        generated_items.push(parse_quote! { #error_item });
+    }
+    for group in &options.groups {
+        let method_name = options.group_method_name(&group.name);
+        let kinds: Vec<_> = group
+            .kinds
+            .iter()
+            .map(|kind| format!("`{}`", kind.value()))
+            .collect();
+        let doc_string = format!(
+            "Visits a node of one of the kinds of the `{}` group: {}, which is `node.kind()`. \
+             Panics by default.",
+            group.name,
+            kinds.join(", ")
+        );
+        let group_name = group.name.to_string();
+        let stub = options.stub(quote! { unimplemented!(#group_name) });
+        generated_items.push(parse_quote! {
+            #[doc=#doc_string]
+            #[allow(unused_variables)]
+            #asyncness fn #method_name(&mut self, #node_param #param_decls) -> #result_type {
+                #stub
+            }
+        });
+    }
+    if options.token_hook {
+        let stub = options.stub(quote! { unimplemented!("token `{}`", kind) });
+        generated_items.push(parse_quote! {
+            #[doc=r"Visits an anonymous node (a token) of any type, e.g. an operator or punctuation, of the given kind. Panics by default."]
+            #[allow(unused_variables)]
+            #asyncness fn visit_token(&mut self, #node_param, kind: &str #param_decls) -> #result_type {
+                #stub
+            }
+        });
+    }
+    if options.unknown == UnknownKinds::Hook {
+        let stub = options.stub(quote! { panic!("unknown node kind: {}", node.kind()) });
+        generated_items.push(parse_quote! {
+            #[doc=r"Visits a node whose kind is not in `node-types.json`, e.g. one added by a newer version of the grammar. Panics by default."]
+            #[allow(unused_variables)]
+            #asyncness fn visit_unknown(&mut self, #node_param #param_decls) -> #result_type {
+                #stub
+            }
+        });
+    }
+    if options.error_hooks {
+        let error_stub = options.stub(quote! { panic!("syntax error at {:?}", node.range()) });
+        let missing_stub =
+            options.stub(quote! { panic!("missing `{}` at {:?}", node.kind(), node.range()) });
+        generated_items.push(parse_quote! {
+            #[doc=r"Visits an `ERROR` node, which the parser produces for text it could not parse. Panics by default."]
+            #[allow(unused_variables)]
+            #asyncness fn visit_error(&mut self, #node_param #param_decls) -> #result_type {
+                #error_stub
+            }
+        });
+        generated_items.push(parse_quote! {
+            #[doc=r"Visits a `MISSING` node, which the parser inserts with zero width where a required token or node is missing, instead of the method of its kind. Panics by default."]
+            #[allow(unused_variables)]
+            #asyncness fn visit_missing(&mut self, #node_param #param_decls) -> #result_type {
+                #missing_stub
+            }
+        });
+    }
+    if options.extras == ExtraNodes::Hook {
+        let stub = options.stub(quote! { panic!("extra node: {}", node.kind()) });
+        generated_items.push(parse_quote! {
+            #[doc=r"Visits an extra, such as a comment, instead of the method of its kind. Panics by default."]
+            #[allow(unused_variables)]
+            #asyncness fn visit_extra(&mut self, #node_param #param_decls) -> #result_type {
+                #stub
+            }
+        });
+    }
+    if options.after_visit {
+        let return_type = options.self_return_type();
+        generated_items.push(parse_quote! {
+            #[doc=r"Called by `visit()` after every dispatched node, e.g. to check invariants in debug builds."]
+            fn after_visit(&mut self, node: &::tree_sitter::Node, result: &#return_type) {}
+        });
+    }
+    if options.chain {
+        generated_items.extend(chain::trait_items(&parsed, &options));
+    }
+    if options.visit_children {
+        generated_items.extend(children::trait_items(&input, &parsed, &options));
+    }
+    if options.try_visit_children {
+        generated_items.extend(try_children::trait_items(&input, &parsed, &options));
+    }
+    if options.kind_table {
+        generated_items.extend(kind_table::trait_items(&parsed, &options));
+    }
+    if options.cursor {
+        generated_items.extend(cursor::trait_items(&parsed, &options));
+    }
+    if options.field_hooks {
+        generated_items.extend(field_hooks::trait_items(&parsed, &options));
+    }
+    if options.revisit {
+        generated_items.extend(revisit::trait_items(&options));
+    }
+    if options.parallel {
+        generated_items.extend(parallel::trait_items(&options));
+    }
+
+    let shim_fns = options.renames.iter().map(|rename| {
+        let (old, new) = (rename.old.value(), rename.new.value());
+        let shim_name = options.method_name(&old);
+        let new_name = options.method_name(&new);
+        let return_type = method_return_type(&options, &new, options.self_return_type());
+        let doc_string = format!(
+            "Visits a node of type `{}`, which was renamed to `{}`. Forwards to `{}()`.",
+            old, new, new_name
+        );
+        let note = format!(
+            "node kind `{}` was renamed to `{}`, override `{}()` instead",
+            old, new, new_name
+        );
+        let call = options.awaited(quote! { self.#new_name(node #param_args) });
+        let shim_fn: TraitItem = parse_quote! {
+            #[doc=#doc_string]
+            #[deprecated(note=#note)]
+            #asyncness fn #shim_name(&mut self, #node_param #param_decls) -> #return_type {
+                #call
+            }
+        };
+        shim_fn
+    });
+
+    if external_hook {
+        let return_type = options.result_type(options.self_return_type());
+        let stub = options.stub(quote! { unimplemented!("external token `{}`", node.kind()) });
+        let hook: TraitItem = parse_quote! {
+            /// Visits a token produced by the external scanner of the grammar. The methods of the
+            /// external tokens forward here by default.
+            #[allow(unused_variables)]
+            #asyncness fn visit_external(&mut self, #node_param #param_decls) -> #return_type {
+                #record_unhandled
+                #stub
+            }
+        };
+        generated_items.push(hook);
+    }
+
+    // Items already defined in the trait body take precedence over the generated ones, e.g. a method
+    // of a node kind with a custom default implementation, which `visit()` then dispatches to.
+    let user_defined: Vec<Ident> = input
+        .items
+        .iter()
+        .filter_map(trait_item_ident)
+        .cloned()
+        .collect();
+    // The methods of grouped kinds are replaced by the single method of their group.
+    let group_methods: Vec<Ident> = options
+        .groups
+        .iter()
+        .map(|group| options.group_method_name(&group.name))
+        .collect();
+    let trait_fns = trait_fns
+        .into_iter()
+        .filter(|item| !trait_item_ident(item).is_some_and(|ident| group_methods.contains(ident)));
+    input.items = generated_items
+        .into_iter()
+        .chain(trait_fns)
+        .chain(shim_fns)
+        .filter(|item| !trait_item_ident(item).is_some_and(|ident| user_defined.contains(ident)))
+        .chain(input.items)
+        .collect();
+
+    let mut extra_items = Vec::new();
+    if options.fallible {
+        extra_items.push(fallible::generate(&input));
+    }
+    if !options.flavors.is_empty() {
+        extra_items.push(flavors::generate(&input, &parsed, &options));
+    }
+    if options.text_provider {
+        extra_items.push(text_provider::generate(&input.vis, &options));
+    }
+    if options.session {
+        extra_items.push(session::generate(&input));
+    }
+    if options.notify {
+        extra_items.push(watcher::generate(&input));
+    }
+    if options.batch {
+        extra_items.push(batch::generate(&input));
+    }
+    if options.owned_parse {
+        extra_items.push(owned_parse::generate(&input));
+    }
+    if options.catch_unimplemented {
+        extra_items.push(catch_unimplemented::generate(
+            &input,
+            &parsed,
+            &options,
+            external_hook,
+        ));
+    }
+    if options.chain {
+        extra_items.push(chain::generate(&input, &parsed, &options, external_hook));
+    }
+    if options.field_children {
+        extra_items.push(field_children::generate(&input, &parsed));
+    }
+    if options.tracing {
+        extra_items.push(tracing::generate(&input.ident));
+    }
+    if options.coverage {
+        extra_items.push(coverage::generate(&input, &parsed, &options));
+    }
+    if options.memoize {
+        extra_items.push(memoize::generate(&input, &parsed, &options, external_hook));
+    }
+    if options.fields {
+        extra_items.push(fields::generate(&input, &parsed));
+    }
+    if options.nodes {
+        extra_items.push(nodes::generate(&input, &parsed));
+    }
+    if options.accessors {
+        extra_items.push(accessors::generate(&input, &parsed, &options));
+    }
+    if options.node_ext {
+        extra_items.push(node_ext::generate(&input, &parsed, &options));
+    }
+    if options.subtype_enums {
+        extra_items.push(subtype_enums::generate(&input, &parsed));
+    }
+    if options.node_kind {
+        extra_items.push(node_kind::generate(&input, &parsed));
+    }
+    if options.kinds {
+        extra_items.push(kinds::generate(&input, &parsed));
+    }
+    if options.correspondence {
+        extra_items.push(correspondence::generate(&input));
+    }
+    if options.fingerprint {
+        extra_items.push(fingerprint::generate(&input));
+    }
+    if options.fn_visitor {
+        extra_items.push(fn_visitor::generate(
+            &input,
+            &parsed,
+            &options,
+            external_hook,
+        ));
+    }
+    if options.dyn_trait {
+        extra_items.push(dyn_trait::generate(&input, &parsed, &options));
+    }
+    if options.combinators {
+        extra_items.push(combinators::generate(
+            &input,
+            &parsed,
+            &options,
+            external_hook,
+        ));
+    }
+    if options.emitter {
+        extra_items.push(emitter::generate(&input, &parsed, &options));
+    }
+    if let Some(enum_dispatch) = &options.enum_dispatch {
+        match enum_dispatch::generate(&input, &parsed, &options, enum_dispatch, external_hook) {
+            Ok(tokens) => extra_items.push(tokens),
+            Err(err) => return err.to_compile_error(),
+        }
+    }
+    if options.introspection {
+        extra_items.push(introspection::generate(&input, &parsed));
+    }
+    if options.kind_frequencies {
+        extra_items.push(kind_frequencies::generate(&input, &parsed));
+    }
+    if options.html {
+        extra_items.push(html::generate(&input, &parsed));
+    }
+    if options.source_map {
+        extra_items.push(source_map::generate(&input));
+    }
+    if options.interner {
+        extra_items.push(interner::generate(&input, &parsed));
+    }
+    if options.assert_tree {
+        extra_items.push(assert_tree::generate(&input, &parsed));
+    }
+    if options.sexp_printer {
+        extra_items.push(sexp_printer::generate(&input));
+    }
+    if options.serialize {
+        extra_items.push(serialize::generate(&input, &parsed));
+    }
+    if options.kind_table {
+        extra_items.push(kind_table::generate(&input, &parsed));
+    }
+    if options.libloading {
+        extra_items.push(dynamic_language::generate(&input));
+    }
+    // The kinds left out of the trait are still kinds of the language.
+    if options.language_check {
+        match options.load_all_node_types(attr_span) {
+            Ok(node_types) => extra_items.push(language_check::generate(&input, &node_types)),
+            Err(err) => return err.to_compile_error(),
+        }
+    }
+    if options.siblings {
+        extra_items.push(siblings::generate(&input, &parsed));
+    }
+    if options.token_visitor {
+        extra_items.push(token_visitor::generate(&input, &parsed));
+    }
+    if options.walker {
+        extra_items.push(walker::generate(&input, &options));
+    }
+    if options.query_captures {
+        extra_items.push(query_captures::generate(&input, &options));
+    }
+    if options.ancestors {
+        match ancestors::generate(&input, &parsed, &options) {
+            Ok(tokens) => extra_items.push(tokens),
+            Err(err) => return err.to_compile_error(),
+        }
+    }
+    if !options.mixins.is_empty() {
+        match mixins::generate(&input, &parsed, &options) {
+            Ok(tokens) => extra_items.push(tokens),
+            Err(err) => return err.to_compile_error(),
+        }
+    }
+
+    extra_items.push(options.tracked_files());
+
+    quote! {
+        #input
+        #(#extra_items)*
+    }
+}
+
+/// The implementation of `#[visitor_impl]`: records which methods an implementation of a
+/// visitor trait overrides.
+pub fn visitor_impl(_args: TokenStream, input: TokenStream) -> TokenStream {
+    match syn::parse2::<ItemImpl>(input) {
+        Ok(input) => chain::annotate_impl(input).into_token_stream(),
+        Err(err) => err.to_compile_error(),
+    }
+}
+
+/// The implementation of `#[query_visitor]`: generates a trait visiting the captures of a
+/// tree-sitter query.
+pub fn query_visitor(args: TokenStream, input: TokenStream) -> TokenStream {
+    let result = syn::parse2::<query_visitor::QueryVisitorArgs>(args).and_then(|args| {
+        let input = syn::parse2::<ItemTrait>(input)?;
+        query_visitor::generate(args, input)
+    });
+    result.unwrap_or_else(|err| err.to_compile_error())
+}
+
+/// The implementation of `#[derive(VisitorDelegate)]`: implements a visitor trait for a wrapper
+/// struct by forwarding every typed `visit_*` method to one of its fields.
+pub fn visitor_delegate(input: TokenStream) -> TokenStream {
+    match syn::parse2::<DeriveInput>(input).and_then(delegate::derive) {
+        Ok(tokens) => tokens,
+        Err(err) => err.to_compile_error(),
+    }
+}
@@ -0,0 +1,94 @@
+//! Handling of `#[visit("kind", ...)]` attributes on methods in the body of the visitor trait,
+//! and of the `group(name = [...])` option, which map node kinds to those methods instead of to
+//! generated `visit_<kind>()` methods.
+
+use syn::punctuated::Punctuated;
+use syn::{Ident, ItemTrait, LitStr, Token, TraitItem};
+
+use crate::node_types::Node;
+use crate::options::Options;
+
+/// Removes the `#[visit(...)]` attributes from the methods of the trait, returning the node kinds
+/// they map to each method.
+pub(crate) fn extract(
+    item: &mut ItemTrait,
+    node_types: &[Node],
+) -> syn::Result<Vec<(String, Ident)>> {
+    let mut mappings: Vec<(String, Ident)> = Vec::new();
+    for trait_item in &mut item.items {
+        let method = match trait_item {
+            TraitItem::Method(method) => method,
+            _ => continue,
+        };
+        let mut attrs = Vec::new();
+        for attr in method.attrs.drain(..) {
+            if !attr.path.is_ident("visit") {
+                attrs.push(attr);
+                continue;
+            }
+            let kinds = attr.parse_args_with(Punctuated::<LitStr, Token![,]>::parse_terminated)?;
+            if kinds.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &attr,
+                    "expected at least one node kind, e.g. `#[visit(\"function_definition\")]`",
+                ));
+            }
+            for kind in kinds {
+                let value = kind.value();
+                if !node_types.iter().any(|node| node.r#type == value) {
+                    return Err(syn::Error::new(
+                        kind.span(),
+                        format!("unknown node kind `{}`", value),
+                    ));
+                }
+                if let Some((_, other)) = mappings.iter().find(|(mapped, _)| *mapped == value) {
+                    return Err(syn::Error::new(
+                        kind.span(),
+                        format!("node kind `{}` is already mapped to `{}()`", value, other),
+                    ));
+                }
+                mappings.push((value, method.sig.ident.clone()));
+            }
+        }
+        method.attrs = attrs;
+    }
+    Ok(mappings)
+}
+
+/// Maps the kinds of each `group(name = [...])` to the method of the group, e.g. `visit_binary()`,
+/// failing for kinds that are unknown, already mapped or have a dedicated return type.
+pub(crate) fn add_groups(
+    mappings: &mut Vec<(String, Ident)>,
+    options: &Options,
+    node_types: &[Node],
+) -> syn::Result<()> {
+    for group in &options.groups {
+        let method = options.group_method_name(&group.name);
+        for kind in &group.kinds {
+            let value = kind.value();
+            if !node_types.iter().any(|node| node.r#type == value) {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    format!("unknown node kind `{}`", value),
+                ));
+            }
+            if let Some((_, other)) = mappings.iter().find(|(mapped, _)| *mapped == value) {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    format!("node kind `{}` is already mapped to `{}()`", value, other),
+                ));
+            }
+            if options.return_type(&value).is_some() {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    format!(
+                        "node kind `{}` has a dedicated return type and cannot be grouped",
+                        value
+                    ),
+                ));
+            }
+            mappings.push((value, method.clone()));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! Generation of `Memoized`, which caches the results of visiting nodes by their id, so repeated
+//! visits of the same tree do not redo the work.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+use crate::options::{HookSignature, Options};
+use crate::supertrait_predicate;
+
+pub(crate) fn generate(
+    item: &ItemTrait,
+    node_types: &[Node],
+    options: &Options,
+    external_hook: bool,
+) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = &item.ident;
+    let supertrait_predicate = supertrait_predicate(item);
+
+    let methods = node_types.iter().map(|node| {
+        let method_name = options.method_name(&node.r#type);
+        quote! {
+            fn #method_name(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
+                self.inner.#method_name(node)
+            }
+        }
+    });
+    let hook_methods = options
+        .hooks(node_types, external_hook)
+        .into_iter()
+        .map(|hook| {
+            let name = &hook.name;
+            hook.forward(options, &quote! { Self::ReturnType }, |args| {
+                match hook.signature {
+                    HookSignature::Invalidate => quote! {
+                        Memoized::invalidate(self, node);
+                        self.inner.#name(#args)
+                    },
+                    HookSignature::Split => quote! { Memoized::new(self.inner.#name()) },
+                    _ => quote! { self.inner.#name(#args) },
+                }
+            })
+        });
+
+    quote! {
+        /// A wrapper caching the results of `visit()` by the id of the visited node, so that
+        /// visiting the same nodes again, e.g. to answer several queries about one tree, returns the
+        /// cached results instead of visiting their subtrees again.
+        ///
+        /// Only the visits made through the wrapper are cached: the wrapped visitor visits the
+        /// children of a node with its own `visit()`, which does not go through the cache.
+        ///
+        /// Ids are only unique among the nodes of trees that are alive, and are shared by a tree and
+        /// the trees reparsed from it incrementally where their nodes were reused. After editing and
+        /// reparsing a tree, pass the edit to `edit()` to drop the results of the nodes it touched;
+        /// when visiting an unrelated tree, call `clear()` first.
+        #vis struct Memoized<V: #trait_name> {
+            /// The wrapped visitor.
+            pub inner: V,
+            cache: ::std::collections::HashMap<
+                usize,
+                (::std::ops::Range<usize>, V::ReturnType),
+            >,
+        }
+
+        impl<V: #trait_name> Memoized<V>
+        where
+            V::ReturnType: ::std::clone::Clone,
+        {
+            /// Wraps a visitor, with an empty cache.
+            pub fn new(inner: V) -> Self {
+                Self {
+                    inner,
+                    cache: ::std::collections::HashMap::new(),
+                }
+            }
+
+            /// Returns the cached result of visiting a node, or visits it with the wrapped visitor
+            /// and caches the result.
+            pub fn visit(&mut self, node: &::tree_sitter::Node) -> V::ReturnType {
+                if let ::std::option::Option::Some((_, result)) = self.cache.get(&node.id()) {
+                    return ::std::clone::Clone::clone(result);
+                }
+                let result = self.inner.visit(node);
+                self.cache.insert(
+                    node.id(),
+                    (node.byte_range(), ::std::clone::Clone::clone(&result)),
+                );
+                result
+            }
+
+            /// Returns the cached result of visiting a node, if any.
+            pub fn cached(&self, node: &::tree_sitter::Node) -> ::std::option::Option<&V::ReturnType> {
+                self.cache.get(&node.id()).map(|(_, result)| result)
+            }
+
+            /// Returns whether the result of visiting a node is cached.
+            pub fn contains(&self, node: &::tree_sitter::Node) -> bool {
+                self.cache.contains_key(&node.id())
+            }
+
+            /// Returns the number of cached results.
+            pub fn len(&self) -> usize {
+                self.cache.len()
+            }
+
+            /// Returns whether no results are cached.
+            pub fn is_empty(&self) -> bool {
+                self.cache.is_empty()
+            }
+
+            /// Drops the cached result of a node.
+            pub fn invalidate(&mut self, node: &::tree_sitter::Node) {
+                self.cache.remove(&node.id());
+            }
+
+            /// Drops the cached results of the nodes overlapping or adjacent to a byte range,
+            /// including all the ancestors of the nodes in the range.
+            pub fn invalidate_range(&mut self, range: ::std::ops::Range<usize>) {
+                self.cache
+                    .retain(|_, (cached, _)| cached.end < range.start || cached.start > range.end);
+            }
+
+            /// Updates the cache for an edit of the tree, as passed to `Tree::edit`: drops the
+            /// results of the nodes overlapping or adjacent to the edited range, and shifts the
+            /// ranges of the nodes after it, whose results are kept.
+            pub fn edit(&mut self, edit: &::tree_sitter::InputEdit) {
+                self.invalidate_range(edit.start_byte..edit.old_end_byte);
+                for (range, _) in self.cache.values_mut() {
+                    if range.start > edit.old_end_byte {
+                        range.start = range.start - edit.old_end_byte + edit.new_end_byte;
+                        range.end = range.end - edit.old_end_byte + edit.new_end_byte;
+                    }
+                }
+            }
+
+            /// Drops all the cached results.
+            pub fn clear(&mut self) {
+                self.cache.clear();
+            }
+
+            /// Returns the wrapped visitor.
+            pub fn into_inner(self) -> V {
+                self.inner
+            }
+        }
+
+        /// Visits nodes through the cache, forwarding the other methods to the wrapped visitor.
+        /// `invalidate()` drops the cached result of the node as well.
+        impl<V> #trait_name for Memoized<V>
+        where
+            V: #trait_name,
+            V::ReturnType: ::std::clone::Clone,
+            #supertrait_predicate
+        {
+            type ReturnType = V::ReturnType;
+
+            fn visit(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
+                Memoized::visit(self, node)
+            }
+
+            #(#methods)*
+
+            #(#hook_methods)*
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Generation of partial handler traits ("mixins") and the combined visitor implementation
+//! dispatching each node kind to the mixin that claims it.
+
+use std::collections::HashMap;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ItemTrait;
+
+use crate::options::Options;
+use crate::{
+    fresh_type_param, method_return_type, supertrait_predicate, trait_params, trait_path,
+    trait_predicates, Node,
+};
+
+pub(crate) fn generate(
+    item: &ItemTrait,
+    node_types: &[Node],
+    options: &Options,
+) -> syn::Result<TokenStream> {
+    let vis = &item.vis;
+    let trait_name = &item.ident;
+    let base_name = format_ident!("{}Mixin", trait_name);
+    let supertrait_predicate = supertrait_predicate(item);
+    let trait_params = trait_params(item);
+    let trait_predicates = trait_predicates(item);
+    let trait_ref = trait_path(item, quote! {});
+    let m = &fresh_type_param(item, "M");
+    let return_type_item = options.return_type_item();
+    let context_item = options.context_item();
+    let context_impl = if options.context {
+        quote! { type Context = <#m as #base_name>::Context; }
+    } else {
+        quote! {}
+    };
+    let error_item = options.error_item();
+    let error_impl = if options.fallible {
+        quote! { type Error = <#m as #base_name>::Error; }
+    } else {
+        quote! {}
+    };
+
+    let mut claimed = HashMap::new();
+    for mixin in &options.mixins {
+        for kind in &mixin.kinds {
+            let value = kind.value();
+            if !node_types.iter().any(|node| node.r#type == value) {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    format!("unknown node kind `{}`", value),
+                ));
+            }
+            if let Some(other) = claimed.insert(value.clone(), &mixin.name) {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    format!(
+                        "node kind `{}` is claimed by both `{}` and `{}`",
+                        value, other, mixin.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    let unclaimed: Vec<_> = node_types
+        .iter()
+        .filter(|node| node.named && !claimed.contains_key(&node.r#type))
+        .map(|node| format!("`{}`", node.r#type))
+        .collect();
+    if !unclaimed.is_empty() {
+        return Err(syn::Error::new(
+            options.mixins[0].name.span(),
+            format!(
+                "named node kinds not claimed by any mixin: {}",
+                unclaimed.join(", ")
+            ),
+        ));
+    }
+
+    let param_decls = &options.param_decls();
+    let param_args = &options.param_args();
+    let asyncness = &options.asyncness();
+    let mixin_traits = options.mixins.iter().map(|mixin| {
+        let name = &mixin.name;
+        let methods = mixin.kinds.iter().map(|kind| {
+            let method_name = options.method_name(&kind.value());
+            let return_type =
+                method_return_type(options, &kind.value(), options.self_return_type());
+            let doc_string = format!("Visits a node of type `{:?}`", kind.value());
+            quote! {
+                #[doc = #doc_string]
+                #asyncness fn #method_name(&mut self, node: &::tree_sitter::Node<'_> #param_decls) -> #return_type;
+            }
+        });
+
+        let doc_string = format!("Partial handler for `{}`.", trait_name);
+        quote! {
+            #[doc = #doc_string]
+            #vis trait #name: #base_name {
+                #(#methods)*
+            }
+        }
+    });
+
+    let mixin_names = options.mixins.iter().map(|mixin| &mixin.name);
+    let forwarding_methods = options.mixins.iter().flat_map(|mixin| {
+        let name = &mixin.name;
+        mixin.kinds.iter().map(move |kind| {
+            let method_name = options.method_name(&kind.value());
+            let return_type =
+                method_return_type(options, &kind.value(), options.self_return_type());
+            let call = options.awaited(quote! { <#m as #name>::#method_name(self, node #param_args) });
+            quote! {
+                #asyncness fn #method_name(&mut self, node: &::tree_sitter::Node<'_> #param_decls) -> #return_type {
+                    #call
+                }
+            }
+        })
+    });
+
+    let base_doc_string = format!(
+        "The common base of all the partial handlers for `{}`.",
+        trait_name
+    );
+
+    Ok(quote! {
+        #[doc = #base_doc_string]
+        #vis trait #base_name {
+            #return_type_item
+            #context_item
+            #error_item
+        }
+
+        #(#mixin_traits)*
+
+        impl<#trait_params #m> #trait_ref for #m
+        where
+            #m: #(#mixin_names)+*,
+            #trait_predicates
+            #supertrait_predicate
+        {
+            type ReturnType = <#m as #base_name>::ReturnType;
+            #context_impl
+            #error_impl
+
+            #(#forwarding_methods)*
+        }
+    })
+}
@@ -0,0 +1,153 @@
+//! Generation of `NodeExt`, an extension trait of `Node` with a typed check and conversion for
+//! each named node kind and a membership check for each supertype.
+
+use std::collections::HashSet;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ItemTrait;
+
+use crate::node_types::{Node, NodeRef};
+use crate::options::Options;
+use crate::{sanitize_identifier, upper_camel_case};
+
+/// Collects the kinds a supertype stands for, descending into the supertypes among its subtypes.
+fn concrete_subtypes<'a>(
+    node_types: &'a [Node],
+    subtypes: &'a [NodeRef],
+    kinds: &mut Vec<&'a NodeRef>,
+) {
+    for subtype in subtypes {
+        let nested = node_types
+            .iter()
+            .find(|node| subtype.named && node.r#type == subtype.r#type && node.is_supertype());
+        match nested {
+            Some(nested) => concrete_subtypes(node_types, &nested.subtypes, kinds),
+            None if !kinds.contains(&subtype) => kinds.push(subtype),
+            None => {}
+        }
+    }
+}
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node], options: &Options) -> TokenStream {
+    let vis = &item.vis;
+    let named: Vec<_> = node_types
+        .iter()
+        .filter(|node| node.named && !node.is_supertype())
+        .collect();
+    let supertypes: Vec<_> = node_types
+        .iter()
+        .filter(|node| node.is_supertype())
+        .collect();
+
+    // Supertypes are named without their leading underscore (`is_expression()` for
+    // `_expression`), unless that clashes with the name of a kind.
+    let mut taken: HashSet<String> = named
+        .iter()
+        .map(|node| options.kind_name(&node.r#type))
+        .collect();
+    let supertype_names: Vec<_> = supertypes
+        .iter()
+        .map(|node| {
+            let name = options.kind_name(&node.r#type);
+            let stripped = name.trim_start_matches('_');
+            if !stripped.is_empty() && taken.insert(stripped.to_string()) {
+                stripped.to_string()
+            } else {
+                name
+            }
+        })
+        .collect();
+
+    let (kind_declarations, kind_methods): (Vec<_>, Vec<_>) = named
+        .iter()
+        .map(|node| {
+            let kind = &node.r#type;
+            let name = options.kind_name(kind);
+            let is_method = format_ident!("is_{}", name);
+            let as_method = format_ident!("as_{}", name);
+            let is_doc = format!("Returns `true` if the node is of kind `{}`.", kind);
+            let as_doc = format!("Returns the node if it is of kind `{}`.", kind);
+            // With `nodes`, the conversion returns the typed wrapper of the kind.
+            let (as_type, as_body) = if options.nodes {
+                let wrapper = format_ident!("{}", upper_camel_case(&sanitize_identifier(kind)));
+                (
+                    quote! { nodes::#wrapper<'tree> },
+                    quote! { ::std::convert::TryFrom::try_from(*self).ok() },
+                )
+            } else {
+                (
+                    quote! { ::tree_sitter::Node<'tree> },
+                    quote! { self.#is_method().then_some(*self) },
+                )
+            };
+            let declarations = quote! {
+                #[doc = #is_doc]
+                fn #is_method(&self) -> bool;
+
+                #[doc = #as_doc]
+                fn #as_method(&self) -> ::std::option::Option<#as_type>;
+            };
+            let methods = quote! {
+                fn #is_method(&self) -> bool {
+                    self.is_named() && self.kind() == #kind
+                }
+
+                fn #as_method(&self) -> ::std::option::Option<#as_type> {
+                    #as_body
+                }
+            };
+            (declarations, methods)
+        })
+        .unzip();
+
+    let (supertype_declarations, supertype_methods): (Vec<_>, Vec<_>) = supertypes
+        .iter()
+        .zip(&supertype_names)
+        .map(|(node, name)| {
+            let is_method = format_ident!("is_{}", name);
+            let doc_string = format!(
+                "Returns `true` if the node is of one of the kinds of the supertype `{}`.",
+                node.r#type
+            );
+            let mut kinds = Vec::new();
+            concrete_subtypes(node_types, &node.subtypes, &mut kinds);
+            let patterns = kinds.iter().map(|subtype| {
+                let kind = &subtype.r#type;
+                let named = subtype.named;
+                quote! { (#kind, #named) }
+            });
+            let declaration = quote! {
+                #[doc = #doc_string]
+                fn #is_method(&self) -> bool;
+            };
+            let method = quote! {
+                fn #is_method(&self) -> bool {
+                    matches!((self.kind(), self.is_named()), #(#patterns)|*)
+                }
+            };
+            (declaration, method)
+        })
+        .unzip();
+
+    let doc_string = format!(
+        "Typed checks of the kinds handled by `{}`, e.g. `node.is_add_expr()`, instead of comparing \
+         `node.kind()` with strings.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #vis trait NodeExt<'tree> {
+            #(#kind_declarations)*
+
+            #(#supertype_declarations)*
+        }
+
+        impl<'tree> NodeExt<'tree> for ::tree_sitter::Node<'tree> {
+            #(#kind_methods)*
+
+            #(#supertype_methods)*
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Generation of the `NodeKind` enum, with a variant for each node kind of the visitor trait, so
+//! node kinds can be matched exhaustively instead of comparing strings.
+
+use std::collections::HashSet;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Ident, ItemTrait};
+
+use crate::node_types::Node;
+use crate::{sanitize_identifier, upper_camel_case};
+
+/// Returns the names of the variants of `NodeKind`, in the order of `node_types`. Anonymous kinds
+/// whose name clashes with that of another kind, such as a keyword named like a rule, get a `Token`
+/// suffix.
+pub(crate) fn variant_names(node_types: &[Node]) -> Vec<Ident> {
+    let base_name = |node: &Node| {
+        let sanitized = sanitize_identifier(&node.r#type);
+        let name = upper_camel_case(&sanitized);
+        if name.is_empty() {
+            // Kinds made of underscores only, like the `_` wildcard of many grammars.
+            "Underscore".repeat(sanitized.len())
+        } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("Kind{}", name)
+        } else {
+            name
+        }
+    };
+    let mut taken: Vec<String> = node_types
+        .iter()
+        .filter(|node| node.named)
+        .map(base_name)
+        .chain(["Unknown".to_string()])
+        .collect();
+    node_types
+        .iter()
+        .map(|node| {
+            let name = base_name(node);
+            if node.named {
+                return format_ident!("{}", name);
+            }
+            let mut unique = name.clone();
+            let mut suffix = 1;
+            while taken.contains(&unique) {
+                unique = if suffix == 1 {
+                    format!("{}Token", name)
+                } else {
+                    format!("{}Token{}", name, suffix)
+                };
+                suffix += 1;
+            }
+            taken.push(unique.clone());
+            format_ident!("{}", unique)
+        })
+        .collect()
+}
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    let variants = variant_names(node_types);
+    let kinds: Vec<_> = node_types.iter().map(|node| &node.r#type).collect();
+    let named: Vec<_> = node_types.iter().map(|node| node.named).collect();
+    let docs = node_types.iter().map(|node| {
+        if node.is_supertype() {
+            format!(
+                "The supertype `{}`, which never appears in a tree.",
+                node.r#type
+            )
+        } else if node.named {
+            format!("A node of kind `{}`.", node.r#type)
+        } else {
+            let name = format!("{:?}", node.r#type).replace('`', "\\`");
+            format!("An anonymous node `{}`.", name)
+        }
+    });
+
+    // Named kinds come first, so that `from_str` prefers them over anonymous kinds with the same
+    // name.
+    let mut by_name: Vec<_> = variants.iter().zip(node_types).collect();
+    by_name.sort_by_key(|(_, node)| !node.named);
+    let mut seen = HashSet::new();
+    by_name.retain(|(_, node)| seen.insert(&node.r#type));
+    let (str_variants, str_kinds): (Vec<_>, Vec<_>) = by_name
+        .into_iter()
+        .map(|(variant, node)| (variant, &node.r#type))
+        .unzip();
+
+    let doc_string = format!(
+        "The kinds of the nodes handled by `{}`, with `Unknown` for any other kind.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #vis enum NodeKind {
+            #(
+                #[doc = #docs]
+                #variants,
+            )*
+            /// A node of a kind that is not in `node-types.json`.
+            Unknown,
+        }
+
+        impl NodeKind {
+            /// All the known node kinds, in the order of `node-types.json`.
+            pub const ALL: &'static [NodeKind] = &[#(NodeKind::#variants),*];
+
+            /// Returns the kind as returned by `Node::kind()`, or an empty string for `Unknown`.
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    #(NodeKind::#variants => #kinds,)*
+                    NodeKind::Unknown => "",
+                }
+            }
+
+            /// Returns whether nodes of the kind are named, `false` for `Unknown`.
+            pub fn is_named(self) -> bool {
+                match self {
+                    #(NodeKind::#variants => #named,)*
+                    NodeKind::Unknown => false,
+                }
+            }
+        }
+
+        impl ::std::convert::From<&::tree_sitter::Node<'_>> for NodeKind {
+            fn from(node: &::tree_sitter::Node<'_>) -> Self {
+                match (node.kind(), node.is_named()) {
+                    #((#kinds, #named) => NodeKind::#variants,)*
+                    _ => NodeKind::Unknown,
+                }
+            }
+        }
+
+        /// Parses a kind as returned by `Node::kind()`, preferring named kinds over anonymous ones
+        /// with the same name. Never fails, returning `Unknown` for any other string.
+        impl ::std::str::FromStr for NodeKind {
+            type Err = ::std::convert::Infallible;
+
+            fn from_str(kind: &str) -> ::std::result::Result<Self, Self::Err> {
+                ::std::result::Result::Ok(match kind {
+                    #(#str_kinds => NodeKind::#str_variants,)*
+                    _ => NodeKind::Unknown,
+                })
+            }
+        }
+
+        impl ::std::fmt::Display for NodeKind {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! The model of `node-types.json`, which describes the node types of a grammar.
+//!
+//! Unknown keys are ignored, so that files generated by newer versions of tree-sitter can still be
+//! loaded.
+
+use proc_macro2::Span;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::load_json;
+
+/// A node type of the grammar.
+#[derive(Deserialize)]
+pub(crate) struct Node {
+    /// The kind of the node, as returned by `Node::kind()`.
+    pub r#type: String,
+    /// Whether the node is named, i.e. corresponds to a rule rather than a string literal.
+    pub named: bool,
+    /// Whether the node is the root of the grammar. Only set by newer versions of tree-sitter.
+    #[serde(default)]
+    pub root: bool,
+    /// The children of the node by field name.
+    #[serde(default)]
+    pub fields: BTreeMap<String, ChildTypes>,
+    /// The named children of the node that are not in a field.
+    #[serde(default)]
+    pub children: Option<ChildTypes>,
+    /// The node types a supertype stands for, empty for other node types.
+    #[serde(default)]
+    pub subtypes: Vec<NodeRef>,
+    /// Whether the node can appear anywhere in the tree, like comments.
+    #[serde(default)]
+    pub extra: bool,
+    /// The languages that have the node type, when merging the node types of several languages
+    /// and not all of them have it.
+    #[serde(skip)]
+    pub languages: Vec<String>,
+}
+
+impl Node {
+    /// Returns `true` if this is a supertype, which never appears in a tree.
+    pub fn is_supertype(&self) -> bool {
+        !self.subtypes.is_empty()
+    }
+
+    /// Returns `true` if nodes of this type never have children: anonymous tokens, and named nodes
+    /// without fields or children that are not supertypes.
+    pub fn is_terminal(&self) -> bool {
+        !self.named || (self.fields.is_empty() && self.children.is_none() && !self.is_supertype())
+    }
+
+    /// Describes the fields and children of the node for documentation, as Markdown paragraphs
+    /// each starting with a blank line, or an empty string for terminal nodes.
+    pub fn shape_doc(&self) -> String {
+        let mut doc = String::new();
+        if self.is_supertype() {
+            let subtypes: Vec<_> = self.subtypes.iter().map(NodeRef::doc_name).collect();
+            doc.push_str(&format!("\n\nSubtypes: {}.", subtypes.join(", ")));
+            return doc;
+        }
+        if !self.fields.is_empty() {
+            doc.push_str("\n\nFields:\n");
+            for (field, child_types) in &self.fields {
+                doc.push_str(&format!("\n- `{}` {}", field, child_types.doc()));
+            }
+        }
+        if let Some(children) = &self.children {
+            doc.push_str(&format!("\n\nChildren not in a field {}.", children.doc()));
+        }
+        doc
+    }
+
+    /// Describes the languages that have the node type for documentation, as a Markdown paragraph
+    /// starting with a blank line, or an empty string if all of them have it.
+    pub fn languages_doc(&self) -> String {
+        if self.languages.is_empty() {
+            return String::new();
+        }
+        let languages: Vec<_> = self
+            .languages
+            .iter()
+            .map(|language| format!("`{}`", language))
+            .collect();
+        format!("\n\nOnly in {}.", languages.join(", "))
+    }
+
+    /// Merges the same node type of another language into this one.
+    fn merge(&mut self, other: Node) {
+        self.root |= other.root;
+        self.extra |= other.extra;
+        // Fields that only some of the languages have are optional.
+        for (field, child_types) in &mut self.fields {
+            if !other.fields.contains_key(field) {
+                child_types.required = false;
+            }
+        }
+        for (field, mut child_types) in other.fields {
+            match self.fields.get_mut(&field) {
+                Some(existing) => existing.merge(child_types),
+                None => {
+                    child_types.required = false;
+                    self.fields.insert(field, child_types);
+                }
+            }
+        }
+        self.children = match (self.children.take(), other.children) {
+            (Some(mut children), Some(other)) => {
+                children.merge(other);
+                Some(children)
+            }
+            (Some(mut children), None) | (None, Some(mut children)) => {
+                children.required = false;
+                Some(children)
+            }
+            (None, None) => None,
+        };
+        for subtype in other.subtypes {
+            if !self.subtypes.contains(&subtype) {
+                self.subtypes.push(subtype);
+            }
+        }
+    }
+}
+
+/// The kinds of nodes that can appear in a field or as children of a node.
+#[derive(Deserialize)]
+pub(crate) struct ChildTypes {
+    pub multiple: bool,
+    pub required: bool,
+    pub types: Vec<NodeRef>,
+}
+
+impl ChildTypes {
+    /// Merges the children of the same field or node type of another language into these.
+    fn merge(&mut self, other: ChildTypes) {
+        self.multiple |= other.multiple;
+        self.required &= other.required;
+        for node_ref in other.types {
+            if !self.types.contains(&node_ref) {
+                self.types.push(node_ref);
+            }
+        }
+    }
+
+    /// Describes the multiplicity and kinds of the children for documentation, e.g.
+    /// "(required, multiple): `a`, `b`".
+    fn doc(&self) -> String {
+        let kinds: Vec<_> = self.types.iter().map(NodeRef::doc_name).collect();
+        format!(
+            "({}{}): {}",
+            if self.required {
+                "required"
+            } else {
+                "optional"
+            },
+            if self.multiple { ", multiple" } else { "" },
+            kinds.join(", ")
+        )
+    }
+}
+
+/// A reference to a node type by its kind and whether it is named.
+#[derive(Deserialize, PartialEq, Eq)]
+pub(crate) struct NodeRef {
+    pub r#type: String,
+    pub named: bool,
+}
+
+impl NodeRef {
+    /// Returns the kind as inline code for documentation, quoted if it is anonymous.
+    fn doc_name(&self) -> String {
+        if self.named {
+            format!("`{}`", self.r#type)
+        } else {
+            let quoted = format!("{:?}", self.r#type).replace('`', "\\`");
+            format!("`{}`", quoted)
+        }
+    }
+}
+
+/// Loads `node-types.json` from a path resolved with `resolve_path()`, failing with an error at
+/// `span` if it cannot be read or parsed.
+pub(crate) fn load(path: &str, span: Span) -> syn::Result<Vec<Node>> {
+    load_json(path, span)
+}
+
+/// Merges the node types of several languages, given by name, into their union, in the order in
+/// which they first appear. Node types that several languages have get the union of their fields,
+/// children and subtypes, and node types that only some languages have list these languages.
+pub(crate) fn merge(languages: Vec<(String, Vec<Node>)>) -> Vec<Node> {
+    let count = languages.len();
+    let mut merged: Vec<Node> = Vec::new();
+    for (language, node_types) in languages {
+        for mut node in node_types {
+            let existing = merged
+                .iter_mut()
+                .find(|existing| existing.r#type == node.r#type && existing.named == node.named);
+            match existing {
+                Some(existing) => {
+                    existing.merge(node);
+                    existing.languages.push(language.clone());
+                }
+                None => {
+                    node.languages.push(language.clone());
+                    merged.push(node);
+                }
+            }
+        }
+    }
+    for node in &mut merged {
+        if node.languages.len() == count {
+            node.languages.clear();
+        }
+    }
+    merged
+}
+
+/// Returns the first supertype that lists the given node type among its subtypes, if any.
+pub(crate) fn supertype_of<'a>(node: &Node, node_types: &'a [Node]) -> Option<&'a Node> {
+    node_types.iter().find(|supertype| {
+        supertype
+            .subtypes
+            .iter()
+            .any(|subtype| subtype.r#type == node.r#type && subtype.named == node.named)
+    })
+}
+
+/// Expands supertypes among the given node types into their concrete subtypes, recursively.
+pub(crate) fn concrete_types<'a>(types: &'a [NodeRef], node_types: &'a [Node]) -> Vec<&'a NodeRef> {
+    let mut result = Vec::new();
+    for node_ref in types {
+        let supertype = node_types.iter().find(|node| {
+            node.r#type == node_ref.r#type && node.named == node_ref.named && node.is_supertype()
+        });
+        match supertype {
+            Some(supertype) => result.extend(concrete_types(&supertype.subtypes, node_types)),
+            None => result.push(node_ref),
+        }
+    }
+    result
+}
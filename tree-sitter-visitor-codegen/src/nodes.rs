@@ -0,0 +1,111 @@
+//! Generation of the `nodes` module, with a newtype wrapper around `Node` for each named node kind
+//! exposing typed accessors for its fields.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+use crate::{field_ident, sanitize_identifier, upper_camel_case};
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+
+    // Supertypes never appear in a tree, so there is nothing to wrap.
+    let wrappers = node_types
+        .iter()
+        .filter(|node| node.named && !node.is_supertype())
+        .map(|node| {
+            let kind = &node.r#type;
+            let name = format_ident!("{}", upper_camel_case(&sanitize_identifier(kind)));
+            let doc_string = format!("A node of kind `{}`.", kind);
+
+            let accessors = node.fields.iter().map(|(field, child_types)| {
+                let accessor = field_ident(field);
+                if child_types.multiple {
+                    let doc_string = format!("Returns the children in the `{}` field.", field);
+                    quote! {
+                        #[doc = #doc_string]
+                        pub fn #accessor(&self) -> ::std::vec::Vec<::tree_sitter::Node<'tree>> {
+                            let mut cursor = self.0.walk();
+                            let mut children = ::std::vec::Vec::new();
+                            children.extend(self.0.children_by_field_name(#field, &mut cursor));
+                            children
+                        }
+                    }
+                } else {
+                    let doc_string = format!(
+                        "Returns the child in the `{}` field, if {}.",
+                        field,
+                        if child_types.required {
+                            "the tree has no errors"
+                        } else {
+                            "it is present"
+                        }
+                    );
+                    quote! {
+                        #[doc = #doc_string]
+                        pub fn #accessor(&self) -> ::std::option::Option<::tree_sitter::Node<'tree>> {
+                            self.0.child_by_field_name(#field)
+                        }
+                    }
+                }
+            });
+
+            quote! {
+                #[doc = #doc_string]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+                pub struct #name<'tree>(::tree_sitter::Node<'tree>);
+
+                impl<'tree> #name<'tree> {
+                    /// The kind of the wrapped nodes.
+                    pub const KIND: &'static str = #kind;
+
+                    /// Returns the wrapped node.
+                    pub fn as_node(&self) -> &::tree_sitter::Node<'tree> {
+                        &self.0
+                    }
+
+                    /// Unwraps the node.
+                    pub fn into_node(self) -> ::tree_sitter::Node<'tree> {
+                        self.0
+                    }
+
+                    #(#accessors)*
+                }
+
+                /// Wraps a node of the right kind, or returns the node back otherwise.
+                impl<'tree> ::std::convert::TryFrom<::tree_sitter::Node<'tree>> for #name<'tree> {
+                    type Error = ::tree_sitter::Node<'tree>;
+
+                    fn try_from(
+                        node: ::tree_sitter::Node<'tree>,
+                    ) -> ::std::result::Result<Self, Self::Error> {
+                        if node.is_named() && node.kind() == #kind {
+                            ::std::result::Result::Ok(#name(node))
+                        } else {
+                            ::std::result::Result::Err(node)
+                        }
+                    }
+                }
+
+                impl<'tree> ::std::convert::From<#name<'tree>> for ::tree_sitter::Node<'tree> {
+                    fn from(wrapper: #name<'tree>) -> Self {
+                        wrapper.0
+                    }
+                }
+            }
+        });
+
+    let doc_string = format!(
+        "Typed wrappers around the named nodes handled by `{}`, with accessors for their fields.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #vis mod nodes {
+            #(#wrappers)*
+        }
+    }
+}
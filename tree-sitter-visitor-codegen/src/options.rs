@@ -0,0 +1,1519 @@
+//! Parsing of the `#[visitor_trait(...)]` attribute arguments.
+//!
+//! The first argument is always the path to `node-types.json`, followed by an optional, comma-separated
+//! list of options. Options are either bare flags (`text_provider`) or `key = value` pairs.
+
+use crate::config::Config;
+use crate::field_hooks;
+use crate::flavors::FLAVORS;
+use crate::node_types::{self, Node};
+use crate::{sanitize_identifier, snake_case, tracked_file};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use regex::Regex;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{FnArg, Ident, LitBool, LitStr, Pat, Token, Type, TypeParamBound};
+
+#[derive(Default)]
+pub(crate) struct Options {
+    /// Path to `node-types.json`, resolved with `resolve_path()`, with environment variables
+    /// expanded, or read from the environment variable given with `env`.
+    pub path: String,
+    /// Languages whose `node-types.json` files are merged, given instead of `path`.
+    pub languages: Vec<LanguagePath>,
+    /// Path to a TOML file with further options, resolved with `resolve_path()`.
+    pub config: Option<LitStr>,
+    /// Generate an `after_visit` hook called after every dispatch.
+    pub after_visit: bool,
+    /// Generate a `TextProvider` trait for reading node text from different source representations.
+    pub text_provider: bool,
+    /// Also implement `TextProvider` for `ropey::Rope` and `ropey::RopeSlice`.
+    pub ropey: bool,
+    /// Generate a `Session` type for incremental reparsing and re-visiting.
+    pub session: bool,
+    /// Generate a `Watcher` reparsing and revisiting files when they change, using `notify`.
+    pub notify: bool,
+    /// Generate a `visit_batch` driver for visiting many trees with shared state.
+    pub batch: bool,
+    /// Generate an `OwnedParse` type owning a source string and its tree.
+    pub owned_parse: bool,
+    /// Generate a `CatchUnimplemented` wrapper recording nodes reaching default stubs.
+    pub catch_unimplemented: bool,
+    /// Generate a `ChainedVisitor` and track the methods overridden by each implementation.
+    pub chain: bool,
+    /// Generate a `FieldName` enum and a `FieldChildren` iterator over `(field, child)` pairs.
+    pub field_children: bool,
+    /// Generate `visit_field_<name>()` hooks and a `visit_fields()` dispatch by field name.
+    pub field_hooks: bool,
+    /// Generate a `CoverageTracker` counting the methods dispatched to by `visit()`.
+    pub coverage: bool,
+    /// Generate a `Memoized` wrapper caching the results of `visit()` by node id.
+    pub memoize: bool,
+    /// Generate a `Fields` struct with the field ids resolved from a `Language`.
+    pub fields: bool,
+    /// Generate a `nodes` module of typed wrappers with field accessors.
+    pub nodes: bool,
+    /// Generate an `accessors` module with a function per field of each node kind.
+    pub accessors: bool,
+    /// Generate a `NodeExt` trait with typed kind checks and conversions for `Node`.
+    pub node_ext: bool,
+    /// Generate a `supertypes` module with an enum of the subtypes of each supertype.
+    pub subtype_enums: bool,
+    /// Path to a profile of node kind frequencies, resolved with `resolve_path()`, used to order
+    /// the dispatch.
+    pub profile: Option<LitStr>,
+    /// Path to `grammar.json`, resolved with `resolve_path()`, used to document precedence and
+    /// aliases and to dispatch external tokens to a hook.
+    pub grammar: Option<LitStr>,
+    /// Hash that `node-types.json` is expected to have, failing the expansion if it differs.
+    pub expected_hash: Option<LitStr>,
+    /// Node kinds to generate code for, besides those matching `include_pattern`. All the kinds
+    /// are included if neither is given.
+    pub include: Vec<LitStr>,
+    /// Pattern of the node kinds to generate code for.
+    pub include_pattern: Option<Regex>,
+    /// Node kinds to leave out.
+    pub exclude: Vec<LitStr>,
+    /// Pattern of the node kinds to leave out.
+    pub exclude_pattern: Option<Regex>,
+    /// Leave out the anonymous node kinds.
+    pub named_only: bool,
+    /// Dispatch anonymous nodes to a `visit_token()` hook. Implies `named_only`.
+    pub token_hook: bool,
+    /// Dispatch `ERROR` and `MISSING` nodes to `visit_error()` and `visit_missing()`.
+    pub error_hooks: bool,
+    /// Make the methods of subtypes forward to the method of their supertype by default.
+    pub supertypes: bool,
+    /// Generate `visit_named()` and `visit_token()` entry points.
+    pub named_dispatch: bool,
+    /// Generate a `NodeCorrespondence` pairing nodes before and after an incremental reparse.
+    pub correspondence: bool,
+    /// Generate `revisit_changed()`, visiting only what changed after an edit.
+    pub revisit: bool,
+    /// Generate `visit_parallel()`, visiting the top-level children of a tree with rayon.
+    pub parallel: bool,
+    /// Wrap each dispatch of `visit()` in a `tracing` span.
+    pub tracing: bool,
+    /// Generate a `NodeFingerprint` identifying nodes across reparses.
+    pub fingerprint: bool,
+    /// Generate an `FnVisitor` implementing the trait with closures.
+    pub fn_visitor: bool,
+    /// Generate an object-safe `Dyn<Trait>` counterpart of the trait.
+    pub dyn_trait: bool,
+    /// Generate a `MultiVisitor` running several visitors at once and a `MappedVisitor`.
+    pub combinators: bool,
+    /// Generate an emitter trait whose methods stream items into a sink.
+    pub emitter: bool,
+    /// Generate an enum dispatching to a closed set of visitor types.
+    pub enum_dispatch: Option<EnumDispatch>,
+    /// Generate a `grammar` module describing the node types as static data.
+    pub introspection: bool,
+    /// Generate a `KindTable` and a `visit_by_id` dispatch on numeric kind ids.
+    pub kind_table: bool,
+    /// Dispatch on numeric kind ids through a `KindTable` instead of on kind strings.
+    pub kind_id_dispatch: bool,
+    /// Generate `check_language()` comparing the node kinds with those of a `Language`.
+    pub language_check: bool,
+    /// Generate a `NodeKind` enum of the node kinds.
+    pub node_kind: bool,
+    /// Dispatch on the `NodeKind` of nodes instead of on kind strings.
+    pub node_kind_dispatch: bool,
+    /// Dispatch on the grammar name of nodes, so aliased nodes are visited as the rule they were parsed from.
+    pub grammar_name_dispatch: bool,
+    /// Generate a `kinds` module with a constant for the string of every node kind.
+    pub kinds: bool,
+    /// Generate a `KindFrequencies` analysis counting the node kinds in a corpus.
+    pub kind_frequencies: bool,
+    /// Generate an `HtmlRenderer` rendering highlighted source to HTML.
+    pub html: bool,
+    /// Generate a `SourceMapBuilder` mapping generated code back to the source.
+    pub source_map: bool,
+    /// Generate a `SymbolTableInterner` interning the text of nodes.
+    pub interner: bool,
+    /// Generate an `assert_tree!` macro comparing trees with an expected shape.
+    pub assert_tree: bool,
+    /// Generate a `SexpPrinter` rendering trees as indented S-expressions.
+    pub sexp_printer: bool,
+    /// Generate a `SerializableNode` serializing trees with serde.
+    pub serialize: bool,
+    /// Generate a `DynamicLanguage` loading grammars from shared libraries with `libloading`.
+    pub libloading: bool,
+    /// Generate sibling navigation helpers skipping extras.
+    pub siblings: bool,
+    /// Generate a visitor trait for the terminal node kinds only.
+    pub token_visitor: bool,
+    /// Generate `visit_children()` helpers visiting the children of a node.
+    pub visit_children: bool,
+    /// Generate `try_visit_children()` helpers for visitors returning `Result`.
+    pub try_visit_children: bool,
+    /// Generate `visit_cursor()` and methods visiting the node a `TreeCursor` points at.
+    pub cursor: bool,
+    /// Generate a `Walker` driving a visitor over all the nodes of a tree.
+    pub walker: bool,
+    /// Generate a `visit_captures()` driver visiting the nodes captured by a query.
+    pub query_captures: bool,
+    /// Further flavors of the visitor trait to generate, e.g. `immutable`.
+    pub flavors: Vec<Ident>,
+    /// What the dispatch does with nodes of kinds that are not in `node-types.json`.
+    pub unknown: UnknownKinds,
+    /// How the dispatch and the child iteration helpers treat extras, such as comments.
+    pub extras: ExtraNodes,
+    /// The prefix of the names of the methods of node kinds, `visit_` by default.
+    pub prefix: Option<LitStr>,
+    /// How node kinds are turned into the names of their methods.
+    pub case: NamingCase,
+    /// Node kinds mapped to methods of the trait body with `#[visit(...)]`, filled in from the
+    /// trait rather than parsed from the arguments.
+    pub mappings: Vec<(String, Ident)>,
+    /// Extra supertraits of the generated trait.
+    pub supertraits: Vec<TypeParamBound>,
+    /// Bounds on the `ReturnType` associated type.
+    pub return_bounds: Vec<TypeParamBound>,
+    /// The type returned by the visit methods, replacing the `ReturnType` associated type.
+    pub preset_return_type: Option<Type>,
+    /// The type parameter of the trait returned by the visit methods, replacing the `ReturnType`
+    /// associated type.
+    pub return_type_param: Option<Ident>,
+    /// Pass a `ctx: &mut Self::Context` parameter to every visit method, before `params`.
+    pub context: bool,
+    /// Make every visit method an `async fn`, declared with `async`.
+    pub asynchronous: bool,
+    /// Make every visit method return `Result<_, Self::Error>`, with the default implementations
+    /// returning an `UnimplementedNode` error instead of panicking.
+    pub fallible: bool,
+    /// Make the default implementations return `ReturnType::default()` instead of panicking.
+    pub defaults: bool,
+    /// Take nodes by value as `Node<'tree>`, with a `'tree` lifetime parameter on the trait.
+    pub by_value: bool,
+    /// Extra parameters appended to the signature of every visit method.
+    pub params: Vec<Param>,
+    /// Return types of particular methods, overriding `ReturnType`.
+    pub return_types: Vec<ReturnTypeOverride>,
+    /// Kinds of a previous version of the grammar for which to generate deprecated shims.
+    pub renames: Vec<Rename>,
+    /// Partial handler traits, each claiming a set of node kinds.
+    pub mixins: Vec<KindSet>,
+    /// Sets of node kinds visited by a single generated method each, declared with `group(...)`.
+    pub groups: Vec<KindSet>,
+    /// Generate `ancestors()` and `enclosing()` helpers.
+    pub ancestors: bool,
+    /// Kind sets for which to generate `nearest_enclosing_<name>()` helpers.
+    pub enclosing: Vec<KindSet>,
+}
+
+/// The options generating items that implement the visitor trait or call its visit methods, which
+/// rely on the plain signature of the visit methods.
+const VISIT_CALLERS: &[&str] = &[
+    "session",
+    "notify",
+    "batch",
+    "owned_parse",
+    "fn_visitor",
+    "dyn_trait",
+    "combinators",
+    "catch_unimplemented",
+    "chain",
+    "coverage",
+    "memoize",
+    "walker",
+    "query_captures",
+    "enum_dispatch",
+];
+
+/// The options that cannot be combined, each with the groups of options it conflicts with.
+const CONFLICTS: &[(&str, &[&[&str]])] = &[
+    ("node_kind_dispatch", &[&["kind_id_dispatch"]]),
+    (
+        "grammar_name_dispatch",
+        &[&["kind_id_dispatch", "node_kind_dispatch"]],
+    ),
+    (
+        "return_type",
+        &[
+            &["return_bounds"],
+            VISIT_CALLERS,
+            &["try_visit_children", "mixins", "flavors"],
+        ],
+    ),
+    (
+        "return_type_param",
+        &[
+            &["return_type"],
+            VISIT_CALLERS,
+            &["try_visit_children", "mixins", "flavors"],
+        ],
+    ),
+    (
+        "params",
+        &[&[
+            "session",
+            "notify",
+            "batch",
+            "owned_parse",
+            "fn_visitor",
+            "dyn_trait",
+            "combinators",
+            "catch_unimplemented",
+            "memoize",
+        ]],
+    ),
+    ("context", &[VISIT_CALLERS]),
+    ("fallible", &[&["after_visit"], VISIT_CALLERS]),
+    (
+        "async",
+        &[
+            &["after_visit"],
+            VISIT_CALLERS,
+            &["visit_children", "try_visit_children", "tracing"],
+        ],
+    ),
+    ("parallel", &[&["async", "params", "context", "fn_visitor"]]),
+    (
+        "by_value",
+        &[
+            &["async"],
+            VISIT_CALLERS,
+            &[
+                "visit_children",
+                "try_visit_children",
+                "kind_id_dispatch",
+                "kind_table",
+                "mixins",
+                "flavors",
+            ],
+        ],
+    ),
+    (
+        "return_types",
+        &[&[
+            "fn_visitor",
+            "dyn_trait",
+            "combinators",
+            "catch_unimplemented",
+        ]],
+    ),
+];
+
+/// A hook method the options add to the trait, which the generated implementations wrapping
+/// other visitors forward to them.
+pub(crate) struct Hook {
+    pub name: Ident,
+    pub signature: HookSignature,
+}
+
+/// The signature of a hook method.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HookSignature {
+    /// `(node)`, returning the result of a visit method, like the methods of the node kinds.
+    Node,
+    /// `(node, kind)`, returning the result of a visit method, like `visit_token()`.
+    Token,
+    /// `(parent, node)`, returning the result of a visit method, like `visit_field_<name>()`.
+    Field,
+    /// `(node)`, returning the results of visiting children, like `visit_fields()`.
+    Children,
+    /// `(node, result)`, returning nothing, like `after_visit()`.
+    AfterVisit,
+    /// `(node)`, returning nothing, like `invalidate()`.
+    Invalidate,
+    /// `()` taking `&self`, returning a new visitor, like `split()`.
+    Split,
+}
+
+impl Hook {
+    /// Returns an implementation of the hook for a type wrapping other visitors, whose visit
+    /// methods return `return_type`. `call` is given the arguments of the hook and returns the
+    /// body forwarding them, which is awaited with `async`.
+    pub fn forward(
+        &self,
+        options: &Options,
+        return_type: &TokenStream,
+        call: impl FnOnce(TokenStream) -> TokenStream,
+    ) -> TokenStream {
+        let name = &self.name;
+        let param_decls = options.param_decls();
+        let param_args = options.param_args();
+        let result_type = options.result_type(return_type.clone());
+        let asyncness = options.asyncness();
+        let (params, args, output) = match self.signature {
+            HookSignature::Node => (
+                quote! { node: &::tree_sitter::Node #param_decls },
+                quote! { node #param_args },
+                result_type,
+            ),
+            HookSignature::Token => (
+                quote! { node: &::tree_sitter::Node, kind: &str #param_decls },
+                quote! { node, kind #param_args },
+                result_type,
+            ),
+            HookSignature::Field => (
+                quote! { parent: &::tree_sitter::Node, node: &::tree_sitter::Node #param_decls },
+                quote! { parent, node #param_args },
+                result_type,
+            ),
+            HookSignature::Children => (
+                quote! { node: &::tree_sitter::Node #param_decls },
+                quote! { node #param_args },
+                quote! { ::std::vec::Vec<#result_type> },
+            ),
+            // These are never `async`.
+            HookSignature::AfterVisit => {
+                let body = call(quote! { node, result });
+                return quote! {
+                    fn #name(&mut self, node: &::tree_sitter::Node, result: &#return_type) {
+                        #body
+                    }
+                };
+            }
+            HookSignature::Invalidate => {
+                let body = call(quote! { node });
+                return quote! {
+                    fn #name(&mut self, node: &::tree_sitter::Node) {
+                        #body
+                    }
+                };
+            }
+            HookSignature::Split => {
+                let body = call(quote! {});
+                return quote! {
+                    fn #name(&self) -> Self {
+                        #body
+                    }
+                };
+            }
+        };
+        let body = options.awaited(call(args));
+        quote! {
+            #asyncness fn #name(&mut self, #params) -> #output {
+                #body
+            }
+        }
+    }
+}
+
+/// How extras, such as comments, are treated, declared with `extras = "normal" | "skip" | "hook"`.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum ExtraNodes {
+    /// Like any other node, the default.
+    #[default]
+    Normal,
+    /// Left out by the child iteration helpers.
+    Skip,
+    /// Dispatched to the overridable `visit_extra()` method, which panics by default.
+    Hook,
+}
+
+impl ExtraNodes {
+    /// Parses the value of the `extras` option.
+    pub fn parse(value: &LitStr) -> syn::Result<Self> {
+        match value.value().as_str() {
+            "normal" => Ok(ExtraNodes::Normal),
+            "skip" => Ok(ExtraNodes::Skip),
+            "hook" => Ok(ExtraNodes::Hook),
+            other => Err(syn::Error::new(
+                value.span(),
+                format!(
+                    "unknown value `{}` for `extras`, expected `normal`, `skip` or `hook`",
+                    other
+                ),
+            )),
+        }
+    }
+}
+
+/// What the dispatch does with nodes of kinds that are not in `node-types.json`, declared with
+/// `unknown = "panic" | "skip" | "hook"`.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum UnknownKinds {
+    /// Panic, the default.
+    #[default]
+    Panic,
+    /// Return `ReturnType::default()`.
+    Skip,
+    /// Call the overridable `visit_unknown()` method, which panics by default.
+    Hook,
+}
+
+impl UnknownKinds {
+    /// Parses the value of the `unknown` option.
+    pub fn parse(value: &LitStr) -> syn::Result<Self> {
+        match value.value().as_str() {
+            "panic" => Ok(UnknownKinds::Panic),
+            "skip" => Ok(UnknownKinds::Skip),
+            "hook" => Ok(UnknownKinds::Hook),
+            other => Err(syn::Error::new(
+                value.span(),
+                format!(
+                    "unknown value `{}` for `unknown`, expected `panic`, `skip` or `hook`",
+                    other
+                ),
+            )),
+        }
+    }
+}
+
+/// How node kinds are turned into the names of their methods, declared with
+/// `case = "preserve" | "snake"`.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum NamingCase {
+    /// Keep the kind as it is, the default.
+    #[default]
+    Preserve,
+    /// Convert `CamelCase` kinds to `snake_case`.
+    Snake,
+}
+
+impl NamingCase {
+    /// Parses the value of the `case` option.
+    pub fn parse(value: &LitStr) -> syn::Result<Self> {
+        match value.value().as_str() {
+            "preserve" => Ok(NamingCase::Preserve),
+            "snake" => Ok(NamingCase::Snake),
+            other => Err(syn::Error::new(
+                value.span(),
+                format!(
+                    "unknown value `{}` for `case`, expected `preserve` or `snake`",
+                    other
+                ),
+            )),
+        }
+    }
+}
+
+/// An enum over visitor types declared with `enum_dispatch(Name = [Type, ...])`.
+pub(crate) struct EnumDispatch {
+    pub name: Ident,
+    pub variants: Vec<Type>,
+}
+
+impl Parse for EnumDispatch {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let content;
+        syn::bracketed!(content in input);
+        let variants = Punctuated::<Type, Token![,]>::parse_terminated(&content)?
+            .into_iter()
+            .collect();
+
+        Ok(EnumDispatch { name, variants })
+    }
+}
+
+/// A named set of node kinds declared as `Name = ["kind", ...]`, e.g. a partial handler trait in
+/// `mixins(...)`.
+pub(crate) struct KindSet {
+    pub name: Ident,
+    pub kinds: Vec<LitStr>,
+}
+
+impl Parse for KindSet {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let kinds = parse_str_list(input)?;
+
+        Ok(KindSet { name, kinds })
+    }
+}
+
+/// The `node-types.json` of a language declared with `languages(name = "path", ...)`, with
+/// environment variables in the path expanded.
+pub(crate) struct LanguagePath {
+    pub name: Ident,
+    pub path: String,
+}
+
+impl Parse for LanguagePath {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = Ident::parse_any(input)?;
+        input.parse::<Token![=]>()?;
+        let path = expand_env(&input.parse()?)?;
+
+        Ok(LanguagePath { name, path })
+    }
+}
+
+/// A return type declared for the method visiting a node kind with `return_types(kind = Type)`.
+/// The kind is either an identifier or a string literal.
+pub(crate) struct ReturnTypeOverride {
+    pub kind: LitStr,
+    pub ty: Type,
+}
+
+impl Parse for ReturnTypeOverride {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kind = parse_kind(input)?;
+        input.parse::<Token![=]>()?;
+        let ty = input.parse()?;
+
+        Ok(ReturnTypeOverride { kind, ty })
+    }
+}
+
+/// A node kind of a previous version of the grammar renamed to a kind of the current one, declared
+/// with `renames(old = "new")`. The old kind is either an identifier or a string literal.
+pub(crate) struct Rename {
+    pub old: LitStr,
+    pub new: LitStr,
+}
+
+impl Parse for Rename {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let old = parse_kind(input)?;
+        input.parse::<Token![=]>()?;
+        let new = input.parse()?;
+
+        Ok(Rename { old, new })
+    }
+}
+
+/// An extra parameter of the visit methods declared with `params(name: Type, ...)`.
+pub(crate) struct Param {
+    pub name: Ident,
+    pub ty: Type,
+}
+
+impl Parse for Param {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let arg: FnArg = input.parse()?;
+        let pat_type = match arg {
+            FnArg::Typed(pat_type) => pat_type,
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new_spanned(receiver, "expected `name: Type`"))
+            }
+        };
+        let name = match *pat_type.pat {
+            Pat::Ident(pat_ident) if pat_ident.ident != "node" => pat_ident.ident,
+            pat => {
+                return Err(syn::Error::new_spanned(
+                    pat,
+                    "expected a parameter name other than `node`",
+                ))
+            }
+        };
+
+        Ok(Param {
+            name,
+            ty: *pat_type.ty,
+        })
+    }
+}
+
+impl Parse for Options {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Self::parse_with(input, |_, _| Ok(false))
+    }
+}
+
+impl Options {
+    /// Parses the path and the options, passing any option not recognized here to `parse_extra`,
+    /// which should return `Ok(true)` if it consumed the option.
+    pub fn parse_with(
+        input: ParseStream,
+        mut parse_extra: impl FnMut(&Ident, ParseStream) -> syn::Result<bool>,
+    ) -> syn::Result<Self> {
+        let mut options = Options::default();
+        // The path may be omitted in favor of the one in the configuration file.
+        let mut first = true;
+        if input.peek(LitStr) {
+            options.path = expand_env(&input.parse()?)?;
+            first = false;
+        }
+
+        while !input.is_empty() {
+            if !first {
+                input.parse::<Token![,]>()?;
+                if input.is_empty() {
+                    break;
+                }
+            }
+            first = false;
+
+            let key = Ident::parse_any(input)?;
+            match key.to_string().as_str() {
+                "after_visit" => options.after_visit = parse_flag(input)?,
+                "text_provider" => options.text_provider = parse_flag(input)?,
+                "ropey" => options.ropey = parse_flag(input)?,
+                "session" => options.session = parse_flag(input)?,
+                "notify" => options.notify = parse_flag(input)?,
+                "batch" => options.batch = parse_flag(input)?,
+                "owned_parse" => options.owned_parse = parse_flag(input)?,
+                "catch_unimplemented" => options.catch_unimplemented = parse_flag(input)?,
+                "chain" => options.chain = parse_flag(input)?,
+                "coverage" => options.coverage = parse_flag(input)?,
+                "memoize" => options.memoize = parse_flag(input)?,
+                "field_children" => options.field_children = parse_flag(input)?,
+                "field_hooks" => options.field_hooks = parse_flag(input)?,
+                "fields" => options.fields = parse_flag(input)?,
+                "nodes" => options.nodes = parse_flag(input)?,
+                "accessors" => options.accessors = parse_flag(input)?,
+                "node_ext" => options.node_ext = parse_flag(input)?,
+                "subtype_enums" => options.subtype_enums = parse_flag(input)?,
+                "env" => {
+                    input.parse::<Token![=]>()?;
+                    let var: LitStr = input.parse()?;
+                    if !options.path.is_empty() {
+                        return Err(syn::Error::new(
+                            var.span(),
+                            "`env` cannot be combined with a path to `node-types.json`",
+                        ));
+                    }
+                    options.path = env_path(&var)?;
+                }
+                "config" => {
+                    input.parse::<Token![=]>()?;
+                    options.config = Some(input.parse()?);
+                }
+                "profile" => {
+                    input.parse::<Token![=]>()?;
+                    options.profile = Some(input.parse()?);
+                }
+                "grammar" => {
+                    input.parse::<Token![=]>()?;
+                    options.grammar = Some(input.parse()?);
+                }
+                "expected_hash" => {
+                    input.parse::<Token![=]>()?;
+                    options.expected_hash = Some(input.parse()?);
+                }
+                "include" => {
+                    input.parse::<Token![=]>()?;
+                    options.include.extend(parse_str_list(input)?);
+                }
+                "exclude" => {
+                    input.parse::<Token![=]>()?;
+                    options.exclude.extend(parse_str_list(input)?);
+                }
+                "include_pattern" => {
+                    input.parse::<Token![=]>()?;
+                    options.include_pattern = Some(parse_pattern(&input.parse()?)?);
+                }
+                "exclude_pattern" => {
+                    input.parse::<Token![=]>()?;
+                    options.exclude_pattern = Some(parse_pattern(&input.parse()?)?);
+                }
+                "named_only" => options.named_only = parse_flag(input)?,
+                "token_hook" => options.token_hook = parse_flag(input)?,
+                "error_hooks" => options.error_hooks = parse_flag(input)?,
+                "supertypes" => options.supertypes = parse_flag(input)?,
+                "named_dispatch" => options.named_dispatch = parse_flag(input)?,
+                "correspondence" => options.correspondence = parse_flag(input)?,
+                "revisit" => options.revisit = parse_flag(input)?,
+                "parallel" => options.parallel = parse_flag(input)?,
+                "tracing" => options.tracing = parse_flag(input)?,
+                "fingerprint" => options.fingerprint = parse_flag(input)?,
+                "fn_visitor" => options.fn_visitor = parse_flag(input)?,
+                "dyn_trait" => options.dyn_trait = parse_flag(input)?,
+                "combinators" => options.combinators = parse_flag(input)?,
+                "emitter" => options.emitter = parse_flag(input)?,
+                "enum_dispatch" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    options.enum_dispatch = Some(content.parse()?);
+                }
+                "introspection" => options.introspection = parse_flag(input)?,
+                "kind_table" => options.kind_table = parse_flag(input)?,
+                "kind_id_dispatch" => options.kind_id_dispatch = parse_flag(input)?,
+                "language_check" => options.language_check = parse_flag(input)?,
+                "node_kind" => options.node_kind = parse_flag(input)?,
+                "node_kind_dispatch" => options.node_kind_dispatch = parse_flag(input)?,
+                "grammar_name_dispatch" => options.grammar_name_dispatch = parse_flag(input)?,
+                "kinds" => options.kinds = parse_flag(input)?,
+                "kind_frequencies" => options.kind_frequencies = parse_flag(input)?,
+                "html" => options.html = parse_flag(input)?,
+                "source_map" => options.source_map = parse_flag(input)?,
+                "interner" => options.interner = parse_flag(input)?,
+                "assert_tree" => options.assert_tree = parse_flag(input)?,
+                "sexp_printer" => options.sexp_printer = parse_flag(input)?,
+                "serialize" => options.serialize = parse_flag(input)?,
+                "libloading" => options.libloading = parse_flag(input)?,
+                "siblings" => options.siblings = parse_flag(input)?,
+                "token_visitor" => options.token_visitor = parse_flag(input)?,
+                "visit_children" => options.visit_children = parse_flag(input)?,
+                "try_visit_children" => options.try_visit_children = parse_flag(input)?,
+                "cursor" => options.cursor = parse_flag(input)?,
+                "walker" => options.walker = parse_flag(input)?,
+                "query_captures" => options.query_captures = parse_flag(input)?,
+                "ancestors" => options.ancestors = parse_flag(input)?,
+                "enclosing" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    options
+                        .enclosing
+                        .extend(Punctuated::<KindSet, Token![,]>::parse_terminated(
+                            &content,
+                        )?);
+                }
+                "flavors" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    for flavor in Punctuated::<Ident, Token![,]>::parse_terminated(&content)? {
+                        options.add_flavor(flavor)?;
+                    }
+                }
+                "unknown" => {
+                    input.parse::<Token![=]>()?;
+                    options.unknown = UnknownKinds::parse(&input.parse()?)?;
+                }
+                "extras" => {
+                    input.parse::<Token![=]>()?;
+                    options.extras = ExtraNodes::parse(&input.parse()?)?;
+                }
+                "prefix" => {
+                    input.parse::<Token![=]>()?;
+                    options.prefix = Some(input.parse()?);
+                }
+                "return_type" => {
+                    input.parse::<Token![=]>()?;
+                    let ty: LitStr = input.parse()?;
+                    options.preset_return_type = Some(ty.parse()?);
+                }
+                "return_type_param" => {
+                    input.parse::<Token![=]>()?;
+                    options.return_type_param = Some(input.parse()?);
+                }
+                "case" => {
+                    input.parse::<Token![=]>()?;
+                    options.case = NamingCase::parse(&input.parse()?)?;
+                }
+                "supertraits" => options.supertraits.extend(parse_bounds(input)?),
+                "return_bounds" => options.return_bounds.extend(parse_bounds(input)?),
+                "context" => options.context = parse_flag(input)?,
+                "fallible" => options.fallible = parse_flag(input)?,
+                "defaults" => options.defaults = parse_flag(input)?,
+                "async" => options.asynchronous = parse_flag(input)?,
+                "by_value" => options.by_value = parse_flag(input)?,
+                "params" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    options
+                        .params
+                        .extend(Punctuated::<Param, Token![,]>::parse_terminated(&content)?);
+                }
+                "return_types" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    options.return_types.extend(
+                        Punctuated::<ReturnTypeOverride, Token![,]>::parse_terminated(&content)?,
+                    );
+                }
+                "renames" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    options
+                        .renames
+                        .extend(Punctuated::<Rename, Token![,]>::parse_terminated(&content)?);
+                }
+                "languages" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    options.languages.extend(
+                        Punctuated::<LanguagePath, Token![,]>::parse_terminated(&content)?,
+                    );
+                }
+                "mixins" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    options
+                        .mixins
+                        .extend(Punctuated::<KindSet, Token![,]>::parse_terminated(
+                            &content,
+                        )?);
+                }
+                "group" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    options
+                        .groups
+                        .extend(Punctuated::<KindSet, Token![,]>::parse_terminated(
+                            &content,
+                        )?);
+                }
+                other if !parse_extra(&key, input)? => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!("unknown option `{}`", other),
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(config) = options.config.clone() {
+            Config::load(&config)?.merge_into(&mut options, &config)?;
+        }
+        if !options.languages.is_empty() {
+            if !options.path.is_empty() {
+                return Err(syn::Error::new(
+                    options.languages[0].name.span(),
+                    "`languages` cannot be combined with a path to `node-types.json`",
+                ));
+            }
+            if let Some(expected_hash) = &options.expected_hash {
+                return Err(syn::Error::new(
+                    expected_hash.span(),
+                    "`expected_hash` cannot be combined with `languages`",
+                ));
+            }
+            for (i, language) in options.languages.iter().enumerate() {
+                if options.languages[..i]
+                    .iter()
+                    .any(|other| other.name == language.name)
+                {
+                    return Err(syn::Error::new(
+                        language.name.span(),
+                        format!("language `{}` is declared more than once", language.name),
+                    ));
+                }
+            }
+        } else if options.path.is_empty() {
+            return Err(input.error("expected the path to `node-types.json`"));
+        }
+        if let Some(prefix) = &options.prefix {
+            let value = prefix.value();
+            let valid = value.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                return Err(syn::Error::new(
+                    prefix.span(),
+                    "`prefix` must be the non-empty start of an identifier, e.g. \"on_\"",
+                ));
+            }
+        }
+        if !options.enclosing.is_empty() {
+            options.ancestors = true;
+        }
+        // The generated helpers reading source text accept any `TextProvider`.
+        if options.ropey
+            || options.fingerprint
+            || options.html
+            || options.source_map
+            || options.interner
+            || options.sexp_printer
+            || options.serialize
+        {
+            options.text_provider = true;
+        }
+        if options.token_hook {
+            options.named_only = true;
+        }
+        if options.libloading || options.kind_id_dispatch {
+            options.kind_table = true;
+        }
+        if options.node_kind_dispatch {
+            options.node_kind = true;
+        }
+        if options.context {
+            if let Some(param) = options.params.iter().find(|param| param.name == "ctx") {
+                return Err(syn::Error::new(
+                    param.name.span(),
+                    "the parameter `ctx` is already declared by `context`",
+                ));
+            }
+        }
+        if options.parallel && cfg!(not(feature = "rayon")) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`parallel` requires the `rayon` feature of `tree-sitter-visitor`",
+            ));
+        }
+        if options.tracing && cfg!(not(feature = "tracing")) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`tracing` requires the `tracing` feature of `tree-sitter-visitor`",
+            ));
+        }
+        if options.notify && cfg!(not(feature = "notify")) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`notify` requires the `notify` feature of `tree-sitter-visitor`",
+            ));
+        }
+        if options.libloading && cfg!(not(feature = "libloading")) {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`libloading` requires the `libloading` feature of `tree-sitter-visitor`",
+            ));
+        }
+        for (option, groups) in CONFLICTS {
+            if !options.enabled(option) {
+                continue;
+            }
+            let mut conflicting = groups.iter().flat_map(|group| group.iter());
+            if let Some(other) = conflicting.find(|other| options.enabled(other)) {
+                return Err(syn::Error::new(
+                    options.span_of(option),
+                    format!("`{}` cannot be combined with `{}`", option, other),
+                ));
+            }
+        }
+        // Added after the conflicts are checked, since `return_type` conflicts with explicit
+        // `return_bounds` only.
+        if options.unknown == UnknownKinds::Skip || options.defaults {
+            options
+                .return_bounds
+                .push(syn::parse_quote!(::std::default::Default));
+        }
+
+        Ok(options)
+    }
+
+    /// Returns whether the option with the given name, as listed in `CONFLICTS`, is enabled.
+    fn enabled(&self, option: &str) -> bool {
+        match option {
+            "after_visit" => self.after_visit,
+            "async" => self.asynchronous,
+            "batch" => self.batch,
+            "by_value" => self.by_value,
+            "catch_unimplemented" => self.catch_unimplemented,
+            "chain" => self.chain,
+            "combinators" => self.combinators,
+            "context" => self.context,
+            "coverage" => self.coverage,
+            "dyn_trait" => self.dyn_trait,
+            "enum_dispatch" => self.enum_dispatch.is_some(),
+            "fallible" => self.fallible,
+            "flavors" => !self.flavors.is_empty(),
+            "fn_visitor" => self.fn_visitor,
+            "kind_id_dispatch" => self.kind_id_dispatch,
+            "kind_table" => self.kind_table,
+            "memoize" => self.memoize,
+            "mixins" => !self.mixins.is_empty(),
+            "node_kind_dispatch" => self.node_kind_dispatch,
+            "grammar_name_dispatch" => self.grammar_name_dispatch,
+            "notify" => self.notify,
+            "owned_parse" => self.owned_parse,
+            "parallel" => self.parallel,
+            "params" => !self.params.is_empty(),
+            "query_captures" => self.query_captures,
+            "return_bounds" => !self.return_bounds.is_empty(),
+            "return_type" => self.preset_return_type.is_some(),
+            "return_type_param" => self.return_type_param.is_some(),
+            "return_types" => !self.return_types.is_empty(),
+            "session" => self.session,
+            "tracing" => self.tracing,
+            "try_visit_children" => self.try_visit_children,
+            "visit_children" => self.visit_children,
+            "walker" => self.walker,
+            other => unreachable!("unknown option in `CONFLICTS`: {}", other),
+        }
+    }
+
+    /// Returns the span to report a conflict of the option with the given name at: its value if it
+    /// has one, or the attribute.
+    fn span_of(&self, option: &str) -> proc_macro2::Span {
+        match option {
+            "return_type" => self
+                .preset_return_type
+                .as_ref()
+                .map(syn::spanned::Spanned::span),
+            "return_type_param" => self.return_type_param.as_ref().map(Ident::span),
+            "params" => self.params.first().map(|param| param.name.span()),
+            "return_types" => self.return_types.first().map(|ty| ty.kind.span()),
+            _ => None,
+        }
+        .unwrap_or_else(proc_macro2::Span::call_site)
+    }
+
+    /// Adds a flavor of the visitor trait, failing if it is unknown. Flavors given more than once
+    /// are only generated once.
+    pub fn add_flavor(&mut self, flavor: Ident) -> syn::Result<()> {
+        if !FLAVORS.contains(&flavor.to_string().as_str()) {
+            return Err(syn::Error::new(
+                flavor.span(),
+                format!(
+                    "unknown flavor `{}`, expected one of: {}",
+                    flavor,
+                    FLAVORS.join(", ")
+                ),
+            ));
+        }
+        if !self.flavors.contains(&flavor) {
+            self.flavors.push(flavor);
+        }
+        Ok(())
+    }
+
+    /// Returns the declarations of the extra parameters, each preceded by a comma, to be appended to
+    /// the parameters of a visit method. The context parameter comes first.
+    pub fn param_decls(&self) -> TokenStream {
+        let names = self.params.iter().map(|param| &param.name);
+        let types = self.params.iter().map(|param| &param.ty);
+        let context = if self.context {
+            quote! { , ctx: &mut Self::Context }
+        } else {
+            quote! {}
+        };
+        quote! { #context #(, #names: #types)* }
+    }
+
+    /// Returns the names of the extra parameters, each preceded by a comma, to be appended to the
+    /// arguments of a call to a visit method.
+    pub fn param_args(&self) -> TokenStream {
+        let names = self.params.iter().map(|param| &param.name);
+        let context = if self.context {
+            quote! { , ctx }
+        } else {
+            quote! {}
+        };
+        quote! { #context #(, #names)* }
+    }
+
+    /// Returns the declaration of the `Context` associated type with `context`.
+    pub fn context_item(&self) -> TokenStream {
+        if self.context {
+            quote! {
+                /// The context passed to every visit method, borrowed separately from the visitor.
+                type Context;
+            }
+        } else {
+            quote! {}
+        }
+    }
+
+    /// Returns the type returned by the visit methods: the preset `return_type`, or the
+    /// `ReturnType` associated type.
+    pub fn self_return_type(&self) -> TokenStream {
+        match (&self.preset_return_type, &self.return_type_param) {
+            (Some(ty), _) => quote! { #ty },
+            (None, Some(param)) => quote! { #param },
+            (None, None) => quote! { Self::ReturnType },
+        }
+    }
+
+    /// Returns the declaration of the `ReturnType` associated type, with its bounds.
+    pub fn return_type_item(&self) -> TokenStream {
+        let bounds = &self.return_bounds;
+        if bounds.is_empty() {
+            quote! { type ReturnType; }
+        } else {
+            quote! { type ReturnType: #(#bounds)+*; }
+        }
+    }
+
+    /// Returns the declaration of the `Error` associated type with `fallible`.
+    pub fn error_item(&self) -> TokenStream {
+        if self.fallible {
+            quote! {
+                /// The error returned by the visit methods, which must be able to represent nodes
+                /// reaching a default implementation.
+                type Error: ::std::convert::From<UnimplementedNode>;
+            }
+        } else {
+            quote! {}
+        }
+    }
+
+    /// Returns the type returned by visit methods producing `ty`, which is wrapped in a `Result`
+    /// with `fallible`.
+    pub fn result_type(&self, ty: TokenStream) -> TokenStream {
+        if self.fallible {
+            quote! { ::std::result::Result<#ty, Self::Error> }
+        } else {
+            ty
+        }
+    }
+
+    /// Returns the `async` keyword for the visit methods with `async`.
+    pub fn asyncness(&self) -> TokenStream {
+        if self.asynchronous {
+            quote! { async }
+        } else {
+            quote! {}
+        }
+    }
+
+    /// Returns a call to a visit method, awaited with `async`.
+    pub fn awaited(&self, call: TokenStream) -> TokenStream {
+        if self.asynchronous {
+            quote! { #call.await }
+        } else {
+            call
+        }
+    }
+
+    /// Returns a call to a visit method from a dispatcher, which is boxed and awaited with `async`,
+    /// so that the futures of visit methods visiting children through the dispatcher have a
+    /// finite size.
+    pub fn dispatched(&self, call: TokenStream) -> TokenStream {
+        if self.asynchronous {
+            quote! { ::std::boxed::Box::pin(#call).await }
+        } else {
+            call
+        }
+    }
+
+    /// Returns the name of the method visiting nodes of the given kind: the method it is mapped to
+    /// with `#[visit(...)]`, or one following `prefix` and `case`, e.g. `visit_add_expr`.
+    pub fn method_name(&self, kind: &str) -> Ident {
+        if let Some((_, method)) = self.mappings.iter().find(|(mapped, _)| mapped == kind) {
+            return method.clone();
+        }
+        format_ident!("{}{}", self.method_prefix(), self.kind_name(kind))
+    }
+
+    /// Returns the name of the method visiting the kinds of a `group(...)`, following `prefix`,
+    /// e.g. `visit_binary`.
+    pub fn group_method_name(&self, group: &Ident) -> Ident {
+        format_ident!("{}{}", self.method_prefix(), group)
+    }
+
+    fn method_prefix(&self) -> String {
+        self.prefix
+            .as_ref()
+            .map_or_else(|| "visit_".to_string(), LitStr::value)
+    }
+
+    /// Returns the part of the method names standing for the given kind, following `case`.
+    pub fn kind_name(&self, kind: &str) -> String {
+        match self.case {
+            NamingCase::Preserve => sanitize_identifier(kind),
+            NamingCase::Snake => sanitize_identifier(&snake_case(kind)),
+        }
+    }
+
+    /// Returns the type of the nodes passed to the visit methods, by value with `by_value`.
+    pub fn node_type(&self) -> TokenStream {
+        if self.by_value {
+            quote! { ::tree_sitter::Node<'tree> }
+        } else {
+            quote! { &::tree_sitter::Node<'_> }
+        }
+    }
+
+    /// Returns the `node` parameter of the visit methods.
+    pub fn node_param(&self) -> TokenStream {
+        let node_type = self.node_type();
+        quote! { node: #node_type }
+    }
+
+    /// Returns a reference to the `node` parameter of the visit methods.
+    pub fn node_ref(&self) -> TokenStream {
+        if self.by_value {
+            quote! { &node }
+        } else {
+            quote! { node }
+        }
+    }
+
+    /// Returns the body of a default implementation, which is `panic`, or returns an
+    /// `UnimplementedNode` error for `node` with `fallible`, or the default value with `defaults`.
+    pub fn stub(&self, panic: TokenStream) -> TokenStream {
+        if self.defaults && self.fallible {
+            quote! { ::std::result::Result::Ok(::std::default::Default::default()) }
+        } else if self.fallible {
+            let node = self.node_ref();
+            quote! {
+                ::std::result::Result::Err(::std::convert::From::from(UnimplementedNode::new(#node)))
+            }
+        } else if self.defaults || self.returns_unit() {
+            quote! { ::std::default::Default::default() }
+        } else {
+            panic
+        }
+    }
+
+    /// Returns whether the preset `return_type` is `()`, in which case nothing is left to
+    /// implement and the default implementations return it instead of panicking. Nodes of unknown
+    /// kinds still follow `unknown`, see `unknown_arm()`.
+    fn returns_unit(&self) -> bool {
+        matches!(&self.preset_return_type, Some(Type::Tuple(tuple)) if tuple.elems.is_empty())
+    }
+
+    /// Returns a where clause predicate requiring the given type to satisfy the bounds on
+    /// `ReturnType`, if there are any.
+    pub fn return_type_predicate(&self, ty: impl ToTokens) -> TokenStream {
+        let bounds = &self.return_bounds;
+        if bounds.is_empty() {
+            quote! {}
+        } else {
+            quote! { #ty: #(#bounds)+*, }
+        }
+    }
+
+    /// Returns the hook methods the options add to the trait besides the methods of the node kinds
+    /// and the dispatchers, with `visit_external()` if the grammar has external tokens.
+    pub fn hooks(&self, node_types: &[Node], external_hook: bool) -> Vec<Hook> {
+        let hooks = [
+            ("visit_token", HookSignature::Token, self.token_hook),
+            (
+                "visit_unknown",
+                HookSignature::Node,
+                self.unknown == UnknownKinds::Hook,
+            ),
+            ("visit_error", HookSignature::Node, self.error_hooks),
+            ("visit_missing", HookSignature::Node, self.error_hooks),
+            (
+                "visit_extra",
+                HookSignature::Node,
+                self.extras == ExtraNodes::Hook,
+            ),
+            ("visit_external", HookSignature::Node, external_hook),
+            ("visit_fields", HookSignature::Children, self.field_hooks),
+            ("after_visit", HookSignature::AfterVisit, self.after_visit),
+            ("invalidate", HookSignature::Invalidate, self.revisit),
+            ("split", HookSignature::Split, self.parallel),
+        ];
+        let field_hooks = field_hooks::hook_names(node_types)
+            .into_iter()
+            .filter(|_| self.field_hooks)
+            .map(|name| Hook {
+                name: format_ident!("{}", name),
+                signature: HookSignature::Field,
+            });
+        hooks
+            .into_iter()
+            .filter(|(_, _, enabled)| *enabled)
+            .map(|(name, signature, _)| Hook {
+                name: format_ident!("{}", name),
+                signature,
+            })
+            .chain(field_hooks)
+            .collect()
+    }
+
+    /// Returns whether `named_only`, `include` or `exclude` leave out some of the kinds of the
+    /// grammar.
+    pub fn filters_kinds(&self) -> bool {
+        self.named_only
+            || !self.include.is_empty()
+            || self.include_pattern.is_some()
+            || !self.exclude.is_empty()
+            || self.exclude_pattern.is_some()
+    }
+
+    /// Returns the match arm handling nodes of unknown kinds in a dispatch, with `message` being
+    /// the message to panic with, formatted with the kind of the node.
+    pub fn unknown_arm(&self, message: &str) -> TokenStream {
+        match self.unknown {
+            // Unlike the default implementations, this does not return the default value with
+            // `defaults` or a `()` `return_type`: unknown kinds are skipped with `skip` only.
+            UnknownKinds::Panic if self.fallible => {
+                let node = self.node_ref();
+                quote! {
+                    _ => ::std::result::Result::Err(::std::convert::From::from(UnimplementedNode::new(#node)))
+                }
+            }
+            UnknownKinds::Panic => quote! { _ => panic!(#message, node.kind()) },
+            UnknownKinds::Skip if self.fallible => {
+                quote! { _ => ::std::result::Result::Ok(::std::default::Default::default()) }
+            }
+            UnknownKinds::Skip => quote! { _ => ::std::default::Default::default() },
+            UnknownKinds::Hook => {
+                let param_args = self.param_args();
+                let call = self.dispatched(quote! { self.visit_unknown(node #param_args) });
+                quote! { _ => #call }
+            }
+        }
+    }
+
+    /// Loads `node-types.json`, or those of `languages` merged, failing with an error at `span` if
+    /// one cannot be read or parsed, and selects the node types with `select_node_types()`.
+    pub fn load_node_types(&self, span: proc_macro2::Span) -> syn::Result<Vec<Node>> {
+        let node_types = self.load_all_node_types(span)?;
+        self.select_node_types(node_types)
+    }
+
+    /// Loads `node-types.json`, or those of `languages` merged, like `load_node_types()`, but
+    /// without leaving out the kinds `named_only`, `include` and `exclude` leave out.
+    pub fn load_all_node_types(&self, span: proc_macro2::Span) -> syn::Result<Vec<Node>> {
+        Ok(if self.languages.is_empty() {
+            node_types::load(&self.path, span)?
+        } else {
+            let languages = self
+                .languages
+                .iter()
+                .map(|language| {
+                    let node_types = node_types::load(&language.path, span)?;
+                    Ok((language.name.to_string(), node_types))
+                })
+                .collect::<syn::Result<_>>()?;
+            node_types::merge(languages)
+        })
+    }
+
+    /// Returns items including the files read for the options in the expansion: `node-types.json`,
+    /// the configuration, the grammar and the profile, so that Cargo rebuilds the crate when they
+    /// change, e.g. after rerunning `tree-sitter generate`.
+    pub fn tracked_files(&self) -> TokenStream {
+        let node_types = if self.languages.is_empty() {
+            vec![self.path.clone()]
+        } else {
+            self.languages
+                .iter()
+                .map(|language| language.path.clone())
+                .collect()
+        };
+        let others = [&self.config, &self.grammar, &self.profile]
+            .into_iter()
+            .flatten()
+            .map(LitStr::value);
+        let items = node_types
+            .into_iter()
+            .chain(others)
+            .map(|path| tracked_file(&path));
+        quote! { #(#items)* }
+    }
+
+    /// Returns the match arms dispatching `ERROR` and `MISSING` nodes to their hooks with
+    /// `error_hooks`, placed before the arms of the node kinds since missing nodes have the kind of
+    /// the node that is missing.
+    pub fn error_arms(&self) -> TokenStream {
+        if !self.error_hooks {
+            return quote! {};
+        }
+        let param_args = self.param_args();
+        let error = self.dispatched(quote! { self.visit_error(node #param_args) });
+        let missing = self.dispatched(quote! { self.visit_missing(node #param_args) });
+        quote! {
+            _ if node.is_error() => #error,
+            _ if node.is_missing() => #missing,
+        }
+    }
+
+    /// Returns the node types to generate code for, as selected by `include`, `exclude` and their
+    /// patterns, leaving out anonymous ones with `named_only`. Fails if `include` or `exclude`
+    /// lists a kind that is not in the grammar.
+    pub fn select_node_types(&self, node_types: Vec<Node>) -> syn::Result<Vec<Node>> {
+        if let Some(kind) = self
+            .include
+            .iter()
+            .chain(&self.exclude)
+            .find(|kind| !node_types.iter().any(|node| node.r#type == kind.value()))
+        {
+            return Err(syn::Error::new(
+                kind.span(),
+                format!("unknown node kind `{}`", kind.value()),
+            ));
+        }
+
+        let listed =
+            |kinds: &[LitStr], node: &Node| kinds.iter().any(|kind| kind.value() == node.r#type);
+        let matches = |pattern: &Option<Regex>, node: &Node| {
+            pattern
+                .as_ref()
+                .is_some_and(|pattern| pattern.is_match(&node.r#type))
+        };
+        let include_all = self.include.is_empty() && self.include_pattern.is_none();
+        Ok(node_types
+            .into_iter()
+            .filter(|node| node.named || !self.named_only)
+            .filter(|node| {
+                include_all || listed(&self.include, node) || matches(&self.include_pattern, node)
+            })
+            .filter(|node| !listed(&self.exclude, node) && !matches(&self.exclude_pattern, node))
+            .collect())
+    }
+
+    /// Returns the return type declared for the method visiting nodes of the given kind, if any.
+    pub fn return_type(&self, kind: &str) -> Option<&Type> {
+        self.return_types
+            .iter()
+            .find(|return_type| return_type.kind.value() == kind)
+            .map(|return_type| &return_type.ty)
+    }
+}
+
+/// Parses a flag that is either bare (`flag`) or has an explicit boolean value (`flag = false`).
+fn parse_flag(input: ParseStream) -> syn::Result<bool> {
+    if input.peek(Token![=]) {
+        input.parse::<Token![=]>()?;
+        Ok(input.parse::<LitBool>()?.value)
+    } else {
+        Ok(true)
+    }
+}
+
+/// Parses a bracketed list of string literals, e.g. `["a", "b"]`.
+fn parse_str_list(input: ParseStream) -> syn::Result<Vec<LitStr>> {
+    let content;
+    syn::bracketed!(content in input);
+    Ok(Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?
+        .into_iter()
+        .collect())
+}
+
+/// Parses a regular expression in a string literal.
+pub(crate) fn parse_pattern(pattern: &LitStr) -> syn::Result<Regex> {
+    Regex::new(&pattern.value()).map_err(|err| syn::Error::new(pattern.span(), err))
+}
+
+/// Expands the environment variables referenced as `$NAME` or `${NAME}` in a path, e.g.
+/// `$OUT_DIR/node-types.json`. `$$` stands for a literal `$`.
+pub(crate) fn expand_env(path: &LitStr) -> syn::Result<String> {
+    let value = path.value();
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value.as_str();
+    while let Some(dollar) = rest.find('$') {
+        expanded.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        let (name, after) = match rest.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => return Err(syn::Error::new(path.span(), "unterminated `${`")),
+            },
+            None => {
+                let end = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+        };
+        if name.is_empty() {
+            return Err(syn::Error::new(
+                path.span(),
+                "expected the name of an environment variable after `$`",
+            ));
+        }
+        expanded.push_str(&env_var(name, path.span())?);
+        rest = after;
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Returns the path in the environment variable named by `var`.
+pub(crate) fn env_path(var: &LitStr) -> syn::Result<String> {
+    env_var(&var.value(), var.span())
+}
+
+fn env_var(name: &str, span: proc_macro2::Span) -> syn::Result<String> {
+    std::env::var(name).map_err(|err| {
+        syn::Error::new(
+            span,
+            format!("could not read environment variable `{}`: {}", name, err),
+        )
+    })
+}
+
+/// Parses `= "Bound + ..."`.
+fn parse_bounds(input: ParseStream) -> syn::Result<Vec<TypeParamBound>> {
+    input.parse::<Token![=]>()?;
+    parse_bound_list(&input.parse()?)
+}
+
+/// Parses the bounds in a string literal, e.g. `"Clone + Send"`.
+pub(crate) fn parse_bound_list(bounds: &LitStr) -> syn::Result<Vec<TypeParamBound>> {
+    Ok(bounds
+        .parse_with(Punctuated::<TypeParamBound, Token![+]>::parse_terminated)?
+        .into_iter()
+        .collect())
+}
+
+/// Parses a node kind given either as an identifier or as a string literal.
+fn parse_kind(input: ParseStream) -> syn::Result<LitStr> {
+    if input.peek(LitStr) {
+        input.parse()
+    } else {
+        let ident = Ident::parse_any(input)?;
+        Ok(LitStr::new(&ident.to_string(), ident.span()))
+    }
+}
@@ -0,0 +1,86 @@
+//! Generation of the `OwnedParse` type bundling a source string with its tree.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::{fresh_lifetime, fresh_type_param, trait_params, trait_path, trait_predicates};
+
+pub(crate) fn generate(item: &ItemTrait) -> TokenStream {
+    let vis = &item.vis;
+    let trait_params = trait_params(item);
+    let trait_predicates = trait_predicates(item);
+    let trait_ref = trait_path(item, quote! {});
+    let a = fresh_lifetime(item, "a");
+    let v = fresh_type_param(item, "V");
+    let f = fresh_type_param(item, "F");
+
+    quote! {
+        /// A source string together with the syntax tree parsed from it.
+        ///
+        /// Nodes borrow from the tree and visitors usually borrow from the source, so keeping both
+        /// in one owned value allows a parse to be returned from a function and visited later.
+        #vis struct OwnedParse {
+            source: ::std::string::String,
+            tree: ::tree_sitter::Tree,
+        }
+
+        impl OwnedParse {
+            /// Parses `source` with the given language.
+            pub fn parse(
+                language: ::tree_sitter::Language,
+                source: ::std::string::String,
+            ) -> ::std::result::Result<Self, ::tree_sitter::LanguageError> {
+                let mut parser = ::tree_sitter::Parser::new();
+                parser.set_language(&language)?;
+                let tree = parser.parse(&source, None).expect("parsing was cancelled");
+
+                Ok(Self { source, tree })
+            }
+
+            /// Bundles a source string with a tree that was parsed from it.
+            pub fn new(source: ::std::string::String, tree: ::tree_sitter::Tree) -> Self {
+                Self { source, tree }
+            }
+
+            /// Returns the source text.
+            pub fn source(&self) -> &str {
+                &self.source
+            }
+
+            /// Returns the syntax tree.
+            pub fn tree(&self) -> &::tree_sitter::Tree {
+                &self.tree
+            }
+
+            /// Returns the root node of the syntax tree.
+            pub fn root_node(&self) -> ::tree_sitter::Node<'_> {
+                self.tree.root_node()
+            }
+
+            /// Visits the root node with the given visitor.
+            pub fn visit<#trait_params #v: #trait_ref>(&self, visitor: &mut #v) -> #v::ReturnType
+            where
+                #trait_predicates
+            {
+                visitor.visit(&self.tree.root_node())
+            }
+
+            /// Creates a visitor borrowing the source text and visits the root node with it.
+            pub fn visit_with<#a, #trait_params #v, #f>(&#a self, make_visitor: #f) -> #v::ReturnType
+            where
+                #v: #trait_ref,
+                #f: ::std::ops::FnOnce(&#a str) -> #v,
+                #trait_predicates
+            {
+                let mut visitor = make_visitor(&self.source);
+                visitor.visit(&self.tree.root_node())
+            }
+
+            /// Consumes the bundle, returning the source and the tree.
+            pub fn into_parts(self) -> (::std::string::String, ::tree_sitter::Tree) {
+                (self.source, self.tree)
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Generation of `visit_parallel()`, which visits the top-level children of a tree on the rayon
+//! thread pool, and of the `split()` method creating the visitor of each of them.
+
+use quote::quote;
+use syn::{parse_quote, TraitItem};
+
+use crate::options::Options;
+
+pub(crate) fn trait_items(options: &Options) -> Vec<TraitItem> {
+    let return_type = options.self_return_type();
+    let result_type = options.result_type(return_type.clone());
+    let tree = if options.by_value {
+        quote! { 'tree }
+    } else {
+        quote! { '_ }
+    };
+    let child = if options.by_value {
+        quote! { child }
+    } else {
+        quote! { &child }
+    };
+    let (count, nth) = if options.named_only {
+        (quote! { named_child_count }, quote! { named_child })
+    } else {
+        (quote! { child_count }, quote! { child })
+    };
+    // With `fallible`, the results are combined until the first error.
+    let reduce = if options.fallible {
+        quote! { try_reduce_with(|a, b| ::std::result::Result::Ok(combine(a, b))) }
+    } else {
+        quote! { reduce_with(combine) }
+    };
+
+    vec![
+        parse_quote! {
+            /// Creates the visitor of a subtree visited by `visit_parallel()` on another thread, e.g.
+            /// by cloning the configuration of `self` and starting with empty state.
+            fn split(&self) -> Self
+            where
+                Self: Sized;
+        },
+        parse_quote! {
+            /// Visits the top-level children of `tree` in parallel on the rayon thread pool, each
+            /// with a visitor created by `split()`, and merges their results with `combine`, in
+            /// document order. Returns `None` if the root has no children.
+            fn visit_parallel<F>(
+                &self,
+                tree: &#tree ::tree_sitter::Tree,
+                combine: F,
+            ) -> ::std::option::Option<#result_type>
+            where
+                Self: Sized + ::std::marker::Sync,
+                #result_type: ::std::marker::Send,
+                F: ::std::ops::Fn(#return_type, #return_type) -> #return_type
+                    + ::std::marker::Sync
+                    + ::std::marker::Send,
+            {
+                use ::rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+                // Nodes cannot be sent to other threads, so each thread looks up its child by index.
+                (0..tree.root_node().#count())
+                    .into_par_iter()
+                    .map(|index| {
+                        let child = tree.root_node().#nth(index).unwrap();
+                        self.split().visit(#child)
+                    })
+                    .#reduce
+            }
+        },
+    ]
+}
@@ -0,0 +1,311 @@
+//! A reader of tree-sitter query files (`.scm`), given to `#[query_visitor]`, which finds the
+//! capture names of a query and checks its node kinds and field names against `node-types.json`.
+//!
+//! Only the structure of the patterns is read: predicates are skipped, and the query is compiled
+//! by tree-sitter at runtime as usual.
+
+use crate::node_types::Node;
+
+/// A token of a query, with the line it is on.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    /// A string literal, matching an anonymous node.
+    Str(String),
+    /// A node kind, or `_`.
+    Ident(String),
+    /// `name:`, the field of the pattern that follows.
+    Field(String),
+    /// `!name`, a field the node must not have.
+    NegatedField(String),
+    /// `@name`.
+    Capture(String),
+    /// `#name`, the start of a predicate.
+    Predicate(String),
+    /// `*`, `+`, `?` or the `.` anchor.
+    Operator,
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/')
+}
+
+/// Splits a query into tokens, skipping whitespace and comments.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars = source.chars().peekable();
+    // Predicate names end with `?` or `!`, e.g. `#eq?` and `#set!`.
+    let word = |chars: &mut std::iter::Peekable<std::str::Chars>, predicate: bool| {
+        let mut word = String::new();
+        while let Some(c) = chars.next_if(|&c| is_ident_char(c) || predicate && "?!".contains(c)) {
+            word.push(c);
+        }
+        word
+    };
+    while let Some(&c) = chars.peek() {
+        let token = match c {
+            '\n' => {
+                line += 1;
+                chars.next();
+                continue;
+            }
+            ';' => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+                continue;
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' | ')' | '[' | ']' | '*' | '+' | '?' | '.' => {
+                chars.next();
+                match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    '[' => Token::LBracket,
+                    ']' => Token::RBracket,
+                    _ => Token::Operator,
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('r') => value.push('\r'),
+                            Some('t') => value.push('\t'),
+                            Some('0') => value.push('\0'),
+                            Some(c) => value.push(c),
+                            None => return Err(format!("line {}: unterminated string", line)),
+                        },
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            value.push(c);
+                        }
+                        None => return Err(format!("line {}: unterminated string", line)),
+                    }
+                }
+                Token::Str(value)
+            }
+            '@' | '#' | '!' => {
+                chars.next();
+                let name = word(&mut chars, c == '#');
+                if name.is_empty() {
+                    return Err(format!("line {}: expected a name after `{}`", line, c));
+                }
+                match c {
+                    '@' => Token::Capture(name),
+                    '#' => Token::Predicate(name),
+                    _ => Token::NegatedField(name),
+                }
+            }
+            c if is_ident_char(c) => {
+                let name = word(&mut chars, false);
+                if chars.next_if_eq(&':').is_some() {
+                    Token::Field(name)
+                } else {
+                    Token::Ident(name)
+                }
+            }
+            c => return Err(format!("line {}: unexpected character `{}`", line, c)),
+        };
+        tokens.push((token, line));
+    }
+    Ok(tokens)
+}
+
+/// Reads the patterns of a query, checking them against the node types.
+struct Reader<'a> {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    node_types: &'a [Node],
+    captures: Vec<String>,
+}
+
+impl Reader<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(token, _)| token.clone())
+            .ok_or_else(|| format!("line {}: unexpected end of the query", self.line()))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn error(&self, message: String) -> String {
+        // The token the error is about has already been read.
+        let line = self.tokens[self.position.saturating_sub(1)].1;
+        format!("line {}: {}", line, message)
+    }
+
+    fn check_kind(&self, kind: &str, named: bool) -> Result<(), String> {
+        if self
+            .node_types
+            .iter()
+            .any(|node| node.r#type == kind && node.named == named)
+        {
+            Ok(())
+        } else if named {
+            Err(self.error(format!("unknown node kind `{}`", kind)))
+        } else {
+            Err(self.error(format!("unknown anonymous node kind {:?}", kind)))
+        }
+    }
+
+    /// Checks that `field` is a field of `parent`, or of any node kind if `parent` could be of
+    /// several kinds.
+    fn check_field(&self, parent: Option<&str>, field: &str) -> Result<(), String> {
+        let parent_node = parent.and_then(|kind| {
+            self.node_types
+                .iter()
+                .find(|node| node.named && node.r#type == kind && !node.is_supertype())
+        });
+        match parent_node {
+            Some(node) if !node.fields.contains_key(field) => Err(self.error(format!(
+                "node kind `{}` has no field `{}`",
+                node.r#type, field
+            ))),
+            Some(_) => Ok(()),
+            None if self
+                .node_types
+                .iter()
+                .any(|node| node.fields.contains_key(field)) =>
+            {
+                Ok(())
+            }
+            None => Err(self.error(format!("unknown field `{}`", field))),
+        }
+    }
+
+    /// Reads a pattern, with its quantifiers and captures, in a node of kind `parent`.
+    fn pattern(&mut self, parent: Option<&str>) -> Result<(), String> {
+        match self.next()? {
+            Token::LParen => self.parenthesized()?,
+            Token::LBracket => {
+                while self.peek() != Some(&Token::RBracket) {
+                    self.pattern(parent)?;
+                }
+                self.next()?;
+            }
+            Token::Str(kind) => self.check_kind(&kind, false)?,
+            Token::Ident(kind) if kind == "_" => {}
+            Token::Field(field) => {
+                self.check_field(parent, &field)?;
+                return self.pattern(parent);
+            }
+            Token::NegatedField(field) => return self.check_field(parent, &field),
+            Token::Operator => return Ok(()),
+            token => return Err(self.error(format!("unexpected {:?}", token))),
+        }
+        while let Some(Token::Operator | Token::Capture(_)) = self.peek() {
+            if let Token::Capture(name) = self.next()? {
+                if !self.captures.contains(&name) {
+                    self.captures.push(name);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the rest of a pattern starting with `(`: a node, a predicate or a group.
+    fn parenthesized(&mut self) -> Result<(), String> {
+        let kind = match self.peek() {
+            Some(Token::Predicate(_)) => return self.skip_group(),
+            Some(Token::Ident(kind)) => kind.clone(),
+            // A group of sibling patterns.
+            _ => {
+                while self.peek() != Some(&Token::RParen) {
+                    self.pattern(None)?;
+                }
+                self.next()?;
+                return Ok(());
+            }
+        };
+        self.next()?;
+        let parent = match kind.as_str() {
+            "_" | "ERROR" => None,
+            "MISSING" => {
+                match self.peek() {
+                    Some(Token::Ident(kind)) => {
+                        let kind = kind.clone();
+                        self.next()?;
+                        self.check_kind(&kind, true)?;
+                    }
+                    Some(Token::Str(kind)) => {
+                        let kind = kind.clone();
+                        self.next()?;
+                        self.check_kind(&kind, false)?;
+                    }
+                    _ => {}
+                }
+                None
+            }
+            _ => {
+                // `supertype/subtype` matches a subtype through its supertype.
+                let node_kind = match kind.split_once('/') {
+                    Some((supertype, subtype)) => {
+                        self.check_kind(supertype, true)?;
+                        subtype
+                    }
+                    None => &kind,
+                };
+                self.check_kind(node_kind, true)?;
+                Some(node_kind.to_string())
+            }
+        };
+        while self.peek() != Some(&Token::RParen) {
+            self.pattern(parent.as_deref())?;
+        }
+        self.next()?;
+        Ok(())
+    }
+
+    /// Skips the rest of a predicate, whose captures refer to those of the pattern.
+    fn skip_group(&mut self) -> Result<(), String> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()? {
+                Token::LParen => depth += 1,
+                Token::RParen => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a query, returning the names of its captures in the order of their indices, or an error
+/// naming the line of the first node kind or field not in the node types.
+pub(crate) fn captures(source: &str, node_types: &[Node]) -> Result<Vec<String>, String> {
+    let mut reader = Reader {
+        tokens: tokenize(source)?,
+        position: 0,
+        node_types,
+        captures: Vec::new(),
+    };
+    while reader.peek().is_some() {
+        reader.pattern(None)?;
+    }
+    Ok(reader.captures)
+}
@@ -0,0 +1,67 @@
+//! Generation of `visit_captures()`, which runs a tree-sitter query and dispatches only the
+//! captured nodes to a visitor.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::options::Options;
+use crate::{fresh_lifetime, fresh_type_param, trait_params, trait_path, trait_predicates};
+
+pub(crate) fn generate(item: &ItemTrait, options: &Options) -> TokenStream {
+    let vis = &item.vis;
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let trait_params = trait_params(item);
+    let trait_predicates = trait_predicates(item);
+    let trait_ref = trait_path(item, quote! {});
+    let query = fresh_lifetime(item, "query");
+    let tree = fresh_lifetime(item, "tree");
+    let v = fresh_type_param(item, "V");
+
+    quote! {
+        /// A node captured by a query, as passed to the callback of `visit_captures()`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #vis struct CapturedNode<'query, 'tree> {
+            /// The name of the capture, without the leading `@`.
+            pub name: &'query str,
+            /// The index of the capture in the query.
+            pub index: u32,
+            /// The index of the pattern that matched.
+            pub pattern_index: usize,
+            /// The captured node.
+            pub node: ::tree_sitter::Node<'tree>,
+        }
+
+        /// Runs `query` over the subtree rooted at `root` and visits every captured node, passing it to
+        /// `f` together with the result of visiting it.
+        ///
+        /// Captures are visited in document order, and a node captured more than once is visited once
+        /// per capture. `source` is the text the tree was parsed from, used to evaluate the predicates
+        /// of the query.
+        #vis fn visit_captures<#query, #tree, #trait_params #v: #trait_ref + ?Sized>(
+            visitor: &mut #v,
+            query: &#query ::tree_sitter::Query,
+            root: ::tree_sitter::Node<#tree>,
+            source: &[u8]
+            #param_decls,
+            mut f: impl ::std::ops::FnMut(CapturedNode<#query, #tree>, #v::ReturnType),
+        ) where
+            #trait_predicates
+        {
+            let capture_names = query.capture_names();
+            let mut cursor = ::tree_sitter::QueryCursor::new();
+            for (query_match, capture_index) in cursor.captures(query, root, source) {
+                let capture = query_match.captures[capture_index];
+                let result = visitor.visit(&capture.node #param_args);
+                let captured = CapturedNode {
+                    name: capture_names[capture.index as usize],
+                    index: capture.index,
+                    pattern_index: query_match.pattern_index,
+                    node: capture.node,
+                };
+                f(captured, result);
+            }
+        }
+    }
+}
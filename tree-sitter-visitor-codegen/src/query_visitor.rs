@@ -0,0 +1,160 @@
+//! Generation of the traits annotated with `#[query_visitor]`, which have a method for each capture
+//! of a tree-sitter query and a `run()` driver dispatching the captures of the query to them.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_quote, Ident, ItemTrait, LitStr, Token, TraitItem};
+
+use crate::node_types;
+use crate::query;
+use crate::{read_file, resolve_path, sanitize_identifier, tracked_file, trait_item_ident};
+
+/// The arguments of `#[query_visitor]`: the path to the query and the path to `node-types.json`.
+pub(crate) struct QueryVisitorArgs {
+    query: LitStr,
+    node_types: LitStr,
+}
+
+impl Parse for QueryVisitorArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let query = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let node_types = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(QueryVisitorArgs { query, node_types })
+    }
+}
+
+/// Returns the name of the method of a capture, e.g. `visit_function_builtin` for
+/// `@function.builtin`.
+fn capture_method_name(capture: &str) -> Ident {
+    format_ident!(
+        "visit_{}",
+        sanitize_identifier(&capture.replace(['.', '-'], "_"))
+    )
+}
+
+pub(crate) fn generate(args: QueryVisitorArgs, mut item: ItemTrait) -> syn::Result<TokenStream> {
+    let path = args.query.value();
+    let node_types = node_types::load(&args.node_types.value(), args.node_types.span())?;
+    let source = String::from_utf8(read_file(&path, args.query.span())?).map_err(|_| {
+        syn::Error::new(args.query.span(), format!("`{}` is not valid UTF-8", path))
+    })?;
+    let captures = query::captures(&source, &node_types)
+        .map_err(|err| syn::Error::new(args.query.span(), format!("`{}`, {}", path, err)))?;
+
+    let reserved = ["visit_capture"];
+    let mut methods: Vec<(String, Ident)> = Vec::new();
+    for capture in &captures {
+        let method = capture_method_name(capture);
+        if reserved.contains(&method.to_string().as_str()) {
+            return Err(syn::Error::new(
+                args.query.span(),
+                format!(
+                    "the capture `@{}` clashes with the generated method `{}()`",
+                    capture, method
+                ),
+            ));
+        }
+        if let Some((other, _)) = methods.iter().find(|(_, other)| *other == method) {
+            return Err(syn::Error::new(
+                args.query.span(),
+                format!(
+                    "the captures `@{}` and `@{}` both map to the method `{}()`",
+                    other, capture, method
+                ),
+            ));
+        }
+        methods.push((capture.clone(), method));
+    }
+
+    // Including the query makes Cargo rebuild the crate when it changes.
+    let resolved = resolve_path(&path);
+    let resolved = std::fs::canonicalize(&resolved).unwrap_or(resolved);
+    let resolved = LitStr::new(&resolved.to_string_lossy(), Span::call_site());
+    let query_doc = format!("The source of `{}`.", path);
+
+    let capture_fns = methods.iter().map(|(capture, method)| {
+        let doc_string = format!(
+            "Called for each node captured as `@{}`, with the index of the pattern that matched. \
+             Does nothing by default.",
+            capture
+        );
+        let item: TraitItem = parse_quote! {
+            #[doc = #doc_string]
+            #[allow(unused_variables)]
+            fn #method(&mut self, node: &::tree_sitter::Node, pattern_index: usize) {}
+        };
+        item
+    });
+    let arms = methods.iter().map(|(capture, method)| {
+        quote! { #capture => self.#method(node, pattern_index), }
+    });
+
+    let generated: Vec<TraitItem> = vec![
+        parse_quote! {
+            #[doc = #query_doc]
+            const QUERY: &'static str = include_str!(#resolved);
+        },
+        parse_quote! {
+            /// The names of the captures of the query, without the leading `@`, in the order of
+            /// their indices.
+            const CAPTURE_NAMES: &'static [&'static str] = &[#(#captures),*];
+        },
+        parse_quote! {
+            /// Compiles the query for the given language.
+            fn query(
+                language: ::tree_sitter::Language,
+            ) -> ::std::result::Result<::tree_sitter::Query, ::tree_sitter::QueryError>
+            where
+                Self: Sized,
+            {
+                ::tree_sitter::Query::new(&language, Self::QUERY)
+            }
+        },
+        parse_quote! {
+            /// Dispatches a node captured as `@name` to the method of the capture. Captures that
+            /// are not in the query are ignored.
+            fn visit_capture(&mut self, name: &str, node: &::tree_sitter::Node, pattern_index: usize) {
+                match name {
+                    #(#arms)*
+                    _ => {}
+                }
+            }
+        },
+        parse_quote! {
+            /// Runs `query`, as compiled by `query()`, over the subtree rooted at `root` and visits
+            /// every captured node with `visit_capture()`, in document order. `source` is the text
+            /// the tree was parsed from, used to evaluate the predicates of the query.
+            fn run(&mut self, query: &::tree_sitter::Query, root: ::tree_sitter::Node<'_>, source: &[u8]) {
+                let capture_names = query.capture_names();
+                let mut cursor = ::tree_sitter::QueryCursor::new();
+                for (query_match, capture_index) in cursor.captures(query, root, source) {
+                    let capture = query_match.captures[capture_index];
+                    let name = capture_names[capture.index as usize];
+                    self.visit_capture(name, &capture.node, query_match.pattern_index);
+                }
+            }
+        },
+    ];
+
+    // As with `#[visitor_trait]`, items defined in the trait body take precedence.
+    let user_defined: Vec<Ident> = item
+        .items
+        .iter()
+        .filter_map(trait_item_ident)
+        .cloned()
+        .collect();
+    item.items = generated
+        .into_iter()
+        .chain(capture_fns)
+        .filter(|item| !trait_item_ident(item).is_some_and(|ident| user_defined.contains(ident)))
+        .chain(item.items)
+        .collect();
+    let tracked_node_types = tracked_file(&args.node_types.value());
+    Ok(quote! {
+        #item
+        #tracked_node_types
+    })
+}
@@ -0,0 +1,85 @@
+//! Generation of `revisit_changed()`, which visits only the parts of a tree that changed after an
+//! edit, and of the `invalidate()` hook called for their ancestors.
+
+use quote::quote;
+use syn::{parse_quote, TraitItem};
+
+use crate::options::Options;
+
+pub(crate) fn trait_items(options: &Options) -> Vec<TraitItem> {
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let asyncness = options.asyncness();
+    let result_type = options.result_type(options.self_return_type());
+    let tree = if options.by_value {
+        quote! { 'tree }
+    } else {
+        quote! { '_ }
+    };
+    let node = if options.by_value {
+        quote! { *node }
+    } else {
+        quote! { node }
+    };
+    let visit = options.awaited(quote! { self.visit(#node #param_args) });
+
+    vec![
+        parse_quote! {
+            /// Visits the parts of `new_tree` that changed since `old_tree`: the outermost of the
+            /// smallest nodes enclosing each range reported by `Tree::changed_ranges` or edited, in
+            /// document order, returning their results. `invalidate()` is then called for each of
+            /// their ancestors, so results cached for them can be recomputed.
+            ///
+            /// `old_tree` must have been updated with `Tree::edit` for every edit, as is required to
+            /// reparse it incrementally, and `edits` are the same edits.
+            #asyncness fn revisit_changed(
+                &mut self,
+                old_tree: &::tree_sitter::Tree,
+                new_tree: &#tree ::tree_sitter::Tree,
+                edits: &[::tree_sitter::InputEdit]
+                #param_decls
+            ) -> ::std::vec::Vec<#result_type> {
+                let root = new_tree.root_node();
+                let mut nodes: ::std::vec::Vec<::tree_sitter::Node> = old_tree
+                    .changed_ranges(new_tree)
+                    .map(|range| range.start_byte..range.end_byte)
+                    .chain(edits.iter().map(|edit| edit.start_byte..edit.new_end_byte))
+                    .filter_map(|range| root.descendant_for_byte_range(range.start, range.end))
+                    .collect();
+                nodes.sort_by_key(|node| (node.start_byte(), ::std::cmp::Reverse(node.end_byte())));
+
+                let mut outermost: ::std::vec::Vec<::tree_sitter::Node> = ::std::vec::Vec::new();
+                for node in nodes {
+                    if outermost.last().map_or(true, |last| node.end_byte() > last.end_byte()) {
+                        outermost.push(node);
+                    }
+                }
+
+                let mut results = ::std::vec::Vec::with_capacity(outermost.len());
+                for node in &outermost {
+                    results.push(#visit);
+                }
+
+                // Ancestors shared by several changed nodes are invalidated once, innermost first.
+                let mut invalidated = ::std::collections::HashSet::new();
+                for node in &outermost {
+                    let mut ancestor = node.parent();
+                    while let ::std::option::Option::Some(node) = ancestor {
+                        if !invalidated.insert(node.id()) {
+                            break;
+                        }
+                        self.invalidate(&node);
+                        ancestor = node.parent();
+                    }
+                }
+                results
+            }
+        },
+        parse_quote! {
+            /// Called by `revisit_changed()` for every ancestor of the changed nodes, e.g. to drop
+            /// results cached for it. Does nothing by default.
+            #[allow(unused_variables)]
+            fn invalidate(&mut self, node: &::tree_sitter::Node) {}
+        },
+    ]
+}
@@ -0,0 +1,182 @@
+//! Generation of `SerializableNode`, which serializes a tree with serde in a shape following
+//! `node-types.json`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+
+    let described = node_types
+        .iter()
+        .filter(|node| node.named && !node.is_supertype());
+    let entry_arms = described.clone().map(|node| {
+        let kind = &node.r#type;
+        let mut entries = Vec::new();
+        if !node.fields.is_empty() {
+            entries.push(quote! { map.serialize_entry("fields", &SerializableFields(self))?; });
+        }
+        if node.children.is_some() {
+            entries.push(quote! { map.serialize_entry("children", &self.children())?; });
+        }
+        if node.is_terminal() {
+            entries.push(quote! { map.serialize_entry("text", &self.text())?; });
+        }
+        quote! { (#kind, true) => { #(#entries)* } }
+    });
+    let field_arms = described
+        .filter(|node| !node.fields.is_empty())
+        .map(|node| {
+            let kind = &node.r#type;
+            let entries = node.fields.iter().map(|(field, child_types)| {
+                if child_types.multiple {
+                    quote! { map.serialize_entry(#field, &self.0.fields(#field))?; }
+                } else {
+                    quote! { map.serialize_entry(#field, &self.0.field(#field))?; }
+                }
+            });
+            quote! { (#kind, true) => { #(#entries)* } }
+        });
+
+    quote! {
+        /// A node that serializes with serde (which requires a dependency on `serde`) as an object
+        /// in the shape of its node type in `node-types.json`, e.g.
+        ///
+        /// ```json
+        /// {"type": "number", "named": true, "start": 0, "end": 1, "text": "1"}
+        /// ```
+        ///
+        /// Every node has its `type`, whether it is `named`, and its `start` and `end` byte
+        /// offsets. Nodes with fields have a `fields` object mapping each field of the node type to
+        /// a node, or `null` if it is optional and absent, or to an array of nodes if it can hold
+        /// several; nodes with children not in a field list them in a `children` array; and nodes
+        /// without either have their `text`. Nodes not described by `node-types.json`, such as
+        /// `ERROR` nodes, list all their named children in `children`, or have their `text` if
+        /// they have no children.
+        ///
+        /// Serialization recurses into the subtree, so very deep trees can overflow the stack.
+        #vis struct SerializableNode<'tree, 's, T: ?Sized = str> {
+            node: ::tree_sitter::Node<'tree>,
+            source: &'s T,
+        }
+
+        impl<T: ?Sized> ::std::clone::Clone for SerializableNode<'_, '_, T> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<T: ?Sized> ::std::marker::Copy for SerializableNode<'_, '_, T> {}
+
+        impl<'tree, 's, T: TextProvider + ?Sized> SerializableNode<'tree, 's, T> {
+            /// Wraps a node of a tree parsed from `source`.
+            pub fn new(node: ::tree_sitter::Node<'tree>, source: &'s T) -> Self {
+                Self { node, source }
+            }
+
+            /// Wraps the root of a tree parsed from `source`.
+            pub fn from_tree(tree: &'tree ::tree_sitter::Tree, source: &'s T) -> Self {
+                Self::new(tree.root_node(), source)
+            }
+
+            /// Returns the wrapped node.
+            pub fn node(&self) -> ::tree_sitter::Node<'tree> {
+                self.node
+            }
+
+            fn text(&self) -> ::std::borrow::Cow<'s, str> {
+                if self.node.end_byte() <= self.source.len_bytes() {
+                    self.source.text(self.node.byte_range())
+                } else {
+                    ::std::borrow::Cow::Borrowed("")
+                }
+            }
+
+            fn field(&self, name: &str) -> ::std::option::Option<Self> {
+                self.node
+                    .child_by_field_name(name)
+                    .map(|child| Self::new(child, self.source))
+            }
+
+            fn fields(&self, name: &str) -> ::std::vec::Vec<Self> {
+                let mut cursor = self.node.walk();
+                self.node
+                    .children_by_field_name(name, &mut cursor)
+                    .map(|child| Self::new(child, self.source))
+                    .collect()
+            }
+
+            /// Returns the named children that are not in a field.
+            fn children(&self) -> ::std::vec::Vec<Self> {
+                let mut children = ::std::vec::Vec::new();
+                let mut cursor = self.node.walk();
+                if cursor.goto_first_child() {
+                    loop {
+                        if cursor.node().is_named() && cursor.field_name().is_none() {
+                            children.push(Self::new(cursor.node(), self.source));
+                        }
+                        if !cursor.goto_next_sibling() {
+                            break;
+                        }
+                    }
+                }
+                children
+            }
+
+            fn named_children(&self) -> ::std::vec::Vec<Self> {
+                let mut cursor = self.node.walk();
+                self.node
+                    .named_children(&mut cursor)
+                    .map(|child| Self::new(child, self.source))
+                    .collect()
+            }
+        }
+
+        impl<T: TextProvider + ?Sized> ::serde::Serialize for SerializableNode<'_, '_, T> {
+            fn serialize<S: ::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> ::std::result::Result<S::Ok, S::Error> {
+                use ::serde::ser::SerializeMap;
+
+                let mut map = serializer.serialize_map(::std::option::Option::None)?;
+                map.serialize_entry("type", self.node.kind())?;
+                map.serialize_entry("named", &self.node.is_named())?;
+                map.serialize_entry("start", &self.node.start_byte())?;
+                map.serialize_entry("end", &self.node.end_byte())?;
+                match (self.node.kind(), self.node.is_named()) {
+                    #(#entry_arms)*
+                    _ if self.node.child_count() == 0 => {
+                        map.serialize_entry("text", &self.text())?;
+                    }
+                    _ => {
+                        map.serialize_entry("children", &self.named_children())?;
+                    }
+                }
+                map.end()
+            }
+        }
+
+        /// The `fields` object of a `SerializableNode`.
+        struct SerializableFields<'a, 'tree, 's, T: ?Sized>(&'a SerializableNode<'tree, 's, T>);
+
+        impl<T: TextProvider + ?Sized> ::serde::Serialize for SerializableFields<'_, '_, '_, T> {
+            fn serialize<S: ::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> ::std::result::Result<S::Ok, S::Error> {
+                use ::serde::ser::SerializeMap;
+
+                let mut map = serializer.serialize_map(::std::option::Option::None)?;
+                match (self.0.node.kind(), self.0.node.is_named()) {
+                    #(#field_arms)*
+                    _ => {}
+                }
+                map.end()
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Generation of the `Session` type bundling a parser, the current tree, its source and a visitor.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+pub(crate) fn generate(item: &ItemTrait) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = &item.ident;
+
+    quote! {
+        /// An error while creating or editing a `Session`.
+        #[derive(Debug, PartialEq, Eq)]
+        #vis enum SessionError {
+            /// The language is not compatible with the version of tree-sitter.
+            Language(::tree_sitter::LanguageError),
+            /// The edited range is reversed, past the end of the source, or does not start and end
+            /// on character boundaries.
+            InvalidRange(::std::ops::Range<usize>),
+            /// The parser did not return a tree, e.g. because parsing was cancelled.
+            Parse,
+        }
+
+        impl ::std::fmt::Display for SessionError {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    SessionError::Language(err) => write!(f, "could not load language: {}", err),
+                    SessionError::InvalidRange(range) => {
+                        write!(f, "invalid edit range: {}..{}", range.start, range.end)
+                    }
+                    SessionError::Parse => write!(f, "parsing was cancelled"),
+                }
+            }
+        }
+
+        impl ::std::error::Error for SessionError {}
+
+        impl ::std::convert::From<::tree_sitter::LanguageError> for SessionError {
+            fn from(err: ::tree_sitter::LanguageError) -> Self {
+                SessionError::Language(err)
+            }
+        }
+
+        /// An incremental analysis session.
+        ///
+        /// Owns a parser, the source text, its current syntax tree and a visitor. Edits are applied
+        /// to both the source and the tree, which is then reparsed incrementally and visited again.
+        #vis struct Session<V> {
+            parser: ::tree_sitter::Parser,
+            tree: ::tree_sitter::Tree,
+            source: ::std::string::String,
+            visitor: V,
+        }
+
+        impl<V: #trait_name> Session<V> {
+            /// Creates a new session, parsing `source` with the given language.
+            pub fn new(
+                language: ::tree_sitter::Language,
+                source: ::std::string::String,
+                visitor: V,
+            ) -> ::std::result::Result<Self, SessionError> {
+                let mut parser = ::tree_sitter::Parser::new();
+                parser.set_language(&language)?;
+                let tree = parser.parse(&source, None).ok_or(SessionError::Parse)?;
+
+                Ok(Self {
+                    parser,
+                    tree,
+                    source,
+                    visitor,
+                })
+            }
+
+            /// Returns the current source text.
+            pub fn source(&self) -> &str {
+                &self.source
+            }
+
+            /// Returns the current syntax tree.
+            pub fn tree(&self) -> &::tree_sitter::Tree {
+                &self.tree
+            }
+
+            /// Returns a reference to the visitor.
+            pub fn visitor(&self) -> &V {
+                &self.visitor
+            }
+
+            /// Returns a mutable reference to the visitor.
+            pub fn visitor_mut(&mut self) -> &mut V {
+                &mut self.visitor
+            }
+
+            /// Consumes the session, returning the visitor.
+            pub fn into_visitor(self) -> V {
+                self.visitor
+            }
+
+            /// Visits the root node of the current tree.
+            pub fn visit(&mut self) -> V::ReturnType {
+                self.visitor.visit(&self.tree.root_node())
+            }
+
+            /// Replaces the text in `range` with `new_text`, reparses and visits the whole tree again.
+            /// If the range is invalid or reparsing fails, the session is left unchanged.
+            pub fn edit(
+                &mut self,
+                range: ::std::ops::Range<usize>,
+                new_text: &str,
+            ) -> ::std::result::Result<V::ReturnType, SessionError> {
+                self.apply_edit(range, new_text)?;
+                Ok(self.visit())
+            }
+
+            /// Replaces the text in `range` with `new_text`, reparses and visits only the outermost of
+            /// the smallest nodes enclosing each changed range, in document order. If the range is
+            /// invalid or reparsing fails, the session is left unchanged.
+            pub fn edit_changed(
+                &mut self,
+                range: ::std::ops::Range<usize>,
+                new_text: &str,
+            ) -> ::std::result::Result<::std::vec::Vec<V::ReturnType>, SessionError> {
+                let changed = self.apply_edit(range, new_text)?;
+                let root = self.tree.root_node();
+
+                let mut nodes: ::std::vec::Vec<::tree_sitter::Node> = changed
+                    .iter()
+                    .filter_map(|range| root.descendant_for_byte_range(range.start, range.end))
+                    .collect();
+                nodes.sort_by_key(|node| (node.start_byte(), ::std::cmp::Reverse(node.end_byte())));
+
+                let mut outermost: ::std::vec::Vec<::tree_sitter::Node> = ::std::vec::Vec::new();
+                for node in nodes {
+                    if outermost.last().map_or(true, |last| node.end_byte() > last.end_byte()) {
+                        outermost.push(node);
+                    }
+                }
+
+                Ok(outermost.iter().map(|node| self.visitor.visit(node)).collect())
+            }
+
+            /// Applies an edit to the source and the tree and reparses, returning the byte ranges
+            /// that changed (including the edited range itself).
+            fn apply_edit(
+                &mut self,
+                range: ::std::ops::Range<usize>,
+                new_text: &str,
+            ) -> ::std::result::Result<::std::vec::Vec<::std::ops::Range<usize>>, SessionError> {
+                if range.start > range.end
+                    || range.end > self.source.len()
+                    || !self.source.is_char_boundary(range.start)
+                    || !self.source.is_char_boundary(range.end)
+                {
+                    return Err(SessionError::InvalidRange(range));
+                }
+
+                let start_position = Self::point_at(&self.source, range.start);
+                let old_end_position = Self::point_at(&self.source, range.end);
+                let old_text = self.source[range.clone()].to_string();
+                self.source.replace_range(range.clone(), new_text);
+                let new_end_byte = range.start + new_text.len();
+                let new_end_position = Self::point_at(&self.source, new_end_byte);
+
+                let mut tree = self.tree.clone();
+                tree.edit(&::tree_sitter::InputEdit {
+                    start_byte: range.start,
+                    old_end_byte: range.end,
+                    new_end_byte,
+                    start_position,
+                    old_end_position,
+                    new_end_position,
+                });
+
+                let new_tree = match self.parser.parse(&self.source, Some(&tree)) {
+                    Some(new_tree) => new_tree,
+                    None => {
+                        self.source.replace_range(range.start..new_end_byte, &old_text);
+                        return Err(SessionError::Parse);
+                    }
+                };
+                let mut changed: ::std::vec::Vec<_> = tree
+                    .changed_ranges(&new_tree)
+                    .map(|range| range.start_byte..range.end_byte)
+                    .collect();
+                changed.push(range.start..new_end_byte);
+                self.tree = new_tree;
+
+                Ok(changed)
+            }
+
+            fn point_at(source: &str, byte: usize) -> ::tree_sitter::Point {
+                let before = &source.as_bytes()[..byte];
+                let row = before.iter().filter(|&&b| b == b'\n').count();
+                let column = match before.iter().rposition(|&b| b == b'\n') {
+                    Some(newline) => byte - newline - 1,
+                    None => byte,
+                };
+
+                ::tree_sitter::Point::new(row, column)
+            }
+        }
+    }
+}
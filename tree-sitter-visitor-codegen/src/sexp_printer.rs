@@ -0,0 +1,151 @@
+//! Generation of `SexpPrinter`, which renders trees as indented S-expressions with their field
+//! labels and byte ranges, e.g. for snapshot tests.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+pub(crate) fn generate(item: &ItemTrait) -> TokenStream {
+    let vis = &item.vis;
+
+    quote! {
+        /// Renders a tree as an S-expression with one node per line, each indented by two spaces
+        /// per level and labelled with the field it is in and its byte range, e.g.
+        ///
+        /// ```text
+        /// (add_expr [0..5]
+        ///   lhs: (number [0..1])
+        ///   rhs: (number [4..5]))
+        /// ```
+        ///
+        /// The output only depends on the tree, so it is stable across runs and its line diffs
+        /// point at the nodes that changed. `MISSING` nodes are printed as `(MISSING kind)`.
+        #[derive(Debug)]
+        #vis struct SexpPrinter<'s, S: ?Sized = str> {
+            source: ::std::option::Option<&'s S>,
+            anonymous: bool,
+            ranges: bool,
+        }
+
+        impl<S: ?Sized> ::std::clone::Clone for SexpPrinter<'_, S> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<S: ?Sized> ::std::marker::Copy for SexpPrinter<'_, S> {}
+
+        impl ::std::default::Default for SexpPrinter<'_> {
+            fn default() -> Self {
+                Self {
+                    source: ::std::option::Option::None,
+                    anonymous: false,
+                    ranges: true,
+                }
+            }
+        }
+
+        impl<'s> SexpPrinter<'s> {
+            /// Creates a printer of named nodes with their byte ranges.
+            pub fn new() -> Self {
+                Self::default()
+            }
+        }
+
+        impl<'s, S: TextProvider + ?Sized> SexpPrinter<'s, S> {
+            /// Prints the text of named nodes without children, taken from the source the tree was
+            /// parsed from.
+            pub fn source<T: TextProvider + ?Sized>(self, source: &'s T) -> SexpPrinter<'s, T> {
+                SexpPrinter {
+                    source: ::std::option::Option::Some(source),
+                    anonymous: self.anonymous,
+                    ranges: self.ranges,
+                }
+            }
+
+            /// Sets whether anonymous nodes are printed, as their quoted kind. They are not by
+            /// default.
+            pub fn anonymous(mut self, anonymous: bool) -> Self {
+                self.anonymous = anonymous;
+                self
+            }
+
+            /// Sets whether byte ranges are printed. They are by default.
+            pub fn ranges(mut self, ranges: bool) -> Self {
+                self.ranges = ranges;
+                self
+            }
+
+            /// Returns the S-expression of the subtree rooted at a node.
+            pub fn print(&self, node: &::tree_sitter::Node) -> ::std::string::String {
+                let mut out = ::std::string::String::new();
+                self.write(node, &mut out)
+                    .expect("writing to a string does not fail");
+                out
+            }
+
+            /// Writes the S-expression of the subtree rooted at a node. The tree is walked with a
+            /// cursor, so deep trees do not overflow the stack.
+            pub fn write(
+                &self,
+                node: &::tree_sitter::Node,
+                out: &mut impl ::std::fmt::Write,
+            ) -> ::std::fmt::Result {
+                let mut cursor = node.walk();
+                let mut depth = 0;
+                loop {
+                    let node = cursor.node();
+                    if node.is_named() || self.anonymous || depth == 0 {
+                        if depth > 0 {
+                            out.write_char('\n')?;
+                        }
+                        write!(out, "{:indent$}", "", indent = depth * 2)?;
+                        if let ::std::option::Option::Some(field) =
+                            cursor.field_name().filter(|_| depth > 0)
+                        {
+                            write!(out, "{}: ", field)?;
+                        }
+                        if !node.is_named() {
+                            write!(out, "{:?}", node.kind())?;
+                        } else if node.is_missing() {
+                            write!(out, "(MISSING {}", node.kind())?;
+                        } else {
+                            write!(out, "({}", node.kind())?;
+                        }
+                        if self.ranges {
+                            write!(out, " [{}..{}]", node.start_byte(), node.end_byte())?;
+                        }
+                        if let ::std::option::Option::Some(source) = self.source {
+                            if node.is_named()
+                                && node.child_count() == 0
+                                && node.end_byte() <= source.len_bytes()
+                            {
+                                write!(out, " {:?}", source.text(node.byte_range()))?;
+                            }
+                        }
+                    }
+                    // Only named nodes are printed with their children, in parentheses.
+                    if node.is_named() {
+                        if cursor.goto_first_child() {
+                            depth += 1;
+                            continue;
+                        }
+                        out.write_char(')')?;
+                    }
+                    // Closes the nodes whose last child was reached.
+                    loop {
+                        if depth == 0 {
+                            return ::std::result::Result::Ok(());
+                        }
+                        if cursor.goto_next_sibling() {
+                            break;
+                        }
+                        cursor.goto_parent();
+                        depth -= 1;
+                        out.write_char(')')?;
+                    }
+                }
+            }
+        }
+    }
+}
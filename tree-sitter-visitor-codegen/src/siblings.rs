@@ -0,0 +1,54 @@
+//! Generation of sibling navigation helpers skipping extras, such as comments.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::Node;
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    let extra_kinds = node_types
+        .iter()
+        .filter(|node| node.extra)
+        .map(|node| &node.r#type);
+
+    quote! {
+        /// The node kinds declared as extras in `node-types.json`.
+        #vis const EXTRA_KINDS: &[&str] = &[#(#extra_kinds),*];
+
+        /// Returns `true` if the node is an extra, such as a comment, either because the parser
+        /// marked it as one or because its kind is declared as an extra.
+        #vis fn is_extra_node(node: &::tree_sitter::Node) -> bool {
+            node.is_extra() || EXTRA_KINDS.contains(&node.kind())
+        }
+
+        /// Returns the next sibling of `node`, skipping extras.
+        #vis fn next_semantic_sibling<'tree>(
+            node: &::tree_sitter::Node<'tree>,
+        ) -> ::std::option::Option<::tree_sitter::Node<'tree>> {
+            let mut sibling = node.next_sibling();
+            while let ::std::option::Option::Some(node) = sibling {
+                if !is_extra_node(&node) {
+                    break;
+                }
+                sibling = node.next_sibling();
+            }
+            sibling
+        }
+
+        /// Returns the previous sibling of `node`, skipping extras.
+        #vis fn prev_semantic_sibling<'tree>(
+            node: &::tree_sitter::Node<'tree>,
+        ) -> ::std::option::Option<::tree_sitter::Node<'tree>> {
+            let mut sibling = node.prev_sibling();
+            while let ::std::option::Option::Some(node) = sibling {
+                if !is_extra_node(&node) {
+                    break;
+                }
+                sibling = node.prev_sibling();
+            }
+            sibling
+        }
+    }
+}
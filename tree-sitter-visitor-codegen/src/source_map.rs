@@ -0,0 +1,203 @@
+//! Generation of `SourceMapBuilder`, which records where the text emitted by a code generator comes
+//! from in the source and exports it as a source map.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+pub(crate) fn generate(item: &ItemTrait) -> TokenStream {
+    let vis = &item.vis;
+
+    quote! {
+        /// A mapping from a position in the output to the position in the source it was generated
+        /// from. Lines are 0-based, and columns are 0-based UTF-16 code units, as in source maps.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #vis struct SourceMapping {
+            pub generated_line: usize,
+            pub generated_column: usize,
+            pub original_line: usize,
+            pub original_column: usize,
+        }
+
+        /// Builds the output of a code generator together with its mappings to the source, which can
+        /// be exported as a [source map](https://sourcemaps.info/spec.html).
+        ///
+        /// Text pushed with `push_node()` is mapped to the start of a node, while text pushed with
+        /// `push_str()` or written with `write!` continues the previous mapping.
+        #[derive(Debug)]
+        #vis struct SourceMapBuilder<'source, S: ?Sized = str> {
+            source: &'source S,
+            output: ::std::string::String,
+            line: usize,
+            column: usize,
+            mappings: ::std::vec::Vec<SourceMapping>,
+        }
+
+        impl<S: ?Sized> ::std::clone::Clone for SourceMapBuilder<'_, S> {
+            fn clone(&self) -> Self {
+                Self {
+                    source: self.source,
+                    output: self.output.clone(),
+                    line: self.line,
+                    column: self.column,
+                    mappings: self.mappings.clone(),
+                }
+            }
+        }
+
+        impl<'source, S: TextProvider + ?Sized> SourceMapBuilder<'source, S> {
+            /// Creates a builder with an empty output for code generated from `source`.
+            pub fn new(source: &'source S) -> Self {
+                Self {
+                    source,
+                    output: ::std::string::String::new(),
+                    line: 0,
+                    column: 0,
+                    mappings: ::std::vec::Vec::new(),
+                }
+            }
+
+            /// Appends text to the output.
+            pub fn push_str(&mut self, text: &str) {
+                for c in text.chars() {
+                    if c == '\n' {
+                        self.line += 1;
+                        self.column = 0;
+                    } else {
+                        self.column += c.len_utf16();
+                    }
+                }
+                self.output.push_str(text);
+            }
+
+            /// Maps the current position of the output to the start of a node.
+            pub fn mark(&mut self, node: &::tree_sitter::Node) {
+                self.mark_at(node.start_byte(), node.start_position());
+            }
+
+            /// Maps the current position of the output to a position in the source, given as a byte
+            /// offset and the matching point.
+            pub fn mark_at(&mut self, byte: usize, position: ::tree_sitter::Point) {
+                let line_start = byte - position.column;
+                let original_column = self.source.text(line_start..byte).encode_utf16().count();
+                let mapping = SourceMapping {
+                    generated_line: self.line,
+                    generated_column: self.column,
+                    original_line: position.row,
+                    original_column,
+                };
+                match self.mappings.last_mut() {
+                    ::std::option::Option::Some(last)
+                        if (last.generated_line, last.generated_column) == (self.line, self.column) =>
+                    {
+                        *last = mapping;
+                    }
+                    _ => self.mappings.push(mapping),
+                }
+            }
+
+            /// Appends text generated from a node to the output, mapping it to the start of the node.
+            pub fn push_node(&mut self, node: &::tree_sitter::Node, text: &str) {
+                self.mark(node);
+                self.push_str(text);
+            }
+
+            /// Returns the output so far.
+            pub fn output(&self) -> &str {
+                &self.output
+            }
+
+            /// Returns the mappings so far, in the order of the output.
+            pub fn mappings(&self) -> &[SourceMapping] {
+                &self.mappings
+            }
+
+            /// Consumes the builder, returning the output.
+            pub fn into_output(self) -> ::std::string::String {
+                self.output
+            }
+
+            /// Returns the mappings encoded as the `mappings` field of a source map.
+            pub fn encoded_mappings(&self) -> ::std::string::String {
+                fn push_vlq(encoded: &mut ::std::string::String, value: i64) {
+                    const BASE64: &[u8] =
+                        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+                    let mut vlq = if value < 0 { ((-value) << 1) | 1 } else { value << 1 };
+                    loop {
+                        let mut digit = vlq & 0b11111;
+                        vlq >>= 5;
+                        if vlq > 0 {
+                            digit |= 0b100000;
+                        }
+                        encoded.push(BASE64[digit as usize] as char);
+                        if vlq == 0 {
+                            break;
+                        }
+                    }
+                }
+
+                let mut encoded = ::std::string::String::new();
+                let (mut line, mut column) = (0, 0);
+                let (mut original_line, mut original_column) = (0, 0);
+                for (index, mapping) in self.mappings.iter().enumerate() {
+                    if mapping.generated_line > line {
+                        for _ in line..mapping.generated_line {
+                            encoded.push(';');
+                        }
+                        line = mapping.generated_line;
+                        column = 0;
+                    } else if index > 0 {
+                        encoded.push(',');
+                    }
+                    push_vlq(&mut encoded, mapping.generated_column as i64 - column);
+                    // All the mappings are to the only source.
+                    push_vlq(&mut encoded, 0);
+                    push_vlq(&mut encoded, mapping.original_line as i64 - original_line);
+                    push_vlq(&mut encoded, mapping.original_column as i64 - original_column);
+                    column = mapping.generated_column as i64;
+                    original_line = mapping.original_line as i64;
+                    original_column = mapping.original_column as i64;
+                }
+                encoded
+            }
+
+            /// Returns a version 3 source map as JSON, with `file` being the name of the output and
+            /// `source_name` the name of the source, whose content is embedded.
+            pub fn to_json(&self, file: &str, source_name: &str) -> ::std::string::String {
+                fn quote(text: &str) -> ::std::string::String {
+                    let mut quoted = ::std::string::String::from("\"");
+                    for c in text.chars() {
+                        match c {
+                            '"' => quoted.push_str("\\\""),
+                            '\\' => quoted.push_str("\\\\"),
+                            '\n' => quoted.push_str("\\n"),
+                            '\r' => quoted.push_str("\\r"),
+                            '\t' => quoted.push_str("\\t"),
+                            c if (c as u32) < 0x20 => {
+                                quoted.push_str(&::std::format!("\\u{:04x}", c as u32))
+                            }
+                            c => quoted.push(c),
+                        }
+                    }
+                    quoted.push('"');
+                    quoted
+                }
+
+                ::std::format!(
+                    "{{\"version\":3,\"file\":{},\"sources\":[{}],\"sourcesContent\":[{}],\"names\":[],\"mappings\":{}}}",
+                    quote(file),
+                    quote(source_name),
+                    quote(&self.source.full_text()),
+                    quote(&self.encoded_mappings())
+                )
+            }
+        }
+
+        impl<S: TextProvider + ?Sized> ::std::fmt::Write for SourceMapBuilder<'_, S> {
+            fn write_str(&mut self, text: &str) -> ::std::fmt::Result {
+                self.push_str(text);
+                Ok(())
+            }
+        }
+    }
+}
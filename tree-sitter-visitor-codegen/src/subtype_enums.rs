@@ -0,0 +1,159 @@
+//! Generation of the `supertypes` module, with an enum of the subtypes of each supertype, so
+//! implementations can match over them exhaustively.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Ident, ItemTrait};
+
+use crate::node_types::{Node, NodeRef};
+use crate::{sanitize_identifier, upper_camel_case};
+
+/// Returns the name of the enum of a supertype, or of the variant of a subtype.
+fn type_name(kind: &str) -> String {
+    let name = upper_camel_case(&sanitize_identifier(kind));
+    if name.is_empty() {
+        // Kinds made of underscores only.
+        "Underscore".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("Kind{}", name)
+    } else {
+        name
+    }
+}
+
+/// Returns the names of the variants of a supertype, in the order of its subtypes. Anonymous
+/// subtypes whose name clashes with that of another subtype get a `Token` suffix.
+fn variant_names(subtypes: &[NodeRef]) -> Vec<Ident> {
+    let mut taken: Vec<String> = subtypes
+        .iter()
+        .filter(|subtype| subtype.named)
+        .map(|subtype| type_name(&subtype.r#type))
+        .collect();
+    subtypes
+        .iter()
+        .map(|subtype| {
+            let name = type_name(&subtype.r#type);
+            if subtype.named {
+                return format_ident!("{}", name);
+            }
+            let mut unique = name.clone();
+            let mut suffix = 1;
+            while taken.contains(&unique) {
+                unique = if suffix == 1 {
+                    format!("{}Token", name)
+                } else {
+                    format!("{}Token{}", name, suffix)
+                };
+                suffix += 1;
+            }
+            taken.push(unique.clone());
+            format_ident!("{}", unique)
+        })
+        .collect()
+}
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    let is_supertype = |subtype: &NodeRef| {
+        subtype.named
+            && node_types
+                .iter()
+                .any(|node| node.r#type == subtype.r#type && node.is_supertype())
+    };
+
+    let enums = node_types
+        .iter()
+        .filter(|node| node.is_supertype())
+        .map(|node| {
+            let name = format_ident!("{}", type_name(&node.r#type));
+            let variants = variant_names(&node.subtypes);
+            let doc_string = format!(
+                "The node kinds of the supertype `{}`, each variant wrapping a node of the kind.",
+                node.r#type
+            );
+
+            let declarations = node.subtypes.iter().zip(&variants).map(|(subtype, variant)| {
+                if is_supertype(subtype) {
+                    let nested = format_ident!("{}", type_name(&subtype.r#type));
+                    let doc_string = format!("A node of the supertype `{}`.", subtype.r#type);
+                    quote! {
+                        #[doc = #doc_string]
+                        #variant(#nested<'tree>)
+                    }
+                } else {
+                    let kind = format!("{:?}", subtype.r#type).replace('`', "\\`");
+                    let doc_string = format!("A node of kind `{}`.", kind);
+                    quote! {
+                        #[doc = #doc_string]
+                        #variant(::tree_sitter::Node<'tree>)
+                    }
+                }
+            });
+            let (nested, direct): (Vec<_>, Vec<_>) = node
+                .subtypes
+                .iter()
+                .zip(&variants)
+                .partition(|(subtype, _)| is_supertype(subtype));
+            let arms = direct.iter().map(|(subtype, variant)| {
+                let kind = &subtype.r#type;
+                let named = subtype.named;
+                quote! { (#kind, #named) => ::std::option::Option::Some(Self::#variant(node)) }
+            });
+            let nested_checks = nested.iter().map(|(subtype, variant)| {
+                let nested = format_ident!("{}", type_name(&subtype.r#type));
+                quote! {
+                    if let ::std::option::Option::Some(inner) = #nested::classify(node) {
+                        return ::std::option::Option::Some(Self::#variant(inner));
+                    }
+                }
+            });
+            let node_arms = node.subtypes.iter().zip(&variants).map(|(subtype, variant)| {
+                if is_supertype(subtype) {
+                    quote! { Self::#variant(inner) => inner.node() }
+                } else {
+                    quote! { Self::#variant(node) => *node }
+                }
+            });
+
+            quote! {
+                #[doc = #doc_string]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+                pub enum #name<'tree> {
+                    #(#declarations,)*
+                }
+
+                impl<'tree> #name<'tree> {
+                    /// Returns the variant for the kind of a node, or `None` if the node is not of a
+                    /// subtype.
+                    pub fn classify(node: ::tree_sitter::Node<'tree>) -> ::std::option::Option<Self> {
+                        match (node.kind(), node.is_named()) {
+                            #(#arms,)*
+                            _ => {
+                                #(#nested_checks)*
+                                ::std::option::Option::None
+                            }
+                        }
+                    }
+
+                    /// Returns the wrapped node.
+                    pub fn node(&self) -> ::tree_sitter::Node<'tree> {
+                        match self {
+                            #(#node_arms,)*
+                        }
+                    }
+                }
+            }
+        });
+
+    let doc_string = format!(
+        "An enum for each supertype of the node kinds handled by `{}`.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #vis mod supertypes {
+            #(#enums)*
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Generation of the `TextProvider` abstraction over source text representations.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Visibility;
+
+use crate::options::Options;
+
+pub(crate) fn generate(vis: &Visibility, options: &Options) -> TokenStream {
+    let ropey_impls = if options.ropey {
+        quote! {
+            impl TextProvider for ::ropey::Rope {
+                fn text(&self, range: ::std::ops::Range<usize>) -> ::std::borrow::Cow<'_, str> {
+                    self.byte_slice(range).into()
+                }
+
+                fn len_bytes(&self) -> usize {
+                    ::ropey::Rope::len_bytes(self)
+                }
+
+                fn chunk_at(&self, byte: usize) -> &[u8] {
+                    if byte >= self.len_bytes() {
+                        return &[];
+                    }
+                    let (chunk, chunk_start, _, _) = self.chunk_at_byte(byte);
+                    &chunk.as_bytes()[byte - chunk_start..]
+                }
+            }
+
+            impl<'r> TextProvider for ::ropey::RopeSlice<'r> {
+                fn text(&self, range: ::std::ops::Range<usize>) -> ::std::borrow::Cow<'_, str> {
+                    self.byte_slice(range).into()
+                }
+
+                fn len_bytes(&self) -> usize {
+                    ::ropey::RopeSlice::len_bytes(self)
+                }
+
+                fn chunk_at(&self, byte: usize) -> &[u8] {
+                    if byte >= self.len_bytes() {
+                        return &[];
+                    }
+                    let (chunk, chunk_start, _, _) = self.chunk_at_byte(byte);
+                    &chunk.as_bytes()[byte - chunk_start..]
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        /// Random access to the source text a tree was parsed from.
+        ///
+        /// Implemented for contiguous sources (`str`, `[u8]`, `String`) and, when enabled, for
+        /// `ropey` ropes, so node text can be read without first materializing the whole source.
+        #vis trait TextProvider {
+            /// Returns the source text in the given byte range.
+            fn text(&self, range: ::std::ops::Range<usize>) -> ::std::borrow::Cow<'_, str>;
+
+            /// Returns the length of the source in bytes.
+            fn len_bytes(&self) -> usize;
+
+            /// Returns a contiguous chunk of the source starting at `byte`, or an empty slice
+            /// if `byte` is past the end of the source.
+            fn chunk_at(&self, byte: usize) -> &[u8];
+
+            /// Returns the whole source text.
+            fn full_text(&self) -> ::std::borrow::Cow<'_, str> {
+                self.text(0..self.len_bytes())
+            }
+
+            /// Returns the source text of the given node.
+            fn node_text(&self, node: &::tree_sitter::Node) -> ::std::borrow::Cow<'_, str> {
+                self.text(node.byte_range())
+            }
+
+            /// Parses the source chunk by chunk, without requiring it to be contiguous.
+            fn parse_with(
+                &self,
+                parser: &mut ::tree_sitter::Parser,
+                old_tree: ::std::option::Option<&::tree_sitter::Tree>,
+            ) -> ::std::option::Option<::tree_sitter::Tree> {
+                parser.parse_with(&mut |byte, _| self.chunk_at(byte), old_tree)
+            }
+        }
+
+        impl<T: TextProvider + ?Sized> TextProvider for &T {
+            fn text(&self, range: ::std::ops::Range<usize>) -> ::std::borrow::Cow<'_, str> {
+                (**self).text(range)
+            }
+
+            fn len_bytes(&self) -> usize {
+                (**self).len_bytes()
+            }
+
+            fn chunk_at(&self, byte: usize) -> &[u8] {
+                (**self).chunk_at(byte)
+            }
+        }
+
+        impl TextProvider for str {
+            fn text(&self, range: ::std::ops::Range<usize>) -> ::std::borrow::Cow<'_, str> {
+                ::std::borrow::Cow::Borrowed(&self[range])
+            }
+
+            fn len_bytes(&self) -> usize {
+                self.len()
+            }
+
+            fn chunk_at(&self, byte: usize) -> &[u8] {
+                self.as_bytes().get(byte..).unwrap_or_default()
+            }
+        }
+
+        impl TextProvider for ::std::string::String {
+            fn text(&self, range: ::std::ops::Range<usize>) -> ::std::borrow::Cow<'_, str> {
+                self.as_str().text(range)
+            }
+
+            fn len_bytes(&self) -> usize {
+                self.len()
+            }
+
+            fn chunk_at(&self, byte: usize) -> &[u8] {
+                self.as_str().chunk_at(byte)
+            }
+        }
+
+        impl TextProvider for [u8] {
+            fn text(&self, range: ::std::ops::Range<usize>) -> ::std::borrow::Cow<'_, str> {
+                ::std::string::String::from_utf8_lossy(&self[range])
+            }
+
+            fn len_bytes(&self) -> usize {
+                self.len()
+            }
+
+            fn chunk_at(&self, byte: usize) -> &[u8] {
+                self.get(byte..).unwrap_or_default()
+            }
+        }
+
+        #ropey_impls
+    }
+}
@@ -0,0 +1,78 @@
+//! Generation of the token visitor trait, a smaller visitor trait covering only the terminal node
+//! kinds of the grammar.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::node_types::Node;
+use crate::{derived_trait_name, method_name, sanitize_identifier};
+
+pub(crate) fn generate(item: &ItemTrait, node_types: &[Node]) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = derived_trait_name(&item.ident, "TokenVisitor");
+
+    let (methods, match_arms): (Vec<_>, Vec<_>) = node_types
+        .iter()
+        .filter(|node| node.is_terminal())
+        .map(|node| {
+            let kind = &node.r#type;
+            let method_name = method_name(kind);
+            let sanitized_name = sanitize_identifier(kind);
+            let doc_string = format!("Visits a token of type `{:?}`", kind);
+
+            let method = quote! {
+                #[doc = #doc_string]
+                fn #method_name(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
+                    unimplemented!(#sanitized_name)
+                }
+            };
+            let match_arm = quote! {
+                #kind => self.#method_name(node)
+            };
+
+            (method, match_arm)
+        })
+        .unzip();
+
+    let doc_string = format!(
+        "A visitor for the terminal node kinds of the grammar only, i.e. the anonymous tokens and \
+         the named nodes without children, for lexical tools that don't need all of `{}`.",
+        item.ident
+    );
+
+    quote! {
+        #[doc = #doc_string]
+        #vis trait #trait_name {
+            type ReturnType;
+
+            /// Visits a token of any type.
+            fn visit(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
+                match node.kind() {
+                    #(#match_arms,)*
+                    _ => panic!("unknown token kind: {}", node.kind())
+                }
+            }
+
+            /// Visits all the nodes without children in the subtree rooted at `node`, in document
+            /// order, passing each of them to `f` together with the result of visiting it.
+            fn visit_tokens<'tree>(
+                &mut self,
+                node: &::tree_sitter::Node<'tree>,
+                f: &mut dyn ::std::ops::FnMut(::tree_sitter::Node<'tree>, Self::ReturnType),
+            ) {
+                if node.child_count() == 0 {
+                    let result = self.visit(node);
+                    f(*node, result);
+                    return;
+                }
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor) {
+                    self.visit_tokens(&child, f);
+                }
+            }
+
+            #(#methods)*
+        }
+    }
+}
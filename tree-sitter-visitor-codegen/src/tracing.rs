@@ -0,0 +1,52 @@
+//! Generation of the instrumentation of `visit()` with `tracing` spans carrying the kind, the byte
+//! range and the depth of the visited node.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+
+use crate::upper_snake_case;
+
+fn depth_ident(trait_name: &Ident) -> Ident {
+    format_ident!(
+        "{}_TRACING_DEPTH",
+        upper_snake_case(&trait_name.to_string())
+    )
+}
+
+/// Wraps the body of a dispatcher in a span named `visit`, entered for the duration of the visit.
+pub(crate) fn instrument(trait_name: &Ident, body: TokenStream) -> TokenStream {
+    let depth = depth_ident(trait_name);
+
+    quote! {
+        // Restores the depth even if the visit panics.
+        struct Ascend;
+        impl ::std::ops::Drop for Ascend {
+            fn drop(&mut self) {
+                #depth.with(|depth| depth.set(depth.get() - 1));
+            }
+        }
+        let depth = #depth.with(|depth| depth.replace(depth.get() + 1));
+        let _ascend = Ascend;
+        let span = ::tracing::trace_span!(
+            "visit",
+            kind = node.kind(),
+            start_byte = node.start_byte(),
+            end_byte = node.end_byte(),
+            depth,
+        );
+        let _entered = span.enter();
+        #body
+    }
+}
+
+/// The thread-local depth of the dispatches in progress, shared by all dispatchers of the trait.
+pub(crate) fn generate(trait_name: &Ident) -> TokenStream {
+    let depth = depth_ident(trait_name);
+
+    quote! {
+        ::std::thread_local! {
+            static #depth: ::std::cell::Cell<usize> = ::std::cell::Cell::new(0);
+        }
+    }
+}
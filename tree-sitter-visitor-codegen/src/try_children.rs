@@ -0,0 +1,86 @@
+//! Generation of `try_visit_children()` and `try_visit_children_all()`, which visit the children
+//! of a node with a visitor returning `Result`.
+
+use quote::quote;
+use syn::{parse_quote, ItemTrait, TraitItem};
+
+use crate::extras;
+use crate::node_types::Node;
+use crate::options::Options;
+use crate::{fresh_type_param, trait_path};
+
+pub(crate) fn trait_items(
+    item: &ItemTrait,
+    node_types: &[Node],
+    options: &Options,
+) -> Vec<TraitItem> {
+    // With `fallible`, `visit()` returns `Result<ReturnType, Error>` whatever `ReturnType` is.
+    let (generics, t, e, where_clause) = if options.fallible {
+        let t = options.self_return_type();
+        (quote! {}, t, quote! { Self::Error }, quote! {})
+    } else {
+        let t = fresh_type_param(item, "T");
+        let e = fresh_type_param(item, "E");
+        let trait_path = trait_path(item, quote! { ReturnType = ::std::result::Result<#t, #e> });
+        (
+            quote! { <#t, #e> },
+            quote! { #t },
+            quote! { #e },
+            quote! { where Self: #trait_path + Sized, },
+        )
+    };
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let keep = match extras::skipped(node_types, options, quote! { child }) {
+        Some(skipped) => quote! { .filter(|child| !#skipped) },
+        None => quote! {},
+    };
+    vec![
+        parse_quote! {
+            #[doc=r"Visits all the children of a node in document order, stopping at the first error.
+            Only available if `ReturnType` is a `Result`, unless the trait is `fallible`."]
+            fn try_visit_children #generics (
+                &mut self,
+                node: &::tree_sitter::Node
+                #param_decls
+            ) -> ::std::result::Result<::std::vec::Vec<#t>, #e>
+            #where_clause
+            {
+                let mut cursor = node.walk();
+                let children: ::std::vec::Vec<_> = node.children(&mut cursor)#keep.collect();
+                let mut results = ::std::vec::Vec::with_capacity(children.len());
+                for child in &children {
+                    results.push(self.visit(child #param_args)?);
+                }
+                ::std::result::Result::Ok(results)
+            }
+        },
+        parse_quote! {
+            #[doc=r"Visits all the children of a node in document order, returning the errors of all
+            the children that failed, if any. Only available if `ReturnType` is a `Result`, unless
+            the trait is `fallible`."]
+            fn try_visit_children_all #generics (
+                &mut self,
+                node: &::tree_sitter::Node
+                #param_decls
+            ) -> ::std::result::Result<::std::vec::Vec<#t>, ::std::vec::Vec<#e>>
+            #where_clause
+            {
+                let mut values = ::std::vec::Vec::new();
+                let mut errors = ::std::vec::Vec::new();
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor)#keep {
+                    match self.visit(&child #param_args) {
+                        ::std::result::Result::Ok(value) => values.push(value),
+                        ::std::result::Result::Err(error) => errors.push(error),
+                    }
+                }
+                if errors.is_empty() {
+                    ::std::result::Result::Ok(values)
+                } else {
+                    ::std::result::Result::Err(errors)
+                }
+            }
+        },
+    ]
+}
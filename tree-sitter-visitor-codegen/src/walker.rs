@@ -0,0 +1,169 @@
+//! Generation of `Walker`, which drives a visitor over every node of a tree in a configurable order
+//! instead of leaving the recursion to the visitor methods.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+use crate::options::Options;
+use crate::{fresh_lifetime, fresh_type_param, trait_params, trait_path, trait_predicates};
+
+pub(crate) fn generate(item: &ItemTrait, options: &Options) -> TokenStream {
+    let vis = &item.vis;
+    let param_decls = options.param_decls();
+    let param_args = options.param_args();
+    let trait_params = trait_params(item);
+    let trait_predicates = trait_predicates(item);
+    let trait_ref = trait_path(item, quote! {});
+    let tree = fresh_lifetime(item, "tree");
+    let v = fresh_type_param(item, "V");
+
+    quote! {
+        /// The order in which a `Walker` visits the nodes of a tree.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        #vis enum WalkOrder {
+            /// Depth-first, visiting each node before its children.
+            #[default]
+            PreOrder,
+            /// Breadth-first, visiting all the nodes of a level before the nodes of the next one.
+            BreadthFirst,
+        }
+
+        /// A node reached by a `Walker`, together with its depth below the root of the walk.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #vis struct WalkStep<'tree> {
+            /// The node.
+            pub node: ::tree_sitter::Node<'tree>,
+            /// The depth of the node, i.e. its level in a breadth-first walk, with the root at 0.
+            pub depth: usize,
+        }
+
+        /// Walks the nodes of a tree, dispatching each of them to a visitor.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        #vis struct Walker {
+            order: WalkOrder,
+            reverse: bool,
+            leaves_only: bool,
+            skip_extras: bool,
+            named_only: bool,
+        }
+
+        impl Walker {
+            /// Creates a walker visiting nodes in pre-order.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Sets the order in which nodes are visited.
+            pub fn order(mut self, order: WalkOrder) -> Self {
+                self.order = order;
+                self
+            }
+
+            /// Visits nodes in breadth-first (level) order.
+            pub fn breadth_first(self) -> Self {
+                self.order(WalkOrder::BreadthFirst)
+            }
+
+            /// Visits the children of each node in reverse document order, i.e. right to left, as
+            /// needed by backward analyses.
+            pub fn reverse(mut self) -> Self {
+                self.reverse = true;
+                self
+            }
+
+            /// Visits only leaves, i.e. tokens and other nodes without children, skipping the
+            /// interior structure of the tree.
+            pub fn leaves_only(mut self) -> Self {
+                self.leaves_only = true;
+                self
+            }
+
+            /// Skips extra nodes, such as comments, along with their subtrees. A node whose children
+            /// are all extras is considered a leaf.
+            pub fn skip_extras(mut self) -> Self {
+                self.skip_extras = true;
+                self
+            }
+
+            /// Visits only named nodes, skipping anonymous tokens such as punctuation and keywords.
+            pub fn named_only(mut self) -> Self {
+                self.named_only = true;
+                self
+            }
+
+            /// Returns an iterator over the nodes of the subtree rooted at `root`, in walk order.
+            pub fn nodes<'tree>(&self, root: ::tree_sitter::Node<'tree>) -> WalkNodes<'tree> {
+                let mut pending = ::std::collections::VecDeque::new();
+                pending.push_back(WalkStep { node: root, depth: 0 });
+                WalkNodes {
+                    walker: *self,
+                    pending,
+                }
+            }
+
+            /// Visits the nodes of the subtree rooted at `root` in walk order, passing each of them
+            /// to `f` together with the result of visiting it.
+            pub fn walk<#tree, #trait_params #v: #trait_ref + ?Sized>(
+                &self,
+                visitor: &mut #v,
+                root: ::tree_sitter::Node<#tree>
+                #param_decls,
+                mut f: impl ::std::ops::FnMut(WalkStep<#tree>, #v::ReturnType),
+            ) where
+                #trait_predicates
+            {
+                for step in self.nodes(root) {
+                    let result = visitor.visit(&step.node #param_args);
+                    f(step, result);
+                }
+            }
+        }
+
+        /// An iterator over the nodes of a tree in the order of a `Walker`.
+        #[derive(Debug, Clone)]
+        #vis struct WalkNodes<'tree> {
+            walker: Walker,
+            pending: ::std::collections::VecDeque<WalkStep<'tree>>,
+        }
+
+        impl<'tree> ::std::iter::Iterator for WalkNodes<'tree> {
+            type Item = WalkStep<'tree>;
+
+            fn next(&mut self) -> ::std::option::Option<Self::Item> {
+                loop {
+                    let step = match self.walker.order {
+                        WalkOrder::PreOrder => self.pending.pop_back()?,
+                        WalkOrder::BreadthFirst => self.pending.pop_front()?,
+                    };
+
+                    let mut cursor = step.node.walk();
+                    let mut children: ::std::vec::Vec<_> = step
+                        .node
+                        .children(&mut cursor)
+                        .filter(|node| !(self.walker.skip_extras && node.is_extra()))
+                        .map(|node| WalkStep {
+                            node,
+                            depth: step.depth + 1,
+                        })
+                        .collect();
+                    if self.walker.reverse {
+                        children.reverse();
+                    }
+                    let is_leaf = children.is_empty();
+                    match self.walker.order {
+                        // The pending nodes are a stack, so the first child is pushed last.
+                        WalkOrder::PreOrder => self.pending.extend(children.into_iter().rev()),
+                        WalkOrder::BreadthFirst => self.pending.extend(children),
+                    }
+
+                    if (is_leaf || !self.walker.leaves_only)
+                        && (step.node.is_named() || !self.walker.named_only)
+                    {
+                        return ::std::option::Option::Some(step);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Generation of `Watcher`, which monitors files with `notify`, reparses them incrementally when they
+//! change and visits them again.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ItemTrait;
+
+pub(crate) fn generate(item: &ItemTrait) -> TokenStream {
+    let vis = &item.vis;
+    let trait_name = &item.ident;
+
+    quote! {
+        /// An error while watching files.
+        #[derive(Debug)]
+        #vis enum WatchError {
+            /// The files could not be watched.
+            Notify(::notify::Error),
+            /// A file could not be read.
+            Io(::std::io::Error),
+            /// The language is not compatible with the version of tree-sitter.
+            Language(::tree_sitter::LanguageError),
+        }
+
+        impl ::std::fmt::Display for WatchError {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    WatchError::Notify(err) => write!(f, "could not watch files: {}", err),
+                    WatchError::Io(err) => write!(f, "could not read file: {}", err),
+                    WatchError::Language(err) => write!(f, "could not load language: {}", err),
+                }
+            }
+        }
+
+        impl ::std::error::Error for WatchError {}
+
+        impl ::std::convert::From<::notify::Error> for WatchError {
+            fn from(err: ::notify::Error) -> Self {
+                WatchError::Notify(err)
+            }
+        }
+
+        impl ::std::convert::From<::std::io::Error> for WatchError {
+            fn from(err: ::std::io::Error) -> Self {
+                WatchError::Io(err)
+            }
+        }
+
+        impl ::std::convert::From<::tree_sitter::LanguageError> for WatchError {
+            fn from(err: ::tree_sitter::LanguageError) -> Self {
+                WatchError::Language(err)
+            }
+        }
+
+        /// The result of visiting a file again after it changed, as sent by `Watcher::run()`.
+        #[derive(Debug)]
+        #vis struct WatchUpdate<R> {
+            /// The path of the file.
+            pub path: ::std::path::PathBuf,
+            /// The result of visiting the root node of the file, or the error reading it.
+            pub result: ::std::result::Result<R, WatchError>,
+        }
+
+        /// Watches files, reparsing them incrementally when they change and visiting them again.
+        ///
+        /// The source and the tree of every file seen so far are kept, so that a change is parsed
+        /// as an edit of the previous tree.
+        #vis struct Watcher<V> {
+            parser: ::tree_sitter::Parser,
+            files: ::std::collections::HashMap<
+                ::std::path::PathBuf,
+                (::std::string::String, ::tree_sitter::Tree),
+            >,
+            visitor: V,
+            filter: ::std::boxed::Box<dyn ::std::ops::Fn(&::std::path::Path) -> bool + ::std::marker::Send>,
+            watcher: ::notify::RecommendedWatcher,
+            events: ::std::sync::mpsc::Receiver<::notify::Result<::notify::Event>>,
+        }
+
+        impl<V: #trait_name> Watcher<V> {
+            /// Creates a watcher parsing files with the given language, not yet watching any path.
+            pub fn new(
+                language: ::tree_sitter::Language,
+                visitor: V,
+            ) -> ::std::result::Result<Self, WatchError> {
+                let mut parser = ::tree_sitter::Parser::new();
+                parser.set_language(&language)?;
+                let (sender, events) = ::std::sync::mpsc::channel();
+                let watcher = ::notify::recommended_watcher(sender)?;
+
+                Ok(Self {
+                    parser,
+                    files: ::std::collections::HashMap::new(),
+                    visitor,
+                    filter: ::std::boxed::Box::new(|_| true),
+                    watcher,
+                    events,
+                })
+            }
+
+            /// Only reparses the changed files for which `filter` returns `true`, e.g. to select
+            /// files by extension in a watched directory.
+            pub fn with_filter(
+                mut self,
+                filter: impl ::std::ops::Fn(&::std::path::Path) -> bool + ::std::marker::Send + 'static,
+            ) -> Self {
+                self.filter = ::std::boxed::Box::new(filter);
+                self
+            }
+
+            /// Starts watching a file, or all the files in a directory recursively.
+            pub fn watch(&mut self, path: &::std::path::Path) -> ::std::result::Result<(), WatchError> {
+                ::notify::Watcher::watch(&mut self.watcher, path, ::notify::RecursiveMode::Recursive)?;
+                Ok(())
+            }
+
+            /// Returns a reference to the visitor.
+            pub fn visitor(&self) -> &V {
+                &self.visitor
+            }
+
+            /// Returns a mutable reference to the visitor.
+            pub fn visitor_mut(&mut self) -> &mut V {
+                &mut self.visitor
+            }
+
+            /// Consumes the watcher, returning the visitor.
+            pub fn into_visitor(self) -> V {
+                self.visitor
+            }
+
+            /// Returns the current source and tree of a file, if it has been parsed.
+            pub fn file(
+                &self,
+                path: &::std::path::Path,
+            ) -> ::std::option::Option<(&str, &::tree_sitter::Tree)> {
+                self.files
+                    .get(path)
+                    .map(|(source, tree)| (source.as_str(), tree))
+            }
+
+            /// Reads a file, reparses it and visits its root node. Returns `None` if the file was
+            /// parsed before and its contents have not changed since.
+            pub fn update(
+                &mut self,
+                path: &::std::path::Path,
+            ) -> ::std::result::Result<::std::option::Option<V::ReturnType>, WatchError> {
+                let source = ::std::fs::read_to_string(path)?;
+                let old_tree = match self.files.get_mut(path) {
+                    ::std::option::Option::Some((old_source, _)) if *old_source == source => {
+                        return Ok(::std::option::Option::None);
+                    }
+                    ::std::option::Option::Some((old_source, tree)) => {
+                        tree.edit(&Self::diff(old_source, &source));
+                        ::std::option::Option::Some(&*tree)
+                    }
+                    ::std::option::Option::None => ::std::option::Option::None,
+                };
+                let tree = self
+                    .parser
+                    .parse(&source, old_tree)
+                    .expect("parsing was cancelled");
+                let result = self.visitor.visit(&tree.root_node());
+                self.files.insert(path.to_path_buf(), (source, tree));
+
+                Ok(::std::option::Option::Some(result))
+            }
+
+            /// Processes file system events until `updates` is disconnected or the watcher stops,
+            /// sending the result of visiting every changed file again. Files that are removed are
+            /// forgotten. A file that cannot be read is sent with the error, and the watcher goes
+            /// on with the other files.
+            pub fn run(
+                &mut self,
+                updates: &::std::sync::mpsc::Sender<WatchUpdate<V::ReturnType>>,
+            ) -> ::std::result::Result<(), WatchError> {
+                while let Ok(event) = self.events.recv() {
+                    let event = event?;
+                    for path in event.paths {
+                        match event.kind {
+                            ::notify::EventKind::Create(_) | ::notify::EventKind::Modify(_)
+                                if path.is_file() && (self.filter)(&path) =>
+                            {
+                                let result = match self.update(&path) {
+                                    Ok(::std::option::Option::Some(result)) => Ok(result),
+                                    Ok(::std::option::Option::None) => continue,
+                                    Err(err) => Err(err),
+                                };
+                                if updates.send(WatchUpdate { path, result }).is_err() {
+                                    return Ok(());
+                                }
+                            }
+                            ::notify::EventKind::Remove(_) => {
+                                self.files.remove(&path);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Ok(())
+            }
+
+            /// Returns the edit turning `old` into `new`, replacing everything between their common
+            /// prefix and common suffix.
+            pub fn diff(old: &str, new: &str) -> ::tree_sitter::InputEdit {
+                let (old_bytes, new_bytes) = (old.as_bytes(), new.as_bytes());
+                let prefix = old_bytes
+                    .iter()
+                    .zip(new_bytes)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                let suffix = old_bytes[prefix..]
+                    .iter()
+                    .rev()
+                    .zip(new_bytes[prefix..].iter().rev())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                let old_end_byte = old.len() - suffix;
+                let new_end_byte = new.len() - suffix;
+
+                ::tree_sitter::InputEdit {
+                    start_byte: prefix,
+                    old_end_byte,
+                    new_end_byte,
+                    start_position: Self::point_at(old_bytes, prefix),
+                    old_end_position: Self::point_at(old_bytes, old_end_byte),
+                    new_end_position: Self::point_at(new_bytes, new_end_byte),
+                }
+            }
+
+            /// Returns the row and byte column of a byte offset in the text.
+            fn point_at(text: &[u8], byte: usize) -> ::tree_sitter::Point {
+                let before = &text[..byte];
+                let row = before.iter().filter(|&&b| b == b'\n').count();
+                let column = before
+                    .iter()
+                    .rposition(|&b| b == b'\n')
+                    .map_or(byte, |newline| byte - newline - 1);
+                ::tree_sitter::Point::new(row, column)
+            }
+        }
+    }
+}
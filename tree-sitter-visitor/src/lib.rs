@@ -3,14 +3,28 @@
 //!
 //! It generates `visit_<node type>()` methods for each node type in the tree-sitter grammar and a generic `visit(...)`
 //! that dispatches to the appropriate typed method. All the trait methods have default implementations that panic, making
-//! it easier to implement visitors for large grammars incrementally.
+//! it easier to implement visitors for large grammars incrementally. The documentation of each method lists the fields of
+//! the node type with their multiplicity and allowed kinds, and its other children, so the shape of a node shows up on
+//! hover without opening the grammar.
+//!
+//! Methods and associated types already defined in the trait body are kept instead of the generated ones, so a method
+//! can be given a custom default implementation, e.g. one returning a placeholder instead of panicking, and `visit()`
+//! still dispatches to it.
+//!
+//! Methods in the trait body can be mapped to node kinds with `#[visit("kind", ...)]`, e.g.
+//! `#[visit("if_statement", "while_statement")] fn visit_conditional(...)`, to rename the method of a kind or to handle
+//! several kinds in one method. `visit()` and the code generated by the other options dispatch these kinds to the
+//! mapped method, and no `visit_<node type>()` methods are generated for them. Families of kinds can also be grouped
+//! without declaring the method with the `group` option. This cannot be combined with `chain`, `catch_unimplemented`,
+//! `enum_dispatch`, `fn_visitor`, `dyn_trait`, `combinators`, `mixins` or `flavors`, and `#[visit(...)]` cannot be used
+//! with `VisitorDelegate`, which forwards the methods of the `group`s passed to it instead.
 //!
 //! # Example:
 //!
 //! ```rust
 //! use tree_sitter_visitor::visitor_trait;
-//! 
-//! #[visitor_trait("../../tree-sitter-tests/src/node-types.json")]
+//!
+//! #[visitor_trait("../tree-sitter-tests/src/node-types.json")]
 //! trait CppVisitor { }
 //! ```
 //!
@@ -39,135 +53,457 @@
 //!     /* ... */
 //! }
 //! ```
-#![feature(proc_macro_span)]
+//!
+//! The annotated trait can have generic parameters, lifetimes and a where clause (e.g.
+//! `trait MyVisitor<'ctx, T: Clone>`). They are kept on the trait, so `ReturnType` and the methods can refer to them,
+//! and are carried over to the implementations generated by the options below, except for `session`, `notify`,
+//! `batch`, `catch_unimplemented`, `coverage`, `memoize` and `dyn_trait`, which do not support generic traits.
+//!
+//! Paths given to the attribute, such as the path to `node-types.json`, are relative to the root of the crate (the
+//! directory containing its `Cargo.toml`), which works on stable Rust. With the `nightly` feature, which requires Rust
+//! 1.88 or later, they are relative to the file containing the attribute instead.
+//!
+//! Environment variables referenced as `$NAME` or `${NAME}` in the path to `node-types.json` are expanded, so a
+//! file produced by a build script can be used, e.g. `#[visitor_trait("$OUT_DIR/node-types.json")]` for a parser
+//! generated in `build.rs`. The path can also be read from an environment variable with `env = "NAME"` instead of
+//! being given as the first argument, e.g. one set by the build script with `cargo:rustc-env`, or from `env` in the
+//! configuration file.
+//!
+//! The files read by the macros (`node-types.json`, the configuration, the grammar, the profile and queries) are
+//! included in the expansion with `include_bytes!`, so Cargo recompiles the crate when they change, e.g. after rerunning
+//! `tree-sitter generate`, without a clean rebuild.
+//!
+//! The code generation is also available as a library, `tree-sitter-visitor-codegen`, whose `generate(path, config)`
+//! returns the code of `#[visitor_trait]` as formatted Rust source, so a build script can write the trait into
+//! `OUT_DIR` to be `include!`d instead, which keeps it readable and spares IDEs from expanding a large macro.
+//!
+//! # Options
+//!
+//! Additional options can be passed to the attribute after the path, e.g.
+//! `#[visitor_trait("node-types.json", text_provider)]`:
+//!
+//! - `text_provider` - generates a `TextProvider` trait for reading node text from `str`, `[u8]` and `String` sources,
+//!   and for parsing them chunk by chunk. It is implied by `fingerprint`, `html`, `interner`, `serialize`,
+//!   `sexp_printer` and `source_map`, whose helpers read the source through it, so they accept any `TextProvider`, e.g.
+//!   a rope with `ropey`.
+//! - `ropey` - additionally implements `TextProvider` for `ropey::Rope` and `ropey::RopeSlice` (requires a
+//!   dependency on `ropey`).
+//! - `session` - generates a `Session<V>` type that owns a parser, the source and its tree, applies edits with incremental
+//!   reparsing and re-runs the visitor on the whole tree or only on the changed nodes. Edits with a range outside the
+//!   source or not on character boundaries are rejected with a `SessionError`.
+//! - `batch` - generates a `visit_batch` driver that visits many trees with a single shared visitor in a deterministic
+//!   order, returning the per-file results together with the visitor's aggregate state. File ids given to more than one
+//!   tree are rejected with a `DuplicateFileId` error.
+//! - `owned_parse` - generates an `OwnedParse` type that owns a source string together with its tree, so a parse
+//!   can be returned from a function and visited later.
+//! - `mixins(Name = ["kind", ...], ...)` - generates partial handler traits, each declaring the methods for the
+//!   node kinds it claims, and implements the visitor trait for every type implementing all of them by
+//!   dispatching each kind to its mixin. Kinds claimed by more than one mixin, and named kinds claimed by none,
+//!   are reported as compile errors.
+//! - `chain` - generates a `ChainedVisitor<A, B>` that dispatches each node kind to `A` if it overrides the corresponding
+//!   method and to `B` otherwise. Overridden methods are recorded by annotating the `impl` block of `A` with
+//!   `#[visitor_impl]`. The hooks added by the other options, such as `visit_token()`, `visit_error()`, the field hooks,
+//!   `after_visit()` and `invalidate()`, are dispatched the same way.
+//! - `fn_visitor` - generates an `FnVisitor` implementing the trait with a closure per node kind, so one-off analyses can
+//!   be assembled without defining a struct and an `impl` block. The hooks visiting nodes, such as `visit_token()` or
+//!   `visit_error()`, are given closures with `hook("visit_token", ...)`.
+//! - `dyn_trait` - generates an object-safe `Dyn<Trait><R>` companion trait (e.g. `DynCalcVisitor<R>`) taking the return
+//!   type as a type parameter instead of an associated type, with the methods prefixed with `dyn_` (`dyn_visit()`,
+//!   `dyn_visit_number()`, ...), implemented for every visitor, and implements the trait for
+//!   `Box<dyn DynCalcVisitor<R>>`, forwarding `visit()` and the methods of the node kinds, so visitors can be stored as
+//!   trait objects, e.g. in a registry of visitors loaded from plugins, and still be used with generic drivers.
+//! - `combinators` - generates a `MultiVisitor` implementing the trait for tuples of 2 to 8 visitors, e.g.
+//!   `MultiVisitor::new((lints, metrics, symbols))`, by forwarding `visit()` and each method to every visitor and returning
+//!   a tuple of their results, and a `MappedVisitor::new(visitor, map)` mapping the results of a visitor, e.g. to bring
+//!   visitors to a common `ReturnType`. Together with `walker`, several analyses whose methods don't recurse run in a
+//!   single traversal of the tree. Cannot be combined with the options that cannot be combined with `fn_visitor`.
+//! - `enum_dispatch(Name = [Type, ...])` - generates an enum with a variant for each of the given visitor types that
+//!   implements the trait by matching on the variant, avoiding `dyn` dispatch when the visitor is selected at runtime from
+//!   a fixed set. `visit()`, the methods of the node kinds and the hooks added by the other options are forwarded to the
+//!   variant. All the types must have the same `ReturnType`.
+//! - `after_visit` - generates an `after_visit(&mut self, node, &result)` hook, a no-op by default, that
+//!   `visit()` calls after every dispatched node, so invariants can be checked uniformly in one place.
+//! - `catch_unimplemented` - generates a `CatchUnimplemented<V>` wrapper that runs a visitor under `catch_unwind`,
+//!   records nodes reaching default method stubs as `Unhandled` kind and range records, and continues with a
+//!   fallback value instead of aborting.
+//! - `emitter` - generates a companion `<Name>Emitter` trait (e.g. `CalcEmitter` for `CalcVisitor`) whose
+//!   `emit_<node type>()` methods stream zero or more items into a caller-provided sink. The default methods recurse
+//!   into the children of the node. Kinds left out by `named_only`, `include` or `exclude` are recursed through as
+//!   well, and other unknown kinds follow `unknown`, with `hook` recursing through them.
+//! - `kind_table` - generates a `KindTable` mapping the numeric kind ids of a `Language` to the visitor's node kinds,
+//!   resolved lazily and cached per language (so languages loaded at runtime work as well), and a `visit_by_id()` method
+//!   dispatching on `node.kind_id()` through it. This is tested with languages loaded from shared libraries (see
+//!   `libloading`), but not with languages loaded from WebAssembly with a `WasmStore`.
+//! - `kind_id_dispatch` - implies `kind_table` and makes `visit()` (and `visit_named()` and `visit_token()`) match on
+//!   the index of the node's numeric kind id in the `KindTable` of its language instead of comparing kind strings, which
+//!   compiles to a jump table. The table last used is cached per thread, so the lookup is cheap while visiting a tree.
+//!   The `profile` option has no effect on this dispatch.
+//! - `language_check` - generates a `check_language()` function comparing the node kinds of the visitor with the visible
+//!   node kinds of a `Language` and returning a `LanguageReport` of the `missing` and `extra` kinds, and an
+//!   `assert_language_compatible()` panicking with the report, to be called from a test so a parser regenerated from a
+//!   changed grammar without regenerating the visitor trait is caught. The kinds left out by `named_only`, `include` and
+//!   `exclude` are compared as well, so they are not reported as `extra`.
+//! - `node_kind` - generates a `NodeKind` enum with a variant for every node kind (e.g. `NodeKind::AddExpr`, and
+//!   `NodeKind::PLUS` for the anonymous `"+"`) and `Unknown` for any other kind, with `From<&Node>`, `FromStr` and
+//!   `as_str()` conversions, so downstream code can match node kinds exhaustively instead of comparing strings.
+//!   Anonymous kinds named like another kind get a `Token` suffix.
+//! - `node_kind_dispatch` - implies `node_kind` and makes `visit()` (and `visit_named()` and `visit_token()`) match on
+//!   the `NodeKind` of the node instead of its kind string. Cannot be combined with `kind_id_dispatch`.
+//! - `grammar_name_dispatch` - makes `visit()` (and `visit_named()` and `visit_token()`) match on `node.grammar_name()`
+//!   instead of `node.kind()`, so a node aliased to another kind is visited with the method of the rule it was parsed
+//!   from and aliases sharing a kind can be told apart. Aliases of hidden rules fall back to their kind. Cannot be
+//!   combined with `kind_id_dispatch` nor `node_kind_dispatch`.
+//! - `kinds` - generates a `kinds` module with a constant for the string of every node kind (e.g. `kinds::ADD_EXPR` for
+//!   `"add_expr"` and `kinds::PLUS` for the anonymous `"+"`), the arrays `ALL_KINDS` and `NAMED_KINDS`, and `id()` and
+//!   `ids()` functions resolving kinds to their ids in a `Language`, so queries and configuration can refer to node kinds
+//!   without hardcoding strings that drift out of sync with the grammar. Anonymous kinds named like another kind get a
+//!   `_TOKEN` suffix.
+//! - `libloading` - implies `kind_table` and generates a `DynamicLanguage` that loads a grammar shared library at
+//!   runtime (requires the `libloading` feature of this crate and a dependency on `libloading`) and, on first use,
+//!   validates that its node kinds match the visitor's, reporting mismatches as errors.
+//! - `introspection` - generates a `grammar` module exposing the node types as static data: for each kind, its
+//!   fields with their allowed kinds and multiplicity, its children, and whether it is named, extra or a supertype.
+//! - `fields` - generates a `Fields` struct holding the id of every field in the grammar, resolved once per
+//!   `Language`, so hot paths can use `child_by_field_id` instead of looking children up by name. Its getters
+//!   (e.g. `fields.lhs(&node)`) check the kind of the returned child against `node-types.json` in debug builds.
+//! - `nodes` - generates a `nodes` module with a newtype wrapper around `Node` for every named node kind, e.g.
+//!   `nodes::AddExpr<'tree>`, with an accessor for each of its fields (`add_expr.lhs()` returning an
+//!   `Option<Node<'tree>>`, or a `Vec<Node<'tree>>` for fields with multiple children) instead of
+//!   `child_by_field_name("lhs")` calls, and a `TryFrom<Node>` impl checking the kind of the node.
+//! - `accessors` - generates an `accessors` module with a function for every field of every node kind, e.g.
+//!   `accessors::add_expr_lhs(&node)`, which panics if the node is of another kind and returns the child in the field, or
+//!   panics with the kind and the range of the node if a required field is absent (which only happens in trees with
+//!   errors), an `Option<Node>` for optional fields or a `Vec<Node>` for fields with multiple children, so typos in field
+//!   names fail to compile instead of returning `None` at runtime.
+//! - `node_ext` - generates a `NodeExt` extension trait implemented for `Node` with an `is_<node type>()` check and an
+//!   `as_<node type>()` conversion returning `Option<Node>` (or the wrapper of the kind with `nodes`) for every named
+//!   node kind, e.g. `node.is_add_expr()` and `node.as_add_expr()`, and an `is_<supertype>()` check for every supertype,
+//!   e.g. `node.is_expression()` for `_expression`, including the kinds of nested supertypes, replacing
+//!   `node.kind() == "add_expr"` comparisons in code outside the visitors.
+//! - `subtype_enums` - generates a `supertypes` module with an enum for every supertype in `node-types.json`, e.g.
+//!   `supertypes::Expression<'tree>` for `_expression`, with a variant wrapping the node for each of its subtypes
+//!   (`Expression::BinaryExpression(node)`), or the enum of a subtype that is a supertype itself, and a
+//!   `classify(node)` constructor returning `None` for other kinds, so matches over the subtypes are exhaustive and
+//!   fail to compile when the grammar adds one.
+//! - `field_children` - generates a `FieldName` enum of the fields in the grammar and a `FieldChildren` iterator
+//!   over the children of a node yielding `(Option<FieldName>, Node)` pairs, walking them with a `TreeCursor`.
+//! - `field_hooks` - generates a `visit_field_<name>()` hook for each field in the grammar, taking the parent and the
+//!   child and visiting the child with `visit()` by default, and `visit_fields()`, which dispatches the children of a
+//!   node that are in a field to the hook of their field, so visitors can treat e.g. every `condition` alike.
+//! - `walker` - generates a `Walker` that visits every node of a subtree with a visitor, in pre-order or
+//!   breadth-first (level) order and optionally with children right to left (`reverse()`), passing each result to a
+//!   callback together with the depth of the node, so analyses don't have to implement traversals with a `TreeCursor`.
+//!   The walk can be restricted to leaves (`leaves_only()`) or named nodes (`named_only()`) and skip extras such as
+//!   comments (`skip_extras()`).
+//! - `ancestors` - generates an `ancestors(node)` iterator and an `enclosing(node, &NodeKindSet)` helper returning
+//!   the nearest ancestor of one of the given kinds, replacing `node.parent()` loops.
+//! - `enclosing(name = ["kind", ...], ...)` - implies `ancestors` and generates a `nearest_enclosing_<name>(node)`
+//!   helper and an `ENCLOSING_<NAME>` kind set for each given set of kinds, e.g. `enclosing(function = [...])` for
+//!   `nearest_enclosing_function()`.
+//! - `siblings` - generates `next_semantic_sibling(node)` and `prev_semantic_sibling(node)`, which skip extras such as
+//!   comments (nodes the parser marks as extra or whose kind is declared as an extra in `node-types.json`).
+//! - `fingerprint` - generates a `NodeFingerprint` combining a hash of the kinds on the path from the root, a hash of
+//!   the structure and text of the subtree, and the position of the node among its siblings of the same kind, which
+//!   identifies "the same" node across reparses of edited source so cached results can be carried forward.
+//! - `correspondence` - generates a `NodeCorrespondence` that, after an incremental reparse, pairs the nodes of the
+//!   old tree with their counterparts in the new one wherever they are outside the changed and edited ranges, so
+//!   re-analysis can be limited to the nodes that actually changed.
+//! - `revisit` - generates `revisit_changed(old_tree, new_tree, edits)`, which after an incremental reparse visits only
+//!   the outermost of the smallest nodes enclosing each changed or edited range, returning their results, and then calls
+//!   an `invalidate(node)` hook, doing nothing by default, once for every ancestor of these nodes, so editor tooling
+//!   can drop the results it cached for them instead of visiting the whole tree on every keystroke.
+//! - `parallel` - generates `visit_parallel(tree, combine)`, which visits the top-level children of the root of a tree on
+//!   the rayon thread pool, each with a visitor created by a required `split()` method, e.g. cloning the configuration of
+//!   the visitor with empty state, and merges their results in document order with `combine`. It requires the `rayon`
+//!   feature of this crate and a dependency on `rayon`, and cannot be combined with `async`, `params`, `context` or
+//!   `fn_visitor`. The wrappers generated by the other options and `VisitorDelegate` split the visitors they wrap, cloning
+//!   the other fields of a delegating struct.
+//! - `tracing` - wraps each dispatch of `visit()` (and of `visit_named()` and `visit_token()`) in a `tracing` span named
+//!   `visit` at the `TRACE` level, carrying the `kind`, the `start_byte` and `end_byte` and the `depth` of the nesting of
+//!   dispatches of the visited node, so the time spent on each node kind shows up in flamegraphs, e.g. with
+//!   `tracing-flame`. It requires the `tracing` feature of this crate and a dependency on `tracing`, and cannot be
+//!   combined with `async`.
+//! - `coverage` - generates a `CoverageTracker<V>` wrapper that counts the `visit_*` methods `visit()` dispatches to
+//!   while running a visitor, e.g. over a corpus, and reports the handlers that were never exercised.
+//! - `memoize` - generates a `Memoized<V>` wrapper whose `visit()` caches the results of the wrapped visitor by node id, so
+//!   repeated queries against the same tree, such as hover, diagnostics and completion in one language server request, do
+//!   not visit the same subtrees again. `Memoized<V>` implements the trait, and caches the nodes visited through it, while
+//!   the wrapped visitor visits the children of a node directly. The results must implement `Clone`. Cached results are
+//!   dropped with `invalidate(node)`, `invalidate_range(range)`, which drops those of the nodes overlapping a byte range
+//!   and so of their ancestors, `edit(edit)`, which does so for an `InputEdit` and keeps the results of the nodes the
+//!   parser can reuse, and `clear()`. With `revisit`, the `invalidate()` hook of the trait drops the cached result of the
+//!   node as well.
+//! - `visit_children` - generates `visit_children(node)` and `visit_named_children(node)`, which visit all the children
+//!   or only the named children of a node in document order with a `TreeCursor` and return their results, and
+//!   `visit_children_with(node, init, combine)` and `visit_named_children_with(node, init, combine)`, which combine the
+//!   results with a closure instead, e.g. `self.visit_children_with(node, 0, |sum, n| sum + n)`.
+//!   Also generates `visit_field_all(node, field)` and `visit_kind_all(node, kind)`, which only visit the children in the
+//!   given field or of the given kind, e.g. `self.visit_field_all(node, "argument")`.
+//! - `try_visit_children` - generates `try_visit_children(node)`, which visits the children of a node with a visitor
+//!   whose `ReturnType` is a `Result` (or a `fallible` one) and stops at the first error, and
+//!   `try_visit_children_all(node)`, which visits all of them and returns every error.
+//! - `cursor` - generates `visit_cursor(cursor)`, which dispatches the node a `TreeCursor` points at like `visit()`,
+//!   and a `visit_cursor_<node type>(cursor)` method for each node type, which forwards to `visit_<node type>()` by
+//!   default, so implementations can descend with `goto_first_child()` and `goto_next_sibling()` instead of indexed
+//!   `child(i)` lookups, one method at a time. The methods must leave the cursor on the node they were given.
+//!   `visit_cursor_children(cursor)` visits the children of the node with the cursor and returns their results.
+//! - `return_types(kind = Type, "kind" = Type, ...)` - declares a dedicated return type for the methods visiting the
+//!   given node kinds, e.g. `return_types(parameter_list = Vec<Param>)`, instead of `ReturnType`. Since `visit()` can
+//!   only return `ReturnType`, it panics for these kinds and their methods have to be called directly. Cannot be
+//!   combined with `fn_visitor`, `dyn_trait`, `combinators` and `catch_unimplemented`.
+//! - `params(name: Type, ...)` - appends extra parameters to the signature of every visit method, e.g.
+//!   `params(interner: &mut Interner, file: FileId)`, which `visit()` and the other generated dispatchers and
+//!   walkers pass through, instead of smuggling per-run state through `self`. They should be references or `Copy`
+//!   types. Cannot be combined with `session`, `notify`, `batch`, `owned_parse`, `fn_visitor`, `dyn_trait`,
+//!   `combinators`, `catch_unimplemented` and `memoize`.
+//! - `context` - passes a `ctx: &mut Self::Context` parameter, with `Context` being a second associated type, to every
+//!   visit method before the `params`, so per-visit context such as a scope stack, the source text or an arena can be
+//!   borrowed separately from the visitor state. `visit()` and the other generated dispatchers pass it through.
+//!   Cannot be combined with the options that cannot be combined with `params`, nor with `chain`, `coverage`, `memoize`,
+//!   `walker`, `query_captures` and `enum_dispatch`.
+//! - `fallible` - adds an `Error` associated type and makes every visit method, `visit()` and the other generated
+//!   dispatchers return `Result<ReturnType, Error>`. The default implementations return an `UnimplementedNode` error
+//!   with the kind and the byte range of the node instead of panicking, and so do the dispatchers for nodes of unknown
+//!   kinds, so `Error` must implement `From<UnimplementedNode>`. This makes visitors that only handle some node kinds
+//!   usable where panics are unacceptable. The helpers of `visit_children` return the `Result`s of the children, and
+//!   `try_visit_children(node)` returns a `Result<Vec<ReturnType>, Error>`. Cannot be combined with `after_visit`, nor
+//!   with the options that cannot be combined with `context`.
+//! - `defaults` - bounds `ReturnType` by `Default` and makes the default implementations of the visit methods return
+//!   `ReturnType::default()` (or `Ok` of it with `fallible`) instead of panicking, so visitors accumulating their findings
+//!   in `self` only implement the methods of the kinds they look at. Nodes of unknown kinds still follow `unknown`.
+//! - `async` - makes every visit method, `visit()` and the other generated dispatchers `async fn`s, so visitors can
+//!   await per node, e.g. database lookups in a language server. The dispatchers box the futures of the methods they
+//!   call, so methods can await `self.visit(&child)` recursively without boxing it themselves. Cannot be combined
+//!   with the options that cannot be combined with `fallible`.
+//! - `by_value` - makes every visit method, `visit()` and the other generated dispatchers take the node by value as
+//!   `node: Node<'tree>`, adding a `'tree` lifetime parameter to the trait (e.g. `impl<'tree> CalcVisitor<'tree> for
+//!   Printer<'tree>`), so `ReturnType` can borrow from the tree or its source, e.g. `&'tree str` slices or nodes.
+//!   Cannot be combined with `async`, `kind_table`, `kind_id_dispatch`, `mixins`, `flavors`, `VisitorDelegate`, nor
+//!   with the options that cannot be combined with `fallible` other than `after_visit`.
+//! - `flavors(immutable, ...)` - generates further flavors of the visitor trait from the same grammar and options (such as
+//!   `params`, `return_types` and `supertraits`), named after the annotated trait, so projects needing more than one don't
+//!   repeat the path and the options:
+//!   - `immutable` - a `<Name>RefVisitor` trait (e.g. `CalcRefVisitor` for `CalcVisitor`) whose methods take `&self`,
+//!     for visitors that don't need to mutate their state, with the same `visit_token()`, `visit_unknown()`,
+//!     `visit_error()`, `visit_missing()` and `visit_extra()` hooks as the annotated trait.
+//!   - `enter_leave` - a `<Name>Walker` trait (e.g. `CalcWalker`) with `enter_<node type>()` and `leave_<node type>()`
+//!     hooks doing nothing by default, and a `walk(root)` driver traversing the tree depth-first with a `TreeCursor`
+//!     without recursion, calling the enter hook of each node on the way down and its leave hook on the way up, for
+//!     linters and other visitors that only need events and no return values. Since the only state of the traversal
+//!     is the cursor, pathologically deep trees, e.g. of machine-generated or minified code, don't overflow the stack.
+//!   - `fold` - a `<Name>Folder` trait (e.g. `CalcFolder`) whose `fold_<node type>(node, children)` methods receive
+//!     the results of folding the named children of the node, and a `fold_tree(root)` driver computing them bottom-up
+//!     with a `TreeCursor` without recursion, for evaluators and metrics that would otherwise recurse by hand.
+//!     Anonymous nodes are skipped. All its methods return `ReturnType`, regardless of `return_types`.
+//!   - `rewrite` - a `<Name>Rewriter` trait (e.g. `CalcRewriter`) whose `rewrite_<node type>(node, source)` methods
+//!     can return replacement text for a node, and a `rewrite(root, source)` driver that collects the replacements as
+//!     non-overlapping `SourceEdit`s, not rewriting the children of replaced nodes, and applies them, returning the
+//!     rewritten source and the matching `InputEdit`s for reparsing it incrementally, as a base for codemods.
+//! - `unknown = "panic" | "skip" | "hook"` - sets what `visit()` and the other generated dispatchers do with nodes of
+//!   kinds that are not in `node-types.json`, e.g. added by a newer version of the grammar: `panic` (the default),
+//!   `skip`, which returns `ReturnType::default()` and therefore requires `ReturnType: Default`, or `hook`, which calls
+//!   a `visit_unknown(node)` method that panics by default and can be overridden to degrade gracefully.
+//! - `extras = "normal" | "skip" | "hook"` - sets how extras such as comments (nodes the parser marks as extra or
+//!   whose kind is declared as an extra in `node-types.json`) are treated: like any other node (`normal`, the default),
+//!   left out by `visit_children()`, `try_visit_children()`, `visit_cursor_children()` and their variants (`skip`), or
+//!   dispatched by `visit()` and the other generated dispatchers to a `visit_extra(node)` method instead of the method
+//!   of their kind (`hook`), which panics by default, so comment handling lives in one place.
+//! - `prefix = "on_"` and `case = "preserve" | "snake"` - set how the methods of node kinds are named: `prefix` replaces
+//!   the default `visit_` prefix (e.g. `on_function_definition()`), and `case = "snake"` converts `CamelCase` kinds to
+//!   snake case (e.g. `visit_html_element()` for `HTMLElement`) instead of keeping them as they are (`preserve`, the
+//!   default). `visit()`, the other dispatchers and the code generated by the other options follow the chosen names.
+//!   Kinds that map to the same method are reported as errors.
+//! - `supertraits = "Send + Sync + ..."` - adds supertraits to the generated trait, so every visitor is guaranteed to
+//!   satisfy them, e.g. to be usable in threaded drivers. The generated generic implementations (such as
+//!   `ChainedVisitor`) require them as well.
+//! - `return_bounds = "Clone + Send + ..."` - adds bounds to the `ReturnType` associated type, so generic drivers can
+//!   rely on the capabilities they need from the results of any visitor.
+//! - `return_type = "Type"` - makes the visit methods return the given type instead of declaring a `ReturnType` associated
+//!   type, so implementations have no associated items. With `return_type = "()"`, the default implementations return `()`
+//!   instead of panicking, so visitors that accumulate their results in `self` only implement the methods they care about,
+//!   while nodes of unknown kinds still follow `unknown`. Cannot be combined with `return_bounds` nor with the options
+//!   generating code generic over implementations of the trait (`session`, `notify`, `batch`, `owned_parse`, `fn_visitor`,
+//!   `dyn_trait`, `combinators`, `catch_unimplemented`, `chain`, `coverage`, `memoize`, `walker`, `query_captures`,
+//!   `enum_dispatch`, `try_visit_children`, `mixins` and `flavors`).
+//! - `return_type_param = R` - makes the trait generic over the type returned by the visit methods (e.g.
+//!   `trait CalcVisitor<R>`) instead of declaring a `ReturnType` associated type, with `R` bounded by `return_bounds`,
+//!   so one type can implement the visitor several times with different return types, e.g. `CalcVisitor<()>` for a
+//!   collection pass and `CalcVisitor<Value>` for evaluation, without wrapper newtypes. Calls that are ambiguous between
+//!   the implementations name the return type, e.g. `CalcVisitor::<()>::visit(self, &child)`. Cannot be combined with
+//!   `return_type` nor with the other options that cannot be combined with it, except `return_bounds`.
+//! - `include = ["kind", ...]`, `include_pattern = "regex"`, `exclude = ["kind", ...]` and `exclude_pattern = "regex"` -
+//!   restrict the trait, its dispatch and the code generated by the other options to the selected node kinds, e.g.
+//!   `include = ["function_definition", "call_expression"]` or `exclude_pattern = "^preproc_"`, for visitors that only
+//!   care about a few kinds of a large grammar. If `include` or `include_pattern` is given, only the kinds listed in or
+//!   matching either are selected, and the kinds listed in `exclude` or matching `exclude_pattern` are left out. Nodes
+//!   of the other kinds are handled like nodes of unknown kinds, so they can be routed to a fallback hook with
+//!   `unknown = "hook"`. Kinds listed in `include` and `exclude` that are not in the grammar are reported as errors.
+//! - `named_only` - leaves the anonymous node kinds (tokens such as `"+"` and `"("`) out of the trait, its dispatch and
+//!   the code generated by the other options, so large grammars don't get hundreds of `visit_PLUS`-style methods.
+//!   Anonymous nodes are then handled like nodes of unknown kinds (see `unknown`).
+//! - `token_hook` - implies `named_only` and makes `visit()` dispatch all anonymous nodes (operators and punctuation) to a
+//!   single `visit_token(node, kind)` method, which panics by default, while the named node kinds keep their methods, so
+//!   the trait of a grammar with a large set of punctuation shrinks to its named kinds and the tokens are told apart by
+//!   their `kind` in one place. Cannot be combined with `named_dispatch`.
+//! - `error_hooks` - makes `visit()` and the other generated dispatchers dispatch the `ERROR` nodes the parser produces
+//!   for text it could not parse to a `visit_error(node)` method, and the zero-width `MISSING` nodes it inserts where a
+//!   required node is missing to a `visit_missing(node)` method instead of the method of their kind, so tools can report
+//!   syntax problems on malformed input. Both panic by default.
+//! - `supertypes` - makes the default implementation of the method of every subtype of a supertype in `node-types.json`
+//!   (such as `_expression`) forward to the method of the supertype (e.g. `visit__expression()`) instead of panicking, so
+//!   a visitor can handle, say, any expression in one place and only override the methods of the kinds it treats
+//!   specially. Kinds listed by several supertypes forward to the first one, and supertypes that are subtypes themselves
+//!   forward in turn. Methods whose `return_types` differ from that of their supertype do not forward.
+//! - `named_dispatch` - generates `visit_named(node)` and `visit_token(node)` entry points in addition to `visit()`,
+//!   which only dispatch among the named and the anonymous node kinds respectively, so semantic and lexical
+//!   handling can be routed to different code paths.
+//! - `token_visitor` - generates a companion `<Name>TokenVisitor` trait (e.g. `CalcTokenVisitor` for `CalcVisitor`)
+//!   covering only the terminal node kinds (anonymous tokens and named nodes without children), with a
+//!   `visit_tokens()` method visiting the leaves of a subtree in document order, for lexical tools like
+//!   highlighters that don't need the full grammar.
+//! - `profile = "path/to/profile.json"` - orders the match arms of `visit()` by descending frequency according to a
+//!   profile mapping node kinds to visit counts (e.g. `{"identifier": 1200, "call": 310}`), such as the one produced
+//!   by `CoverageTracker::profile()`, so the most common kinds are compared first. The path is resolved like the path
+//!   to `node-types.json`.
+//! - `languages(c = "path/to/c/node-types.json", cpp = "path/to/cpp/node-types.json", ...)` - given instead of the path
+//!   to `node-types.json`, generates a single trait for several languages that share most node kinds, such as C, C++ and
+//!   CUDA. The trait and its dispatch cover the union of their node kinds, each kind appearing once. Kinds that several
+//!   languages have get the union of their fields and children, with the fields that only some of them have being
+//!   optional, and the documentation of the methods of the kinds that only some languages have lists these languages.
+//!   The paths are resolved like the path to `node-types.json`. Cannot be combined with `expected_hash`.
+//! - `expected_hash = "0123456789abcdef"` - pins the grammar: expansion fails if the hash of `node-types.json` differs
+//!   from the given one, with an error showing the actual hash, so changes to a vendored grammar require an explicit,
+//!   reviewed update of the visitor. The hash is the 64-bit FNV-1a hash of the file, with CRLF line endings treated as
+//!   LF.
+//! - `grammar = "path/to/grammar.json"` - reads the `grammar.json` of the grammar as well, resolved like the path to
+//!   `node-types.json`, to document the precedence and associativity of each kind (e.g. "Precedence: `1`,
+//!   left-associative") and the rules aliased to it in the docs of its method. The methods of the tokens produced by the
+//!   external scanner, which `node-types.json` doesn't tell apart, forward by default to a dedicated
+//!   `visit_external(node)` hook, which panics by default, unless they have a dedicated return type.
+//! - `renames(old = "new", "old-kind" = "new", ...)` - eases migration to a grammar version that renamed node kinds by
+//!   generating a deprecated `visit_<old>()` shim for every old kind, which forwards to `visit_<new>()`. `visit()`
+//!   dispatches both kinds through the shim, so implementations overriding either method keep working while they are
+//!   migrated one method at a time.
+//! - `group(name = ["kind", ...], ...)` - maps each set of node kinds to a single generated `visit_<name>()` method,
+//!   e.g. `group(binary = ["add_expr", "sub_expr", "mul_expr"])` generating `visit_binary()`, which panics by default,
+//!   instead of their `visit_<node type>()` methods, like `#[visit(...)]` without declaring the method, so analyses
+//!   treating a family of kinds identically implement one method and look at `node.kind()` where they differ. Grouped
+//!   kinds cannot have `return_types`.
+//! - `config = "path/to/visitor.toml"` - reads further options from a TOML file, resolved like the path to
+//!   `node-types.json`, keeping the attribute short and the configuration reviewable. The file mirrors the attribute:
+//!   flags are booleans (`walker = true`), `key = "value"` options are strings, `params`, `flavors`, `include` and
+//!   `exclude` are arrays of strings (`params = ["depth: usize"]`), and `return_types`, `renames`, `languages`,
+//!   `mixins`, `enclosing`, `group` and `enum_dispatch` are tables (`[renames]` followed by `sum = "add_expr"`). The
+//!   path to `node-types.json` can be given as `path` in the file instead of in the attribute. Paths in the file are
+//!   relative to the file itself. Options in the file are added to those in the attribute, and the crate is rebuilt
+//!   when the file changes.
+//! - `query_captures` - generates a `visit_captures(visitor, query, root, source, f)` driver that runs a tree-sitter
+//!   query and dispatches only the captured nodes to the visitor, in document order, passing each `CapturedNode`
+//!   (capture name and index, pattern index and node) to `f` together with the result of visiting it, so expensive
+//!   handlers run exactly on the nodes a query selects.
+//! - `notify` - generates a `Watcher<V>` that watches files and directories (requires the `notify` feature of this crate
+//!   and a dependency on `notify`), reparses changed files incrementally, treating the difference from their previous
+//!   contents as an edit of the previous tree, and visits them again. `Watcher::run()` sends a `WatchUpdate` with the path
+//!   and the result, or the error reading the file, for every changed file through a channel, as needed by the "watch"
+//!   modes of linters and formatters.
+//! - `kind_frequencies` - generates a `KindFrequencies<F>` analysis that counts how often each node kind appears in the
+//!   trees of a corpus and in which files `F`, and cross-references the counts with the grammar, listing the declared
+//!   kinds that were never observed and the observed kinds that are not declared (such as `ERROR`). It prints a report
+//!   with `Display` and exports the counts for the `profile` option, to prioritize handlers and prune dead ones.
+//! - `html` - generates an `HtmlRenderer` that renders source to HTML, wrapping nodes in `<span class="...">` elements
+//!   according to a mapping from node kinds to CSS classes (`HtmlRenderer::new().class("number", "num")`) or to the
+//!   captures of a highlights query (`HtmlRenderer::capture_spans()`), and highlighting `ERROR` nodes. Spans of
+//!   several layers, such as the trees of injected languages, are combined with `HtmlRenderer::render_spans()`, which
+//!   closes and reopens overlapping spans so the output is always well-formed.
+//! - `source_map` - generates a `SourceMapBuilder` for code generators, which accumulates the output while recording
+//!   mappings from output positions to the nodes the text was generated from (`push_node(node, text)`), and exports them as
+//!   a version 3 source map with `to_json()`. With `flavors(rewrite)`, the rewriter's `rewrite_mapped()` (and
+//!   `SourceEdit::apply_mapped()`) records the rewritten source in a `SourceMapBuilder`, mapping each replacement to the
+//!   node it replaces and each line of kept text to where it comes from.
+//! - `interner` - generates a `SymbolTableInterner` that interns strings and the text of nodes into `Symbol`s, which
+//!   compare and hash as integers, with an `intern_<kind>(node, source)` method for every named leaf kind (e.g.
+//!   `intern_identifier`) checking the kind of the node in debug builds.
+//! - `assert_tree` - generates an `assert_tree!` macro for tests, asserting that a node has a shape written with the
+//!   kinds and fields of the grammar, e.g. `assert_tree!(node, add_expr { lhs: number, rhs: number })`, instead of
+//!   comparing `to_sexp()` strings. Misspelled kinds and fields are compile errors, and mismatches panic with a line diff
+//!   of the expected and the actual shape.
+//! - `sexp_printer` - generates a `SexpPrinter` that renders a tree as an S-expression with one node per line, labelled
+//!   with the field it is in and its byte range, e.g. `SexpPrinter::new().source(source).print(&tree.root_node())`, in a
+//!   stable format whose line diffs point at the nodes that changed, for snapshot tests. Anonymous nodes, byte ranges and
+//!   the text of leaves are printed with `anonymous(true)`, `ranges(true)` (the default) and `source(source)`.
+//! - `serialize` - generates a `SerializableNode` that serializes a tree with serde (requires a dependency on `serde`),
+//!   e.g. `serde_json::to_string(&SerializableNode::from_tree(&tree, source))`, as objects following `node-types.json`:
+//!   each node has its `type`, whether it is `named` and its `start` and `end` byte offsets, and either a `fields` object
+//!   keyed by the fields of its node type and a `children` array of the named children not in a field, or its `text`,
+//!   for dumping trees to JSON for tooling in other languages and golden tests.
 
-use proc_macro::Span;
 use proc_macro::TokenStream;
-use quote::{format_ident, quote, ToTokens};
-use serde::Deserialize;
-use serde_json::from_reader;
-use std::fs::File;
-use syn::{parse_macro_input, parse_quote, AttributeArgs, ItemTrait, Lit, NestedMeta, TraitItem};
 
-#[derive(Deserialize)]
-struct Node {
-    r#type: String,
+#[proc_macro_attribute]
+pub fn visitor_trait(args: TokenStream, input: TokenStream) -> TokenStream {
+    tree_sitter_visitor_codegen::visitor_trait(args.into(), input.into()).into()
 }
 
-fn sanitize_identifier(name: &str) -> String {
-    let mut result = String::with_capacity(name.len());
-    for c in name.chars() {
-        if ('a'..='z').contains(&c)
-            || ('A'..='Z').contains(&c)
-            || ('0'..='9').contains(&c)
-            || c == '_'
-        {
-            result.push(c);
-        } else {
-            let replacement = match c {
-                '~' => "TILDE",
-                '`' => "BQUOTE",
-                '!' => "BANG",
-                '@' => "AT",
-                '#' => "POUND",
-                '$' => "DOLLAR",
-                '%' => "PERCENT",
-                '^' => "CARET",
-                '&' => "AMP",
-                '*' => "STAR",
-                '(' => "LPAREN",
-                ')' => "RPAREN",
-                '-' => "DASH",
-                '+' => "PLUS",
-                '=' => "EQ",
-                '{' => "LBRACE",
-                '}' => "RBRACE",
-                '[' => "LBRACK",
-                ']' => "RBRACK",
-                '\\' => "BSLASH",
-                '|' => "PIPE",
-                ':' => "COLON",
-                ';' => "SEMI",
-                '"' => "DQUOTE",
-                '\'' => "SQUOTE",
-                '<' => "LT",
-                '>' => "GT",
-                ',' => "COMMA",
-                '.' => "DOT",
-                '?' => "QMARK",
-                '/' => "SLASH",
-                '\n' => "LF",
-                '\r' => "CR",
-                '\t' => "TAB",
-                _ => continue,
-            };
-            if !result.is_empty() && !result.ends_with('_') {
-                result.push('_');
-            }
-            result += replacement;
-        }
-    }
-    result
+/// Records which methods an implementation of a visitor trait overrides.
+///
+/// Placed on an `impl` block of a trait generated with the `chain` option, it fills in the trait's
+/// `HANDLED_METHODS` constant, which `ChainedVisitor` uses to decide which visitor handles a node.
+#[proc_macro_attribute]
+pub fn visitor_impl(args: TokenStream, input: TokenStream) -> TokenStream {
+    tree_sitter_visitor_codegen::visitor_impl(args.into(), input.into()).into()
 }
 
+/// Generates a trait visiting the captures of a tree-sitter query, with a `visit_<capture>()`
+/// method for each capture of the query and a `run()` driver dispatching the captures to them.
+///
+/// The first argument is the path to the query, the second the path to `node-types.json`, against
+/// which the node kinds and field names of the query are checked.
+///
+/// ```ignore
+/// #[query_visitor("queries/highlights.scm", "node-types.json")]
+/// pub trait Highlights {}
+///
+/// impl Highlights for Keywords {
+///     fn visit_keyword(&mut self, node: &tree_sitter::Node, _pattern_index: usize) {
+///         self.0.push(node.kind().to_string());
+///     }
+/// }
+///
+/// let query = Keywords::query(language)?;
+/// keywords.run(&query, tree.root_node(), source.as_bytes());
+/// ```
 #[proc_macro_attribute]
-pub fn visitor_trait(args: TokenStream, input: TokenStream) -> TokenStream {
-    let args = parse_macro_input!(args as AttributeArgs);
-    let mut input = parse_macro_input!(input as ItemTrait);
-
-    let path_to_json = match args.into_iter().next() {
-        Some(NestedMeta::Lit(Lit::Str(s))) => s.value(),
-        _ => panic!("expected a filename"),
-    };
-
-    let call_site_file = Span::call_site().source_file().path();
-    let cwd = call_site_file.parent().unwrap();
-    let filename = cwd.join(&path_to_json);
-    let file = File::open(filename).unwrap();
-    let parsed: Vec<Node> = from_reader(file).expect("could not parse the node types JSON");
-
-    let (trait_fns, match_arms): (Vec<_>, Vec<_>) = parsed
-        .iter()
-        .map(|symbol| {
-            let raw_name = &symbol.r#type;
-            let sanitized_name = sanitize_identifier(&symbol.r#type);
-            let method_name = format_ident!("visit_{}", sanitized_name);
-            let doc_name = format!("{:?}", raw_name).replace('`', "\\`");
-            let doc_string = format!("Visits a node of type `{}`", doc_name);
-
-            let trait_fn: TraitItem = parse_quote! {
-                #[doc=#doc_string]
-                fn #method_name(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
-                    unimplemented!(#sanitized_name)
-                }
-            };
-
-            let match_arm = quote! {
-                #raw_name => self.#method_name(node)
-            };
-
-            (trait_fn, match_arm)
-        })
-        .unzip();
-
-    let return_item: TraitItem = parse_quote! {
-        type ReturnType;
-    };
-    let dispatch_visit_fn: TraitItem = parse_quote! {
-        #[doc=r"Visits a node of any type."]
-        fn visit(&mut self, node: &::tree_sitter::Node) -> Self::ReturnType {
-            match node.kind() {
-                #(#match_arms,)*
-                _ => panic!("unknown node kind: {}", node.kind())
-            }
-        }
-    };
-
-    input.items = [return_item, dispatch_visit_fn]
-        .into_iter()
-        .chain(trait_fns)
-        .chain(input.items)
-        .collect();
+pub fn query_visitor(args: TokenStream, input: TokenStream) -> TokenStream {
+    tree_sitter_visitor_codegen::query_visitor(args.into(), input.into()).into()
+}
 
-    TokenStream::from(input.into_token_stream())
+/// Implements a visitor trait for a wrapper struct by forwarding every typed `visit_*` method to one of its
+/// fields.
+///
+/// The trait and the path to `node-types.json` (followed by the same options that were passed to
+/// `#[visitor_trait]`) are given in a `#[visitor_delegate(...)]` attribute. The field to delegate to is
+/// marked with `#[delegate]`, which can be omitted if the struct has a single field.
+///
+/// Methods listed in `overrides(...)` are not forwarded, and instead call an inherent method with the same
+/// name that must be defined on the wrapper.
+///
+/// ```ignore
+/// #[derive(VisitorDelegate)]
+/// #[visitor_delegate(CalcVisitor, "node-types.json", overrides(visit_number))]
+/// struct Logging<V> {
+///     #[delegate]
+///     inner: V,
+///     numbers: usize,
+/// }
+///
+/// impl<V: CalcVisitor> Logging<V> {
+///     fn visit_number(&mut self, node: &tree_sitter::Node) -> V::ReturnType {
+///         self.numbers += 1;
+///         self.inner.visit_number(node)
+///     }
+/// }
+/// ```
+#[proc_macro_derive(VisitorDelegate, attributes(visitor_delegate, delegate))]
+pub fn visitor_delegate(input: TokenStream) -> TokenStream {
+    tree_sitter_visitor_codegen::visitor_delegate(input.into()).into()
 }